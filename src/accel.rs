@@ -0,0 +1,482 @@
+//! Hardware CRC instructions, used in place of the table loops when the CPU has them.
+//!
+//! x86 CPUs with SSE4.2 have a `crc32` instruction for CRC-32C (Castagnoli) that folds eight
+//! bytes per cycle or so, where a byte-wise table loop manages one; AArch64 CPUs with the CRC
+//! extension have one for CRC-32/ISO-HDLC too. For every other polynomial of up to 64 bits,
+//! [`fold`] uses carry-less multiplication (`pclmulqdq` on x86, PMULL on AArch64) to reduce
+//! large buffers 64 bytes at a time to a single 16-byte block, which the table then finishes.
+//!
+//! With `std` the features are detected at run time; without it, only builds that enable them
+//! at compile time, such as `-C target-cpu=native`, use the instructions. Everything else goes
+//! through the software path of the caller.
+
+use crate::engine::Width;
+use crate::CrcParams;
+
+/// The CRC-32C polynomial in normal form.
+const CASTAGNOLI: u128 = 0x1EDC_6F41;
+/// The CRC-32 polynomial of zlib and Ethernet.
+const ISO_HDLC: u128 = 0x04C1_1DB7;
+
+/// Feeds `data` into `crc`, a register in table layout, with whatever the CPU offers for
+/// `params`: a CRC instruction, or [`fold`] with `folding` for large buffers. `software` is
+/// the caller's own update, used when neither applies and for what folding leaves over.
+///
+/// x86 tries folding first, since four folding lanes outrun the single chain of `crc32`
+/// instructions; the AArch64 CRC instructions are quick enough to go first.
+#[inline]
+pub(crate) fn update<W: Width>(
+    params: &CrcParams<W>,
+    folding: Option<&Folding>,
+    crc: W,
+    data: &[u8],
+    software: impl Fn(W, &[u8]) -> W,
+) -> W {
+    let folded = || folding.and_then(|folding| fold(folding, crc, data, &software));
+    let accelerated = if cfg!(target_arch = "aarch64") {
+        instruction(params, crc, data).or_else(folded)
+    } else {
+        folded().or_else(|| instruction(params, crc, data))
+    };
+    accelerated.unwrap_or_else(|| software(crc, data))
+}
+
+/// Whether [`update`] has something faster than the caller's software path for `params`, at
+/// least on buffers long enough to fold.
+pub(crate) fn available<W: Width>(params: &CrcParams<W>, folding: Option<&Folding>) -> bool {
+    folding.is_some() && clmul() || instruction(params, W::ZERO, &[]).is_some()
+}
+
+/// Feeds `data` into `crc` using a CRC instruction if the CPU has one for `params`.
+///
+/// Only the polynomial and input reflection matter: init and xorout only touch the register
+/// before and after.
+fn instruction<W: Width>(params: &CrcParams<W>, crc: W, data: &[u8]) -> Option<W> {
+    if params.width != 32 || !params.refin {
+        return None;
+    }
+    let register: u128 = crc.into();
+    let crc = match params.poly.into() {
+        CASTAGNOLI => crc32c(register as u32, data),
+        ISO_HDLC => crc32(register as u32, data),
+        _ => None,
+    }?;
+    Some(W::from_u128(crc.into()))
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+fn crc32c(crc: u32, data: &[u8]) -> Option<u32> {
+    #[cfg(feature = "std")]
+    let sse42 = std::is_x86_feature_detected!("sse4.2");
+    #[cfg(not(feature = "std"))]
+    let sse42 = cfg!(target_feature = "sse4.2");
+    // SAFETY: SSE4.2 support was checked above.
+    sse42.then(|| unsafe { x86::crc32c(crc, data) })
+}
+
+/// SSE4.2 only covers CRC-32C.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+fn crc32(_crc: u32, _data: &[u8]) -> Option<u32> {
+    None
+}
+
+#[cfg(target_arch = "aarch64")]
+fn crc_extension() -> bool {
+    #[cfg(feature = "std")]
+    return std::arch::is_aarch64_feature_detected!("crc");
+    #[cfg(not(feature = "std"))]
+    return cfg!(target_feature = "crc");
+}
+
+#[cfg(target_arch = "aarch64")]
+fn crc32c(crc: u32, data: &[u8]) -> Option<u32> {
+    // SAFETY: the CRC extension was detected.
+    crc_extension().then(|| unsafe { aarch64::crc32c(crc, data) })
+}
+
+#[cfg(target_arch = "aarch64")]
+fn crc32(crc: u32, data: &[u8]) -> Option<u32> {
+    // SAFETY: the CRC extension was detected.
+    crc_extension().then(|| unsafe { aarch64::crc32(crc, data) })
+}
+
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64")))]
+fn crc32c(_crc: u32, _data: &[u8]) -> Option<u32> {
+    None
+}
+
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64")))]
+fn crc32(_crc: u32, _data: &[u8]) -> Option<u32> {
+    None
+}
+
+/// Buffers shorter than this are left to the tables, which win on short inputs.
+const FOLD_MIN: usize = 64;
+
+/// Carry-less multiplication constants for one polynomial, see [`fold`].
+///
+/// Folding a 128-bit block `A = A_hi * x^64 + A_lo` over `n` bits replaces it with
+/// `A_hi * (x^(n + 64) mod P) + A_lo * (x^n mod P)`, which is congruent and again fits 128
+/// bits. The keys are those remainders for `n` = 128 and 512, in the qword order of the
+/// loaded blocks. Reflected blocks hold `A_hi` in the low qword, and their products come
+/// out shifted by one bit, which the keys make up for by using `x^(n - 1)` instead.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Folding {
+    refin: bool,
+    keys: [[u64; 2]; 2],
+}
+
+impl Folding {
+    pub(crate) const fn new(width: u32, poly: u64, refin: bool) -> Option<Self> {
+        if width == 0 || width > 64 {
+            return None;
+        }
+        let mut keys = [[0; 2]; 2];
+        let mut i = 0;
+        while i < 2 {
+            let n = if i == 0 { 128 } else { 512 };
+            keys[i] = if refin {
+                [
+                    xpow(width, poly, n + 63).reverse_bits(),
+                    xpow(width, poly, n - 1).reverse_bits(),
+                ]
+            } else {
+                [xpow(width, poly, n), xpow(width, poly, n + 64)]
+            };
+            i += 1;
+        }
+        Some(Self { refin, keys })
+    }
+}
+
+/// `x^exp` modulo the polynomial of the given width.
+const fn xpow(width: u32, poly: u64, exp: u32) -> u64 {
+    let mask = u128::MAX >> (128 - width);
+    let mut value = 1u128;
+    let mut i = 0;
+    while i < exp {
+        let top = value >> (width - 1) & 1;
+        value = (value << 1) & mask;
+        if top != 0 {
+            value ^= poly as u128;
+        }
+        i += 1;
+    }
+    value as u64
+}
+
+/// Feeds `data` into `crc`, a register in table layout, by carry-less multiplication if the
+/// CPU supports it and `data` is long enough to pay off. What is left after folding goes
+/// through `software`.
+///
+/// The register is XORed into the first bytes of the data, which the table layout allows
+/// for any width, so folding itself always starts from an empty register.
+fn fold<W: Width>(
+    folding: &Folding,
+    crc: W,
+    data: &[u8],
+    software: &impl Fn(W, &[u8]) -> W,
+) -> Option<W> {
+    let blocks = data.len() / 16 * 16;
+    if blocks < FOLD_MIN || !clmul() {
+        return None;
+    }
+    let mut head: [u8; 16] = data[..16].try_into().unwrap();
+    let register: u128 = crc.into();
+    let len = (W::BITS / 8) as usize;
+    let register = if folding.refin {
+        &register.to_le_bytes()[..len]
+    } else {
+        &register.to_be_bytes()[16 - len..]
+    };
+    for (byte, reg) in head.iter_mut().zip(register) {
+        *byte ^= reg;
+    }
+    let folded = fold_blocks(folding, &head, &data[16..blocks]);
+    Some(software(software(W::ZERO, &folded), &data[blocks..]))
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+fn clmul() -> bool {
+    #[cfg(feature = "std")]
+    return std::is_x86_feature_detected!("pclmulqdq") && std::is_x86_feature_detected!("ssse3");
+    #[cfg(not(feature = "std"))]
+    return cfg!(all(target_feature = "pclmulqdq", target_feature = "ssse3"));
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+fn fold_blocks(folding: &Folding, head: &[u8; 16], blocks: &[u8]) -> [u8; 16] {
+    // SAFETY: only called after `clmul` returned true.
+    unsafe { x86::fold(&folding.keys, folding.refin, head, blocks) }
+}
+
+/// PMULL comes with the AES extension.
+#[cfg(target_arch = "aarch64")]
+fn clmul() -> bool {
+    #[cfg(feature = "std")]
+    return std::arch::is_aarch64_feature_detected!("aes");
+    #[cfg(not(feature = "std"))]
+    return cfg!(target_feature = "aes");
+}
+
+#[cfg(target_arch = "aarch64")]
+fn fold_blocks(folding: &Folding, head: &[u8; 16], blocks: &[u8]) -> [u8; 16] {
+    // SAFETY: only called after `clmul` returned true.
+    unsafe { aarch64::fold(&folding.keys, folding.refin, head, blocks) }
+}
+
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64")))]
+fn clmul() -> bool {
+    false
+}
+
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64")))]
+fn fold_blocks(_folding: &Folding, _head: &[u8; 16], _blocks: &[u8]) -> [u8; 16] {
+    unreachable!()
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod x86 {
+    #[cfg(target_arch = "x86")]
+    use core::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use core::arch::x86_64::*;
+
+    #[target_feature(enable = "sse4.2")]
+    pub(super) unsafe fn crc32c(mut crc: u32, mut data: &[u8]) -> u32 {
+        #[cfg(target_arch = "x86_64")]
+        {
+            let mut words = data.chunks_exact(8);
+            let mut wide = u64::from(crc);
+            for word in &mut words {
+                wide = _mm_crc32_u64(wide, u64::from_le_bytes(word.try_into().unwrap()));
+            }
+            crc = wide as u32;
+            data = words.remainder();
+        }
+        let mut words = data.chunks_exact(4);
+        for word in &mut words {
+            crc = _mm_crc32_u32(crc, u32::from_le_bytes(word.try_into().unwrap()));
+        }
+        for &byte in words.remainder() {
+            crc = _mm_crc32_u8(crc, byte);
+        }
+        crc
+    }
+
+    /// Folds `head` and `blocks`, at least 48 bytes in whole blocks, into one block.
+    #[target_feature(enable = "pclmulqdq,ssse3")]
+    pub(super) unsafe fn fold(
+        keys: &[[u64; 2]; 2],
+        refin: bool,
+        head: &[u8; 16],
+        blocks: &[u8],
+    ) -> [u8; 16] {
+        // Unreflected blocks are big-endian: the first byte holds the highest powers of x.
+        let swap = _mm_set_epi8(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
+        let load = |bytes: &[u8]| {
+            let block = _mm_loadu_si128(bytes.as_ptr().cast());
+            if refin {
+                block
+            } else {
+                _mm_shuffle_epi8(block, swap)
+            }
+        };
+        let step = |acc, key, next| {
+            let lo = _mm_clmulepi64_si128(acc, key, 0x00);
+            let hi = _mm_clmulepi64_si128(acc, key, 0x11);
+            _mm_xor_si128(_mm_xor_si128(lo, hi), next)
+        };
+        let [single, quad] = keys.map(|[lo, hi]| _mm_set_epi64x(hi as i64, lo as i64));
+
+        let mut lanes = [
+            load(head),
+            load(&blocks[..16]),
+            load(&blocks[16..32]),
+            load(&blocks[32..48]),
+        ];
+        let mut rest = &blocks[48..];
+        while rest.len() >= 64 {
+            for (i, lane) in lanes.iter_mut().enumerate() {
+                *lane = step(*lane, quad, load(&rest[16 * i..]));
+            }
+            rest = &rest[64..];
+        }
+        let mut acc = step(
+            step(step(lanes[0], single, lanes[1]), single, lanes[2]),
+            single,
+            lanes[3],
+        );
+        for block in rest.chunks_exact(16) {
+            acc = step(acc, single, load(block));
+        }
+
+        let acc = if refin {
+            acc
+        } else {
+            _mm_shuffle_epi8(acc, swap)
+        };
+        let mut out = [0; 16];
+        _mm_storeu_si128(out.as_mut_ptr().cast(), acc);
+        out
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+mod aarch64 {
+    use core::arch::aarch64::*;
+
+    #[target_feature(enable = "crc")]
+    pub(super) unsafe fn crc32c(mut crc: u32, data: &[u8]) -> u32 {
+        let mut words = data.chunks_exact(8);
+        for word in &mut words {
+            crc = __crc32cd(crc, u64::from_le_bytes(word.try_into().unwrap()));
+        }
+        for &byte in words.remainder() {
+            crc = __crc32cb(crc, byte);
+        }
+        crc
+    }
+
+    #[target_feature(enable = "crc")]
+    pub(super) unsafe fn crc32(mut crc: u32, data: &[u8]) -> u32 {
+        let mut words = data.chunks_exact(8);
+        for word in &mut words {
+            crc = __crc32d(crc, u64::from_le_bytes(word.try_into().unwrap()));
+        }
+        for &byte in words.remainder() {
+            crc = __crc32b(crc, byte);
+        }
+        crc
+    }
+
+    /// Same as the x86 version, with PMULL on 128-bit integers.
+    #[target_feature(enable = "neon,aes")]
+    pub(super) unsafe fn fold(
+        keys: &[[u64; 2]; 2],
+        refin: bool,
+        head: &[u8; 16],
+        blocks: &[u8],
+    ) -> [u8; 16] {
+        let load = |bytes: &[u8]| {
+            let block: [u8; 16] = bytes[..16].try_into().unwrap();
+            if refin {
+                u128::from_le_bytes(block)
+            } else {
+                u128::from_be_bytes(block)
+            }
+        };
+        let step = |acc: u128, [lo, hi]: [u64; 2], next: u128| {
+            vmull_p64(acc as u64, lo) ^ vmull_p64((acc >> 64) as u64, hi) ^ next
+        };
+        let [single, quad] = *keys;
+
+        let mut lanes = [
+            load(head),
+            load(&blocks[..16]),
+            load(&blocks[16..32]),
+            load(&blocks[32..48]),
+        ];
+        let mut rest = &blocks[48..];
+        while rest.len() >= 64 {
+            for (i, lane) in lanes.iter_mut().enumerate() {
+                *lane = step(*lane, quad, load(&rest[16 * i..]));
+            }
+            rest = &rest[64..];
+        }
+        let mut acc = step(
+            step(step(lanes[0], single, lanes[1]), single, lanes[2]),
+            single,
+            lanes[3],
+        );
+        for block in rest.chunks_exact(16) {
+            acc = step(acc, single, load(block));
+        }
+
+        if refin {
+            acc.to_le_bytes()
+        } else {
+            acc.to_be_bytes()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{catalog, table};
+
+    #[test]
+    fn instruction_test() {
+        let data: Vec<u8> = (0..1000u32).map(|i| (i * 31 + i / 7) as u8).collect();
+        for params in [
+            catalog::CRC_32_ISCSI.params,
+            catalog::CRC_32_ISO_HDLC.params,
+        ] {
+            let table = table::build(&params);
+            for len in [0, 1, 3, 4, 7, 8, 9, 15, 16, 1000] {
+                let start = table::start(&params);
+                let expected = table::update(&table, true, start, &data[..len]);
+                // Falls back to `None` on CPUs without the instruction.
+                let crc = instruction(&params, start, &data[..len]).unwrap_or(expected);
+                assert_eq!(crc, expected, "{:?} {}", params, len);
+            }
+        }
+        // Other algorithms are left to the caller. u64 registers for CRC-32C are fine.
+        assert_eq!(instruction(&catalog::CRC_32_BZIP2.params, 0, b"1"), None);
+        let wide = CrcParams {
+            width: 32,
+            poly: 0x1EDC_6F41u64,
+            init: 0,
+            refin: true,
+            refout: true,
+            xorout: 0,
+        };
+        let table = table::build(&catalog::CRC_32_ISCSI.params);
+        if let Some(crc) = instruction(&wide, 0, b"123456789") {
+            assert_eq!(crc, u64::from(table::update(&table, true, 0, b"123456789")));
+        }
+    }
+
+    fn folded<W: Width + core::fmt::Debug>(params: CrcParams<W>, data: &[u8]) {
+        let poly: u128 = params.poly.into();
+        let folding = Folding::new(params.width, poly as u64, params.refin).unwrap();
+        let table = table::build(&params);
+        let start = table::start(&params);
+        for len in [0, 63, 64, 79, 80, 127, 128, 200, 1000, data.len()] {
+            let expected = table::update(&table, params.refin, start, &data[..len]);
+            let software = |crc, data: &[u8]| table::update(&table, params.refin, crc, data);
+            match fold(&folding, start, &data[..len], &software) {
+                Some(crc) => assert_eq!(crc, expected, "{:?} {}", params, len),
+                None => assert!(len < FOLD_MIN || !clmul()),
+            }
+        }
+    }
+
+    #[test]
+    fn fold_test() {
+        let data: Vec<u8> = (0..4099u32).map(|i| (i * 31 + i / 7) as u8).collect();
+        folded(catalog::CRC_32_ISO_HDLC.params, &data);
+        folded(catalog::CRC_32_BZIP2.params, &data);
+        folded(catalog::CRC_64_XZ.params, &data);
+        folded(catalog::CRC_64_ECMA_182.params, &data);
+        folded(catalog::CRC_16_MODBUS.params, &data);
+        folded(catalog::CRC_16_XMODEM.params, &data);
+        folded(catalog::CRC_24_OPENPGP.params, &data);
+        folded(catalog::CRC_12_UMTS.params, &data);
+        folded(catalog::CRC_5_USB.params, &data);
+        let wide = CrcParams {
+            width: 32,
+            ..CrcParams::<u64>::from(0x04C1_1DB7)
+        };
+        folded(wide, &data);
+        folded(
+            CrcParams {
+                refin: true,
+                ..wide
+            },
+            &data,
+        );
+        assert!(Folding::new(65, 0, false).is_none());
+    }
+}
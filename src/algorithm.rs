@@ -0,0 +1,1756 @@
+//! A shareable, table-driven CRC algorithm and its running digests.
+//!
+//! The state types such as [`CRC32`](crate::CRC32) carry their parameters in every instance,
+//! which is fine for a handful of messages. When one algorithm checksums many messages,
+//! build a [`Crc`] once, which also derives a 256-entry lookup table, and start a cheap
+//! [`Digest`] per message:
+//!
+//! ```text
+//! let crc = Crc::new(&catalog::CRC_32_ISCSI);
+//! let mut digest = crc.digest();
+//! digest.update(b"123456789");
+//! assert_eq!(digest.finalize(), 0xE306_9283);
+//! ```
+//!
+//! Many short records are faster through a [`MultiStreamDigest`], which advances several
+//! messages at once.
+//!
+//! Algorithms outside the [`catalog`](crate::catalog) can be described with a
+//! [`CrcBuilder`], which checks the settings against each other.
+
+use crate::checksum::Checksum;
+use crate::engine::{Params, Width};
+use crate::framing::{read_uint, Endian};
+use crate::gf2::{self, Factors, Polynomial};
+use crate::strategy::{Strategy, Table256};
+use crate::table;
+use crate::{accel, catalog};
+use crate::{CrcParams, DynCrc};
+use core::fmt;
+
+/// A CRC algorithm together with its lookup tables.
+///
+/// `S` decides how much is precomputed, from nothing with [`NoTable`](crate::strategy::NoTable) over the default single
+/// 256-entry table to the slice-by-16 tables of [`Sliced<16>`](crate::strategy::Sliced); see [`Crc::with_strategy`]
+/// and the [`strategy`](crate::strategy) module.
+///
+/// A `Crc` is `Send + Sync`, so one instance can serve every thread, by reference, in an
+/// `Arc` or through [`Crc::cached`]; its [`Digest`]s are `Copy` and can be sent elsewhere.
+pub struct Crc<W: Width, S: Strategy = Table256> {
+    params: CrcParams<W>,
+    /// The catalogued check value and name, if built from a catalogue entry.
+    check: Option<W>,
+    name: Option<&'static str>,
+    /// Carry-less multiplication keys for large buffers, for widths up to 64.
+    folding: Option<accel::Folding>,
+    tables: S::Tables<W>,
+}
+
+impl<W: Width> Crc<W> {
+    /// The 256-entry lookup table, in the layout described at [`Crc::tables`].
+    pub fn table(&self) -> &[W; 256] {
+        &self.tables
+    }
+
+    /// Starts `N` messages that are updated together; see [`MultiStreamDigest`].
+    pub fn multi_stream_digest<const N: usize>(&self) -> MultiStreamDigest<'_, W, N> {
+        MultiStreamDigest {
+            crc: self,
+            registers: [table::start(&self.params); N],
+        }
+    }
+
+    /// Builds the lookup table for `params`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `params.width` is zero or wider than `W`.
+    pub fn new(params: impl Into<CrcParams<W>>) -> Self {
+        Self::with_strategy(params)
+    }
+
+    /// Like [`Crc::new`], but also keeps the entry's check value for [`Crc::self_check`].
+    pub fn from_algorithm(algorithm: &catalog::Algorithm<W>) -> Self {
+        Self::with_strategy_from_algorithm(algorithm)
+    }
+}
+
+impl<W: Width, S: Strategy> Crc<W, S> {
+    /// Builds the tables of strategy `S` for `params`, e.g.
+    /// `Crc::<u32, Sliced<16>>::with_strategy(&CRC_32_ISCSI)` for slice-by-16.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `params.width` is zero or wider than `W`.
+    pub fn with_strategy(params: impl Into<CrcParams<W>>) -> Self {
+        let params = table::checked(params.into());
+        let tables = S::build(&params);
+        let poly: u128 = params.poly.into();
+        Self {
+            params,
+            check: None,
+            name: None,
+            folding: accel::Folding::new(params.width, poly as u64, params.refin),
+            tables,
+        }
+    }
+
+    /// Like [`Crc::with_strategy`], but also keeps the entry's check value for
+    /// [`Crc::self_check`].
+    pub fn with_strategy_from_algorithm(algorithm: &catalog::Algorithm<W>) -> Self {
+        Self {
+            check: Some(algorithm.check),
+            name: Some(algorithm.name),
+            ..Self::with_strategy(algorithm.params)
+        }
+    }
+
+    /// A shared instance for `algorithm`, built on first use and kept for the life of the
+    /// program, so threads that all use the same standard algorithm build its tables once
+    /// instead of each paying for them:
+    ///
+    /// ```text
+    /// let crc = Crc::cached(&catalog::CRC_32_ISO_HDLC);
+    /// ```
+    ///
+    /// Instances are kept per register type, strategy, parameters, name and check value.
+    #[cfg(feature = "std")]
+    pub fn cached(algorithm: &catalog::Algorithm<W>) -> &'static Self
+    where
+        Self: Send + Sync + 'static,
+    {
+        use std::any::{Any, TypeId};
+        use std::collections::HashMap;
+        use std::sync::{Mutex, OnceLock, PoisonError};
+
+        type Key = (TypeId, CrcParams<u128>, &'static str, u128);
+        static CACHE: OnceLock<Mutex<HashMap<Key, &'static (dyn Any + Send + Sync)>>> =
+            OnceLock::new();
+
+        let params = algorithm.params;
+        let key = (
+            TypeId::of::<Self>(),
+            CrcParams {
+                width: params.width,
+                poly: params.poly.into(),
+                init: params.init.into(),
+                refin: params.refin,
+                refout: params.refout,
+                xorout: params.xorout.into(),
+            },
+            algorithm.name,
+            algorithm.check.into(),
+        );
+        // Building under the lock makes every instance be built exactly once.
+        let mut cache = CACHE
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let crc = *cache
+            .entry(key)
+            .or_insert_with(|| Box::leak(Box::new(Self::with_strategy_from_algorithm(algorithm))));
+        crc.downcast_ref().unwrap()
+    }
+
+    pub fn params(&self) -> &CrcParams<W> {
+        &self.params
+    }
+
+    /// The precomputed tables of strategy `S`, e.g. `[[W; 256]; N]` for
+    /// [`Sliced<N>`](crate::strategy::Sliced), where table `k` holds the effect of a byte
+    /// followed by `k` zero bytes.
+    ///
+    /// Entries are in the layout the update loop works on: reflected algorithms in the low
+    /// `width` bits, MSB-first ones at the top of `W`. For a ROM with right-aligned entries,
+    /// shift those of MSB-first algorithms right by `W::BITS - width`; `codegen::generate`
+    /// prints the table in that form.
+    pub fn tables(&self) -> &S::Tables<W> {
+        &self.tables
+    }
+
+    /// The catalogue name, if built with [`Crc::from_algorithm`].
+    pub fn name(&self) -> Option<&'static str> {
+        self.name
+    }
+
+    /// The checksum of `data` as a whole.
+    pub fn checksum(&self, data: &[u8]) -> W {
+        let mut digest = self.digest();
+        digest.update(data);
+        digest.finalize()
+    }
+
+    /// The checksum of the first `nbits` bits of `data`; see [`Digest::update_bits`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` holds fewer than `nbits` bits.
+    pub fn checksum_bits(&self, data: &[u8], nbits: usize) -> W {
+        let mut digest = self.digest();
+        digest.update_bits(data, nbits);
+        digest.finalize()
+    }
+
+    /// The checksum of a bit slice; see [`Digest::update_bitslice`].
+    #[cfg(feature = "bitvec")]
+    pub fn checksum_bitslice(&self, bits: &bitvec::slice::BitSlice<u8, bitvec::order::Msb0>) -> W {
+        let mut digest = self.digest();
+        digest.update_bitslice(bits);
+        digest.finalize()
+    }
+
+    /// The checksum of `data`, computed on all cores for large buffers.
+    ///
+    /// `data` is split into one chunk per available core, but no smaller than 1 MiB, the
+    /// chunks are checksummed on scoped threads and their checksums combined, which takes
+    /// time logarithmic in the chunk length.
+    #[cfg(feature = "std")]
+    pub fn checksum_parallel(&self, data: &[u8]) -> W
+    where
+        Self: Sync,
+        W: Send,
+    {
+        const MIN_CHUNK: usize = 1 << 20;
+        let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let chunk = data.len().div_ceil(threads).max(MIN_CHUNK);
+        self.checksum_chunks(data, chunk)
+    }
+
+    #[cfg(feature = "std")]
+    fn checksum_chunks(&self, data: &[u8], chunk: usize) -> W
+    where
+        Self: Sync,
+        W: Send,
+    {
+        if chunk >= data.len() {
+            return self.checksum(data);
+        }
+        let crcs: Vec<W> = std::thread::scope(|scope| {
+            let threads: Vec<_> = data
+                .chunks(chunk)
+                .map(|part| scope.spawn(move || self.checksum(part)))
+                .collect();
+            threads
+                .into_iter()
+                .map(|thread| thread.join().unwrap())
+                .collect()
+        });
+        let mut lens = data.chunks(chunk).map(|part| part.len() as u64);
+        lens.next();
+        crcs.into_iter()
+            .reduce(|crc, next| self.combine(crc, next, lens.next().unwrap()))
+            .unwrap()
+    }
+
+    /// The checksum of everything `reader` yields until end of file, read through one 64 KiB
+    /// buffer.
+    #[cfg(feature = "std")]
+    pub fn checksum_reader(&self, mut reader: impl std::io::Read) -> std::io::Result<W> {
+        let mut buf = vec![0; 64 * 1024];
+        let mut digest = self.digest();
+        loop {
+            match reader.read(&mut buf) {
+                Ok(0) => return Ok(digest.finalize()),
+                Ok(n) => digest.update(&buf[..n]),
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// The checksum of the file at `path`; see [`Crc::checksum_reader`].
+    #[cfg(feature = "std")]
+    pub fn checksum_path(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<W> {
+        self.checksum_reader(std::fs::File::open(path)?)
+    }
+
+    /// The checksum of the file at `path`, read and computed by `threads` threads at once.
+    ///
+    /// The file is cut into 64 MiB segments that the threads take in turn, each reading
+    /// through its own file handle, and the segment checksums are combined in file order as
+    /// in [`Crc::checksum_parallel`]. Only the length the file has when the call starts is
+    /// read. This pays off for large files on storage that serves several readers at once,
+    /// such as SSDs and network file systems.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `threads` is zero.
+    #[cfg(feature = "std")]
+    pub fn checksum_path_parallel(
+        &self,
+        path: impl AsRef<std::path::Path>,
+        threads: usize,
+    ) -> std::io::Result<W>
+    where
+        Self: Sync,
+        W: Send,
+    {
+        const SEGMENT: u64 = 64 << 20;
+        self.checksum_segments(path.as_ref(), threads, SEGMENT)
+    }
+
+    #[cfg(feature = "std")]
+    fn checksum_segments(
+        &self,
+        path: &std::path::Path,
+        threads: usize,
+        segment: u64,
+    ) -> std::io::Result<W>
+    where
+        Self: Sync,
+        W: Send,
+    {
+        use std::io::{Read, Seek, SeekFrom};
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        assert!(threads > 0, "no threads to checksum with");
+        let len = std::fs::metadata(path)?.len();
+        let segments = len.div_ceil(segment);
+        if threads == 1 || segments <= 1 {
+            return self.checksum_path(path);
+        }
+        let next = AtomicU64::new(0);
+        let worker = || -> std::io::Result<Vec<(u64, W)>> {
+            let mut file = std::fs::File::open(path)?;
+            let mut buf = vec![0; 1 << 20];
+            let mut crcs = Vec::new();
+            loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                if index >= segments {
+                    return Ok(crcs);
+                }
+                let start = index * segment;
+                let mut remaining = segment.min(len - start);
+                file.seek(SeekFrom::Start(start))?;
+                let mut digest = self.digest();
+                while remaining > 0 {
+                    let n = remaining.min(buf.len() as u64) as usize;
+                    file.read_exact(&mut buf[..n])?;
+                    digest.update(&buf[..n]);
+                    remaining -= n as u64;
+                }
+                crcs.push((index, digest.finalize()));
+            }
+        };
+        let mut crcs = std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..segments.min(threads as u64))
+                .map(|_| scope.spawn(worker))
+                .collect();
+            workers
+                .into_iter()
+                .map(|worker| worker.join().unwrap())
+                .collect::<std::io::Result<Vec<_>>>()
+        })?
+        .concat();
+        crcs.sort_unstable_by_key(|&(index, _)| index);
+        Ok(crcs
+            .into_iter()
+            .map(|(index, crc)| (segment.min(len - index * segment), crc))
+            .reduce(|(_, crc), (len_b, next)| (len_b, self.combine(crc, next, len_b)))
+            .unwrap()
+            .1)
+    }
+
+    /// The checksum of `a` followed by `b`, from the checksums of both and the length of `b`
+    /// in bytes, like zlib's `crc32_combine`. Takes time logarithmic in `len_b` and works for
+    /// every width and parameter set.
+    pub fn combine(&self, crc_a: W, crc_b: W, len_b: u64) -> W {
+        Params::from(self.params).combine(crc_a, crc_b, len_b)
+    }
+
+    /// The checksum of a message followed by `len` zero bytes, given the message's checksum
+    /// `crc`. Takes time logarithmic in `len`, so padding and holes in sparse files cost
+    /// nothing like feeding the zeros through [`Digest::update`].
+    pub fn extend_zeros(&self, crc: W, len: u64) -> W {
+        Params::from(self.params).extend_zeros(crc, len)
+    }
+
+    /// Checks that `expected` is the checksum of `data`.
+    pub fn verify(&self, data: &[u8], expected: W) -> Result<(), CrcError> {
+        self.check_match(expected.into(), self.checksum(data).into())
+    }
+
+    fn check_match(&self, expected: u128, actual: u128) -> Result<(), CrcError> {
+        if expected != actual {
+            return Err(CrcError::Mismatch {
+                expected,
+                actual,
+                algorithm: self.name,
+            });
+        }
+        Ok(())
+    }
+
+    /// Appends the checksum of `buf` to it in `endian` byte order, as
+    /// `ceil(width / 8)` bytes.
+    #[cfg(feature = "std")]
+    pub fn append_checksum(&self, buf: &mut Vec<u8>, endian: Endian) {
+        let crc: u128 = self.checksum(buf).into();
+        let len = self.trailer_len();
+        match endian {
+            Endian::Big => buf.extend_from_slice(&crc.to_be_bytes()[16 - len..]),
+            Endian::Little => buf.extend_from_slice(&crc.to_le_bytes()[..len]),
+        }
+    }
+
+    /// Checks the trailing checksum of `frame`, as written by [`Crc::append_checksum`], and
+    /// returns the data in front of it.
+    pub fn validate_and_strip<'a>(
+        &self,
+        frame: &'a [u8],
+        endian: Endian,
+    ) -> Result<&'a [u8], CrcError> {
+        let data_len = frame
+            .len()
+            .checked_sub(self.trailer_len())
+            .ok_or(CrcError::Truncated)?;
+        let (data, trailer) = frame.split_at(data_len);
+        let expected = read_uint(trailer, endian);
+        self.check_match(expected, self.checksum(data).into())?;
+        Ok(data)
+    }
+
+    /// The algorithm's residue, see [`Crc::check_residue`].
+    pub fn residue(&self) -> W {
+        Params::from(self.params).residue()
+    }
+
+    /// Validates a whole frame, data followed by its CRC, in one pass by comparing the
+    /// register with the algorithm's residue instead of recomputing and comparing the CRC.
+    ///
+    /// The CRC must be sent the way the algorithm reads bits: little-endian for reflected
+    /// algorithms, big-endian otherwise. Since frames are whole bytes, the width must be a
+    /// multiple of eight.
+    pub fn check_residue(&self, frame: &[u8]) -> bool {
+        self.checksum(frame) ^ self.params.xorout == self.residue()
+    }
+
+    /// Repairs a frame, laid out as for [`Crc::check_residue`], that has exactly one bit
+    /// flipped, and returns that bit's position as `byte * 8 + bit`, counting bits from the
+    /// least significant.
+    ///
+    /// Every single-bit error leaves its own difference to the residue as long as the
+    /// frame is shorter than the polynomial's period, so the bit can be found by walking the
+    /// frame once. Returns `None` for intact frames and when no single bit is to blame; a
+    /// frame is only flipped when one bit alone explains the difference. Frames with more
+    /// errors can still be miscorrected unless the algorithm's Hamming distance at this
+    /// length is at least 4.
+    pub fn correct_single_bit(&self, frame: &mut [u8]) -> Option<usize> {
+        let params = Params::from(self.params);
+        let syndrome = params.unfinalize(self.checksum(frame) ^ self.residue());
+        if syndrome == W::ZERO {
+            return None;
+        }
+        let linear = Params {
+            init: W::ZERO,
+            xorout: W::ZERO,
+            ..Params::from(self.params)
+        };
+        // The register difference a flip of each bit makes, from the last bit sent backwards:
+        // every bit earlier is one more multiplication by x.
+        let mut difference = linear.update_bits(W::ZERO, 1, 1);
+        let mut found = None;
+        for sent in (0..frame.len() * 8).rev() {
+            if difference == syndrome {
+                if found.is_some() {
+                    return None;
+                }
+                let bit = if self.params.refin {
+                    sent % 8
+                } else {
+                    7 - sent % 8
+                };
+                found = Some(sent / 8 * 8 + bit);
+            }
+            difference = linear.update_bits(difference, 0, 1);
+        }
+        let position = found?;
+        frame[position / 8] ^= 1 << (position % 8);
+        Some(position)
+    }
+
+    fn trailer_len(&self) -> usize {
+        self.params.width.div_ceil(8) as usize
+    }
+
+    /// Checks the lookup table by checksumming `"123456789"`, e.g. at boot to catch
+    /// corrupted RAM.
+    ///
+    /// The result is compared with the catalogued check value when the `Crc` was built with
+    /// [`Crc::from_algorithm`], and otherwise with the bit-by-bit reference implementation.
+    pub fn self_check(&self) -> bool {
+        const CHECK: &[u8] = b"123456789";
+        let expected = self
+            .check
+            .unwrap_or_else(|| Params::from(self.params).checksum(CHECK));
+        self.verify(CHECK, expected).is_ok()
+    }
+
+    /// Starts a new message.
+    pub fn digest(&self) -> Digest<'_, W, S> {
+        Digest {
+            crc: self,
+            register: table::start(&self.params),
+        }
+    }
+}
+
+/// Why [`Crc::verify`] or [`Crc::validate_and_strip`] rejected data.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CrcError {
+    /// The frame is shorter than the checksum.
+    Truncated,
+    Mismatch {
+        expected: u128,
+        actual: u128,
+        /// The catalogue name, if the [`Crc`] was built from a catalogue entry.
+        algorithm: Option<&'static str>,
+    },
+}
+
+impl fmt::Display for CrcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Truncated => write!(f, "frame is shorter than its checksum"),
+            Self::Mismatch {
+                expected,
+                actual,
+                algorithm,
+            } => {
+                if let Some(name) = algorithm {
+                    write!(f, "{} ", name)?;
+                }
+                write!(
+                    f,
+                    "checksum mismatch: expected {:#x}, computed {:#x}",
+                    expected, actual
+                )
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CrcError {}
+
+impl<W: Width, S: Strategy> fmt::Debug for Crc<W, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Crc")
+            .field("name", &self.name)
+            .field("params", &crate::checksum::HexParams(&self.params))
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<W: Width + defmt::Format, S: Strategy> defmt::Format for Crc<W, S> {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "Crc {{ name: {}, params: {} }}", self.name, self.params);
+    }
+}
+
+/// Instances are equal when they compute the same algorithm, whatever their tables. The
+/// catalogue name and check value are part of the comparison.
+impl<W: Width, S: Strategy> PartialEq for Crc<W, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.params == other.params && self.check == other.check && self.name == other.name
+    }
+}
+
+impl<W: Width, S: Strategy> Eq for Crc<W, S> {}
+
+// Derived impls would require `S: Clone`, although only the tables are cloned.
+impl<W: Width, S: Strategy> Clone for Crc<W, S> {
+    fn clone(&self) -> Self {
+        Self {
+            params: self.params,
+            check: self.check,
+            name: self.name,
+            folding: self.folding,
+            tables: self.tables.clone(),
+        }
+    }
+}
+
+/// The running state of one message, borrowing its [`Crc`].
+pub struct Digest<'a, W: Width, S: Strategy = Table256> {
+    crc: &'a Crc<W, S>,
+    register: W,
+}
+
+impl<W: Width, S: Strategy> Clone for Digest<'_, W, S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<W: Width, S: Strategy> Copy for Digest<'_, W, S> {}
+
+/// Shows the algorithm, by name if it has one, and the running checksum in hex.
+impl<W: Width, S: Strategy> fmt::Debug for Digest<'_, W, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("Digest");
+        match self.crc.name {
+            Some(name) => debug.field("name", &name),
+            None => debug.field("params", &crate::checksum::HexParams(&self.crc.params)),
+        };
+        debug
+            .field(
+                "checksum",
+                &crate::checksum::Hex::new(self.finalize(), self.crc.params.width),
+            )
+            .finish()
+    }
+}
+
+/// Shows the algorithm's name, or its width without one, and the running checksum in hex.
+#[cfg(feature = "defmt")]
+impl<W: Width, S: Strategy> defmt::Format for Digest<'_, W, S> {
+    fn format(&self, f: defmt::Formatter) {
+        let checksum = self.finalize_checksum();
+        match self.crc.name {
+            Some(name) => {
+                defmt::write!(f, "Digest {{ name: {=str}, checksum: {} }}", name, checksum)
+            }
+            None => defmt::write!(
+                f,
+                "Digest {{ width: {=u32}, checksum: {} }}",
+                self.crc.params.width,
+                checksum
+            ),
+        }
+    }
+}
+
+/// Digests are equal when their algorithms are and they reached the same state.
+impl<W: Width, S: Strategy> PartialEq for Digest<'_, W, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.register == other.register && self.crc == other.crc
+    }
+}
+
+impl<W: Width, S: Strategy> Eq for Digest<'_, W, S> {}
+
+impl<W: Width, S: Strategy> Digest<'_, W, S> {
+    pub fn update(&mut self, data: &[u8]) {
+        let crc = self.crc;
+        let refin = crc.params.refin;
+        self.register = accel::update(
+            &crc.params,
+            crc.folding.as_ref(),
+            self.register,
+            data,
+            |register, data| S::update(&crc.tables, refin, register, data),
+        );
+    }
+
+    /// Feeds each buffer of `bufs` in turn, as if they were one contiguous message.
+    pub fn update_vectored(&mut self, bufs: &[&[u8]]) {
+        self.update_segments(bufs.iter().copied())
+    }
+
+    /// Like [`Digest::update_vectored`] for any sequence of segments.
+    pub fn update_segments<'b>(&mut self, segments: impl IntoIterator<Item = &'b [u8]>) {
+        for segment in segments {
+            self.update(segment);
+        }
+    }
+
+    /// Feeds bytes produced lazily; see [`CRC::update_iter`](crate::CRC::update_iter).
+    pub fn update_iter(&mut self, bytes: impl IntoIterator<Item = u8>) {
+        let mut block = [0; 64];
+        let mut len = 0;
+        for byte in bytes {
+            block[len] = byte;
+            len += 1;
+            if len == block.len() {
+                self.update(&block);
+                len = 0;
+            }
+        }
+        self.update(&block[..len]);
+    }
+
+    /// Feeds the first `nbits` bits of `data`, for messages that do not end on a byte
+    /// boundary, such as CAN frames or USB tokens.
+    ///
+    /// Bits are taken in the order the algorithm reads them, so the bits of a partial last
+    /// byte are the most significant ones, or the least significant ones for reflected
+    /// algorithms; the others are ignored. Later updates continue right after the last bit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` holds fewer than `nbits` bits.
+    pub fn update_bits(&mut self, data: &[u8], nbits: usize) {
+        assert!(
+            nbits <= data.len() * 8,
+            "{} bits do not fit the buffer",
+            nbits
+        );
+        let (whole, rest) = (nbits / 8, nbits % 8);
+        self.update(&data[..whole]);
+        if rest == 0 {
+            return;
+        }
+        let params = &self.crc.params;
+        let last = if params.refin {
+            data[whole].reverse_bits()
+        } else {
+            data[whole]
+        };
+        let register = table::to_engine(params, self.register);
+        let register =
+            Params::from(*params).update_bits(register, (last >> (8 - rest)) as u64, rest as u32);
+        self.register = table::from_engine(params, register);
+    }
+
+    /// Feeds the bits of `bits` in slice order, the way they appear on the wire.
+    ///
+    /// For MSB-first algorithms, feeding `data.view_bits()` is the same as feeding `data`.
+    /// Reflected algorithms read each byte from its least significant bit, so for them the
+    /// slice has to list the bits in that order, as a parser of an LSB-first format does.
+    /// The bits go through the register one at a time, which suits header fields rather
+    /// than bulk data.
+    #[cfg(feature = "bitvec")]
+    pub fn update_bitslice(&mut self, bits: &bitvec::slice::BitSlice<u8, bitvec::order::Msb0>) {
+        use bitvec::field::BitField;
+        let params = &self.crc.params;
+        let engine = Params::from(*params);
+        let mut register = table::to_engine(params, self.register);
+        for chunk in bits.chunks(64) {
+            register = engine.update_bits(register, chunk.load_be::<u64>(), chunk.len() as u32);
+        }
+        self.register = table::from_engine(params, register);
+    }
+
+    /// Feeds 16-bit words in `endian` byte order; see
+    /// [`CRC::update_u16_slice`](crate::CRC::update_u16_slice).
+    pub fn update_u16_slice(&mut self, words: &[u16], endian: Endian) {
+        self.update_iter(words.iter().flat_map(|word| match endian {
+            Endian::Big => word.to_be_bytes(),
+            Endian::Little => word.to_le_bytes(),
+        }));
+    }
+
+    /// Feeds 32-bit words in `endian` byte order.
+    pub fn update_u32_slice(&mut self, words: &[u32], endian: Endian) {
+        self.update_iter(words.iter().flat_map(|word| match endian {
+            Endian::Big => word.to_be_bytes(),
+            Endian::Little => word.to_le_bytes(),
+        }));
+    }
+
+    /// Feeds `len` zero bytes in time logarithmic in `len`; see [`Crc::extend_zeros`].
+    pub fn update_zeros(&mut self, len: u64) {
+        let params = &self.crc.params;
+        let register = table::to_engine(params, self.register);
+        let register = Params::from(*params).update_zeros(register, len);
+        self.register = table::from_engine(params, register);
+    }
+
+    /// Starts over with a new message.
+    pub fn reset(&mut self) {
+        *self = self.crc.digest();
+    }
+
+    /// Continues from `checksum`, the finalized checksum of earlier data computed elsewhere,
+    /// e.g. by a hardware CRC unit or zlib's `crc32()`. The final XOR and output reflection
+    /// are undone, so updating from here gives the checksum of the earlier data followed by
+    /// the new one. Bits of `checksum` above the width are ignored.
+    pub fn resume_from(&mut self, checksum: W) {
+        self.set_raw_state(Params::from(self.crc.params).unfinalize(checksum));
+    }
+
+    /// The raw CRC register, before the output reflection and the final XOR, in the form
+    /// described at [`CRC32::raw_state`](crate::CRC32::raw_state): unreflected and
+    /// right-aligned, whatever the strategy keeps internally.
+    pub fn raw_state(&self) -> W {
+        table::to_engine(&self.crc.params, self.register)
+    }
+
+    /// Replaces the register by `register`, in the form [`Digest::raw_state`] returns. Bits
+    /// above the width are ignored.
+    pub fn set_raw_state(&mut self, register: W) {
+        let params = &self.crc.params;
+        let mask = !W::ZERO >> (W::BITS - params.width);
+        self.register = table::from_engine(params, register & mask);
+    }
+
+    /// Returns the checksum and resets the digest for the next message.
+    pub fn finalize_reset(&mut self) -> W {
+        let crc = self.finalize();
+        self.reset();
+        crc
+    }
+
+    /// The running checksum; same as [`Digest::finalize`], which has no side effects.
+    pub fn peek(&self) -> W {
+        self.finalize()
+    }
+
+    /// An independent copy of the digest.
+    pub fn clone_state(&self) -> Self {
+        *self
+    }
+
+    /// The checksum of the data so far; the digest can keep going afterwards.
+    pub fn finalize(&self) -> W {
+        table::finalize(&self.crc.params, self.register)
+    }
+
+    /// [`Digest::finalize`] with the width attached, for printing or comparing with a
+    /// parsed value.
+    pub fn finalize_checksum(&self) -> Checksum<W> {
+        Checksum::new(self.finalize(), self.crc.params.width)
+    }
+}
+
+/// Buffer length from which carry-less multiplication beats interleaved table lookups.
+const MULTI_STREAM_MAX: usize = 128;
+
+/// The running states of `N` independent messages of one [`Crc`].
+///
+/// Every lookup of a table-driven CRC needs the register the previous lookup produced, so a
+/// single message keeps the CPU waiting on memory. [`MultiStreamDigest::update`] advances `N`
+/// registers by a byte each per step, and their lookups overlap; three or four streams are
+/// usually the sweet spot. This helps with many small to medium records, e.g. taken four at
+/// a time:
+///
+/// ```text
+/// let mut streams = crc.multi_stream_digest::<4>();
+/// for chunk in records.chunks_exact(4) {
+///     streams.reset();
+///     streams.update([chunk[0], chunk[1], chunk[2], chunk[3]]);
+///     checksums.extend(streams.finalize());
+/// }
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct MultiStreamDigest<'a, W: Width, const N: usize = 4> {
+    crc: &'a Crc<W>,
+    registers: [W; N],
+}
+
+impl<'a, W: Width, const N: usize> MultiStreamDigest<'a, W, N> {
+    pub fn crc(&self) -> &'a Crc<W> {
+        self.crc
+    }
+
+    /// Feeds `data[i]` to stream `i`. The buffers may differ in length: they are interleaved
+    /// up to the shortest, and what is left of each is fed on its own.
+    ///
+    /// Once the shortest buffer reaches 128 bytes and the CPU can speed up
+    /// the algorithm, each stream is updated like a [`Digest`] instead, which is faster there.
+    pub fn update(&mut self, data: [&[u8]; N]) {
+        let crc = self.crc;
+        let len = data.iter().map(|data| data.len()).min().unwrap_or(0);
+        if len >= MULTI_STREAM_MAX && accel::available(&crc.params, crc.folding.as_ref()) {
+            for (register, data) in self.registers.iter_mut().zip(data) {
+                let mut digest = Digest {
+                    crc,
+                    register: *register,
+                };
+                digest.update(data);
+                *register = digest.register;
+            }
+            return;
+        }
+        table::update_interleaved(
+            &crc.tables,
+            crc.params.refin,
+            &mut self.registers,
+            data,
+            len,
+        );
+        for (register, data) in self.registers.iter_mut().zip(data) {
+            *register = table::update(&crc.tables, crc.params.refin, *register, &data[len..]);
+        }
+    }
+
+    /// The checksum of each stream so far; the streams can still be updated.
+    pub fn finalize(&self) -> [W; N] {
+        self.registers
+            .map(|register| table::finalize(&self.crc.params, register))
+    }
+
+    /// Restarts every stream.
+    pub fn reset(&mut self) {
+        self.registers = [table::start(&self.crc.params); N];
+    }
+
+    /// Splits the streams into digests that continue on their own.
+    pub fn into_digests(self) -> [Digest<'a, W>; N] {
+        self.registers.map(|register| Digest {
+            crc: self.crc,
+            register,
+        })
+    }
+}
+
+/// Why a [`CrcBuilder`] refused its settings.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BuildError {
+    /// No polynomial was set.
+    MissingPoly,
+    /// The width is zero or wider than the register type.
+    InvalidWidth(u32),
+    /// The named value has bits set above the width.
+    TooWide(&'static str),
+    /// The polynomial is zero, which makes every checksum equal.
+    ZeroPoly,
+    /// The polynomial lacks the `+ 1` term, so it is a multiple of `x` and the lowest bit of
+    /// the register never takes part.
+    EvenPoly,
+    /// The polynomial has more than one irreducible factor, with these degrees.
+    ReduciblePoly(Factors),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingPoly => write!(f, "no polynomial given"),
+            Self::InvalidWidth(width) => write!(f, "unsupported CRC width {}", width),
+            Self::TooWide(value) => write!(f, "{} does not fit the CRC width", value),
+            Self::ZeroPoly => write!(f, "polynomial is zero"),
+            Self::EvenPoly => write!(f, "polynomial is divisible by x"),
+            Self::ReduciblePoly(factors) => {
+                write!(f, "polynomial is reducible, factor degrees {:?}", factors)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BuildError {}
+
+/// Step-by-step construction of a [`Crc`] or its [`CrcParams`].
+///
+/// Only the polynomial is mandatory. The width defaults to that of `W`, `init` and `xorout`
+/// to zero, and neither input nor output are reflected.
+#[derive(Clone, Copy, Debug)]
+pub struct CrcBuilder<W> {
+    width: u32,
+    poly: Option<W>,
+    init: W,
+    refin: bool,
+    refout: bool,
+    xorout: W,
+}
+
+impl<W: Width> Default for CrcBuilder<W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Starts from existing parameters, e.g. to validate ones read from a file with
+/// [`CrcBuilder::params`].
+impl<W: Width> From<CrcParams<W>> for CrcBuilder<W> {
+    fn from(params: CrcParams<W>) -> Self {
+        Self {
+            width: params.width,
+            poly: Some(params.poly),
+            init: params.init,
+            refin: params.refin,
+            refout: params.refout,
+            xorout: params.xorout,
+        }
+    }
+}
+
+impl<W: Width> CrcBuilder<W> {
+    pub fn new() -> Self {
+        Self {
+            width: W::BITS,
+            poly: None,
+            init: W::ZERO,
+            refin: false,
+            refout: false,
+            xorout: W::ZERO,
+        }
+    }
+
+    pub fn width(mut self, width: u32) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// The polynomial in normal form, without its leading `x^width` term.
+    pub fn poly(mut self, poly: W) -> Self {
+        self.poly = Some(poly);
+        self
+    }
+
+    /// Sets the width and polynomial from a [`Polynomial`], which can be given in any
+    /// representation, e.g. `Polynomial::from_koopman(0x82608EDB)`.
+    pub fn polynomial(mut self, polynomial: Polynomial) -> Self {
+        self.width = polynomial.width();
+        self.poly = Some(W::from_u128(polynomial.normal().into()));
+        self
+    }
+
+    pub fn init(mut self, init: W) -> Self {
+        self.init = init;
+        self
+    }
+
+    /// Sets both `refin` and `refout`, which agree in almost every algorithm in use.
+    pub fn reflect(self, reflect: bool) -> Self {
+        self.refin(reflect).refout(reflect)
+    }
+
+    pub fn refin(mut self, refin: bool) -> Self {
+        self.refin = refin;
+        self
+    }
+
+    pub fn refout(mut self, refout: bool) -> Self {
+        self.refout = refout;
+        self
+    }
+
+    pub fn xorout(mut self, xorout: W) -> Self {
+        self.xorout = xorout;
+        self
+    }
+
+    /// Validates the settings.
+    pub fn params(self) -> Result<CrcParams<W>, BuildError> {
+        if !(1..=W::BITS).contains(&self.width) {
+            return Err(BuildError::InvalidWidth(self.width));
+        }
+        let poly = self.poly.ok_or(BuildError::MissingPoly)?;
+        let spare = !(!W::ZERO >> (W::BITS - self.width));
+        for (name, value) in [("poly", poly), ("init", self.init), ("xorout", self.xorout)] {
+            if value & spare != W::ZERO {
+                return Err(BuildError::TooWide(name));
+            }
+        }
+        Ok(CrcParams {
+            width: self.width,
+            poly,
+            init: self.init,
+            refin: self.refin,
+            refout: self.refout,
+            xorout: self.xorout,
+        })
+    }
+
+    pub fn build(self) -> Result<Crc<W>, BuildError> {
+        self.params().map(Crc::new)
+    }
+
+    /// Like [`CrcBuilder::build`], but also rejects a polynomial that is zero, even or
+    /// reducible, which is what a typo or a placeholder constant tends to look like.
+    ///
+    /// Some standard polynomials are reducible on purpose: `x + 1` times a primitive
+    /// polynomial, as in CRC-16/ARC's `0x8005`, detects every odd number of bit errors. Such
+    /// designs fail here with [`BuildError::ReduciblePoly`] listing the degrees `[1, 15]`;
+    /// build them with [`CrcBuilder::build`], or inspect a polynomial first with
+    /// [`gf2::factors`].
+    pub fn build_validated(self) -> Result<Crc<W>, BuildError> {
+        let params = self.params()?;
+        let poly: u128 = params.poly.into();
+        if poly == 0 {
+            return Err(BuildError::ZeroPoly);
+        }
+        if poly & 1 == 0 {
+            return Err(BuildError::EvenPoly);
+        }
+        let factors = gf2::factors(params.width, poly);
+        if !factors.is_irreducible() {
+            return Err(BuildError::ReduciblePoly(factors));
+        }
+        Ok(Crc::new(params))
+    }
+}
+
+impl<W: Width, S: Strategy> crate::backend::CrcBackend<W> for Digest<'_, W, S> {
+    fn update(&mut self, data: &[u8]) {
+        Digest::update(self, data)
+    }
+
+    fn finalize(&mut self) -> W {
+        Digest::finalize(self)
+    }
+}
+
+impl<W: Width, S: Strategy> DynCrc for Digest<'_, W, S> {
+    fn dyn_update(&mut self, data: &[u8]) {
+        Digest::update(self, data)
+    }
+
+    fn dyn_finalize(&self) -> u128 {
+        Digest::finalize(self).into()
+    }
+
+    fn dyn_reset(&mut self) {
+        Digest::reset(self)
+    }
+}
+
+impl<W: Width, S: Strategy> Extend<u8> for Digest<'_, W, S> {
+    fn extend<I: IntoIterator<Item = u8>>(&mut self, iter: I) {
+        self.update_iter(iter);
+    }
+}
+
+impl<'b, W: Width, S: Strategy> Extend<&'b u8> for Digest<'_, W, S> {
+    fn extend<I: IntoIterator<Item = &'b u8>>(&mut self, iter: I) {
+        self.update_iter(iter.into_iter().copied());
+    }
+}
+
+/// `finish` is the checksum, truncated to 64 bits for wider algorithms.
+impl<W: Width, S: Strategy> core::hash::Hasher for Digest<'_, W, S> {
+    fn write(&mut self, bytes: &[u8]) {
+        self.update(bytes);
+    }
+
+    fn finish(&self) -> u64 {
+        Into::<u128>::into(self.finalize()) as u64
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Width, S: Strategy> std::io::Write for Digest<'_, W, S> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check<W: Width + fmt::Debug>(algorithm: &catalog::Algorithm<W>) {
+        let crc = Crc::new(algorithm);
+        let mut digest = crc.digest();
+        digest.update(b"1234");
+        let mut other = crc.digest();
+        other.update(b"12345678");
+        digest.update(b"56789");
+        other.update(b"9");
+        assert_eq!(digest.finalize(), algorithm.check, "{}", algorithm.name);
+        assert_eq!(
+            other.finalize_reset(),
+            algorithm.check,
+            "{}",
+            algorithm.name
+        );
+        other.update(b"123456789");
+        assert_eq!(other.finalize(), algorithm.check, "{}", algorithm.name);
+    }
+
+    #[test]
+    fn digest_test() {
+        check(&catalog::CRC_5_USB);
+        check(&catalog::CRC_8_AUTOSAR);
+        check(&catalog::CRC_12_UMTS);
+        check(&catalog::CRC_16_MODBUS);
+        check(&catalog::CRC_16_XMODEM);
+        check(&catalog::CRC_24_OPENPGP);
+        check(&catalog::CRC_32_BZIP2);
+        check(&catalog::CRC_32_ISO_HDLC);
+        check(&catalog::CRC_64_NVME);
+        check(&catalog::CRC_64_ECMA_182);
+
+        let crc = Crc::<u128>::new(crate::CrcParams::from(0x8BB7u128 << 100 | 1));
+        let mut digest = crc.digest();
+        digest.update(b"hello world");
+        let mut state = <crate::CRC128 as crate::CRC>::create(crc.params);
+        crate::CRC::update(&mut state, b"hello world");
+        assert_eq!(digest.finalize(), crate::CRC::finalize(&state));
+
+        let crc = Crc::new(&catalog::CRC_16_MODBUS);
+        let mut digest = crc.digest();
+        digest.update_u16_slice(&[0x3231, 0x3433], Endian::Little);
+        digest.update_u32_slice(&[0x3536_3738], Endian::Big);
+        digest.update(b"9");
+        assert_eq!(digest.finalize(), catalog::CRC_16_MODBUS.check);
+    }
+
+    #[test]
+    fn builder_test() {
+        let crc = CrcBuilder::new()
+            .width(16)
+            .poly(0x1021u16)
+            .init(0xFFFF)
+            .reflect(false)
+            .xorout(0)
+            .build()
+            .unwrap();
+        assert_eq!(*crc.params(), catalog::CRC_16_IBM_3740.params);
+        let umts = CrcBuilder::new().width(12).poly(0x80Fu16).refout(true);
+        assert_eq!(umts.params(), Ok(catalog::CRC_12_UMTS.params));
+        let zlib = CrcBuilder::new()
+            .polynomial(Polynomial::from_koopman(0x8260_8EDB))
+            .init(!0u32)
+            .reflect(true)
+            .xorout(!0);
+        assert_eq!(zlib.params(), Ok(catalog::CRC_32_ISO_HDLC.params));
+        assert_eq!(
+            CrcBuilder::<u8>::new()
+                .polynomial(Polynomial::from_reversed(32, 0xEDB8_8320))
+                .params(),
+            Err(BuildError::InvalidWidth(32))
+        );
+
+        assert_eq!(
+            CrcBuilder::<u8>::new().width(9).poly(0x07).params(),
+            Err(BuildError::InvalidWidth(9))
+        );
+        assert_eq!(
+            CrcBuilder::<u16>::new().width(12).params(),
+            Err(BuildError::MissingPoly)
+        );
+        assert_eq!(
+            CrcBuilder::new().width(5).poly(0x05u8).init(0x20).params(),
+            Err(BuildError::TooWide("init"))
+        );
+    }
+
+    #[test]
+    fn build_validated_test() {
+        assert!(CrcBuilder::from(catalog::CRC_32_ISO_HDLC.params)
+            .build_validated()
+            .is_ok());
+        assert_eq!(
+            CrcBuilder::<u16>::new()
+                .poly(0)
+                .build_validated()
+                .unwrap_err(),
+            BuildError::ZeroPoly
+        );
+        assert_eq!(
+            CrcBuilder::<u16>::new()
+                .poly(0x1020)
+                .build_validated()
+                .unwrap_err(),
+            BuildError::EvenPoly
+        );
+        let Err(BuildError::ReduciblePoly(factors)) =
+            CrcBuilder::from(catalog::CRC_16_ARC.params).build_validated()
+        else {
+            panic!("0x8005 is reducible");
+        };
+        assert!(factors.degrees().eq([1, 15]));
+
+        // The placeholder default of CRC128 is not a generator anyone designed.
+        let default = <crate::CRC128 as Default>::default().params;
+        assert!(matches!(
+            CrcBuilder::from(default).build_validated(),
+            Err(BuildError::ReduciblePoly(_))
+        ));
+    }
+
+    #[test]
+    fn self_check_test() {
+        let crc = Crc::from_algorithm(&catalog::CRC_16_KERMIT);
+        assert!(crc.self_check());
+        assert_eq!(crc.verify(b"123456789", 0x2189), Ok(()));
+        assert_eq!(crc.checksum(b"hello world"), {
+            let mut digest = crc.digest();
+            digest.update(b"hello ");
+            digest.update(b"world");
+            digest.finalize()
+        });
+        let err = crc.verify(b"123456780", 0x2189).unwrap_err();
+        assert!(matches!(
+            err,
+            CrcError::Mismatch {
+                expected: 0x2189,
+                algorithm: Some("CRC-16/KERMIT"),
+                ..
+            }
+        ));
+        assert!(err
+            .to_string()
+            .starts_with("CRC-16/KERMIT checksum mismatch"));
+        assert!(Crc::new(&catalog::CRC_5_USB).self_check());
+        assert!(Crc::new(0x1234_5679u32).self_check());
+
+        // A flipped bit in the entry the first byte looks up is caught.
+        let mut crc = Crc::from_algorithm(&catalog::CRC_32_ISO_HDLC);
+        crc.tables[0xCE] ^= 0x100;
+        assert!(!crc.self_check());
+        let mut crc = Crc::new(&catalog::CRC_32_ISO_HDLC);
+        crc.tables[0xCE] ^= 0x100;
+        assert!(!crc.self_check());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn frame_test() {
+        let modbus = Crc::new(&catalog::CRC_16_MODBUS);
+        let mut frame = vec![0x01, 0x03, 0x00, 0x00, 0x00, 0x0A];
+        modbus.append_checksum(&mut frame, Endian::Little);
+        assert_eq!(frame[6..], [0xC5, 0xCD]);
+        assert_eq!(
+            modbus.validate_and_strip(&frame, Endian::Little),
+            Ok(&frame[..6])
+        );
+        assert_eq!(
+            modbus.validate_and_strip(&frame, Endian::Big),
+            Err(CrcError::Mismatch {
+                expected: 0xC5CD,
+                actual: 0xCDC5,
+                algorithm: None,
+            })
+        );
+        assert_eq!(
+            modbus.validate_and_strip(&frame[..1], Endian::Little),
+            Err(CrcError::Truncated)
+        );
+
+        // CRC-24 trailers are three bytes.
+        let openpgp = Crc::new(&catalog::CRC_24_OPENPGP);
+        let mut frame = b"123456789".to_vec();
+        openpgp.append_checksum(&mut frame, Endian::Big);
+        assert_eq!(frame[9..], [0x21, 0xCF, 0x02]);
+        assert_eq!(
+            openpgp.validate_and_strip(&frame, Endian::Big),
+            Ok(&b"123456789"[..])
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn residue_test() {
+        for (algorithm, endian) in [
+            (catalog::CRC_32_ISO_HDLC, Endian::Little),
+            (catalog::CRC_32_BZIP2, Endian::Big),
+        ] {
+            let crc = Crc::new(&algorithm);
+            assert_eq!(crc.residue(), algorithm.residue);
+            let mut frame = b"hello world".to_vec();
+            crc.append_checksum(&mut frame, endian);
+            assert!(crc.check_residue(&frame));
+            frame[3] ^= 0x10;
+            assert!(!crc.check_residue(&frame));
+        }
+        let sdlc = Crc::new(&catalog::CRC_16_IBM_SDLC);
+        let mut frame = vec![0x55; 40];
+        sdlc.append_checksum(&mut frame, Endian::Little);
+        assert!(sdlc.check_residue(&frame));
+    }
+
+    #[cfg(feature = "std")]
+    fn correct_each_bit<W: Width>(algorithm: &catalog::Algorithm<W>, endian: Endian) {
+        let crc = Crc::new(algorithm);
+        let mut frame = b"\x01\x03\x00\x00\x00\x0A".to_vec();
+        crc.append_checksum(&mut frame, endian);
+        let intact = frame.clone();
+        assert_eq!(crc.correct_single_bit(&mut frame), None);
+        for position in 0..frame.len() * 8 {
+            frame[position / 8] ^= 1 << (position % 8);
+            assert_eq!(crc.correct_single_bit(&mut frame), Some(position));
+            assert_eq!(frame, intact, "{} bit {}", algorithm.name, position);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn correct_single_bit_test() {
+        correct_each_bit(&catalog::CRC_16_MODBUS, Endian::Little);
+        correct_each_bit(&catalog::CRC_32_BZIP2, Endian::Big);
+
+        // CRC-32 has a Hamming distance above 4 at this length, so two flipped bits are
+        // never mistaken for one.
+        let crc = Crc::new(&catalog::CRC_32_BZIP2);
+        let mut frame = vec![0x5A; 64];
+        crc.append_checksum(&mut frame, Endian::Big);
+        frame[1] ^= 0x01;
+        frame[40] ^= 0x80;
+        let damaged = frame.clone();
+        assert_eq!(crc.correct_single_bit(&mut frame), None);
+        assert_eq!(frame, damaged);
+    }
+
+    fn strategy<W: Width + fmt::Debug, S: Strategy>(params: CrcParams<W>, data: &[u8]) {
+        // Without folding, so that large buffers really go through the tables.
+        let mut bytewise = Crc::new(params);
+        let mut other = Crc::<W, S>::with_strategy(params);
+        bytewise.folding = None;
+        other.folding = None;
+        let folded = Crc::<W, S>::with_strategy(params);
+        for (start, end) in [(0, 0), (3, 10), (1, 998), (0, data.len())] {
+            let data = &data[start..end];
+            let expected = bytewise.checksum(data);
+            assert_eq!(other.checksum(data), expected, "{:?}", params);
+            assert_eq!(folded.checksum(data), expected, "{:?}", params);
+        }
+        let mut digest = other.digest();
+        digest.update(&data[..5]);
+        digest.update(&data[5..]);
+        assert_eq!(digest.finalize(), bytewise.checksum(data));
+    }
+
+    #[test]
+    fn strategy_test() {
+        use crate::strategy::{NoTable, Sliced, Table16};
+
+        let data: Vec<u8> = (0..1000u32).map(|i| (i * 7 + i / 13) as u8).collect();
+        let darc = CrcParams {
+            width: 82,
+            poly: 0x0_308C_0111_0110_1140_0440_u128,
+            init: 0,
+            refin: true,
+            refout: true,
+            xorout: 0,
+        };
+        macro_rules! all {
+            ($params:expr) => {
+                strategy::<_, NoTable>($params, &data);
+                strategy::<_, Table16>($params, &data);
+                strategy::<_, Sliced<8>>($params, &data);
+                strategy::<_, Sliced<16>>($params, &data);
+            };
+        }
+        all!(catalog::CRC_5_USB.params);
+        all!(catalog::CRC_8_SMBUS.params);
+        all!(catalog::CRC_12_UMTS.params);
+        all!(catalog::CRC_24_OPENPGP.params);
+        all!(catalog::CRC_32_ISO_HDLC.params);
+        all!(catalog::CRC_32_BZIP2.params);
+        all!(catalog::CRC_64_XZ.params);
+        all!(darc);
+        all!(CrcParams::from(0x8BB7u128 << 100 | 1));
+        let crc = Crc::<u32, Sliced<16>>::with_strategy_from_algorithm(&catalog::CRC_32_ISCSI);
+        assert!(crc.self_check());
+        assert!(Crc::<u16, NoTable>::with_strategy(&catalog::CRC_16_KERMIT).self_check());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn parallel_test() {
+        let data: Vec<u8> = (0..10_007u32)
+            .map(|i| ((i * 13) ^ (i >> 5)) as u8)
+            .collect();
+        for algorithm in [&catalog::CRC_32_ISO_HDLC, &catalog::CRC_32_BZIP2] {
+            let crc = Crc::new(algorithm);
+            let expected = crc.checksum(&data);
+            for chunk in [100, 4096, 10_006, 20_000] {
+                assert_eq!(crc.checksum_chunks(&data, chunk), expected, "{}", chunk);
+            }
+            assert_eq!(crc.checksum_parallel(&data), expected);
+        }
+        let crc = Crc::new(&catalog::CRC_12_UMTS);
+        assert_eq!(crc.checksum_chunks(&data, 333), crc.checksum(&data));
+    }
+
+    #[test]
+    fn traits_test() {
+        let crc = Crc::from_algorithm(&catalog::CRC_16_MODBUS);
+        assert_eq!(crc, Crc::from_algorithm(&catalog::CRC_16_MODBUS));
+        assert_ne!(crc, Crc::new(&catalog::CRC_16_MODBUS));
+        let mut digest = crc.digest();
+        digest.update(b"123456789");
+        assert_eq!(
+            format!("{:?}", digest),
+            "Digest { name: \"CRC-16/MODBUS\", checksum: 0x4b37 }"
+        );
+        let copy = digest;
+        digest.update(b"more");
+        assert_ne!(copy, digest);
+        assert_eq!(copy.finalize(), 0x4B37);
+
+        let custom = Crc::new(catalog::CRC_12_UMTS.params);
+        assert_eq!(
+            format!("{:?}", custom.digest()),
+            "Digest { params: CrcParams { width: 12, poly: 0x80f, init: 0x000, refin: false, \
+             refout: true, xorout: 0x000 }, checksum: 0x000 }"
+        );
+        use crate::CRC;
+        let mut state = crate::CRC32::from_algorithm(&catalog::CRC_32_ISO_HDLC);
+        state.update(b"123456789");
+        assert!(format!("{:?}", state).ends_with("checksum: 0xcbf43926, .. }"));
+        assert_ne!(
+            state,
+            crate::CRC32::from_algorithm(&catalog::CRC_32_ISO_HDLC)
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn cached_test() {
+        let crc = Crc::<u32>::cached(&catalog::CRC_32_ISO_HDLC);
+        assert_eq!(crc.checksum(b"123456789"), 0xCBF4_3926);
+        let shared: Vec<usize> = std::thread::scope(|scope| {
+            let threads: Vec<_> = (0..4)
+                .map(|_| {
+                    scope.spawn(|| {
+                        Crc::<u32>::cached(&catalog::CRC_32_ISO_HDLC) as *const _ as usize
+                    })
+                })
+                .collect();
+            threads
+                .into_iter()
+                .map(|thread| thread.join().unwrap())
+                .collect()
+        });
+        assert!(shared.iter().all(|&ptr| ptr == crc as *const _ as usize));
+
+        let sliced = Crc::<u32, crate::strategy::Sliced<16>>::cached(&catalog::CRC_32_ISO_HDLC);
+        assert_eq!(sliced.checksum(b"123456789"), 0xCBF4_3926);
+        let bzip2 = Crc::<u32>::cached(&catalog::CRC_32_BZIP2);
+        assert!(!core::ptr::eq(crc, bzip2));
+        assert_eq!(bzip2.name(), Some("CRC-32/BZIP2"));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn reader_test() {
+        let data: Vec<u8> = (0..200_000u32).map(|i| (i * 7) as u8).collect();
+        let crc = Crc::new(&catalog::CRC_64_XZ);
+        let expected = crc.checksum(&data);
+        assert_eq!(crc.checksum_reader(&data[..]).unwrap(), expected);
+
+        let path = std::env::temp_dir().join(format!("crclib-path-{}", std::process::id()));
+        std::fs::write(&path, &data).unwrap();
+        assert_eq!(crc.checksum_path(&path).unwrap(), expected);
+        std::fs::remove_file(&path).unwrap();
+        assert!(crc.checksum_path(&path).is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn path_parallel_test() {
+        let data: Vec<u8> = (0..100_003u32).map(|i| (i ^ i >> 7) as u8).collect();
+        let crc = Crc::new(&catalog::CRC_32_ISCSI);
+        let expected = crc.checksum(&data);
+        let path = std::env::temp_dir().join(format!("crclib-parallel-{}", std::process::id()));
+        std::fs::write(&path, &data).unwrap();
+        for (threads, segment) in [(1, 1000), (3, 1000), (4, 100_003), (8, 4096), (2, 1 << 20)] {
+            assert_eq!(
+                crc.checksum_segments(&path, threads, segment).unwrap(),
+                expected,
+                "{} {}",
+                threads,
+                segment
+            );
+        }
+        assert_eq!(crc.checksum_path_parallel(&path, 4).unwrap(), expected);
+        std::fs::remove_file(&path).unwrap();
+        assert!(crc.checksum_path_parallel(&path, 4).is_err());
+    }
+
+    #[test]
+    fn multi_stream_test() {
+        let data: Vec<u8> = (0..5000u32).map(|i| (i * 7 + i / 89) as u8).collect();
+        let records: [&[u8]; 4] = [&data[..1000], &data[1000..1003], &data[..0], &data[1003..]];
+        let long: [&[u8]; 3] = [&data[..1000], &data[1000..1300], &data[1300..]];
+        for crc in [
+            Crc::new(&catalog::CRC_32_ISCSI),
+            Crc::new(&catalog::CRC_32_BZIP2),
+        ] {
+            let mut streams = crc.multi_stream_digest();
+            streams.update(records.map(|record| &record[..record.len() / 2]));
+            streams.update(records.map(|record| &record[record.len() / 2..]));
+            assert_eq!(
+                streams.finalize(),
+                records.map(|record| crc.checksum(record))
+            );
+            let mut long_streams = crc.multi_stream_digest();
+            long_streams.update(long);
+            assert_eq!(
+                long_streams.finalize(),
+                long.map(|record| crc.checksum(record))
+            );
+
+            let [mut first, ..] = streams.into_digests();
+            first.update(b"tail");
+            let mut whole = data[..1000].to_vec();
+            whole.extend_from_slice(b"tail");
+            assert_eq!(first.finalize(), crc.checksum(&whole));
+        }
+
+        let crc = Crc::new(&catalog::CRC_5_USB);
+        let mut streams = crc.multi_stream_digest::<3>();
+        streams.update([b"1234", b"12345678", b"x"]);
+        streams.reset();
+        streams.update([b"123456789"; 3]);
+        assert_eq!(streams.finalize(), [catalog::CRC_5_USB.check; 3]);
+    }
+
+    #[test]
+    fn resume_from_test() {
+        let data = b"The quick brown fox jumps over the lazy dog";
+        for algorithm in [
+            &catalog::CRC_32_ISO_HDLC,
+            &catalog::CRC_32_BZIP2,
+            &catalog::CRC_32_ISCSI,
+        ] {
+            let crc = Crc::new(algorithm);
+            let mut digest = crc.digest();
+            digest.update(b"earlier data");
+            digest.resume_from(crc.checksum(&data[..10]));
+            digest.update(&data[10..]);
+            assert_eq!(digest.finalize(), crc.checksum(data), "{}", algorithm.name);
+        }
+        // Reflected output only, and a width below the register's.
+        let crc = Crc::new(&catalog::CRC_12_UMTS);
+        let mut digest = crc.digest();
+        digest.resume_from(crc.checksum(&data[..7]) | 0xF000);
+        digest.update(&data[7..]);
+        assert_eq!(digest.finalize(), crc.checksum(data));
+        let crc32 = Crc::new(&catalog::CRC_32_ISO_HDLC);
+        let mut digest = crc32.digest();
+        digest.resume_from(crate::zlib::crc32(0, b"1234"));
+        digest.update(b"56789");
+        assert_eq!(digest.finalize(), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn raw_state_test() {
+        use crate::CRC;
+        for algorithm in [&catalog::CRC_32_ISCSI, &catalog::CRC_32_MPEG_2] {
+            let crc = Crc::new(algorithm);
+            let mut digest = crc.digest();
+            digest.update(b"1234");
+            let mut state = crate::CRC32::from_algorithm(algorithm);
+            state.update(b"1234");
+            assert_eq!(digest.raw_state(), state.raw_state());
+
+            let mut resumed = crc.digest();
+            resumed.set_raw_state(digest.raw_state());
+            resumed.update(b"56789");
+            assert_eq!(resumed.finalize(), algorithm.check);
+        }
+        let crc = Crc::new(&catalog::CRC_12_UMTS);
+        let mut digest = crc.digest();
+        digest.set_raw_state(0xF123);
+        assert_eq!(digest.raw_state(), 0x123);
+    }
+
+    #[test]
+    fn combine_test() {
+        let data = b"The quick brown fox jumps over the lazy dog";
+        let darc = CrcParams {
+            width: 82,
+            poly: 0x0_308C_0111_0110_1140_0440_u128,
+            init: 0,
+            refin: true,
+            refout: true,
+            xorout: 0,
+        };
+        let darc = Crc::new(darc);
+        for split in [0, 1, 20, data.len()] {
+            let (a, b) = data.split_at(split);
+            let combine =
+                |crc: &Crc<u32>| crc.combine(crc.checksum(a), crc.checksum(b), b.len() as u64);
+            for algorithm in [&catalog::CRC_32_ISO_HDLC, &catalog::CRC_32_MPEG_2] {
+                let crc = Crc::new(algorithm);
+                assert_eq!(combine(&crc), crc.checksum(data));
+            }
+            let usb = Crc::new(&catalog::CRC_5_USB);
+            let combined = usb.combine(usb.checksum(a), usb.checksum(b), b.len() as u64);
+            assert_eq!(combined, usb.checksum(data));
+            let combined = darc.combine(darc.checksum(a), darc.checksum(b), b.len() as u64);
+            assert_eq!(combined, darc.checksum(data));
+        }
+    }
+
+    fn zeros<W: Width + fmt::Debug>(algorithm: &catalog::Algorithm<W>) {
+        let crc = Crc::new(algorithm);
+        for len in [0, 1, 17, 3000] {
+            let mut message = b"123456789".to_vec();
+            message.resize(9 + len, 0);
+            let expected = crc.checksum(&message);
+            assert_eq!(crc.extend_zeros(algorithm.check, len as u64), expected);
+            let mut digest = crc.digest();
+            digest.update_vectored(&[b"1234", b"56789"]);
+            digest.update_zeros(len as u64);
+            assert_eq!(digest.finalize(), expected, "{} {}", algorithm.name, len);
+        }
+    }
+
+    #[test]
+    fn bits_test() {
+        let can = Crc::new(&catalog::CRC_15_CAN);
+        let frame = [0x12, 0x34, 0x56, 0x78, 0x9A];
+        for len in [0, 5, 19, 33, 40] {
+            assert_eq!(
+                can.checksum_bits(&frame, len),
+                crate::can::crc15(&frame, len)
+            );
+        }
+        assert_eq!(can.checksum_bits(&frame, 40), can.checksum(&frame));
+
+        let usb = Crc::new(&catalog::CRC_5_USB);
+        let field = crate::usb::address_endpoint(0x15, 0xE);
+        assert_eq!(
+            usb.checksum_bits(&field.to_le_bytes(), 11),
+            crate::usb::token_crc5(field)
+        );
+        // Bits past the end are ignored, and updates resume mid-byte.
+        let mut digest = usb.digest();
+        digest.update_bits(&[field as u8 | 0x80], 7);
+        digest.update_bits(&[(field >> 7) as u8 | 0xF0], 4);
+        assert_eq!(digest.finalize(), crate::usb::token_crc5(field));
+    }
+
+    #[test]
+    fn table_test() {
+        let crc = Crc::new(&catalog::CRC_32_ISO_HDLC);
+        assert_eq!(crc.table()[1], 0x7707_3096);
+        assert_eq!(crc.table()[255], 0x2D02_EF8D);
+        let sliced =
+            Crc::<u32, crate::strategy::Sliced<8>>::with_strategy(&catalog::CRC_32_ISO_HDLC);
+        assert_eq!(&sliced.tables()[0], crc.table());
+        assert_eq!(
+            sliced.tables()[1][1],
+            crc.table()[crc.table()[1] as usize & 0xFF] ^ 0x0077_0730
+        );
+
+        assert_eq!(Crc::new(&catalog::CRC_16_XMODEM).table()[1], 0x1021);
+        // CRC-15 sits in the top 15 bits of its u16 entries.
+        assert_eq!(Crc::new(&catalog::CRC_15_CAN).table()[1] >> 1, 0x4599);
+    }
+
+    #[cfg(feature = "bitvec")]
+    #[test]
+    fn bitslice_test() {
+        use bitvec::prelude::*;
+        let can = Crc::new(&catalog::CRC_15_CAN);
+        let frame: Vec<u8> = (0..100u8).map(|i| i.wrapping_mul(37)).collect();
+        assert_eq!(
+            can.checksum_bitslice(frame.view_bits()),
+            can.checksum(&frame)
+        );
+        let bits = &frame.view_bits::<Msb0>()[3..700];
+        let mut shifted = bits.to_bitvec();
+        shifted.force_align();
+        assert_eq!(
+            can.checksum_bitslice(bits),
+            can.checksum_bits(shifted.as_raw_slice(), bits.len())
+        );
+
+        // USB tokens go out LSB first.
+        let field = crate::usb::address_endpoint(0x15, 0xE);
+        let mut token = BitVec::<u8, Msb0>::new();
+        token.extend((0..11).map(|bit| field >> bit & 1 != 0));
+        let usb = Crc::new(&catalog::CRC_5_USB);
+        assert_eq!(usb.checksum_bitslice(&token), crate::usb::token_crc5(field));
+    }
+
+    #[test]
+    fn zeros_test() {
+        zeros(&catalog::CRC_5_USB);
+        zeros(&catalog::CRC_12_UMTS);
+        zeros(&catalog::CRC_16_MODBUS);
+        zeros(&catalog::CRC_32_ISO_HDLC);
+        zeros(&catalog::CRC_64_ECMA_182);
+        // A gigabyte of zeros, which would take a while to feed through the table.
+        let crc = Crc::new(&catalog::CRC_64_XZ);
+        let gib = crc.extend_zeros(crc.checksum(b""), 1 << 30);
+        let half = crc.extend_zeros(crc.checksum(b""), 1 << 29);
+        assert_eq!(crc.combine(half, half, 1 << 29), gib);
+    }
+}
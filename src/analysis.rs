@@ -0,0 +1,243 @@
+//! Error-detection properties of a generator polynomial at a given message length.
+//!
+//! A CRC misses exactly the error patterns that are multiples of its generator polynomial,
+//! so how good a polynomial is depends on the message length: the Hamming distance (the
+//! fewest flipped bits that can go undetected) drops as messages grow, down to 2 once the
+//! codeword is longer than the polynomial's period. [`analyze`] computes what Koopman's CRC
+//! tables list for one polynomial and length. Only the polynomial matters; init, reflection
+//! and xorout leave the error-detection properties unchanged.
+//!
+//! Undetected patterns are searched up to weight 4, which takes time quadratic in the
+//! codeword length; that is enough to classify short and medium messages. Above that the
+//! result is a lower bound.
+
+use std::collections::{HashMap, HashSet};
+
+/// The Hamming distance of a polynomial at a message length.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HammingDistance {
+    /// Some error of this many bits goes undetected, and every smaller one is detected.
+    Exact(u32),
+    /// Every error of fewer bits is detected; larger weights were not searched.
+    AtLeast(u32),
+}
+
+/// A property that makes a polynomial a poor choice.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Weakness {
+    /// The polynomial lacks the `+1` term, so the low bits of the CRC carry no information.
+    NoConstantTerm,
+    /// The polynomial's period is shorter than the codeword, so two flipped bits `period`
+    /// apart go undetected.
+    ShortPeriod { period: usize },
+}
+
+/// What [`analyze`] found.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Report {
+    pub hamming_distance: HammingDistance,
+    /// The longest burst error that is always detected, in bits.
+    pub burst: u32,
+    /// Whether every error flipping an odd number of bits is detected, which is the case
+    /// when `x + 1` divides the polynomial.
+    pub detects_odd_errors: bool,
+    pub weaknesses: Vec<Weakness>,
+}
+
+/// Analyzes the `width`-bit polynomial `poly`, in normal form without the `x^width` term, for
+/// messages of `data_bits` bits followed by the CRC.
+///
+/// # Panics
+///
+/// Panics if `width` is 0 or above 64.
+pub fn analyze(width: u32, poly: u64, data_bits: usize) -> Report {
+    assert!(
+        (1..=64).contains(&width),
+        "CRC width {} out of range",
+        width
+    );
+    let mut weaknesses = Vec::new();
+    // The generator is x^t times a polynomial with a constant term; the x^t factor only
+    // shifts codewords, so the rest is analysed on a codeword t bits shorter.
+    let shift = if poly == 0 {
+        width
+    } else {
+        poly.trailing_zeros()
+    };
+    if shift > 0 {
+        weaknesses.push(Weakness::NoConstantTerm);
+    }
+    let odd = divisible_by_x_plus_1(poly);
+    let burst = width - shift;
+    if burst == 0 {
+        return Report {
+            hamming_distance: HammingDistance::Exact(1),
+            burst,
+            detects_odd_errors: false,
+            weaknesses,
+        };
+    }
+
+    let reduced = Reduced {
+        width: burst,
+        poly: poly >> shift,
+    };
+    let len = data_bits + burst as usize;
+    let residues = reduced.residues(len);
+    let hamming_distance = if let Some(period) = (1..len).find(|&k| residues[k] == 1) {
+        weaknesses.push(Weakness::ShortPeriod { period });
+        HammingDistance::Exact(2)
+    } else if !odd && has_weight3(&residues) {
+        HammingDistance::Exact(3)
+    } else if has_weight4(&residues) {
+        HammingDistance::Exact(4)
+    } else if odd {
+        HammingDistance::AtLeast(6)
+    } else {
+        HammingDistance::AtLeast(5)
+    };
+    Report {
+        hamming_distance,
+        burst,
+        detects_odd_errors: odd,
+        weaknesses,
+    }
+}
+
+/// Whether the full generator, `x^width` included, has an even number of terms.
+fn divisible_by_x_plus_1(poly: u64) -> bool {
+    poly.count_ones() % 2 == 1
+}
+
+/// A generator with a constant term.
+struct Reduced {
+    width: u32,
+    poly: u64,
+}
+
+impl Reduced {
+    /// `x^k mod g` for every `k` below `len`.
+    fn residues(&self, len: usize) -> Vec<u64> {
+        let mask = u64::MAX >> (64 - self.width);
+        let top = 1 << (self.width - 1);
+        let mut residues = Vec::with_capacity(len);
+        let mut value = 1u64;
+        for _ in 0..len {
+            residues.push(value);
+            value = if value & top != 0 {
+                ((value << 1) ^ self.poly) & mask
+            } else {
+                (value << 1) & mask
+            };
+        }
+        residues
+    }
+}
+
+/// Whether `1 + x^a + x^b` with `0 < a < b` is a codeword.
+fn has_weight3(residues: &[u64]) -> bool {
+    let mut seen = HashSet::new();
+    for &r in &residues[1..] {
+        if seen.contains(&(r ^ 1)) {
+            return true;
+        }
+        seen.insert(r);
+    }
+    false
+}
+
+/// Whether `1 + x^a + x^b + x^c` with `0 < a < b < c` is a codeword.
+fn has_weight4(residues: &[u64]) -> bool {
+    let mut first = HashMap::new();
+    for (b, &r) in residues.iter().enumerate().skip(1) {
+        first.entry(r).or_insert(b);
+    }
+    for c in 3..residues.len() {
+        let target = residues[c] ^ 1;
+        let found = (2..c)
+            .zip(&residues[2..c])
+            .any(|(b, &r)| first.get(&(target ^ r)).is_some_and(|&a| a < b));
+        if found {
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The smallest weight of a nonzero multiple of the generator below `x^len`, by trying
+    /// every error pattern of up to 5 bits.
+    fn brute_force(width: u32, poly: u64, len: usize) -> Option<u32> {
+        let generator = 1u128 << width | poly as u128;
+        let divides = |pattern: u128| {
+            let mut rem = pattern;
+            for bit in (width as usize..len).rev() {
+                if rem >> bit & 1 != 0 {
+                    rem ^= generator << (bit - width as usize);
+                }
+            }
+            rem == 0
+        };
+        fn search(
+            start: usize,
+            len: usize,
+            left: u32,
+            pattern: u128,
+            divides: &dyn Fn(u128) -> bool,
+        ) -> bool {
+            if left == 0 {
+                return divides(pattern);
+            }
+            (start..len).any(|bit| search(bit + 1, len, left - 1, pattern | 1 << bit, divides))
+        }
+        (1..=5).find(|&weight| search(0, len, weight, 0, &divides))
+    }
+
+    #[test]
+    fn brute_force_test() {
+        for (width, poly) in [
+            (8, 0x07),
+            (8, 0x31),
+            (8, 0x9B),
+            (5, 0x05),
+            (7, 0x09),
+            (6, 0x2F),
+        ] {
+            for data_bits in [4, 9, 16, 24] {
+                let report = analyze(width, poly, data_bits);
+                let expected = brute_force(width, poly, data_bits + width as usize);
+                match report.hamming_distance {
+                    HammingDistance::Exact(hd) => assert_eq!(Some(hd), expected),
+                    HammingDistance::AtLeast(hd) => {
+                        assert!(expected.is_none_or(|found| found >= hd), "{:#X}", poly)
+                    }
+                }
+                assert_eq!(report.burst, width);
+            }
+        }
+    }
+
+    #[test]
+    fn koopman_test() {
+        // CRC-8 0x07 is (x + 1) times a primitive polynomial of period 127.
+        let report = analyze(8, 0x07, 119);
+        assert_eq!(report.hamming_distance, HammingDistance::Exact(4));
+        assert!(report.detects_odd_errors && report.weaknesses.is_empty());
+        let report = analyze(8, 0x07, 120);
+        assert_eq!(report.hamming_distance, HammingDistance::Exact(2));
+        assert_eq!(report.weaknesses, [Weakness::ShortPeriod { period: 127 }]);
+
+        // The IEEE CRC-32 has HD 5 up to 2974 data bits and HD 4 above.
+        let crc32 = |bits| analyze(32, 0x04C1_1DB7, bits).hamming_distance;
+        assert_eq!(crc32(2974), HammingDistance::AtLeast(5));
+        assert_eq!(crc32(2975), HammingDistance::Exact(4));
+
+        let report = analyze(16, 0x1020, 64);
+        assert_eq!(report.burst, 11);
+        assert_eq!(report.weaknesses[0], Weakness::NoConstantTerm);
+        assert!(!analyze(32, 0x04C1_1DB7, 8).detects_odd_errors);
+    }
+}
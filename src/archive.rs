@@ -0,0 +1,230 @@
+//! Checking gzip trailers and zip headers against decompressed data.
+//!
+//! Both formats store the CRC-32/ISO-HDLC of the uncompressed data: gzip in the 8-byte
+//! trailer after the deflate stream, together with the length modulo 2^32 (`ISIZE`), and
+//! zip in each local file header and its central directory entry. The `check_*` functions
+//! take the CRC and length a decompressor accumulated, e.g. with a [`Digest`](crate::Digest)
+//! fed each output chunk, so the data never has to be held at once. All integers are
+//! little-endian.
+//!
+//! A zip writer that streams sets bit 3 of the general-purpose flags and zeroes the local
+//! header's fields; the values then follow the data in a data descriptor, which
+//! [`check_zip_data_descriptor`] checks.
+
+use crate::oneshot::crc32;
+
+const ZIP_LOCAL_SIGNATURE: u32 = 0x0403_4B50;
+const ZIP_CENTRAL_SIGNATURE: u32 = 0x0201_4B50;
+const ZIP_DESCRIPTOR_SIGNATURE: u32 = 0x0807_4B50;
+/// Length of a zip local file header without the file name and extra field.
+pub const ZIP_LOCAL_HEADER_LEN: usize = 30;
+/// Length of a zip central directory header without the variable-length fields.
+pub const ZIP_CENTRAL_HEADER_LEN: usize = 46;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ArchiveError {
+    /// The header or descriptor is shorter than its fixed part.
+    Truncated,
+    /// The zip header does not start with its signature.
+    Signature,
+    /// The local header defers its values to a data descriptor.
+    DataDescriptor,
+    Crc {
+        expected: u32,
+        actual: u32,
+    },
+    /// The stored length differs from that of the data, modulo 2^32 for gzip.
+    Size {
+        expected: u64,
+        actual: u64,
+    },
+}
+
+impl core::fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Truncated => write!(f, "archive header is truncated"),
+            Self::Signature => write!(f, "zip header has a wrong signature"),
+            Self::DataDescriptor => write!(f, "zip local header defers to a data descriptor"),
+            Self::Crc { expected, actual } => write!(
+                f,
+                "CRC-32 mismatch: archive has {:#010X}, data has {:#010X}",
+                expected, actual
+            ),
+            Self::Size { expected, actual } => write!(
+                f,
+                "size mismatch: archive has {} bytes, data has {}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ArchiveError {}
+
+fn le32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn check(stored_crc: u32, stored_len: u64, crc: u32, len: u64) -> Result<(), ArchiveError> {
+    if stored_crc != crc {
+        return Err(ArchiveError::Crc {
+            expected: stored_crc,
+            actual: crc,
+        });
+    }
+    if stored_len != len {
+        return Err(ArchiveError::Size {
+            expected: stored_len,
+            actual: len,
+        });
+    }
+    Ok(())
+}
+
+/// The gzip trailer for data with checksum `crc` and length `len`.
+pub fn gzip_trailer(crc: u32, len: u64) -> [u8; 8] {
+    let mut trailer = [0; 8];
+    trailer[..4].copy_from_slice(&crc.to_le_bytes());
+    trailer[4..].copy_from_slice(&(len as u32).to_le_bytes());
+    trailer
+}
+
+/// Checks a gzip member's trailer against the CRC and length of its decompressed data.
+pub fn check_gzip_trailer(trailer: &[u8; 8], crc: u32, len: u64) -> Result<(), ArchiveError> {
+    let isize = le32(trailer, 4) as u64;
+    check(le32(trailer, 0), isize, crc, len & 0xFFFF_FFFF)
+}
+
+/// [`check_gzip_trailer`] for data at hand.
+pub fn verify_gzip_trailer(trailer: &[u8; 8], data: &[u8]) -> Result<(), ArchiveError> {
+    check_gzip_trailer(trailer, crc32(data), data.len() as u64)
+}
+
+/// Checks a zip local file header against the CRC and length of the entry's decompressed
+/// data.
+///
+/// Fails with [`ArchiveError::DataDescriptor`] if bit 3 of the flags is set. Entries larger
+/// than 4 GiB store their sizes in a ZIP64 extra field, which is not read here.
+pub fn check_zip_local_header(header: &[u8], crc: u32, len: u64) -> Result<(), ArchiveError> {
+    if header.len() < ZIP_LOCAL_HEADER_LEN {
+        return Err(ArchiveError::Truncated);
+    }
+    if le32(header, 0) != ZIP_LOCAL_SIGNATURE {
+        return Err(ArchiveError::Signature);
+    }
+    if header[6] & 0x08 != 0 {
+        return Err(ArchiveError::DataDescriptor);
+    }
+    check(le32(header, 14), le32(header, 22) as u64, crc, len)
+}
+
+/// Checks a zip central directory header like [`check_zip_local_header`].
+pub fn check_zip_central_header(header: &[u8], crc: u32, len: u64) -> Result<(), ArchiveError> {
+    if header.len() < ZIP_CENTRAL_HEADER_LEN {
+        return Err(ArchiveError::Truncated);
+    }
+    if le32(header, 0) != ZIP_CENTRAL_SIGNATURE {
+        return Err(ArchiveError::Signature);
+    }
+    check(le32(header, 16), le32(header, 24) as u64, crc, len)
+}
+
+/// Checks the 32-bit data descriptor after an entry's data, with or without its optional
+/// signature.
+pub fn check_zip_data_descriptor(
+    descriptor: &[u8],
+    crc: u32,
+    len: u64,
+) -> Result<(), ArchiveError> {
+    let fields = match descriptor.get(..4).map(|bytes| le32(bytes, 0)) {
+        Some(ZIP_DESCRIPTOR_SIGNATURE) => &descriptor[4..],
+        _ => descriptor,
+    };
+    if fields.len() < 12 {
+        return Err(ArchiveError::Truncated);
+    }
+    check(le32(fields, 0), le32(fields, 8) as u64, crc, len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DATA: &[u8] = b"hello world\n";
+
+    #[test]
+    fn gzip_test() {
+        // The trailer of `gzip` run on DATA.
+        let trailer = [0x2D, 0x3B, 0x08, 0xAF, 0x0C, 0x00, 0x00, 0x00];
+        assert_eq!(verify_gzip_trailer(&trailer, DATA), Ok(()));
+        assert_eq!(gzip_trailer(crc32(DATA), DATA.len() as u64), trailer);
+        assert_eq!(
+            check_gzip_trailer(&trailer, 0xAF08_3B2D, (1 << 32) + 12),
+            Ok(())
+        );
+        assert_eq!(
+            verify_gzip_trailer(&trailer, b"hello world!"),
+            Err(ArchiveError::Crc {
+                expected: 0xAF08_3B2D,
+                actual: crc32(b"hello world!")
+            })
+        );
+        assert_eq!(
+            check_gzip_trailer(&trailer, 0xAF08_3B2D, 13),
+            Err(ArchiveError::Size {
+                expected: 12,
+                actual: 13
+            })
+        );
+    }
+
+    #[test]
+    fn zip_test() {
+        // The headers of a zip file storing DATA as `a.txt`.
+        let mut local = [
+            0x50, 0x4B, 0x03, 0x04, 0x14, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x21, 0x00,
+            0x2D, 0x3B, 0x08, 0xAF, 0x0C, 0x00, 0x00, 0x00, 0x0C, 0x00, 0x00, 0x00, 0x05, 0x00,
+            0x00, 0x00, 0x61, 0x2E, 0x74, 0x78, 0x74,
+        ];
+        let central = [
+            0x50, 0x4B, 0x01, 0x02, 0x14, 0x03, 0x14, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x21, 0x00, 0x2D, 0x3B, 0x08, 0xAF, 0x0C, 0x00, 0x00, 0x00, 0x0C, 0x00, 0x00, 0x00,
+            0x05, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80, 0x01,
+            0x00, 0x00, 0x00, 0x00, 0x61, 0x2E, 0x74, 0x78, 0x74,
+        ];
+        let crc = crc32(DATA);
+        assert_eq!(check_zip_local_header(&local, crc, 12), Ok(()));
+        assert_eq!(check_zip_central_header(&central, crc, 12), Ok(()));
+        assert!(matches!(
+            check_zip_central_header(&central, crc ^ 1, 12),
+            Err(ArchiveError::Crc { .. })
+        ));
+        assert_eq!(
+            check_zip_local_header(&central, crc, 12),
+            Err(ArchiveError::Signature)
+        );
+        assert_eq!(
+            check_zip_local_header(&local[..29], crc, 12),
+            Err(ArchiveError::Truncated)
+        );
+        local[6] |= 0x08;
+        assert_eq!(
+            check_zip_local_header(&local, crc, 12),
+            Err(ArchiveError::DataDescriptor)
+        );
+
+        let descriptor = [
+            0x50, 0x4B, 0x07, 0x08, 0x2D, 0x3B, 0x08, 0xAF, 0x0C, 0x00, 0x00, 0x00, 0x0C, 0x00,
+            0x00, 0x00,
+        ];
+        assert_eq!(check_zip_data_descriptor(&descriptor, crc, 12), Ok(()));
+        assert_eq!(check_zip_data_descriptor(&descriptor[4..], crc, 12), Ok(()));
+        assert_eq!(
+            check_zip_data_descriptor(&descriptor[4..15], crc, 12),
+            Err(ArchiveError::Truncated)
+        );
+    }
+}
@@ -0,0 +1,168 @@
+//! The CRC-24 checksum line of OpenPGP ASCII armor (RFC 4880 section 6.1).
+//!
+//! An armored block ends its base64 body with a line like `=njUN`: an equals sign and the
+//! base64 encoding of the three big-endian bytes of the CRC-24 of the decoded data. The
+//! checksum is optional since RFC 9580, so [`decode_armor`] only checks it when present.
+
+use crate::base64;
+use crate::catalog::CRC_24_OPENPGP;
+use crate::const_crc::ConstCRC32;
+use crate::{trace, CRC};
+
+/// CRC-24/OPENPGP run as a CRC-32 with its parameters shifted up by a byte, which leaves the
+/// low byte of the register zero and the CRC-24 in the top three. The type's table is built
+/// at compile time.
+type Crc24Openpgp = ConstCRC32<
+    { CRC_24_OPENPGP.params.poly << 8 },
+    { CRC_24_OPENPGP.params.init << 8 },
+    false,
+    false,
+    0,
+>;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ArmorError {
+    /// The text is not a well-formed armored block or checksum line.
+    Malformed,
+    Mismatch {
+        expected: u32,
+        actual: u32,
+    },
+}
+
+impl std::fmt::Display for ArmorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Malformed => write!(f, "malformed ASCII armor"),
+            Self::Mismatch { expected, actual } => write!(
+                f,
+                "armor CRC-24 mismatch: checksum line says {:#08X}, data has {:#08X}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ArmorError {}
+
+/// The armor CRC-24 of `data`, i.e. of the decoded bytes rather than the base64 text.
+pub fn crc24(data: &[u8]) -> u32 {
+    let mut crc = Crc24Openpgp::new();
+    crc.update(data);
+    crc.finalize() >> 8
+}
+
+/// Formats the checksum line for `crc`, e.g. `"=njUN"`.
+pub fn encode_checksum_line(crc: u32) -> String {
+    format!("={}", base64::encode(&crc.to_be_bytes()[1..]))
+}
+
+/// Parses a checksum line such as `=njUN`; surrounding whitespace is ignored.
+pub fn decode_checksum_line(line: &str) -> Result<u32, ArmorError> {
+    let encoded = line.trim().strip_prefix('=').ok_or(ArmorError::Malformed)?;
+    let bytes = base64::decode(encoded).ok_or(ArmorError::Malformed)?;
+    let [a, b, c] = bytes[..] else {
+        return Err(ArmorError::Malformed);
+    };
+    Ok(u32::from_be_bytes([0, a, b, c]))
+}
+
+/// Checks `data` against a checksum line.
+pub fn verify_checksum_line(line: &str, data: &[u8]) -> Result<(), ArmorError> {
+    let (expected, actual) = (decode_checksum_line(line)?, crc24(data));
+    trace::verified("CRC-24/OPENPGP", "armor", expected as u64, actual as u64);
+    if expected != actual {
+        return Err(ArmorError::Mismatch { expected, actual });
+    }
+    Ok(())
+}
+
+/// Decodes the first armored block in `text` and checks its checksum line, if it has one.
+///
+/// Armor headers such as `Version:` are skipped; the packets themselves are returned
+/// undecoded.
+pub fn decode_armor(text: &str) -> Result<Vec<u8>, ArmorError> {
+    let mut lines = text
+        .lines()
+        .map(str::trim_end)
+        .skip_while(|line| !(line.starts_with("-----BEGIN ") && line.ends_with("-----")));
+    lines.next().ok_or(ArmorError::Malformed)?;
+    // Armor headers end at the first blank line.
+    for line in lines.by_ref() {
+        if line.is_empty() {
+            break;
+        }
+    }
+    let (mut body, mut checksum) = (String::new(), None);
+    for line in lines {
+        if line.starts_with("-----END ") {
+            let data = base64::decode(&body).ok_or(ArmorError::Malformed)?;
+            if let Some(line) = checksum {
+                verify_checksum_line(line, &data)?;
+            }
+            return Ok(data);
+        }
+        match line.starts_with('=') {
+            true if checksum.is_none() => checksum = Some(line),
+            false if checksum.is_none() => body.push_str(line),
+            _ => return Err(ArmorError::Malformed),
+        }
+    }
+    Err(ArmorError::Malformed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MESSAGE: &str = "\
+-----BEGIN PGP MESSAGE-----
+Comment: not a real packet
+
+aGVsbG8g
+d29ybGQ=
+=sDy3
+-----END PGP MESSAGE-----
+";
+
+    #[test]
+    fn checksum_line_test() {
+        let check = crc::Crc::<u32>::new(&crc::CRC_24_OPENPGP).checksum(b"123456789");
+        assert_eq!(crc24(b"123456789"), check);
+        assert_eq!(check, 0x21_CF02);
+        let large: Vec<u8> = (0..10_000u32).map(|i| (i * 7 + (i >> 5)) as u8).collect();
+        let expected = crc::Crc::<u32>::new(&crc::CRC_24_OPENPGP).checksum(&large);
+        assert_eq!(crc24(&large), expected);
+
+        let line = encode_checksum_line(crc24(b"hello world"));
+        assert_eq!(decode_checksum_line(&line), Ok(crc24(b"hello world")));
+        assert_eq!(verify_checksum_line(&line, b"hello world"), Ok(()));
+        assert!(matches!(
+            verify_checksum_line(&line, b"hello_world"),
+            Err(ArmorError::Mismatch { .. })
+        ));
+        assert_eq!(decode_checksum_line("njUN"), Err(ArmorError::Malformed));
+        assert_eq!(
+            decode_checksum_line("=yZRlqg=="),
+            Err(ArmorError::Malformed)
+        );
+    }
+
+    #[test]
+    fn armor_test() {
+        assert_eq!(encode_checksum_line(crc24(b"hello world")), "=sDy3");
+        assert_eq!(decode_armor(MESSAGE).unwrap(), b"hello world");
+        let tampered = MESSAGE.replace("d29ybGQ=", "d29ybGU=");
+        assert!(matches!(
+            decode_armor(&tampered),
+            Err(ArmorError::Mismatch { .. })
+        ));
+        let unchecked = MESSAGE.replace("=sDy3\n", "");
+        assert_eq!(decode_armor(&unchecked).unwrap(), b"hello world");
+        assert_eq!(
+            decode_armor(&MESSAGE[..MESSAGE.len() - 26]),
+            Err(ArmorError::Malformed)
+        );
+    }
+}
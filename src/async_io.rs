@@ -0,0 +1,220 @@
+//! Checksumming tokio byte streams as they flow.
+//!
+//! [`AsyncCrcReader`] and [`AsyncCrcWriter`] are the async counterparts of
+//! [`CrcReader`](crate::reader::CrcReader) and [`CrcWriter`](crate::writer::CrcWriter). The
+//! CRC is updated inside the poll calls, which never block, so a handler can compute a
+//! trailer while streaming a body without a second pass.
+//!
+//! [`Crc::checksum_path_async`] checksums a file through `tokio::fs`, the async counterpart
+//! of [`Crc::checksum_path`].
+
+use crate::engine::Width;
+use crate::strategy::Strategy;
+use crate::{Crc, CRC};
+use std::io;
+use std::marker::PhantomData;
+use std::path::Path;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, ReadBuf};
+
+pin_project_lite::pin_project! {
+    /// Forwards reads from an inner [`AsyncRead`] and computes the CRC of the bytes returned.
+    pub struct AsyncCrcReader<R, C, N> {
+        #[pin]
+        inner: R,
+        crc: C,
+        _checksum: PhantomData<N>,
+    }
+}
+
+impl<R, C, N> AsyncCrcReader<R, C, N>
+where
+    C: CRC<Width = N>,
+{
+    pub fn new(inner: R, crc: C) -> Self {
+        Self {
+            inner,
+            crc,
+            _checksum: PhantomData,
+        }
+    }
+
+    /// The CRC of the bytes read so far.
+    pub fn checksum(&self) -> N {
+        self.crc.finalize()
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R, C, N> AsyncRead for AsyncCrcReader<R, C, N>
+where
+    R: AsyncRead,
+    C: CRC<Width = N>,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.project();
+        let before = buf.filled().len();
+        ready!(this.inner.poll_read(cx, buf))?;
+        this.crc.update(&buf.filled()[before..]);
+        Poll::Ready(Ok(()))
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// Forwards writes to an inner [`AsyncWrite`] and computes the CRC of the bytes it
+    /// accepted.
+    pub struct AsyncCrcWriter<W, C, N> {
+        #[pin]
+        inner: W,
+        crc: C,
+        _checksum: PhantomData<N>,
+    }
+}
+
+impl<W, C, N> AsyncCrcWriter<W, C, N>
+where
+    C: CRC<Width = N>,
+{
+    pub fn new(inner: W, crc: C) -> Self {
+        Self {
+            inner,
+            crc,
+            _checksum: PhantomData,
+        }
+    }
+
+    /// The CRC of the bytes written so far.
+    pub fn checksum(&self) -> N {
+        self.crc.finalize()
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W, C, N> AsyncWrite for AsyncCrcWriter<W, C, N>
+where
+    W: AsyncWrite,
+    C: CRC<Width = N>,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.project();
+        let n = ready!(this.inner.poll_write(cx, buf))?;
+        this.crc.update(&buf[..n]);
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().inner.poll_shutdown(cx)
+    }
+}
+
+impl<W: Width, S: Strategy> Crc<W, S> {
+    /// The checksum of the file at `path`, read through a 64 KiB buffer.
+    ///
+    /// Needs a tokio runtime with blocking threads, as all of `tokio::fs` does.
+    pub async fn checksum_path_async(&self, path: impl AsRef<Path>) -> io::Result<W> {
+        self.checksum_path_async_with_buffer(path, 64 * 1024).await
+    }
+
+    /// [`Crc::checksum_path_async`] reading `buffer_size` bytes at a time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buffer_size` is zero.
+    pub async fn checksum_path_async_with_buffer(
+        &self,
+        path: impl AsRef<Path>,
+        buffer_size: usize,
+    ) -> io::Result<W> {
+        assert!(buffer_size > 0, "buffer size must not be zero");
+        let mut file = tokio::fs::File::open(path).await?;
+        let mut buf = vec![0; buffer_size];
+        let mut digest = self.digest();
+        loop {
+            match file.read(&mut buf).await? {
+                0 => return Ok(digest.finalize()),
+                n => digest.update(&buf[..n]),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AsyncCrcReader, AsyncCrcWriter};
+    use crate::{catalog, Crc, CRC32};
+    use futures::executor::block_on;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    const TEST_DATA: &[u8] = b"hello world";
+
+    #[test]
+    fn async_io_test() {
+        let mut reader = AsyncCrcReader::new(TEST_DATA, CRC32::default());
+        let mut read = Vec::new();
+        block_on(reader.read_to_end(&mut read)).unwrap();
+        assert_eq!(read, TEST_DATA);
+        assert_eq!(reader.checksum(), 0x44F71378);
+
+        let mut writer = AsyncCrcWriter::new(Vec::new(), CRC32::default());
+        block_on(async {
+            writer.write_all(&TEST_DATA[..5]).await?;
+            writer.write_all(&TEST_DATA[5..]).await?;
+            writer.shutdown().await
+        })
+        .unwrap();
+        assert_eq!(writer.checksum(), 0x44F71378);
+        assert_eq!(writer.into_inner(), TEST_DATA);
+    }
+
+    #[test]
+    fn checksum_path_async_test() {
+        let data: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+        let path = std::env::temp_dir().join(format!("crclib-async-{}", std::process::id()));
+        std::fs::write(&path, &data).unwrap();
+        let crc = Crc::new(&catalog::CRC_32_ISCSI);
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            assert_eq!(
+                crc.checksum_path_async(&path).await.unwrap(),
+                crc.checksum(&data)
+            );
+            assert_eq!(
+                crc.checksum_path_async_with_buffer(&path, 1000)
+                    .await
+                    .unwrap(),
+                crc.checksum(&data)
+            );
+        });
+        std::fs::remove_file(&path).unwrap();
+        assert!(runtime.block_on(crc.checksum_path_async(&path)).is_err());
+    }
+}
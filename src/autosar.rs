@@ -0,0 +1,204 @@
+//! The routines of the AUTOSAR CRC library (`SWS_CRCLibrary`).
+//!
+//! Each function mirrors its `Crc_Calculate*` counterpart, including the start value and
+//! `Crc_IsFirstCall` arguments: on the first call the start value is ignored and the
+//! algorithm's init value used, and on later calls it is the result of the previous call, so
+//! data can be checksummed in pieces. [`Routine`] maps the routines to their catalogue
+//! entries. The E2E profiles built on them are in [`e2e`](crate::e2e).
+
+use crate::catalog::{self, Algorithm, AnyAlgorithm};
+use crate::engine::{Params, Width};
+
+/// A routine of the CRC library.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Routine {
+    /// `Crc_CalculateCRC8`: CRC-8/SAE-J1850.
+    Crc8,
+    /// `Crc_CalculateCRC8H2F`: CRC-8/AUTOSAR.
+    Crc8H2F,
+    /// `Crc_CalculateCRC16`: CRC-16/IBM-3740.
+    Crc16,
+    /// `Crc_CalculateCRC16ARC`: CRC-16/ARC.
+    Crc16Arc,
+    /// `Crc_CalculateCRC32`: CRC-32/ISO-HDLC.
+    Crc32,
+    /// `Crc_CalculateCRC32P4`: CRC-32/AUTOSAR.
+    Crc32P4,
+    /// `Crc_CalculateCRC64`: CRC-64/XZ.
+    Crc64,
+}
+
+impl Routine {
+    pub const ALL: [Routine; 7] = [
+        Self::Crc8,
+        Self::Crc8H2F,
+        Self::Crc16,
+        Self::Crc16Arc,
+        Self::Crc32,
+        Self::Crc32P4,
+        Self::Crc64,
+    ];
+
+    /// The catalogue entry the routine computes.
+    pub fn algorithm(self) -> AnyAlgorithm {
+        match self {
+            Self::Crc8 => AnyAlgorithm::Crc8(catalog::CRC_8_SAE_J1850),
+            Self::Crc8H2F => AnyAlgorithm::Crc8(catalog::CRC_8_AUTOSAR),
+            Self::Crc16 => AnyAlgorithm::Crc16(catalog::CRC_16_IBM_3740),
+            Self::Crc16Arc => AnyAlgorithm::Crc16(catalog::CRC_16_ARC),
+            Self::Crc32 => AnyAlgorithm::Crc32(catalog::CRC_32_ISO_HDLC),
+            Self::Crc32P4 => AnyAlgorithm::Crc32(catalog::CRC_32_AUTOSAR),
+            Self::Crc64 => AnyAlgorithm::Crc64(catalog::CRC_64_XZ),
+        }
+    }
+}
+
+fn calculate<W: Width>(
+    algorithm: &Algorithm<W>,
+    data: &[u8],
+    start_value: W,
+    is_first_call: bool,
+) -> W {
+    let params = Params::from(algorithm.params);
+    let crc = if is_first_call {
+        params.start()
+    } else {
+        params.unfinalize(start_value)
+    };
+    params.finalize(params.update(crc, data))
+}
+
+/// `Crc_CalculateCRC8`.
+pub fn calculate_crc8(data: &[u8], start_value: u8, is_first_call: bool) -> u8 {
+    calculate(&catalog::CRC_8_SAE_J1850, data, start_value, is_first_call)
+}
+
+/// `Crc_CalculateCRC8H2F`.
+pub fn calculate_crc8h2f(data: &[u8], start_value: u8, is_first_call: bool) -> u8 {
+    calculate(&catalog::CRC_8_AUTOSAR, data, start_value, is_first_call)
+}
+
+/// `Crc_CalculateCRC16`.
+pub fn calculate_crc16(data: &[u8], start_value: u16, is_first_call: bool) -> u16 {
+    calculate(&catalog::CRC_16_IBM_3740, data, start_value, is_first_call)
+}
+
+/// `Crc_CalculateCRC16ARC`.
+pub fn calculate_crc16arc(data: &[u8], start_value: u16, is_first_call: bool) -> u16 {
+    calculate(&catalog::CRC_16_ARC, data, start_value, is_first_call)
+}
+
+/// `Crc_CalculateCRC32`.
+pub fn calculate_crc32(data: &[u8], start_value: u32, is_first_call: bool) -> u32 {
+    calculate(&catalog::CRC_32_ISO_HDLC, data, start_value, is_first_call)
+}
+
+/// `Crc_CalculateCRC32P4`.
+pub fn calculate_crc32p4(data: &[u8], start_value: u32, is_first_call: bool) -> u32 {
+    calculate(&catalog::CRC_32_AUTOSAR, data, start_value, is_first_call)
+}
+
+/// `Crc_CalculateCRC64`.
+pub fn calculate_crc64(data: &[u8], start_value: u64, is_first_call: bool) -> u64 {
+    calculate(&catalog::CRC_64_XZ, data, start_value, is_first_call)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The data sets of the specification's example tables.
+    const DATA: [&[u8]; 7] = [
+        &[0x00, 0x00, 0x00, 0x00],
+        &[0xF2, 0x01, 0x83],
+        &[0x0F, 0xAA, 0x00, 0x55],
+        &[0x00, 0xFF, 0x55, 0x11],
+        &[0x33, 0x22, 0x55, 0xAA, 0xBB, 0xCC, 0xDD, 0xEE, 0xFF],
+        &[0x92, 0x6B, 0x55],
+        &[0xFF, 0xFF, 0xFF, 0xFF],
+    ];
+
+    #[test]
+    fn specification_test() {
+        let expected: [(Routine, [u64; 7]); 6] = [
+            (Routine::Crc8, [0x59, 0x37, 0x79, 0xB8, 0xCB, 0x8C, 0x74]),
+            (Routine::Crc8H2F, [0x12, 0xC2, 0xC6, 0x77, 0x11, 0x33, 0x6C]),
+            (
+                Routine::Crc16,
+                [0x84C0, 0xD374, 0x2023, 0xB8F9, 0xF53F, 0x0745, 0x1D0F],
+            ),
+            (
+                Routine::Crc32,
+                [
+                    0x2144_DF1C,
+                    0x24AB_9D77,
+                    0xB6C9_B287,
+                    0x32A0_6212,
+                    0xB0AE_863D,
+                    0x9CDE_A29B,
+                    0xFFFF_FFFF,
+                ],
+            ),
+            (
+                Routine::Crc32P4,
+                [
+                    0x6FB3_2240,
+                    0x4F72_1A25,
+                    0x2066_2DF8,
+                    0x9BD7_996E,
+                    0xA65A_343D,
+                    0xEE68_8A78,
+                    0xFFFF_FFFF,
+                ],
+            ),
+            (
+                Routine::Crc64,
+                [
+                    0xF4A5_8635_1E1B_9F4B,
+                    0x319C_2766_8164_F1C6,
+                    0x54C5_D0F7_667C_1575,
+                    0xA638_22BE_7E07_04E6,
+                    0x701E_CEB2_19A8_E5D5,
+                    0x5FAA_96A9_B59F_3E4E,
+                    0xFFFF_FFFF_0000_0000,
+                ],
+            ),
+        ];
+        for (routine, values) in expected {
+            for (data, value) in DATA.iter().zip(values) {
+                assert_eq!(routine.algorithm().checksum(data), value, "{:?}", routine);
+            }
+        }
+        assert_eq!(calculate_crc8(DATA[1], 0, true), 0x37);
+        assert_eq!(calculate_crc8h2f(DATA[1], 0, true), 0xC2);
+        assert_eq!(calculate_crc16(DATA[1], 0, true), 0xD374);
+        assert_eq!(calculate_crc16arc(b"123456789", 0, true), 0xBB3D);
+        assert_eq!(calculate_crc32(DATA[1], 0, true), 0x24AB_9D77);
+        assert_eq!(calculate_crc32p4(DATA[1], 0, true), 0x4F72_1A25);
+        assert_eq!(calculate_crc64(DATA[1], 0, true), 0x319C_2766_8164_F1C6);
+    }
+
+    #[test]
+    fn continuation_test() {
+        let (a, b) = DATA[4].split_at(4);
+        assert_eq!(
+            calculate_crc8(b, calculate_crc8(a, 0xAA, true), false),
+            0xCB
+        );
+        assert_eq!(
+            calculate_crc16arc(b, calculate_crc16arc(a, 0, true), false),
+            calculate_crc16arc(DATA[4], 0, true)
+        );
+        assert_eq!(
+            calculate_crc32p4(b, calculate_crc32p4(a, 0, true), false),
+            0xA65A_343D
+        );
+        assert_eq!(
+            calculate_crc64(b, calculate_crc64(a, 0, true), false),
+            0x701E_CEB2_19A8_E5D5
+        );
+        let widths = Routine::ALL.map(|routine| routine.algorithm().width());
+        assert_eq!(widths, [8, 8, 16, 16, 32, 32, 64]);
+    }
+}
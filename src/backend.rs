@@ -0,0 +1,245 @@
+//! Pluggable CRC backends.
+//!
+//! Application code that is generic over [`CrcBackend`] runs unchanged on the software
+//! implementations in this crate and on a platform HAL that forwards the work to an MCU CRC
+//! peripheral or DMA engine. [`CRC32`] and its siblings, the [`const_crc`](crate::const_crc)
+//! types and a [`Digest`](crate::Digest) are backends as they are; [`Software`] adapts any
+//! other [`CRC`] implementation. With both traits in scope, `crc.update(data)` on an engine
+//! is ambiguous and has to be spelled `CRC::update(&mut crc, data)` or
+//! `CrcBackend::update(&mut crc, data)`.
+//!
+//! CRC peripherals usually handle only some parameter sets: the STM32 unit has a fixed
+//! polynomial on older parts and no final XOR. A driver that implements [`Configure`] says
+//! which ones it takes, and [`Fallback`] uses the peripheral when it can and a software state
+//! otherwise, behind the same `update`/`finalize` calls.
+
+use crate::const_crc::{ConstCRC128, ConstCRC16, ConstCRC32, ConstCRC64, ConstCRC8};
+use crate::{CrcParams, CRC, CRC128, CRC16, CRC32, CRC64, CRC8};
+
+/// A CRC engine that bytes can be fed into.
+///
+/// Implementors are expected to be configured for a specific algorithm when they are
+/// constructed; the trait only covers streaming data through that configuration.
+pub trait CrcBackend<N> {
+    fn update(&mut self, data: &[u8]);
+    /// Reads the finished checksum. Takes `&mut self` since reading a peripheral's result
+    /// register may have side effects.
+    fn finalize(&mut self) -> N;
+}
+
+/// The software backend, wrapping a table-driven [`CRC`] implementation.
+#[derive(Default)]
+pub struct Software<C>(pub C);
+
+impl<N, C> CrcBackend<N> for Software<C>
+where
+    C: CRC<Width = N>,
+{
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data)
+    }
+
+    fn finalize(&mut self) -> N {
+        self.0.finalize()
+    }
+}
+
+macro_rules! impl_backend {
+    ($($crc:ident: $n:ty),*) => {
+        $(
+            impl CrcBackend<$n> for $crc {
+                fn update(&mut self, data: &[u8]) {
+                    CRC::update(self, data)
+                }
+
+                fn finalize(&mut self) -> $n {
+                    CRC::finalize(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_backend!(CRC8: u8, CRC16: u16, CRC32: u32, CRC64: u64, CRC128: u128);
+
+macro_rules! impl_const_backend {
+    ($($crc:ident: $n:ty),*) => {
+        $(
+            impl<const POLY: $n, const INIT: $n, const REFIN: bool, const REFOUT: bool, const XOROUT: $n>
+                CrcBackend<$n> for $crc<POLY, INIT, REFIN, REFOUT, XOROUT>
+            {
+                fn update(&mut self, data: &[u8]) {
+                    CRC::update(self, data)
+                }
+
+                fn finalize(&mut self) -> $n {
+                    CRC::finalize(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_const_backend!(
+    ConstCRC8: u8,
+    ConstCRC16: u16,
+    ConstCRC32: u32,
+    ConstCRC64: u64,
+    ConstCRC128: u128
+);
+
+/// A backend that can be set up for an algorithm at runtime, such as a peripheral driver.
+pub trait Configure<N>: CrcBackend<N> {
+    /// Sets up a fresh computation of `params`, or returns `false`, leaving the backend
+    /// unusable until configured again, if it cannot compute them.
+    fn configure(&mut self, params: &CrcParams<N>) -> bool;
+}
+
+/// The hardware backend `H` if it supports the algorithm, else the software state `C`.
+///
+/// ```text
+/// let mut crc = Fallback::<_, CRC16>::new(hal_crc, catalog::CRC_16_MODBUS.params);
+/// crc.update(frame);
+/// let checksum = crc.finalize();
+/// let hal_crc = crc.into_hardware(); // give the peripheral back
+/// ```
+pub enum Fallback<H, C> {
+    Hardware(H),
+    /// The hardware that declined the algorithm, if any, and the software state.
+    Software(Option<H>, C),
+}
+
+impl<N, H, C> Fallback<H, C>
+where
+    H: Configure<N>,
+    C: CRC<Width = N>,
+{
+    pub fn new(mut hardware: H, params: CrcParams<N>) -> Self {
+        if hardware.configure(&params) {
+            Self::Hardware(hardware)
+        } else {
+            Self::Software(Some(hardware), C::create(params))
+        }
+    }
+
+    pub fn is_hardware(&self) -> bool {
+        matches!(self, Self::Hardware(_))
+    }
+
+    /// Hands the hardware back, configured or not.
+    pub fn into_hardware(self) -> Option<H> {
+        match self {
+            Self::Hardware(hardware) => Some(hardware),
+            Self::Software(hardware, _) => hardware,
+        }
+    }
+}
+
+impl<N, H, C> CrcBackend<N> for Fallback<H, C>
+where
+    H: CrcBackend<N>,
+    C: CRC<Width = N>,
+{
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Self::Hardware(hardware) => hardware.update(data),
+            Self::Software(_, software) => software.update(data),
+        }
+    }
+
+    fn finalize(&mut self) -> N {
+        match self {
+            Self::Hardware(hardware) => hardware.finalize(),
+            Self::Software(_, software) => software.finalize(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Configure, CrcBackend, Fallback, Software};
+    use crate::const_crc::Crc32Iscsi;
+    use crate::{catalog, Crc, CrcParams, CRC, CRC32};
+
+    const TEST_DATA: &[u8] = b"hello world";
+
+    /// Stands in for a HAL driver that delegates to a peripheral in word-sized writes.
+    struct Peripheral {
+        inner: CRC32,
+        writes: usize,
+    }
+
+    impl CrcBackend<u32> for Peripheral {
+        fn update(&mut self, data: &[u8]) {
+            for word in data.chunks(4) {
+                CRC::update(&mut self.inner, word);
+                self.writes += 1;
+            }
+        }
+
+        fn finalize(&mut self) -> u32 {
+            self.inner.finalize()
+        }
+    }
+
+    /// A peripheral with the fixed CRC-32 polynomial and no final XOR, like the STM32 unit.
+    #[derive(Default)]
+    struct FixedPoly {
+        inner: Option<CRC32>,
+    }
+
+    impl CrcBackend<u32> for FixedPoly {
+        fn update(&mut self, data: &[u8]) {
+            CRC::update(self.inner.as_mut().unwrap(), data)
+        }
+
+        fn finalize(&mut self) -> u32 {
+            self.inner.as_ref().unwrap().finalize()
+        }
+    }
+
+    impl Configure<u32> for FixedPoly {
+        fn configure(&mut self, params: &CrcParams<u32>) -> bool {
+            let supported = params.width == 32 && params.poly == 0x04C1_1DB7 && params.xorout == 0;
+            self.inner = supported.then(|| CRC32::create(*params));
+            supported
+        }
+    }
+
+    fn checksum<B: CrcBackend<u32>>(mut backend: B, data: &[u8]) -> u32 {
+        backend.update(data);
+        backend.finalize()
+    }
+
+    #[test]
+    fn backend_test() {
+        let software = checksum(Software::<CRC32>::default(), TEST_DATA);
+        assert_eq!(checksum(CRC32::default(), TEST_DATA), software);
+        assert_eq!(checksum(Crc32Iscsi::new(), b"123456789"), 0xE306_9283);
+        let mut peripheral = Peripheral {
+            inner: CRC32::default(),
+            writes: 0,
+        };
+        peripheral.update(TEST_DATA);
+        assert_eq!(peripheral.writes, 3);
+        assert_eq!(peripheral.finalize(), software);
+        assert!(software == 0x44F71378, "{:#X}", software);
+    }
+
+    #[test]
+    fn fallback_test() {
+        let mpeg2 = catalog::CRC_32_MPEG_2.params;
+        let crc = Fallback::<_, CRC32>::new(FixedPoly::default(), mpeg2);
+        assert!(crc.is_hardware());
+        assert_eq!(checksum(crc, b"123456789"), catalog::CRC_32_MPEG_2.check);
+
+        let crc = Fallback::<_, CRC32>::new(FixedPoly::default(), catalog::CRC_32_ISCSI.params);
+        assert!(!crc.is_hardware());
+        assert_eq!(checksum(crc, b"123456789"), 0xE306_9283);
+        let crc = Fallback::<_, CRC32>::new(FixedPoly::default(), catalog::CRC_32_ISCSI.params);
+        assert!(crc.into_hardware().is_some());
+
+        let digest = Crc::new(&catalog::CRC_32_ISCSI);
+        assert_eq!(checksum(digest.digest(), b"123456789"), 0xE306_9283);
+    }
+}
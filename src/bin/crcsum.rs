@@ -0,0 +1,601 @@
+//! `crcsum [-a ALGORITHM] [-r] [FILE]...`: prints `checksum  name` for each file, or for stdin
+//! without files or for a `-` argument, like `sha256sum`. The algorithm is any catalogue name
+//! or alias and defaults to CRC-32/ISO-HDLC. With `-r`, directories are walked and their files
+//! checksummed in name order. Unreadable files are reported on stderr and make the exit
+//! status 1; the remaining files are still checksummed, each streamed in 64 KiB chunks.
+//!
+//! `crcsum --files-from LIST` takes the names from the file `LIST`, or from stdin for `-`,
+//! one per line or with `-0` NUL-terminated, so `find -print0 | crcsum --files-from - -0`
+//! works for trees too large for argv. The list is read while the files are checksummed,
+//! one thread per CPU, and lines are printed in the order the files finish.
+//!
+//! `crcsum --sfv FILE...` writes an SFV file for the files to stdout instead, and
+//! `crcsum --check LIST.sfv` (or `--check-sfv`) verifies the files an SFV file lists,
+//! relative to its directory, exiting with 1 unless all of them are intact. It prints
+//! `name: OK` or `name: FAILED` per file, a summary and the overall throughput; files of
+//! 16 MiB and more get a progress bar on stderr while they are read, if stderr is a terminal.
+//! With `--json`, the results are printed as a single JSON object instead, for CI jobs.
+//!
+//! `crcsum --generate c|rust [-a ALGORITHM] [--name NAME]` prints the algorithm's lookup table
+//! as source, see [`codegen`](crclib::codegen).
+//!
+//! `crcsum --cksum [FILE]...` prints `checksum size name` in decimal, byte for byte what POSIX
+//! `cksum` prints, including the missing name for stdin.
+
+use crclib::catalog::{self, AnyAlgorithm};
+use crclib::cksum::Cksum;
+use crclib::codegen::{self, Language};
+use crclib::filelist::{self, Delimiter, Hashed};
+use crclib::{sfv, Checksum};
+use std::fs::{self, File};
+use std::io::IsTerminal;
+use std::io::{self, BufRead, Read, Write};
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+const USAGE: &str = "usage: crcsum [-a ALGORITHM] [-r] [FILE]...
+       crcsum [-a ALGORITHM] --files-from LIST [-0]
+       crcsum --cksum [-r] [FILE]...
+       crcsum --sfv FILE...
+       crcsum --check LIST.sfv [--json]
+       crcsum --generate c|rust [-a ALGORITHM] [--name NAME]
+       crcsum --list";
+
+/// Feeds all of `input` to `update` in 64 KiB chunks.
+fn read_chunks(mut input: impl Read, mut update: impl FnMut(&[u8])) -> io::Result<()> {
+    let mut buf = vec![0; 64 * 1024];
+    loop {
+        match input.read(&mut buf) {
+            Ok(0) => return Ok(()),
+            Ok(n) => update(&buf[..n]),
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+fn checksum(algorithm: &AnyAlgorithm, input: impl Read) -> io::Result<Checksum<u64>> {
+    let mut state = algorithm.state();
+    read_chunks(input, |data| state.dyn_update(data))?;
+    Ok(Checksum::new(
+        state.dyn_finalize() as u64,
+        algorithm.width(),
+    ))
+}
+
+fn cksum(input: impl Read) -> io::Result<Cksum> {
+    let mut state = Cksum::new();
+    read_chunks(input, |data| state.update(data))?;
+    Ok(state)
+}
+
+/// Files at least this long get a progress bar while they are verified.
+const PROGRESS_MIN: u64 = 16 << 20;
+
+/// A progress bar for the file being verified, drawn on stderr if it is a terminal.
+struct Progress {
+    enabled: bool,
+    started: Instant,
+    drawn: Option<Instant>,
+}
+
+impl Progress {
+    /// Starts timing a new file.
+    fn start(&mut self) {
+        self.started = Instant::now();
+    }
+
+    /// Redraws the bar at most ten times a second.
+    fn show(&mut self, name: &str, done: u64, total: u64) {
+        if !self.enabled || total < PROGRESS_MIN {
+            return;
+        }
+        if self
+            .drawn
+            .is_some_and(|drawn| drawn.elapsed() < Duration::from_millis(100))
+        {
+            return;
+        }
+        const WIDTH: u64 = 30;
+        let filled = (done.min(total) * WIDTH / total) as usize;
+        eprint!(
+            "\r{} [{:<width$}] {:3}% {:.1} MB/s",
+            name,
+            "#".repeat(filled),
+            done.min(total) * 100 / total,
+            mb_per_s(done, self.started.elapsed()),
+            width = WIDTH as usize,
+        );
+        self.drawn = Some(Instant::now());
+    }
+
+    fn clear(&mut self) {
+        if self.drawn.take().is_some() {
+            eprint!("\r\x1b[2K");
+        }
+    }
+}
+
+fn mb_per_s(bytes: u64, elapsed: Duration) -> f64 {
+    bytes as f64 / 1e6 / elapsed.as_secs_f64().max(1e-9)
+}
+
+/// Verifies one entry and returns its status and the number of bytes read.
+fn verify_entry(entry: &sfv::SfvEntry, dir: &Path, progress: &mut Progress) -> (sfv::Status, u64) {
+    let file = match File::open(dir.join(&entry.name)) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return (sfv::Status::Missing, 0),
+        Err(err) => return (sfv::Status::Error(err), 0),
+    };
+    let total = file.metadata().map_or(0, |metadata| metadata.len());
+    let mut state = AnyAlgorithm::Crc32(catalog::CRC_32_ISO_HDLC).state();
+    let mut bytes = 0;
+    progress.start();
+    let result = read_chunks(file, |data| {
+        state.dyn_update(data);
+        bytes += data.len() as u64;
+        progress.show(&entry.name, bytes, total);
+    });
+    progress.clear();
+    let status = match result {
+        Ok(()) => match state.dyn_finalize() as u32 {
+            actual if actual == entry.crc => sfv::Status::Ok,
+            actual => sfv::Status::Failed { actual },
+        },
+        Err(err) => sfv::Status::Error(err),
+    };
+    (status, bytes)
+}
+
+/// Quotes `s` as a JSON string.
+fn json_string(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c < ' ' => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// The results of a check as one JSON object.
+fn json_report(report: &sfv::Report, sizes: &[u64], elapsed: Duration) -> String {
+    let files: Vec<String> = report
+        .results
+        .iter()
+        .zip(sizes)
+        .map(|((entry, status), bytes)| {
+            let (status, detail) = match status {
+                sfv::Status::Ok => ("ok", String::new()),
+                sfv::Status::Failed { actual } => {
+                    ("failed", format!(",\"actual\":\"{:08x}\"", actual))
+                }
+                sfv::Status::Missing => ("missing", String::new()),
+                sfv::Status::Error(err) => (
+                    "error",
+                    format!(",\"error\":{}", json_string(&err.to_string())),
+                ),
+            };
+            format!(
+                "{{\"name\":{},\"status\":\"{}\",\"expected\":\"{:08x}\",\"bytes\":{}{}}}",
+                json_string(&entry.name),
+                status,
+                entry.crc,
+                bytes,
+                detail
+            )
+        })
+        .collect();
+    let bytes: u64 = sizes.iter().sum();
+    format!(
+        "{{\"files\":[{}],\"ok\":{},\"failed\":{},\"missing\":{},\"bytes\":{},\"seconds\":{:.3},\"mb_per_s\":{:.1}}}",
+        files.join(","),
+        report.ok(),
+        report.failed(),
+        report.missing(),
+        bytes,
+        elapsed.as_secs_f64(),
+        mb_per_s(bytes, elapsed)
+    )
+}
+
+/// Verifies the entries of an SFV file and prints the status of each entry and a summary, or
+/// with `json` one JSON object; the exit status is 1 unless all are OK.
+fn check(list: &Path, json: bool) -> ExitCode {
+    let entries = match std::fs::read_to_string(list) {
+        Ok(text) => sfv::parse(&text).map_err(|err| err.to_string()),
+        Err(err) => Err(err.to_string()),
+    };
+    let entries = match entries {
+        Ok(entries) => entries,
+        Err(err) => {
+            eprintln!("crcsum: {}: {}", list.display(), err);
+            return ExitCode::FAILURE;
+        }
+    };
+    let dir = list.parent().unwrap_or(Path::new(""));
+    let mut progress = Progress {
+        enabled: io::stderr().is_terminal(),
+        started: Instant::now(),
+        drawn: None,
+    };
+    let mut report = sfv::Report::default();
+    let mut sizes = Vec::new();
+    let mut stdout = io::stdout().lock();
+    let started = Instant::now();
+    for entry in entries {
+        let (status, bytes) = verify_entry(&entry, dir, &mut progress);
+        if !json {
+            let _ = match &status {
+                sfv::Status::Ok => writeln!(stdout, "{}: OK", entry.name),
+                sfv::Status::Failed { .. } => writeln!(stdout, "{}: FAILED", entry.name),
+                sfv::Status::Missing => writeln!(stdout, "{}: MISSING", entry.name),
+                sfv::Status::Error(err) => writeln!(stdout, "{}: FAILED ({})", entry.name, err),
+            };
+        }
+        report.results.push((entry, status));
+        sizes.push(bytes);
+    }
+    let elapsed = started.elapsed();
+    let bytes: u64 = sizes.iter().sum();
+    let _ = if json {
+        writeln!(stdout, "{}", json_report(&report, &sizes, elapsed))
+    } else {
+        writeln!(
+            stdout,
+            "{} OK, {} failed, {} missing\n{:.1} MB in {:.2} s, {:.1} MB/s",
+            report.ok(),
+            report.failed(),
+            report.missing(),
+            bytes as f64 / 1e6,
+            elapsed.as_secs_f64(),
+            mb_per_s(bytes, elapsed)
+        )
+    };
+    if report.is_ok() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// Checksums one input and prints its line.
+type Each<'a> = dyn FnMut(Option<&str>, &mut dyn Read) -> io::Result<()> + 'a;
+
+/// Walks the inputs of a run and counts those that could not be read.
+struct Inputs {
+    recursive: bool,
+    failed: usize,
+}
+
+impl Inputs {
+    /// Runs `each` on every input named by `args`, or on stdin without any, reporting
+    /// errors on stderr and carrying on with the next input. `each` gets the name to print,
+    /// which is `None` for stdin read without a `-` argument.
+    fn run(&mut self, args: &[String], each: &mut Each) {
+        if args.is_empty() {
+            if let Err(err) = each(None, &mut io::stdin().lock()) {
+                self.error("-", &err);
+            }
+        }
+        for arg in args {
+            if arg == "-" {
+                if let Err(err) = each(Some(arg), &mut io::stdin().lock()) {
+                    self.error(arg, &err);
+                }
+            } else {
+                self.visit(Path::new(arg), each);
+            }
+        }
+    }
+
+    /// Checksums the files named in `list` under `algorithm` and writes their lines to `out`
+    /// as they finish, on one thread per CPU.
+    fn run_list(
+        &mut self,
+        list: impl BufRead + Send + 'static,
+        delimiter: Delimiter,
+        algorithm: AnyAlgorithm,
+        out: &mut impl Write,
+    ) {
+        let threads = std::thread::available_parallelism().map_or(1, usize::from);
+        let paths = filelist::read_list(list, delimiter);
+        for hashed in filelist::hash_files_with(paths, threads, algorithm) {
+            match hashed {
+                Ok(Hashed {
+                    path,
+                    result: Ok((_, crc)),
+                }) => {
+                    let crc = Checksum::new(crc, algorithm.width());
+                    let _ = writeln!(out, "{:x}  {}", crc, path.display());
+                }
+                Ok(Hashed {
+                    path,
+                    result: Err(err),
+                }) => self.error(&path.display().to_string(), &err),
+                Err(err) => self.error("file list", &err),
+            }
+        }
+    }
+
+    fn visit(&mut self, path: &Path, each: &mut Each) {
+        let result = fs::metadata(path).and_then(|metadata| {
+            if !metadata.is_dir() {
+                each(Some(&path.display().to_string()), &mut File::open(path)?)
+            } else if self.recursive {
+                self.walk(path, each)
+            } else {
+                Err(io::Error::other("is a directory"))
+            }
+        });
+        if let Err(err) = result {
+            self.error(&path.display().to_string(), &err);
+        }
+    }
+
+    /// Visits the entries of `dir` sorted by name, so the output does not depend on the
+    /// file system. Symbolic links to directories are not followed, which rules out cycles.
+    fn walk(&mut self, dir: &Path, each: &mut Each) -> io::Result<()> {
+        let mut entries = fs::read_dir(dir)?.collect::<io::Result<Vec<_>>>()?;
+        entries.sort_by_key(|entry| entry.file_name());
+        for entry in entries {
+            let path = entry.path();
+            if entry.file_type()?.is_symlink() && path.is_dir() {
+                continue;
+            }
+            self.visit(&path, each);
+        }
+        Ok(())
+    }
+
+    fn error(&mut self, name: &str, err: &io::Error) {
+        eprintln!("crcsum: {}: {}", name, err);
+        self.failed += 1;
+    }
+
+    /// Exit status 1 if any input failed, after a count of the failures on stderr.
+    fn status(&self) -> ExitCode {
+        match self.failed {
+            0 => ExitCode::SUCCESS,
+            1 => {
+                eprintln!("crcsum: 1 input could not be read");
+                ExitCode::FAILURE
+            }
+            n => {
+                eprintln!("crcsum: {} inputs could not be read", n);
+                ExitCode::FAILURE
+            }
+        }
+    }
+}
+
+/// Prints the table of `algorithm`, named after it unless `name` is given.
+fn generate(algorithm: &AnyAlgorithm, language: Language, name: Option<&str>) -> ExitCode {
+    let default_name;
+    let name = match name {
+        Some(name) => name,
+        None => {
+            let base: String = algorithm
+                .name()
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect();
+            default_name = match language {
+                Language::C => format!("{}_table", base.to_ascii_lowercase()),
+                Language::Rust => format!("{}_TABLE", base.to_ascii_uppercase()),
+            };
+            &default_name
+        }
+    };
+    let source = match algorithm {
+        AnyAlgorithm::Crc8(algorithm) => codegen::generate(&algorithm.params, language, name),
+        AnyAlgorithm::Crc16(algorithm) => codegen::generate(&algorithm.params, language, name),
+        AnyAlgorithm::Crc32(algorithm) => codegen::generate(&algorithm.params, language, name),
+        AnyAlgorithm::Crc64(algorithm) => codegen::generate(&algorithm.params, language, name),
+    };
+    match source {
+        Ok(source) => {
+            print!("{}", source);
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("crcsum: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn usage_error(message: &str) -> ExitCode {
+    eprintln!("crcsum: {}\n{}", message, USAGE);
+    ExitCode::from(2)
+}
+
+fn main() -> ExitCode {
+    let mut algorithm = catalog::CRC_32_ISO_HDLC.name.to_string();
+    let mut files = Vec::new();
+    let mut write_sfv = false;
+    let mut posix = false;
+    let mut recursive = false;
+    let mut language = None;
+    let mut table_name = None;
+    let mut check_list = None;
+    let mut json = false;
+    let mut files_from = None;
+    let mut delimiter = Delimiter::Newline;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-a" | "--algorithm" => match args.next() {
+                Some(name) => algorithm = name,
+                None => return usage_error("missing algorithm name"),
+            },
+            "--sfv" => write_sfv = true,
+            "--cksum" => posix = true,
+            "-r" | "--recursive" => recursive = true,
+            "--generate" => match args.next().as_deref() {
+                Some("c") => language = Some(Language::C),
+                Some("rust") => language = Some(Language::Rust),
+                Some(other) => return usage_error(&format!("unknown language {}", other)),
+                None => return usage_error("missing language"),
+            },
+            "--name" => match args.next() {
+                Some(name) => table_name = Some(name),
+                None => return usage_error("missing table name"),
+            },
+            "--check" | "--check-sfv" => match args.next() {
+                Some(list) => check_list = Some(list),
+                None => return usage_error("missing SFV file"),
+            },
+            "--json" => json = true,
+            "--files-from" => match args.next() {
+                Some(list) => files_from = Some(list),
+                None => return usage_error("missing file list"),
+            },
+            "-0" | "--null" => delimiter = Delimiter::Nul,
+            "--list" => {
+                for algorithm in catalog::algorithms() {
+                    println!("{}", algorithm.name());
+                }
+                return ExitCode::SUCCESS;
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return ExitCode::SUCCESS;
+            }
+            _ if arg.starts_with("--algorithm=") => {
+                algorithm = arg["--algorithm=".len()..].to_string();
+            }
+            _ if arg.starts_with('-') && arg.len() > 1 => {
+                return usage_error(&format!("unknown option {}", arg));
+            }
+            _ => files.push(arg),
+        }
+    }
+    if let Some(list) = check_list {
+        return check(Path::new(&list), json);
+    }
+    if json {
+        return usage_error("--json needs --check");
+    }
+    if let Some(list) = files_from {
+        if write_sfv || posix || recursive || language.is_some() || !files.is_empty() {
+            return usage_error("--files-from takes no FILE arguments or other mode");
+        }
+        let Ok(algorithm) = catalog::by_name(&algorithm) else {
+            return usage_error(&format!("unknown algorithm {}", algorithm));
+        };
+        let mut inputs = Inputs {
+            recursive: false,
+            failed: 0,
+        };
+        let mut stdout = io::stdout().lock();
+        if list == "-" {
+            let stdin = io::BufReader::new(io::stdin());
+            inputs.run_list(stdin, delimiter, algorithm, &mut stdout);
+        } else {
+            match File::open(&list) {
+                Ok(file) => {
+                    let file = io::BufReader::new(file);
+                    inputs.run_list(file, delimiter, algorithm, &mut stdout);
+                }
+                Err(err) => inputs.error(&list, &err),
+            }
+        }
+        return inputs.status();
+    }
+    if delimiter == Delimiter::Nul {
+        return usage_error("-0 needs --files-from");
+    }
+    if write_sfv {
+        let names = files.iter().map(String::as_str);
+        return match sfv::create(Path::new(""), names) {
+            Ok(entries) => {
+                print!("{}", sfv::format(&entries));
+                ExitCode::SUCCESS
+            }
+            Err(err) => {
+                eprintln!("crcsum: {}", err);
+                ExitCode::FAILURE
+            }
+        };
+    }
+    if let Some(language) = language {
+        return match catalog::by_name(&algorithm) {
+            Ok(algorithm) => generate(&algorithm, language, table_name.as_deref()),
+            Err(_) => usage_error(&format!("unknown algorithm {}", algorithm)),
+        };
+    }
+    let mut inputs = Inputs {
+        recursive,
+        failed: 0,
+    };
+    let mut stdout = io::stdout().lock();
+    if posix {
+        inputs.run(&files, &mut |name, input| {
+            let state = cksum(input)?;
+            let _ = match name {
+                Some(name) => writeln!(stdout, "{} {} {}", state.finalize(), state.len(), name),
+                None => writeln!(stdout, "{} {}", state.finalize(), state.len()),
+            };
+            Ok(())
+        });
+    } else {
+        let Ok(algorithm) = catalog::by_name(&algorithm) else {
+            return usage_error(&format!("unknown algorithm {}", algorithm));
+        };
+        inputs.run(&files, &mut |name, input| {
+            let crc = checksum(&algorithm, input)?;
+            let _ = writeln!(stdout, "{:x}  {}", crc, name.unwrap_or("-"));
+            Ok(())
+        });
+    }
+    inputs.status()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files_from_test() {
+        let dir = std::env::temp_dir().join(format!("crclib-crcsum-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let (a, b) = (dir.join("a"), dir.join("name with spaces"));
+        fs::write(&a, b"hello world").unwrap();
+        fs::write(&b, b"123456789").unwrap();
+        let mut list = Vec::new();
+        for path in [&a, &b, &dir.join("missing")] {
+            list.extend_from_slice(path.to_str().unwrap().as_bytes());
+            list.push(0);
+        }
+
+        let mut inputs = Inputs {
+            recursive: false,
+            failed: 0,
+        };
+        let mut out = Vec::new();
+        let algorithm = AnyAlgorithm::Crc32(catalog::CRC_32_ISO_HDLC);
+        inputs.run_list(io::Cursor::new(list), Delimiter::Nul, algorithm, &mut out);
+        let out = String::from_utf8(out).unwrap();
+        let mut lines: Vec<&str> = out.split_terminator('\n').collect();
+        lines.sort();
+        assert_eq!(inputs.failed, 1);
+        assert_eq!(
+            lines,
+            [
+                format!("0d4a1185  {}", a.display()),
+                format!("cbf43926  {}", b.display()),
+            ]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,232 @@
+//! Per-block CRC-32C lists for partial verification of large files.
+//!
+//! A [`BlockList`] holds the CRC-32C of every fixed-size block of a file plus the CRC of the
+//! whole file. A client that fetches byte ranges of the file, e.g. over HTTP range requests,
+//! can check each block as it arrives with [`BlockList::verify_block`] instead of waiting for
+//! the entire download, and can resume or re-fetch just the blocks that failed.
+//!
+//! Since the whole-file CRC follows from the block CRCs by CRC combination,
+//! [`BlockList::is_consistent`] lets a receiver check that a list it was sent is
+//! self-consistent before trusting it.
+
+use crate::compare::read_full;
+use crate::const_crc::Crc32Iscsi;
+use crate::oneshot::{crc32c, crc32c_combine};
+use crate::{trace, CRC};
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::path::Path;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BlockError {
+    /// The block index is past the end of the file.
+    OutOfRange,
+    /// The data given for a block is not as long as the block.
+    Length {
+        expected: u64,
+        actual: u64,
+    },
+    Mismatch {
+        expected: u32,
+        actual: u32,
+    },
+}
+
+impl std::fmt::Display for BlockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OutOfRange => write!(f, "block index out of range"),
+            Self::Length { expected, actual } => write!(
+                f,
+                "block length mismatch: expected {} bytes, got {}",
+                expected, actual
+            ),
+            Self::Mismatch { expected, actual } => write!(
+                f,
+                "block CRC mismatch: list has {:#010X}, data has {:#010X}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BlockError {}
+
+/// The block CRCs of a file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlockList {
+    pub block_size: u64,
+    /// Length of the file; the last block may be shorter than `block_size`.
+    pub len: u64,
+    /// CRC-32C of the whole file.
+    pub crc: u32,
+    pub blocks: Vec<u32>,
+}
+
+impl BlockList {
+    /// Reads `reader` to the end, checksumming every `block_size` bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `block_size` is zero.
+    pub fn from_reader<R: Read>(mut reader: R, block_size: u64) -> io::Result<Self> {
+        assert!(block_size > 0, "block size must be non-zero");
+        let mut buf = vec![0; block_size.min(1 << 20) as usize];
+        let mut blocks = Vec::new();
+        let (mut len, mut crc) = (0, crc32c(&[]));
+        loop {
+            let mut block = (&mut reader).take(block_size);
+            let mut block_crc = Crc32Iscsi::new();
+            let mut block_len = 0;
+            loop {
+                let n = read_full(&mut block, &mut buf)?;
+                if n == 0 {
+                    break;
+                }
+                block_crc.update(&buf[..n]);
+                block_len += n as u64;
+            }
+            if block_len == 0 {
+                break;
+            }
+            let block_crc = block_crc.finalize();
+            blocks.push(block_crc);
+            crc = crc32c_combine(crc, block_crc, block_len);
+            len += block_len;
+            if block_len < block_size {
+                break;
+            }
+        }
+        Ok(Self {
+            block_size,
+            len,
+            crc,
+            blocks,
+        })
+    }
+
+    /// Computes the block list of the file at `path`.
+    pub fn from_file(path: &Path, block_size: u64) -> io::Result<Self> {
+        Self::from_reader(File::open(path)?, block_size)
+    }
+
+    /// The byte range of block `index` within the file.
+    pub fn block_range(&self, index: usize) -> Option<Range<u64>> {
+        if index >= self.blocks.len() {
+            return None;
+        }
+        let start = index as u64 * self.block_size;
+        Some(start..(start + self.block_size).min(self.len))
+    }
+
+    /// Checks the data of block `index`.
+    pub fn verify_block(&self, index: usize, data: &[u8]) -> Result<(), BlockError> {
+        let range = self.block_range(index).ok_or(BlockError::OutOfRange)?;
+        if data.len() as u64 != range.end - range.start {
+            return Err(BlockError::Length {
+                expected: range.end - range.start,
+                actual: data.len() as u64,
+            });
+        }
+        let expected = self.blocks[index];
+        let actual = crc32c(data);
+        trace::verified("CRC-32C", "block", expected as u64, actual as u64);
+        if expected != actual {
+            return Err(BlockError::Mismatch { expected, actual });
+        }
+        Ok(())
+    }
+
+    /// Reads and checks only the blocks listed in `indices`, returning one result per index.
+    ///
+    /// I/O errors abort the whole check; a file that ends early reports a length error for
+    /// the affected block.
+    pub fn verify_blocks<R, I>(
+        &self,
+        mut reader: R,
+        indices: I,
+    ) -> io::Result<Vec<Result<(), BlockError>>>
+    where
+        R: Read + Seek,
+        I: IntoIterator<Item = usize>,
+    {
+        let mut buf = Vec::new();
+        let mut results = Vec::new();
+        for index in indices {
+            let Some(range) = self.block_range(index) else {
+                results.push(Err(BlockError::OutOfRange));
+                continue;
+            };
+            reader.seek(SeekFrom::Start(range.start))?;
+            buf.resize((range.end - range.start) as usize, 0);
+            let n = read_full(&mut reader, &mut buf)?;
+            results.push(self.verify_block(index, &buf[..n]));
+        }
+        Ok(results)
+    }
+
+    /// Whether the block CRCs combine to the whole-file CRC.
+    pub fn is_consistent(&self) -> bool {
+        let expected_blocks = self.len.div_ceil(self.block_size);
+        if self.blocks.len() as u64 != expected_blocks {
+            return false;
+        }
+        let combined = self
+            .blocks
+            .iter()
+            .enumerate()
+            .fold(crc32c(&[]), |crc, (index, &block)| {
+                let range = self.block_range(index).unwrap();
+                crc32c_combine(crc, block, range.end - range.start)
+            });
+        combined == self.crc
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{crc32c, BlockError, BlockList};
+    use std::io::Cursor;
+
+    #[test]
+    fn block_list_test() {
+        let data: Vec<u8> = (0..=255).cycle().take(10_000).collect();
+        let list = BlockList::from_reader(&data[..], 4096).unwrap();
+        assert_eq!(list.len, 10_000);
+        assert_eq!(list.crc, crc32c(&data));
+        assert_eq!(list.blocks.len(), 3);
+        assert_eq!(list.blocks[2], crc32c(&data[8192..]));
+        assert_eq!(list.block_range(2), Some(8192..10_000));
+        assert!(list.is_consistent());
+
+        let exact = BlockList::from_reader(&data[..8192], 4096).unwrap();
+        assert_eq!(exact.blocks.len(), 2);
+        assert!(exact.is_consistent());
+        assert!(BlockList::from_reader(&[][..], 4096)
+            .unwrap()
+            .is_consistent());
+
+        let mut tampered = list.clone();
+        tampered.blocks[1] ^= 1;
+        assert!(!tampered.is_consistent());
+    }
+
+    #[test]
+    fn verify_blocks_test() {
+        let mut data: Vec<u8> = (0..=255).cycle().take(10_000).collect();
+        let list = BlockList::from_reader(&data[..], 4096).unwrap();
+        assert_eq!(list.verify_block(2, &data[8192..]), Ok(()));
+        assert!(matches!(
+            list.verify_block(2, &data[8191..]),
+            Err(BlockError::Length { .. })
+        ));
+        assert_eq!(list.verify_block(3, &[]), Err(BlockError::OutOfRange));
+
+        data[5000] ^= 1;
+        let results = list.verify_blocks(Cursor::new(&data), [2, 1, 7]).unwrap();
+        assert_eq!(results[0], Ok(()));
+        assert!(matches!(results[1], Err(BlockError::Mismatch { .. })));
+        assert_eq!(results[2], Err(BlockError::OutOfRange));
+    }
+}
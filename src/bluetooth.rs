@@ -0,0 +1,142 @@
+//! Bluetooth baseband and Low Energy link-layer checks (Core specification, Vol 2 Part B
+//! section 7.1 and Vol 6 Part B section 3.1.1).
+//!
+//! BR/EDR packets carry an 8-bit header error check over the 10-bit packet header and a
+//! CRC-16 over the payload; both start from the master's UAP (upper address part) instead
+//! of a fixed init value, so a sniffer has to know or guess the UAP. They are
+//! [CRC-8/BLUETOOTH](crate::catalog::CRC_8_BLUETOOTH) and CRC-16/KERMIT with `init` set to
+//! the UAP.
+//!
+//! LE packets end in a [CRC-24/BLE](crate::catalog::CRC_24_BLE) over the PDU, started from the `CRCInit` of the connection or
+//! [`ADVERTISING_CRC_INIT`] on the advertising channels. All values are returned in the bit
+//! order they are sent, least significant bit first.
+
+use crate::engine::Params;
+
+/// Length of the LE CRC.
+pub const BLE_CRC_LEN: usize = 3;
+/// The `CRCInit` of advertising channel packets.
+pub const ADVERTISING_CRC_INIT: u32 = 0x55_5555;
+
+const HEC: Params<u8> = Params {
+    width: 8,
+    poly: 0xA7,
+    init: 0x00,
+    refin: true,
+    refout: true,
+    xorout: 0x00,
+};
+
+const PAYLOAD_CRC: Params<u16> = Params {
+    width: 16,
+    poly: 0x1021,
+    init: 0x0000,
+    refin: true,
+    refout: true,
+    xorout: 0x0000,
+};
+
+const CRC_24_BLE: Params<u32> = Params {
+    width: 24,
+    poly: 0x00_065B,
+    init: ADVERTISING_CRC_INIT,
+    refin: true,
+    refout: true,
+    xorout: 0x00_0000,
+};
+
+/// The header error check of the low 10 bits of a packet header: LT_ADDR, TYPE, FLOW, ARQN
+/// and SEQN, in the order they are sent.
+pub fn hec(uap: u8, header: u16) -> u8 {
+    let params = Params { init: uap, ..HEC };
+    // The header goes out LSB first; the engine takes bits most significant first.
+    let bits = (header & 0x3FF).reverse_bits() >> 6;
+    params.finalize(params.update_bits(params.start(), bits as u64, 10))
+}
+
+/// Checks the header error check of a received packet header.
+pub fn verify_hec(uap: u8, header: u16, hec: u8) -> bool {
+    self::hec(uap, header) == hec
+}
+
+/// The CRC-16 of a BR/EDR packet payload, payload header included.
+pub fn payload_crc16(uap: u8, payload: &[u8]) -> u16 {
+    let params = Params {
+        init: uap as u16,
+        ..PAYLOAD_CRC
+    };
+    params.checksum(payload)
+}
+
+/// The CRC-24 of an LE PDU, in the byte order it is sent.
+pub fn ble_crc24(crc_init: u32, pdu: &[u8]) -> [u8; BLE_CRC_LEN] {
+    let params = Params {
+        init: crc_init & 0xFF_FFFF,
+        ..CRC_24_BLE
+    };
+    let crc = params.checksum(pdu).to_le_bytes();
+    [crc[0], crc[1], crc[2]]
+}
+
+/// Checks an LE PDU followed by its CRC-24.
+pub fn verify_ble_packet(crc_init: u32, packet: &[u8]) -> bool {
+    match packet.len().checked_sub(BLE_CRC_LEN) {
+        Some(len) => ble_crc24(crc_init, &packet[..len]) == packet[len..],
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crc as crcl;
+
+    #[test]
+    fn br_edr_test() {
+        assert_eq!(HEC.checksum(b"123456789"), 0x26);
+        let kermit = crcl::Crc::<u16>::new(&crcl::CRC_16_KERMIT);
+        assert_eq!(
+            payload_crc16(0, b"123456789"),
+            kermit.checksum(b"123456789")
+        );
+        assert_ne!(
+            payload_crc16(0x47, b"123456789"),
+            payload_crc16(0, b"123456789")
+        );
+
+        let header = 0b10_1001_0011;
+        let check = hec(0x47, header);
+        assert!(verify_hec(0x47, header, check));
+        assert!(!verify_hec(0x46, header, check));
+        for bit in 0..10 {
+            assert!(!verify_hec(0x47, header ^ 1 << bit, check), "bit {}", bit);
+        }
+        // Bits above the header are ignored.
+        assert_eq!(hec(0x47, header | 0xFC00), check);
+    }
+
+    #[test]
+    fn ble_test() {
+        const CONNECTION: crcl::Algorithm<u32> = crcl::Algorithm {
+            init: 0x12_3456,
+            ..crcl::CRC_24_BLE
+        };
+        let pdu = [0x02, 0x07, 0x03, 0x00, 0x04, 0x00, 0x0A, 0x03, 0x00];
+        let expected = crcl::Crc::<u32>::new(&CONNECTION).checksum(&pdu);
+        assert_eq!(ble_crc24(0x12_3456, &pdu), expected.to_le_bytes()[..3]);
+        let advertising = crcl::Crc::<u32>::new(&crcl::CRC_24_BLE).checksum(b"123456789");
+        assert_eq!(advertising, 0xC2_5A56);
+        assert_eq!(
+            ble_crc24(ADVERTISING_CRC_INIT, b"123456789"),
+            [0x56, 0x5A, 0xC2]
+        );
+
+        let mut packet = pdu.to_vec();
+        packet.extend_from_slice(&ble_crc24(0x12_3456, &pdu));
+        assert!(verify_ble_packet(0x12_3456, &packet));
+        assert!(!verify_ble_packet(ADVERTISING_CRC_INIT, &packet));
+        packet[4] ^= 0x20;
+        assert!(!verify_ble_packet(0x12_3456, &packet));
+        assert!(!verify_ble_packet(0x12_3456, &[0x00, 0x00]));
+    }
+}
@@ -0,0 +1,68 @@
+//! The block and stream CRCs of bzip2 files.
+//!
+//! Each compressed block header stores the CRC-32/BZIP2 of the block's uncompressed data:
+//! the zlib polynomial, but MSB-first and without reflection. The end-of-stream marker is
+//! followed by the combined CRC of all blocks, folded together by rotating the running value
+//! left by one bit before each block CRC is XORed in; [`StreamCrc`] does that. Both are stored
+//! big-endian, though only the first block header is byte-aligned.
+
+use crate::const_crc::Crc32Bzip2;
+use crate::trace;
+
+/// The CRC of a block's uncompressed data.
+pub const fn block_crc(data: &[u8]) -> u32 {
+    Crc32Bzip2::checksum(data)
+}
+
+/// Checks a block CRC read from a block header against the decompressed data.
+pub fn verify_block(stored: u32, data: &[u8]) -> bool {
+    let actual = block_crc(data);
+    trace::verified("CRC-32/BZIP2", "bzip2 block", stored as u64, actual as u64);
+    stored == actual
+}
+
+/// The combined CRC of a stream, built from its block CRCs in order.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct StreamCrc {
+    crc: u32,
+}
+
+impl StreamCrc {
+    pub const fn new() -> Self {
+        Self { crc: 0 }
+    }
+
+    /// Folds in the CRC of the next block.
+    pub fn push(&mut self, block_crc: u32) {
+        self.crc = self.crc.rotate_left(1) ^ block_crc;
+    }
+
+    /// The combined CRC of the blocks so far, as stored after the end-of-stream marker.
+    pub const fn value(&self) -> u32 {
+        self.crc
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bzip2_test() {
+        // The first block header of `bzip2` run on "hello world\n": magic, then the CRC.
+        let header = [
+            0x42, 0x5A, 0x68, 0x39, 0x31, 0x41, 0x59, 0x26, 0x53, 0x59, 0x4E, 0xEC, 0xE8, 0x36,
+        ];
+        let stored = u32::from_be_bytes(header[10..].try_into().unwrap());
+        assert!(verify_block(stored, b"hello world\n"));
+        assert!(!verify_block(stored, b"hello world!"));
+        assert_eq!(block_crc(b"123456789"), 0xFC89_1918);
+
+        let mut stream = StreamCrc::new();
+        stream.push(stored);
+        assert_eq!(stream.value(), stored);
+        stream.push(block_crc(b"123456789"));
+        assert_eq!(stream.value(), 0x9DD9_D06C ^ 0xFC89_1918);
+    }
+}
@@ -0,0 +1,231 @@
+//! CAN and CAN FD frame CRCs (ISO 11898-1:2015, section 10.4.2.6).
+//!
+//! Classic CAN protects each frame with a 15-bit CRC over the destuffed bits from the start
+//! of frame to the end of the data field. The fields are not byte-aligned, so the functions
+//! here take bit streams: bytes holding the bits most significant first and the number of
+//! bits used.
+//!
+//! CAN FD uses a 17-bit CRC for up to 16 data bytes and a 21-bit one above, with two quirks
+//! that trip up tools written for classic CAN:
+//!
+//! - the CRC covers the frame as sent, dynamic stuff bits included;
+//! - ISO CAN FD appends the stuff count, the number of dynamic stuff bits modulo 8 Gray
+//!   coded plus a parity bit ([`stuff_count`]), and covers it too, and starts the register
+//!   with its top bit set. The fixed stuff bits before the stuff count and around the CRC
+//!   sequence are not covered. Non-ISO (Bosch 1.0) CAN FD has neither the stuff count nor
+//!   the init value.
+//!
+//! The catalogue entries ([`CRC_17_CAN_FD`](crate::catalog::CRC_17_CAN_FD) and
+//! [`CRC_21_CAN_FD`](crate::catalog::CRC_21_CAN_FD)) follow the non-ISO init of zero.
+
+use crate::engine::{Params, Width};
+
+const CRC_15_CAN: Params<u16> = Params {
+    width: 15,
+    poly: 0x4599,
+    init: 0x0000,
+    refin: false,
+    refout: false,
+    xorout: 0x0000,
+};
+
+const CRC_17_CAN_FD: Params<u32> = Params {
+    width: 17,
+    poly: 0x1_685B,
+    init: 0x0_0000,
+    refin: false,
+    refout: false,
+    xorout: 0x0_0000,
+};
+
+const CRC_21_CAN_FD: Params<u32> = Params {
+    width: 21,
+    poly: 0x10_2899,
+    init: 0x00_0000,
+    refin: false,
+    refout: false,
+    xorout: 0x00_0000,
+};
+
+/// A frame identifier.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Id {
+    /// 11-bit identifier of a base frame.
+    Standard(u16),
+    /// 29-bit identifier of an extended frame.
+    Extended(u32),
+}
+
+/// The CRC a CAN FD frame carries, selected by its data length.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FdCrc {
+    Crc17,
+    Crc21,
+}
+
+impl FdCrc {
+    /// The CRC of a frame with `len` data bytes.
+    pub fn for_data_len(len: usize) -> Self {
+        if len <= 16 {
+            Self::Crc17
+        } else {
+            Self::Crc21
+        }
+    }
+
+    pub fn width(self) -> u32 {
+        self.params().width
+    }
+
+    fn params(self) -> &'static Params<u32> {
+        match self {
+            Self::Crc17 => &CRC_17_CAN_FD,
+            Self::Crc21 => &CRC_21_CAN_FD,
+        }
+    }
+}
+
+/// Runs `params` over the first `len` bits of `bits`, most significant bit first.
+fn bits_crc<W: Width>(params: &Params<W>, init: W, bits: &[u8], len: usize) -> W {
+    assert!(len <= bits.len() * 8, "{} bits do not fit the buffer", len);
+    let (whole, rest) = (len / 8, len % 8);
+    let mut crc = params.update(init, &bits[..whole]);
+    if rest != 0 {
+        crc = params.update_bits(crc, (bits[whole] >> (8 - rest)) as u64, rest as u32);
+    }
+    params.finalize(crc)
+}
+
+/// The CRC-15 of the first `len` destuffed bits of `bits`, from the start of frame to the
+/// end of the data field.
+///
+/// # Panics
+///
+/// Panics if `bits` holds fewer than `len` bits.
+pub fn crc15(bits: &[u8], len: usize) -> u16 {
+    bits_crc(&CRC_15_CAN, CRC_15_CAN.start(), bits, len)
+}
+
+/// The CRC-15 of a classic data frame, built from its fields.
+///
+/// # Panics
+///
+/// Panics if `data` is longer than 8 bytes.
+pub fn frame_crc(id: Id, data: &[u8]) -> u16 {
+    assert!(data.len() <= 8, "CAN frames carry at most 8 data bytes");
+    let crc = header(id, false, data.len() as u8);
+    CRC_15_CAN.finalize(CRC_15_CAN.update(crc, data))
+}
+
+/// The CRC-15 of a classic remote frame requesting `dlc` bytes.
+pub fn remote_frame_crc(id: Id, dlc: u8) -> u16 {
+    CRC_15_CAN.finalize(header(id, true, dlc))
+}
+
+/// The register after the start of frame, arbitration and control fields.
+fn header(id: Id, remote: bool, dlc: u8) -> u16 {
+    let p = &CRC_15_CAN;
+    // Start of frame.
+    let crc = p.update_bits(p.start(), 0, 1);
+    let crc = match id {
+        // Identifier, RTR, IDE and r0.
+        Id::Standard(id) => {
+            p.update_bits(crc, ((id as u64 & 0x7FF) << 3) | (remote as u64) << 2, 14)
+        }
+        // Base identifier, SRR, IDE, identifier extension, RTR, r1 and r0.
+        Id::Extended(id) => {
+            let id = id as u64 & 0x1FFF_FFFF;
+            let bits = (id >> 18) << 23 | 0b11 << 21 | (id & 0x3_FFFF) << 3 | (remote as u64) << 2;
+            p.update_bits(crc, bits, 34)
+        }
+    };
+    p.update_bits(crc, dlc as u64 & 0xF, 4)
+}
+
+/// The CRC of the first `len` bits of a CAN FD frame as sent.
+///
+/// For ISO CAN FD, `bits` runs from the start of frame through the stuff count and its
+/// parity bit, dynamic stuff bits included and the fixed stuff bit before the stuff count
+/// left out. For non-ISO CAN FD (`iso` false) it ends with the data field.
+///
+/// # Panics
+///
+/// Panics if `bits` holds fewer than `len` bits.
+pub fn fd_crc(crc: FdCrc, iso: bool, bits: &[u8], len: usize) -> u32 {
+    let params = crc.params();
+    let init = if iso {
+        1 << (params.width - 1)
+    } else {
+        params.start()
+    };
+    bits_crc(params, init, bits, len)
+}
+
+/// The 4-bit ISO CAN FD stuff count field for `stuff_bits` dynamic stuff bits: the count
+/// modulo 8 in Gray code followed by an even parity bit.
+pub fn stuff_count(stuff_bits: u32) -> u8 {
+    let count = (stuff_bits % 8) as u8;
+    let gray = count ^ count >> 1;
+    gray << 1 | (gray.count_ones() & 1) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crc as crcl;
+
+    #[test]
+    fn classic_test() {
+        assert_eq!(CRC_15_CAN.checksum(b"123456789"), 0x059E);
+        assert_eq!(crc15(b"123456789", 72), 0x059E);
+
+        // With a zero init, leading zero bits leave the register unchanged, so a frame
+        // padded at the front to whole bytes can be checked byte-wise.
+        let crc15 = crcl::Crc::<u16>::new(&crcl::CRC_15_CAN);
+        // SOF, ID 0x123, RTR, IDE, r0, DLC 2, then 0xCA 0xFE: 35 bits after 5 of padding.
+        let bits: u64 = 0x123 << 23 | 2 << 16 | 0xCAFE;
+        let padded = &bits.to_be_bytes()[3..];
+        assert_eq!(
+            frame_crc(Id::Standard(0x123), &[0xCA, 0xFE]),
+            crc15.checksum(padded)
+        );
+        assert_eq!(super::crc15(padded, 40), crc15.checksum(padded));
+
+        // SOF, base ID, SRR, IDE, extension, RTR, r1, r0 and DLC 0: 39 bits after 1.
+        let id = 0x1234_5678 & 0x1FFF_FFFF;
+        let bits: u64 = (id >> 18) << 27 | 0b11 << 25 | (id & 0x3_FFFF) << 7 | 1 << 6;
+        let padded = &bits.to_be_bytes()[3..];
+        assert_eq!(
+            remote_frame_crc(Id::Extended(id as u32), 0),
+            crc15.checksum(padded)
+        );
+    }
+
+    #[test]
+    fn fd_test() {
+        assert_eq!(CRC_17_CAN_FD.checksum(b"123456789"), 0x0_4F03);
+        assert_eq!(CRC_21_CAN_FD.checksum(b"123456789"), 0x0E_D841);
+        assert_eq!(fd_crc(FdCrc::Crc17, false, b"123456789", 72), 0x0_4F03);
+        assert_eq!(FdCrc::for_data_len(16), FdCrc::Crc17);
+        assert_eq!(FdCrc::for_data_len(20).width(), 21);
+
+        // The ISO init is the zero-init CRC of the message with its first bit flipped.
+        let message = b"123456789";
+        let mut folded = *message;
+        folded[0] ^= 0x80;
+        let crc17 = crcl::Crc::<u32>::new(&crcl::CRC_17_CAN_FD);
+        assert_eq!(
+            fd_crc(FdCrc::Crc17, true, message, 72),
+            crc17.checksum(&folded)
+        );
+
+        let gray: [u8; 8] = [
+            0b0000, 0b0011, 0b0110, 0b0101, 0b1100, 0b1111, 0b1010, 0b1001,
+        ];
+        for (count, field) in gray.into_iter().enumerate() {
+            assert_eq!(stuff_count(count as u32 + 8), field, "{}", count);
+        }
+    }
+}
@@ -0,0 +1,1006 @@
+//! Named parameter sets for common CRC algorithms.
+//!
+//! Names and values follow the [RevEng catalogue](https://reveng.sourceforge.io/crc-catalogue/):
+//! `CRC32::from_algorithm(&catalog::CRC_32_ISO_HDLC)` is the zlib CRC-32, and any entry can
+//! also be passed straight to [`CRC::create`](crate::CRC::create). Each entry carries its
+//! check value, the checksum of `"123456789"`, and its residue.
+//!
+//! Tools that take the algorithm from a configuration file can look entries up at runtime
+//! with [`by_name`], which also knows the common aliases such as `"CRC-32"` or
+//! `"CRC-16/CCITT-FALSE"`. [`iter`] lists every entry with its aliases and metadata, e.g.
+//! for a selection list in a user interface.
+
+use crate::engine::Params;
+use crate::gf2::Polynomial;
+use crate::CrcParams;
+use core::fmt;
+use AnyAlgorithm::{Crc16, Crc32, Crc64, Crc8};
+
+/// A catalogued algorithm.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Algorithm<N> {
+    /// The catalogue name, e.g. `"CRC-16/MODBUS"`.
+    pub name: &'static str,
+    pub params: CrcParams<N>,
+    /// The checksum of the ASCII string `"123456789"`.
+    pub check: N,
+    /// The register after reading a message followed by its CRC, before `xorout`.
+    pub residue: N,
+}
+
+impl<N: Copy> From<&Algorithm<N>> for CrcParams<N> {
+    fn from(algorithm: &Algorithm<N>) -> Self {
+        algorithm.params
+    }
+}
+
+pub const CRC_5_USB: Algorithm<u8> = Algorithm {
+    name: "CRC-5/USB",
+    params: CrcParams {
+        width: 5,
+        poly: 0x05,
+        init: 0x1F,
+        refin: true,
+        refout: true,
+        xorout: 0x1F,
+    },
+    check: 0x19,
+    residue: 0x06,
+};
+
+pub const CRC_8_SMBUS: Algorithm<u8> = Algorithm {
+    name: "CRC-8/SMBUS",
+    params: CrcParams {
+        width: 8,
+        poly: 0x07,
+        init: 0x00,
+        refin: false,
+        refout: false,
+        xorout: 0x00,
+    },
+    check: 0xF4,
+    residue: 0x00,
+};
+
+pub const CRC_8_MAXIM_DOW: Algorithm<u8> = Algorithm {
+    name: "CRC-8/MAXIM-DOW",
+    params: CrcParams {
+        width: 8,
+        poly: 0x31,
+        init: 0x00,
+        refin: true,
+        refout: true,
+        xorout: 0x00,
+    },
+    check: 0xA1,
+    residue: 0x00,
+};
+
+/// AUTOSAR's `Crc_CalculateCRC8H2F`; see [`autosar`](crate::autosar).
+pub const CRC_8_AUTOSAR: Algorithm<u8> = Algorithm {
+    name: "CRC-8/AUTOSAR",
+    params: CrcParams {
+        width: 8,
+        poly: 0x2F,
+        init: 0xFF,
+        refin: false,
+        refout: false,
+        xorout: 0xFF,
+    },
+    check: 0xDF,
+    residue: 0x42,
+};
+
+pub const CRC_8_BLUETOOTH: Algorithm<u8> = Algorithm {
+    name: "CRC-8/BLUETOOTH",
+    params: CrcParams {
+        width: 8,
+        poly: 0xA7,
+        init: 0x00,
+        refin: true,
+        refout: true,
+        xorout: 0x00,
+    },
+    check: 0x26,
+    residue: 0x00,
+};
+
+/// AUTOSAR's `Crc_CalculateCRC8`, as in SAE J1850 OBD messages.
+pub const CRC_8_SAE_J1850: Algorithm<u8> = Algorithm {
+    name: "CRC-8/SAE-J1850",
+    params: CrcParams {
+        width: 8,
+        poly: 0x1D,
+        init: 0xFF,
+        refin: false,
+        refout: false,
+        xorout: 0xFF,
+    },
+    check: 0x4B,
+    residue: 0xC4,
+};
+
+/// The CRC of ATM OAM cells and AAL3/4.
+pub const CRC_10_ATM: Algorithm<u16> = Algorithm {
+    name: "CRC-10/ATM",
+    params: CrcParams {
+        width: 10,
+        poly: 0x233,
+        init: 0x000,
+        refin: false,
+        refout: false,
+        xorout: 0x000,
+    },
+    check: 0x199,
+    residue: 0x000,
+};
+
+/// The FlexRay header CRC; [`flexray`](crate::flexray) runs it over the 20 header bits.
+pub const CRC_11_FLEXRAY: Algorithm<u16> = Algorithm {
+    name: "CRC-11/FLEXRAY",
+    params: CrcParams {
+        width: 11,
+        poly: 0x385,
+        init: 0x01A,
+        refin: false,
+        refout: false,
+        xorout: 0x000,
+    },
+    check: 0x5A3,
+    residue: 0x000,
+};
+
+pub const CRC_12_UMTS: Algorithm<u16> = Algorithm {
+    name: "CRC-12/UMTS",
+    params: CrcParams {
+        width: 12,
+        poly: 0x80F,
+        init: 0x000,
+        refin: false,
+        refout: true,
+        xorout: 0x000,
+    },
+    check: 0xDAF,
+    residue: 0x000,
+};
+
+/// The CRC of classic CAN frames; [`can`](crate::can) computes it over frame bits.
+pub const CRC_15_CAN: Algorithm<u16> = Algorithm {
+    name: "CRC-15/CAN",
+    params: CrcParams {
+        width: 15,
+        poly: 0x4599,
+        init: 0x0000,
+        refin: false,
+        refout: false,
+        xorout: 0x0000,
+    },
+    check: 0x059E,
+    residue: 0x0000,
+};
+
+pub const CRC_16_ARC: Algorithm<u16> = Algorithm {
+    name: "CRC-16/ARC",
+    params: CrcParams {
+        width: 16,
+        poly: 0x8005,
+        init: 0x0000,
+        refin: true,
+        refout: true,
+        xorout: 0x0000,
+    },
+    check: 0xBB3D,
+    residue: 0x0000,
+};
+
+/// The DECT R-CRC over A-fields. The final XOR of `0x0001` only flips the last bit.
+pub const CRC_16_DECT_R: Algorithm<u16> = Algorithm {
+    name: "CRC-16/DECT-R",
+    params: CrcParams {
+        width: 16,
+        poly: 0x0589,
+        init: 0x0000,
+        refin: false,
+        refout: false,
+        xorout: 0x0001,
+    },
+    check: 0x007E,
+    residue: 0x0589,
+};
+
+/// The DECT X-CRC, [`CRC_16_DECT_R`] without the final XOR.
+pub const CRC_16_DECT_X: Algorithm<u16> = Algorithm {
+    name: "CRC-16/DECT-X",
+    params: CrcParams {
+        width: 16,
+        poly: 0x0589,
+        init: 0x0000,
+        refin: false,
+        refout: false,
+        xorout: 0x0000,
+    },
+    check: 0x007F,
+    residue: 0x0000,
+};
+
+/// DNP3 link frames, one per block; see [`dnp3`](crate::dnp3).
+pub const CRC_16_DNP: Algorithm<u16> = Algorithm {
+    name: "CRC-16/DNP",
+    params: CrcParams {
+        width: 16,
+        poly: 0x3D65,
+        init: 0x0000,
+        refin: true,
+        refout: true,
+        xorout: 0xFFFF,
+    },
+    check: 0xEA82,
+    residue: 0x66C5,
+};
+
+/// Often called CRC-16/CCITT-FALSE: the CCITT polynomial MSB-first from `0xFFFF`, no final
+/// XOR. One of the five CCITT-family entries, with [`CRC_16_KERMIT`], [`CRC_16_IBM_SDLC`],
+/// [`CRC_16_SPI_FUJITSU`] and [`CRC_16_XMODEM`]; they share the polynomial `0x1021` and
+/// nothing else.
+pub const CRC_16_IBM_3740: Algorithm<u16> = Algorithm {
+    name: "CRC-16/IBM-3740",
+    params: CrcParams {
+        width: 16,
+        poly: 0x1021,
+        init: 0xFFFF,
+        refin: false,
+        refout: false,
+        xorout: 0x0000,
+    },
+    check: 0x29B1,
+    residue: 0x0000,
+};
+
+/// The X.25 and HDLC frame check sequence: reflected, from `0xFFFF`, inverted at the end.
+pub const CRC_16_IBM_SDLC: Algorithm<u16> = Algorithm {
+    name: "CRC-16/IBM-SDLC",
+    params: CrcParams {
+        width: 16,
+        poly: 0x1021,
+        init: 0xFFFF,
+        refin: true,
+        refout: true,
+        xorout: 0xFFFF,
+    },
+    check: 0x906E,
+    residue: 0xF0B8,
+};
+
+/// The reflected CCITT CRC from zero, what "CRC-16/CCITT" most often means.
+pub const CRC_16_KERMIT: Algorithm<u16> = Algorithm {
+    name: "CRC-16/KERMIT",
+    params: CrcParams {
+        width: 16,
+        poly: 0x1021,
+        init: 0x0000,
+        refin: true,
+        refout: true,
+        xorout: 0x0000,
+    },
+    check: 0x2189,
+    residue: 0x0000,
+};
+
+pub const CRC_16_MODBUS: Algorithm<u16> = Algorithm {
+    name: "CRC-16/MODBUS",
+    params: CrcParams {
+        width: 16,
+        poly: 0x8005,
+        init: 0xFFFF,
+        refin: true,
+        refout: true,
+        xorout: 0x0000,
+    },
+    check: 0x4B37,
+    residue: 0x0000,
+};
+
+/// Better known as CRC-16/AUG-CCITT: what the textbook augmented-message algorithm computes
+/// when started from `0xFFFF`, which is `0x1D0F` for the direct algorithm used here.
+pub const CRC_16_SPI_FUJITSU: Algorithm<u16> = Algorithm {
+    name: "CRC-16/SPI-FUJITSU",
+    params: CrcParams {
+        width: 16,
+        poly: 0x1021,
+        init: 0x1D0F,
+        refin: false,
+        refout: false,
+        xorout: 0x0000,
+    },
+    check: 0xE5CC,
+    residue: 0x0000,
+};
+
+pub const CRC_16_T10_DIF: Algorithm<u16> = Algorithm {
+    name: "CRC-16/T10-DIF",
+    params: CrcParams {
+        width: 16,
+        poly: 0x8BB7,
+        init: 0x0000,
+        refin: false,
+        refout: false,
+        xorout: 0x0000,
+    },
+    check: 0xD0DB,
+    residue: 0x0000,
+};
+
+pub const CRC_16_UMTS: Algorithm<u16> = Algorithm {
+    name: "CRC-16/UMTS",
+    params: CrcParams {
+        width: 16,
+        poly: 0x8005,
+        init: 0x0000,
+        refin: false,
+        refout: false,
+        xorout: 0x0000,
+    },
+    check: 0xFEE8,
+    residue: 0x0000,
+};
+
+pub const CRC_16_USB: Algorithm<u16> = Algorithm {
+    name: "CRC-16/USB",
+    params: CrcParams {
+        width: 16,
+        poly: 0x8005,
+        init: 0xFFFF,
+        refin: true,
+        refout: true,
+        xorout: 0xFFFF,
+    },
+    check: 0xB4C8,
+    residue: 0xB001,
+};
+
+/// The CCITT polynomial MSB-first from zero, also used by ZMODEM and LTE.
+pub const CRC_16_XMODEM: Algorithm<u16> = Algorithm {
+    name: "CRC-16/XMODEM",
+    params: CrcParams {
+        width: 16,
+        poly: 0x1021,
+        init: 0x0000,
+        refin: false,
+        refout: false,
+        xorout: 0x0000,
+    },
+    check: 0x31C3,
+    residue: 0x0000,
+};
+
+/// CAN FD frames with up to 16 data bytes. ISO CAN FD starts from `0x10000` instead.
+pub const CRC_17_CAN_FD: Algorithm<u32> = Algorithm {
+    name: "CRC-17/CAN-FD",
+    params: CrcParams {
+        width: 17,
+        poly: 0x1_685B,
+        init: 0x0_0000,
+        refin: false,
+        refout: false,
+        xorout: 0x0_0000,
+    },
+    check: 0x0_4F03,
+    residue: 0x0_0000,
+};
+
+/// CAN FD frames with more than 16 data bytes. ISO CAN FD starts from `0x100000` instead.
+pub const CRC_21_CAN_FD: Algorithm<u32> = Algorithm {
+    name: "CRC-21/CAN-FD",
+    params: CrcParams {
+        width: 21,
+        poly: 0x10_2899,
+        init: 0x00_0000,
+        refin: false,
+        refout: false,
+        xorout: 0x00_0000,
+    },
+    check: 0x0E_D841,
+    residue: 0x00_0000,
+};
+
+/// Bluetooth LE packets; connections replace the init with their `CRCInit`, see
+/// [`bluetooth`](crate::bluetooth).
+pub const CRC_24_BLE: Algorithm<u32> = Algorithm {
+    name: "CRC-24/BLE",
+    params: CrcParams {
+        width: 24,
+        poly: 0x00_065B,
+        init: 0x55_5555,
+        refin: true,
+        refout: true,
+        xorout: 0x00_0000,
+    },
+    check: 0xC2_5A56,
+    residue: 0x00_0000,
+};
+
+/// The CRC-24 of OpenPGP ASCII armor; [`armor`](crate::armor) formats and checks its
+/// `=XXXX` checksum line.
+pub const CRC_24_OPENPGP: Algorithm<u32> = Algorithm {
+    name: "CRC-24/OPENPGP",
+    params: CrcParams {
+        width: 24,
+        poly: 0x86_4CFB,
+        init: 0xB7_04CE,
+        refin: false,
+        refout: false,
+        xorout: 0x00_0000,
+    },
+    check: 0x21_CF02,
+    residue: 0x00_0000,
+};
+
+/// AUTOSAR's `Crc_CalculateCRC32P4`, the CRC of E2E profile 4.
+pub const CRC_32_AUTOSAR: Algorithm<u32> = Algorithm {
+    name: "CRC-32/AUTOSAR",
+    params: CrcParams {
+        width: 32,
+        poly: 0xF4AC_FB13,
+        init: 0xFFFF_FFFF,
+        refin: true,
+        refout: true,
+        xorout: 0xFFFF_FFFF,
+    },
+    check: 0x1697_D06A,
+    residue: 0x904C_DDBF,
+};
+
+/// bzip2 block and stream CRCs ([`bzip2`](crate::bzip2)) and the ATM AAL5 trailer. It is
+/// also what [`CRC32`](crate::CRC32) computes by default.
+pub const CRC_32_BZIP2: Algorithm<u32> = Algorithm {
+    name: "CRC-32/BZIP2",
+    params: CrcParams {
+        width: 32,
+        poly: 0x04C1_1DB7,
+        init: 0xFFFF_FFFF,
+        refin: false,
+        refout: false,
+        xorout: 0xFFFF_FFFF,
+    },
+    check: 0xFC89_1918,
+    residue: 0xC704_DD7B,
+};
+
+pub const CRC_32_CKSUM: Algorithm<u32> = Algorithm {
+    name: "CRC-32/CKSUM",
+    params: CrcParams {
+        width: 32,
+        poly: 0x04C1_1DB7,
+        init: 0x0000_0000,
+        refin: false,
+        refout: false,
+        xorout: 0xFFFF_FFFF,
+    },
+    check: 0x765E_7680,
+    residue: 0xC704_DD7B,
+};
+
+/// CRC-32C (Castagnoli), the CRC-32 of iSCSI, SCTP, ext4, Btrfs, NVMe and many RPC framings.
+///
+/// Also found by name as `"CRC-32C"` and `"CRC-32/CASTAGNOLI"`. Every way of computing it,
+/// from [`oneshot::crc32c`](crate::oneshot::crc32c) over [`Crc`](crate::Crc) to the
+/// [`Crc32Iscsi`](crate::const_crc::Crc32Iscsi) type, uses the SSE4.2 or ARMv8 CRC
+/// instructions when the CPU has them. The exception is the `const fn`
+/// [`Crc32Iscsi::checksum`](crate::const_crc::Crc32Iscsi::checksum), which has to stay
+/// table-driven to run at compile time.
+pub const CRC_32_ISCSI: Algorithm<u32> = Algorithm {
+    name: "CRC-32/ISCSI",
+    params: CrcParams {
+        width: 32,
+        poly: 0x1EDC_6F41,
+        init: 0xFFFF_FFFF,
+        refin: true,
+        refout: true,
+        xorout: 0xFFFF_FFFF,
+    },
+    check: 0xE306_9283,
+    residue: 0xB798_B438,
+};
+
+/// The CRC-32 of zlib, gzip, PNG and Ethernet.
+pub const CRC_32_ISO_HDLC: Algorithm<u32> = Algorithm {
+    name: "CRC-32/ISO-HDLC",
+    params: CrcParams {
+        width: 32,
+        poly: 0x04C1_1DB7,
+        init: 0xFFFF_FFFF,
+        refin: true,
+        refout: true,
+        xorout: 0xFFFF_FFFF,
+    },
+    check: 0xCBF4_3926,
+    residue: 0xDEBB_20E3,
+};
+
+/// MPEG-2 transport stream PSI/SI sections ([`mpegts`](crate::mpegts)). Without a final
+/// XOR, the CRC of an intact section including its `CRC_32` field is zero.
+pub const CRC_32_MPEG_2: Algorithm<u32> = Algorithm {
+    name: "CRC-32/MPEG-2",
+    params: CrcParams {
+        width: 32,
+        poly: 0x04C1_1DB7,
+        init: 0xFFFF_FFFF,
+        refin: false,
+        refout: false,
+        xorout: 0x0000_0000,
+    },
+    check: 0x0376_E6E7,
+    residue: 0x0000_0000,
+};
+
+pub const CRC_64_ECMA_182: Algorithm<u64> = Algorithm {
+    name: "CRC-64/ECMA-182",
+    params: CrcParams {
+        width: 64,
+        poly: 0x42F0_E1EB_A9EA_3693,
+        init: 0x0000_0000_0000_0000,
+        refin: false,
+        refout: false,
+        xorout: 0x0000_0000_0000_0000,
+    },
+    check: 0x6C40_DF5F_0B49_7347,
+    residue: 0x0000_0000_0000_0000,
+};
+
+pub const CRC_64_GO_ISO: Algorithm<u64> = Algorithm {
+    name: "CRC-64/GO-ISO",
+    params: CrcParams {
+        width: 64,
+        poly: 0x0000_0000_0000_001B,
+        init: 0xFFFF_FFFF_FFFF_FFFF,
+        refin: true,
+        refout: true,
+        xorout: 0xFFFF_FFFF_FFFF_FFFF,
+    },
+    check: 0xB909_56C7_75A4_1001,
+    residue: 0x5300_0000_0000_0000,
+};
+
+pub const CRC_64_NVME: Algorithm<u64> = Algorithm {
+    name: "CRC-64/NVME",
+    params: CrcParams {
+        width: 64,
+        poly: 0xAD93_D235_94C9_3659,
+        init: 0xFFFF_FFFF_FFFF_FFFF,
+        refin: true,
+        refout: true,
+        xorout: 0xFFFF_FFFF_FFFF_FFFF,
+    },
+    check: 0xAE8B_1486_0A79_9888,
+    residue: 0xF310_303B_2B6F_6E42,
+};
+
+/// What [`CRC64::default`](crate::CRC64) computes; xz and NVMe use [`CRC_64_XZ`] and
+/// [`CRC_64_NVME`] instead.
+pub const CRC_64_WE: Algorithm<u64> = Algorithm {
+    name: "CRC-64/WE",
+    params: CrcParams {
+        width: 64,
+        poly: 0x42F0_E1EB_A9EA_3693,
+        init: 0xFFFF_FFFF_FFFF_FFFF,
+        refin: false,
+        refout: false,
+        xorout: 0xFFFF_FFFF_FFFF_FFFF,
+    },
+    check: 0x62EC_59E3_F1A4_F00A,
+    residue: 0xFCAC_BEBD_5931_A992,
+};
+
+/// Also known as CRC-64/GO-ECMA.
+pub const CRC_64_XZ: Algorithm<u64> = Algorithm {
+    name: "CRC-64/XZ",
+    params: CrcParams {
+        width: 64,
+        poly: 0x42F0_E1EB_A9EA_3693,
+        init: 0xFFFF_FFFF_FFFF_FFFF,
+        refin: true,
+        refout: true,
+        xorout: 0xFFFF_FFFF_FFFF_FFFF,
+    },
+    check: 0x995D_C9BB_DF19_39FA,
+    residue: 0x4995_8C9A_BD7D_353F,
+};
+
+/// An entry of the catalogue of any width, as returned by [`by_name`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AnyAlgorithm {
+    Crc8(Algorithm<u8>),
+    Crc16(Algorithm<u16>),
+    Crc32(Algorithm<u32>),
+    Crc64(Algorithm<u64>),
+}
+
+impl AnyAlgorithm {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Crc8(algorithm) => algorithm.name,
+            Crc16(algorithm) => algorithm.name,
+            Crc32(algorithm) => algorithm.name,
+            Crc64(algorithm) => algorithm.name,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        match self {
+            Crc8(algorithm) => algorithm.params.width,
+            Crc16(algorithm) => algorithm.params.width,
+            Crc32(algorithm) => algorithm.params.width,
+            Crc64(algorithm) => algorithm.params.width,
+        }
+    }
+
+    /// The checksum of `"123456789"`, widened to `u64`.
+    pub fn check(&self) -> u64 {
+        match self {
+            Crc8(algorithm) => algorithm.check.into(),
+            Crc16(algorithm) => algorithm.check.into(),
+            Crc32(algorithm) => algorithm.check.into(),
+            Crc64(algorithm) => algorithm.check,
+        }
+    }
+
+    /// The residue, widened to `u64`.
+    pub fn residue(&self) -> u64 {
+        match self {
+            Crc8(algorithm) => algorithm.residue.into(),
+            Crc16(algorithm) => algorithm.residue.into(),
+            Crc32(algorithm) => algorithm.residue.into(),
+            Crc64(algorithm) => algorithm.residue,
+        }
+    }
+
+    /// The polynomial in normal form, widened to `u64`.
+    pub fn poly(&self) -> u64 {
+        match self {
+            Crc8(algorithm) => algorithm.params.poly.into(),
+            Crc16(algorithm) => algorithm.params.poly.into(),
+            Crc32(algorithm) => algorithm.params.poly.into(),
+            Crc64(algorithm) => algorithm.params.poly,
+        }
+    }
+
+    /// Computes the checksum of `data`, widened to `u64`.
+    pub fn checksum(&self, data: &[u8]) -> u64 {
+        match self {
+            Crc8(algorithm) => Params::from(algorithm.params).checksum(data).into(),
+            Crc16(algorithm) => Params::from(algorithm.params).checksum(data).into(),
+            Crc32(algorithm) => Params::from(algorithm.params).checksum(data).into(),
+            Crc64(algorithm) => Params::from(algorithm.params).checksum(data),
+        }
+    }
+
+    /// A fresh state for the entry, for callers that pick the algorithm at runtime.
+    #[cfg(feature = "std")]
+    pub fn state(&self) -> Box<dyn crate::DynCrc + Send + Sync> {
+        use crate::CRC;
+        match self {
+            Crc8(algorithm) => Box::new(crate::CRC8::from_algorithm(algorithm)),
+            Crc16(algorithm) => Box::new(crate::CRC16::from_algorithm(algorithm)),
+            Crc32(algorithm) => Box::new(crate::CRC32::from_algorithm(algorithm)),
+            Crc64(algorithm) => Box::new(crate::CRC64::from_algorithm(algorithm)),
+        }
+    }
+}
+
+/// Every entry with its aliases.
+const ENTRIES: &[(AnyAlgorithm, &[&str])] = &[
+    (Crc8(CRC_5_USB), &[]),
+    (Crc8(CRC_8_SMBUS), &["CRC-8"]),
+    (Crc8(CRC_8_MAXIM_DOW), &["CRC-8/MAXIM", "DOW-CRC"]),
+    (Crc8(CRC_8_AUTOSAR), &[]),
+    (Crc8(CRC_8_BLUETOOTH), &[]),
+    (Crc8(CRC_8_SAE_J1850), &[]),
+    (Crc16(CRC_10_ATM), &["CRC-10", "CRC-10/I-610"]),
+    (Crc16(CRC_11_FLEXRAY), &["CRC-11"]),
+    (Crc16(CRC_12_UMTS), &["CRC-12/3GPP"]),
+    (Crc16(CRC_15_CAN), &["CRC-15"]),
+    (
+        Crc16(CRC_16_ARC),
+        &["ARC", "CRC-16", "CRC-16/LHA", "CRC-IBM"],
+    ),
+    (Crc16(CRC_16_DECT_R), &["R-CRC-16"]),
+    (Crc16(CRC_16_DECT_X), &["X-CRC-16"]),
+    (Crc16(CRC_16_DNP), &[]),
+    (
+        Crc16(CRC_16_IBM_3740),
+        &["CRC-16/AUTOSAR", "CRC-16/CCITT-FALSE"],
+    ),
+    (
+        Crc16(CRC_16_IBM_SDLC),
+        &[
+            "CRC-16/ISO-HDLC",
+            "CRC-16/ISO-IEC-14443-3-B",
+            "CRC-16/X-25",
+            "CRC-B",
+            "X-25",
+        ],
+    ),
+    (
+        Crc16(CRC_16_KERMIT),
+        &[
+            "CRC-16/BLUETOOTH",
+            "CRC-16/CCITT",
+            "CRC-16/CCITT-TRUE",
+            "CRC-16/V-41-LSB",
+            "CRC-CCITT",
+            "KERMIT",
+        ],
+    ),
+    (Crc16(CRC_16_MODBUS), &["MODBUS"]),
+    (Crc16(CRC_16_SPI_FUJITSU), &["CRC-16/AUG-CCITT"]),
+    (Crc16(CRC_16_T10_DIF), &[]),
+    (Crc16(CRC_16_UMTS), &["CRC-16/BUYPASS", "CRC-16/VERIFONE"]),
+    (Crc16(CRC_16_USB), &[]),
+    (
+        Crc16(CRC_16_XMODEM),
+        &[
+            "CRC-16/ACORN",
+            "CRC-16/LTE",
+            "CRC-16/V-41-MSB",
+            "XMODEM",
+            "ZMODEM",
+        ],
+    ),
+    (Crc32(CRC_17_CAN_FD), &[]),
+    (Crc32(CRC_21_CAN_FD), &[]),
+    (Crc32(CRC_24_BLE), &[]),
+    (Crc32(CRC_24_OPENPGP), &["CRC-24"]),
+    (Crc32(CRC_32_AUTOSAR), &[]),
+    (
+        Crc32(CRC_32_BZIP2),
+        &["CRC-32/AAL5", "CRC-32/DECT-B", "B-CRC-32"],
+    ),
+    (Crc32(CRC_32_CKSUM), &["CKSUM", "CRC-32/POSIX"]),
+    (
+        Crc32(CRC_32_ISCSI),
+        &[
+            "CRC-32/BASE91-C",
+            "CRC-32/CASTAGNOLI",
+            "CRC-32/INTERLAKEN",
+            "CRC-32C",
+            "CRC-32/NVME",
+        ],
+    ),
+    (
+        Crc32(CRC_32_ISO_HDLC),
+        &[
+            "CRC-32",
+            "CRC-32/ADCCP",
+            "CRC-32/V-42",
+            "CRC-32/XZ",
+            "CRC-32/ZLIB",
+            "PKZIP",
+        ],
+    ),
+    (Crc32(CRC_32_MPEG_2), &[]),
+    (Crc64(CRC_64_ECMA_182), &["CRC-64"]),
+    (Crc64(CRC_64_GO_ISO), &[]),
+    (Crc64(CRC_64_NVME), &[]),
+    (Crc64(CRC_64_WE), &[]),
+    (Crc64(CRC_64_XZ), &["CRC-64/GO-ECMA"]),
+];
+
+/// The error returned by [`by_name`] for a name that is not in the catalogue.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct UnknownAlgorithm;
+
+impl fmt::Display for UnknownAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown CRC algorithm")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UnknownAlgorithm {}
+
+/// Every entry of the catalogue, ordered by width and name.
+pub fn algorithms() -> impl Iterator<Item = AnyAlgorithm> {
+    ENTRIES.iter().map(|&(algorithm, _)| algorithm)
+}
+
+/// A catalogue entry with its metadata, as listed by [`iter`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Entry {
+    pub algorithm: AnyAlgorithm,
+    /// Other names the algorithm goes by, which [`by_name`] also accepts.
+    pub aliases: &'static [&'static str],
+}
+
+impl Entry {
+    pub fn name(&self) -> &'static str {
+        self.algorithm.name()
+    }
+
+    pub fn check(&self) -> u64 {
+        self.algorithm.check()
+    }
+
+    pub fn residue(&self) -> u64 {
+        self.algorithm.residue()
+    }
+
+    pub fn polynomial(&self) -> Polynomial {
+        Polynomial::new(self.algorithm.width(), self.algorithm.poly())
+    }
+
+    /// The polynomial in Koopman's notation, as used in his CRC tables; see
+    /// [`Polynomial::koopman`].
+    pub fn koopman(&self) -> u64 {
+        self.polynomial().koopman()
+    }
+
+    /// Where the algorithm is documented: its entry in the RevEng catalogue.
+    pub fn citation(&self) -> Citation {
+        Citation(self.name())
+    }
+}
+
+/// The URL of an algorithm's entry in the RevEng catalogue, from [`Entry::citation`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Citation(&'static str);
+
+impl fmt::Display for Citation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "https://reveng.sourceforge.io/crc-catalogue/all.htm#crc.cat."
+        )?;
+        for c in self.0.chars() {
+            let c = if c == '/' { '-' } else { c };
+            write!(f, "{}", c.to_ascii_lowercase())?;
+        }
+        Ok(())
+    }
+}
+
+/// Every entry of the catalogue with its aliases, ordered like [`algorithms`].
+pub fn iter() -> impl Iterator<Item = Entry> {
+    ENTRIES
+        .iter()
+        .map(|&(algorithm, aliases)| Entry { algorithm, aliases })
+}
+
+/// Looks up an algorithm by its catalogue name or an alias, ignoring ASCII case.
+pub fn by_name(name: &str) -> Result<AnyAlgorithm, UnknownAlgorithm> {
+    ENTRIES
+        .iter()
+        .find(|(algorithm, aliases)| {
+            core::iter::once(&algorithm.name())
+                .chain(aliases.iter())
+                .any(|known| known.eq_ignore_ascii_case(name))
+        })
+        .map(|&(algorithm, _)| algorithm)
+        .ok_or(UnknownAlgorithm)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CRC, CRC16, CRC32, CRC64, CRC8};
+
+    fn check<C, N>(algorithms: &[Algorithm<N>])
+    where
+        C: CRC<Width = N>,
+        N: Copy + PartialEq + core::fmt::Debug,
+    {
+        for algorithm in algorithms {
+            let mut crc = C::from_algorithm(algorithm);
+            crc.update(b"123456789");
+            assert_eq!(crc.finalize(), algorithm.check, "{}", algorithm.name);
+        }
+    }
+
+    #[test]
+    fn check_test() {
+        check::<CRC8, _>(&[
+            CRC_5_USB,
+            CRC_8_SMBUS,
+            CRC_8_MAXIM_DOW,
+            CRC_8_AUTOSAR,
+            CRC_8_BLUETOOTH,
+            CRC_8_SAE_J1850,
+        ]);
+        check::<CRC16, _>(&[
+            CRC_10_ATM,
+            CRC_11_FLEXRAY,
+            CRC_12_UMTS,
+            CRC_15_CAN,
+            CRC_16_ARC,
+            CRC_16_DECT_R,
+            CRC_16_DECT_X,
+            CRC_16_DNP,
+            CRC_16_IBM_3740,
+            CRC_16_IBM_SDLC,
+            CRC_16_KERMIT,
+            CRC_16_MODBUS,
+            CRC_16_SPI_FUJITSU,
+            CRC_16_T10_DIF,
+            CRC_16_UMTS,
+            CRC_16_USB,
+            CRC_16_XMODEM,
+        ]);
+        check::<CRC32, _>(&[
+            CRC_17_CAN_FD,
+            CRC_21_CAN_FD,
+            CRC_24_BLE,
+            CRC_24_OPENPGP,
+            CRC_32_AUTOSAR,
+            CRC_32_BZIP2,
+            CRC_32_CKSUM,
+            CRC_32_ISCSI,
+            CRC_32_ISO_HDLC,
+            CRC_32_MPEG_2,
+        ]);
+        check::<CRC64, _>(&[
+            CRC_64_ECMA_182,
+            CRC_64_GO_ISO,
+            CRC_64_NVME,
+            CRC_64_WE,
+            CRC_64_XZ,
+        ]);
+
+        for (algorithm, _) in ENTRIES {
+            let (computed, stated) = match algorithm {
+                Crc8(a) => (Params::from(a.params).residue().into(), a.residue.into()),
+                Crc16(a) => (Params::from(a.params).residue().into(), a.residue.into()),
+                Crc32(a) => (Params::from(a.params).residue().into(), a.residue.into()),
+                Crc64(a) => (Params::from(a.params).residue(), a.residue),
+            };
+            assert_eq!(computed, stated, "{}", algorithm.name());
+        }
+
+        let zlib = crc::Crc::<u32>::new(&crc::CRC_32_ISO_HDLC);
+        let mut crc = CRC32::create(&CRC_32_ISO_HDLC);
+        crc.update(b"hello world");
+        assert_eq!(crc.finalize(), zlib.checksum(b"hello world"));
+    }
+
+    #[test]
+    fn by_name_test() {
+        assert_eq!(by_name("CRC-16/KERMIT"), Ok(Crc16(CRC_16_KERMIT)));
+        assert_eq!(by_name("CRC-32"), Ok(Crc32(CRC_32_ISO_HDLC)));
+        assert_eq!(by_name("CRC-10"), Ok(Crc16(CRC_10_ATM)));
+        assert_eq!(by_name("crc-32/zlib"), Ok(Crc32(CRC_32_ISO_HDLC)));
+        assert_eq!(by_name("CRC-32C").unwrap().name(), "CRC-32/ISCSI");
+        assert_eq!(by_name("CRC-16/KERMITT"), Err(UnknownAlgorithm));
+        for (algorithm, aliases) in ENTRIES {
+            let check = algorithm.checksum(b"123456789");
+            assert_eq!(check, algorithm.check(), "{}", algorithm.name());
+            for alias in *aliases {
+                assert_eq!(by_name(alias), Ok(*algorithm));
+            }
+        }
+        assert_eq!(by_name("CRC-64/NVME").unwrap().width(), 64);
+    }
+
+    #[test]
+    fn iter_test() {
+        assert_eq!(iter().count(), algorithms().count());
+        let zlib = iter()
+            .find(|entry| entry.name() == "CRC-32/ISO-HDLC")
+            .unwrap();
+        assert!(zlib.aliases.contains(&"PKZIP"));
+        assert_eq!(zlib.check(), 0xCBF4_3926);
+        assert_eq!(zlib.residue(), 0xDEBB_20E3);
+        assert_eq!(zlib.koopman(), 0x8260_8EDB);
+        assert_eq!(
+            zlib.citation().to_string(),
+            "https://reveng.sourceforge.io/crc-catalogue/all.htm#crc.cat.crc-32-iso-hdlc"
+        );
+        let can = iter().find(|entry| entry.name() == "CRC-15/CAN").unwrap();
+        assert_eq!(can.koopman(), 0x62CC);
+        for entry in iter() {
+            assert_eq!(entry.check(), entry.algorithm.checksum(b"123456789"));
+        }
+    }
+}
@@ -0,0 +1,213 @@
+//! Resumable CRC-32C hashing of large files.
+//!
+//! [`hash_file`] periodically writes the current offset and CRC register to a sidecar file.
+//! If the process is killed, the next call with the same sidecar continues from the last
+//! checkpoint instead of from the start. The checkpoint also records the file's size and
+//! modification time and is discarded if either changed in the meantime. The sidecar is
+//! replaced atomically by rename, so a crash while writing it leaves the previous checkpoint
+//! intact, and it is removed once the hash is complete.
+
+use crate::catalog::CRC_32_ISCSI;
+use crate::compare::read_full;
+use crate::manifest::mtime_ns;
+use crate::trace;
+use crate::Crc;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::Instant;
+
+const MAGIC: &str = "crclib-checkpoint v1";
+
+/// Progress of an interrupted hash.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// Number of bytes already processed.
+    pub offset: u64,
+    /// The CRC-32C register after `offset` bytes, before finalization.
+    pub state: u32,
+    pub size: u64,
+    pub mtime: Option<u64>,
+}
+
+impl Checkpoint {
+    /// Reads a checkpoint from `sidecar`.
+    ///
+    /// Returns `None` if there is no sidecar or it cannot be parsed.
+    pub fn load(sidecar: &Path) -> io::Result<Option<Self>> {
+        let text = match fs::read_to_string(sidecar) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        let mut lines = text.lines();
+        if lines.next() != Some(MAGIC) {
+            return Ok(None);
+        }
+        let fields: Vec<&str> = lines.next().unwrap_or("").split(' ').collect();
+        let [offset, state, size, mtime] = fields[..] else {
+            return Ok(None);
+        };
+        let parse = || {
+            Some(Self {
+                offset: offset.parse().ok()?,
+                state: u32::from_str_radix(state, 16).ok()?,
+                size: size.parse().ok()?,
+                mtime: match mtime {
+                    "-" => None,
+                    mtime => Some(mtime.parse().ok()?),
+                },
+            })
+        };
+        Ok(parse())
+    }
+
+    /// Atomically replaces `sidecar` with this checkpoint.
+    pub fn store(&self, sidecar: &Path) -> io::Result<()> {
+        let mtime = match self.mtime {
+            Some(mtime) => mtime.to_string(),
+            None => "-".to_string(),
+        };
+        let text = format!(
+            "{}\n{} {:08x} {} {}\n",
+            MAGIC, self.offset, self.state, self.size, mtime
+        );
+        let mut tmp = sidecar.as_os_str().to_owned();
+        tmp.push(".tmp");
+        fs::write(&tmp, text)?;
+        fs::rename(&tmp, sidecar)
+    }
+}
+
+/// The result of [`hash_file`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Hashed {
+    pub size: u64,
+    pub crc: u32,
+    /// Offset the hash was resumed from; zero if it started from the beginning.
+    pub resumed_from: u64,
+}
+
+/// Computes the CRC-32C of the file at `path`, checkpointing to `sidecar` every `interval`
+/// bytes.
+///
+/// # Panics
+///
+/// Panics if `interval` is zero.
+pub fn hash_file(path: &Path, sidecar: &Path, interval: u64) -> io::Result<Hashed> {
+    assert!(interval > 0, "checkpoint interval must be non-zero");
+    let _span = trace::file_span("hash_file_resumable", path);
+    let started = Instant::now();
+    let mut file = File::open(path)?;
+    let metadata = file.metadata()?;
+    let (size, mtime) = (metadata.len(), mtime_ns(&metadata));
+
+    let mut crc = Crc::<u32>::cached(&CRC_32_ISCSI).digest();
+    let mut offset = match Checkpoint::load(sidecar)? {
+        Some(checkpoint)
+            if checkpoint.size == size
+                && checkpoint.mtime == mtime
+                && checkpoint.offset <= size =>
+        {
+            crc.set_raw_state(checkpoint.state);
+            checkpoint.offset
+        }
+        _ => 0,
+    };
+    let resumed_from = offset;
+    file.seek(SeekFrom::Start(offset))?;
+
+    let mut buf = vec![0; interval.min(1 << 20) as usize];
+    loop {
+        let mut chunk = (&mut file).take(interval);
+        let mut chunk_len = 0;
+        loop {
+            let n = read_full(&mut chunk, &mut buf)?;
+            if n == 0 {
+                break;
+            }
+            crc.update(&buf[..n]);
+            chunk_len += n as u64;
+        }
+        offset += chunk_len;
+        if chunk_len < interval {
+            break;
+        }
+        Checkpoint {
+            offset,
+            state: crc.raw_state(),
+            size,
+            mtime,
+        }
+        .store(sidecar)?;
+    }
+
+    trace::hashed("CRC-32C", offset - resumed_from, started);
+    match fs::remove_file(sidecar) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+        _ => {}
+    }
+    Ok(Hashed {
+        size: offset,
+        crc: crc.finalize(),
+        resumed_from,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hash_file, Checkpoint};
+    use crate::catalog::CRC_32_ISCSI;
+    use crate::manifest::mtime_ns;
+    use crate::Crc;
+    use std::fs;
+
+    #[test]
+    fn resume_test() {
+        let dir = std::env::temp_dir().join(format!("crclib-checkpoint-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let (path, sidecar) = (dir.join("image"), dir.join("image.ckpt"));
+        let data: Vec<u8> = (0..=255).cycle().take(100_000).collect();
+        fs::write(&path, &data).unwrap();
+        let crc32c = Crc::<u32>::cached(&CRC_32_ISCSI);
+        let expected = crc32c.checksum(&data);
+
+        let fresh = hash_file(&path, &sidecar, 4096).unwrap();
+        assert_eq!(
+            (fresh.crc, fresh.size, fresh.resumed_from),
+            (expected, 100_000, 0)
+        );
+        assert!(!sidecar.exists());
+
+        // Leave a checkpoint behind as an interrupted run would.
+        let checkpoint = Checkpoint {
+            offset: 60_000,
+            state: {
+                let mut digest = crc32c.digest();
+                digest.update(&data[..60_000]);
+                digest.raw_state()
+            },
+            size: 100_000,
+            mtime: mtime_ns(&fs::metadata(&path).unwrap()),
+        };
+        checkpoint.store(&sidecar).unwrap();
+        assert_eq!(Checkpoint::load(&sidecar).unwrap(), Some(checkpoint));
+        let resumed = hash_file(&path, &sidecar, 4096).unwrap();
+        assert_eq!((resumed.crc, resumed.resumed_from), (expected, 60_000));
+        assert!(!sidecar.exists());
+
+        // A checkpoint for a different version of the file is ignored.
+        Checkpoint {
+            size: 99_999,
+            ..checkpoint
+        }
+        .store(&sidecar)
+        .unwrap();
+        assert_eq!(hash_file(&path, &sidecar, 4096).unwrap().resumed_from, 0);
+
+        fs::write(&sidecar, "garbage").unwrap();
+        assert_eq!(Checkpoint::load(&sidecar).unwrap(), None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
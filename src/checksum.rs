@@ -0,0 +1,258 @@
+//! Finalized checksums that know their width.
+//!
+//! A bare `u32` cannot tell whether it holds a CRC-32 or a CRC-24, so printing it with `{:x}`
+//! drops leading zeros and manifests end up with `"c814"` next to `"0c814"`. A [`Checksum`]
+//! from [`Digest::finalize_checksum`](crate::Digest::finalize_checksum) or a state's
+//! `finalize_checksum` always prints as many hex digits as its width needs, and parses back
+//! from that form.
+
+use crate::engine::Width;
+use crate::hex::{FromHex, HexError};
+use core::fmt;
+use core::str::FromStr;
+
+/// A checksum value together with its width in bits.
+///
+/// `Display` is upper-case hex, zero-padded to the width; `{:x}` and `{:X}` pick the case and
+/// `{:#x}` adds a `0x` prefix. Equality compares the values only, so a checksum parsed from
+/// `"0x00C814"` equals a computed CRC-16 of `0xC814`.
+#[derive(Clone, Copy, Debug)]
+pub struct Checksum<W> {
+    value: W,
+    width: u32,
+}
+
+impl<W: Width> Checksum<W> {
+    /// # Panics
+    ///
+    /// Panics if `width` is zero or wider than `W`.
+    pub fn new(value: W, width: u32) -> Self {
+        assert!(
+            (1..=W::BITS).contains(&width),
+            "CRC width must fit the register type"
+        );
+        Self { value, width }
+    }
+
+    pub fn value(self) -> W {
+        self.value
+    }
+
+    pub fn width(self) -> u32 {
+        self.width
+    }
+
+    /// The checksum in `ceil(width / 8)` bytes, most significant first, e.g. three bytes for
+    /// a CRC-24.
+    pub fn to_be_bytes(self) -> ChecksumBytes {
+        let mut bytes = self.to_le_bytes();
+        bytes.bytes[..bytes.len as usize].reverse();
+        bytes
+    }
+
+    /// The checksum in `ceil(width / 8)` bytes, least significant first.
+    pub fn to_le_bytes(self) -> ChecksumBytes {
+        let value: u128 = self.value.into();
+        ChecksumBytes {
+            bytes: value.to_le_bytes(),
+            len: self.width.div_ceil(8) as u8,
+        }
+    }
+
+    /// The zero-padded hex digits in `buf`, using `digits` for the case.
+    fn hex<'b>(&self, buf: &'b mut [u8; 32], digits: &[u8; 16]) -> &'b str {
+        let value: u128 = self.value.into();
+        let len = self.width.div_ceil(4) as usize;
+        for (i, digit) in buf[..len].iter_mut().rev().enumerate() {
+            *digit = digits[(value >> (4 * i)) as usize & 0xF];
+        }
+        core::str::from_utf8(&buf[..len]).unwrap()
+    }
+
+    fn fmt_hex(&self, f: &mut fmt::Formatter<'_>, digits: &[u8; 16]) -> fmt::Result {
+        f.pad_integral(true, "0x", self.hex(&mut [0; 32], digits))
+    }
+}
+
+/// Formats like `Display`: upper-case hex, zero-padded to the width.
+#[cfg(feature = "defmt")]
+impl<W: Width> defmt::Format for Checksum<W> {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "{=str}", self.hex(&mut [0; 32], b"0123456789ABCDEF"));
+    }
+}
+
+/// Formats a register or parameter for `Debug` as `0x` and as many lower-case hex digits as
+/// `width` bits need.
+pub(crate) struct Hex {
+    value: u128,
+    digits: usize,
+}
+
+impl Hex {
+    pub(crate) fn new(value: impl Into<u128>, width: u32) -> Self {
+        Self {
+            value: value.into(),
+            digits: width.div_ceil(4).max(1) as usize,
+        }
+    }
+}
+
+impl fmt::Debug for Hex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#0width$x}", self.value, width = self.digits + 2)
+    }
+}
+
+/// Formats parameters for `Debug` like their derived impl, but with the polynomial, init and
+/// xorout in hex.
+pub(crate) struct HexParams<'a, N>(pub(crate) &'a crate::CrcParams<N>);
+
+impl<N: Copy + Into<u128>> fmt::Debug for HexParams<'_, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let params = self.0;
+        let hex = |value| Hex::new(value, params.width);
+        f.debug_struct("CrcParams")
+            .field("width", &params.width)
+            .field("poly", &hex(params.poly))
+            .field("init", &hex(params.init))
+            .field("refin", &params.refin)
+            .field("refout", &params.refout)
+            .field("xorout", &hex(params.xorout))
+            .finish()
+    }
+}
+
+/// The bytes of a [`Checksum`], as many as its width needs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChecksumBytes {
+    bytes: [u8; 16],
+    len: u8,
+}
+
+impl core::ops::Deref for ChecksumBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.bytes[..self.len as usize]
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for ChecksumBytes {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "{=[u8]:#04x}", &self[..]);
+    }
+}
+
+impl AsRef<[u8]> for ChecksumBytes {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl<W: Width> PartialEq for Checksum<W> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<W: Width> Eq for Checksum<W> {}
+
+impl<W: Width> PartialEq<W> for Checksum<W> {
+    fn eq(&self, other: &W) -> bool {
+        self.value == *other
+    }
+}
+
+impl<W: Width> fmt::Display for Checksum<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::UpperHex::fmt(self, f)
+    }
+}
+
+impl<W: Width> fmt::LowerHex for Checksum<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_hex(f, b"0123456789abcdef")
+    }
+}
+
+impl<W: Width> fmt::UpperHex for Checksum<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_hex(f, b"0123456789ABCDEF")
+    }
+}
+
+/// Parses hex as [`FromHex`] does. The width is that of the digits given, so `"0C814"` keeps
+/// printing with five digits.
+impl<W: Width + FromHex> FromStr for Checksum<W> {
+    type Err = HexError;
+
+    fn from_str(text: &str) -> Result<Self, HexError> {
+        let value = W::from_hex_str(text)?;
+        let digits = text.len()
+            - if text.starts_with("0x") || text.starts_with("0X") {
+                2
+            } else {
+                0
+            };
+        Ok(Self::new(value, (4 * digits as u32).min(W::BITS)))
+    }
+}
+
+macro_rules! impl_finalize_checksum {
+    ($($crc:ty),*) => {
+        $(
+            impl $crc {
+                /// [`CRC::finalize`](crate::CRC::finalize) with the width attached.
+                pub fn finalize_checksum(&self) -> Checksum<<Self as crate::CRC>::Width> {
+                    Checksum::new(crate::CRC::finalize(self), self.params.width)
+                }
+            }
+        )*
+    };
+}
+
+impl_finalize_checksum!(
+    crate::CRC8,
+    crate::CRC16,
+    crate::CRC32,
+    crate::CRC64,
+    crate::CRC128
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{catalog, Crc, CRC, CRC16};
+
+    #[test]
+    fn checksum_test() {
+        let crc = Crc::new(&catalog::CRC_24_OPENPGP);
+        let mut digest = crc.digest();
+        digest.update(b"123456789");
+        let checksum = digest.finalize_checksum();
+        assert_eq!(format!("{}", checksum), "21CF02");
+        assert_eq!(format!("{:#x}", checksum), "0x21cf02");
+
+        let small = Checksum::new(0x0C14u16, 16);
+        assert_eq!(
+            format!("{} {:x} {:>6X}", small, small, small),
+            "0C14 0c14   0C14"
+        );
+        assert_eq!("0c14".parse::<Checksum<u16>>(), Ok(small));
+        assert_eq!(
+            "0x00C14".parse::<Checksum<u32>>().unwrap().to_string(),
+            "00C14"
+        );
+        assert!("c14g".parse::<Checksum<u16>>().is_err());
+        assert_eq!(*checksum.to_be_bytes(), [0x21, 0xCF, 0x02]);
+        assert_eq!(*checksum.to_le_bytes(), [0x02, 0xCF, 0x21]);
+        assert_eq!(*Checksum::new(0x1Fu8, 5).to_be_bytes(), [0x1F]);
+        assert_eq!(small.to_be_bytes().as_ref(), 0x0C14u16.to_be_bytes());
+
+        let mut state = CRC16::from_algorithm(&catalog::CRC_16_XMODEM);
+        state.update(b"123456789");
+        assert_eq!(state.finalize_checksum(), catalog::CRC_16_XMODEM.check);
+    }
+}
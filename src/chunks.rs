@@ -0,0 +1,256 @@
+//! Chunk manifests for finding which parts of a stream changed.
+//!
+//! [`ChunkManifest::from_reader`] cuts a stream into fixed-size chunks and records the
+//! offset, length and CRC-32C of each; [`ChunkManifest::from_reader_with`] takes the chunk
+//! lengths from the caller instead, e.g. from a content-defined chunker, so that an insertion
+//! only disturbs the chunks around it. [`ChunkManifest::verify`] later re-reads a stream
+//! along the same boundaries and reports the chunks whose data differs, which are the ones a
+//! sync tool has to transfer again. [`ChunkManifest::crc`] combines the chunk CRCs into the
+//! CRC-32C of the whole stream.
+//!
+//! Manifests serialize to one tab-separated `offset length crc` line per chunk via
+//! [`Display`](std::fmt::Display) and back via [`FromStr`](std::str::FromStr).
+
+use crate::compare::read_full;
+use crate::const_crc::Crc32Iscsi;
+use crate::manifest::ParseError;
+use crate::oneshot::{crc32c, crc32c_combine};
+use crate::CRC;
+use std::io::{self, Read};
+
+const HEADER: &str = "# crclib chunks v1 crc32c";
+
+/// One chunk of a stream.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Chunk {
+    pub offset: u64,
+    pub len: u64,
+    /// CRC-32C of the chunk's data.
+    pub crc: u32,
+}
+
+/// The chunks of a stream, in order and without gaps.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ChunkManifest {
+    pub chunks: Vec<Chunk>,
+}
+
+/// What [`ChunkManifest::verify`] found.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Changes {
+    /// Indices of the chunks whose data differs, including those the stream ends inside or
+    /// before.
+    pub changed: Vec<usize>,
+    /// How many bytes the stream has past the last chunk.
+    pub appended: u64,
+}
+
+impl Changes {
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty() && self.appended == 0
+    }
+}
+
+impl ChunkManifest {
+    /// Reads `reader` to the end in chunks of `chunk_size` bytes; the last one may be shorter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is zero.
+    pub fn from_reader(reader: impl Read, chunk_size: u64) -> io::Result<Self> {
+        Self::from_reader_with(reader, std::iter::repeat(chunk_size))
+    }
+
+    /// Reads `reader` to the end in chunks of the lengths `lens` yields. Data left when `lens`
+    /// runs out becomes one final chunk.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lens` yields zero.
+    pub fn from_reader_with(
+        mut reader: impl Read,
+        lens: impl IntoIterator<Item = u64>,
+    ) -> io::Result<Self> {
+        let mut lens = lens.into_iter();
+        let mut buf = vec![0; 64 * 1024];
+        let mut chunks = Vec::new();
+        let mut offset = 0;
+        loop {
+            let len = lens.next().unwrap_or(u64::MAX);
+            assert!(len > 0, "chunk length must be non-zero");
+            let (read, crc) = checksum_chunk(&mut reader, len, &mut buf)?;
+            if read == 0 {
+                break;
+            }
+            chunks.push(Chunk {
+                offset,
+                len: read,
+                crc,
+            });
+            offset += read;
+            if read < len {
+                break;
+            }
+        }
+        Ok(Self { chunks })
+    }
+
+    /// The length of the stream the manifest was made from.
+    pub fn len(&self) -> u64 {
+        self.chunks
+            .last()
+            .map_or(0, |chunk| chunk.offset + chunk.len)
+    }
+
+    /// The CRC-32C of the whole stream, combined from the chunk CRCs without re-reading it.
+    pub fn crc(&self) -> u32 {
+        self.chunks.iter().fold(crc32c(&[]), |crc, chunk| {
+            crc32c_combine(crc, chunk.crc, chunk.len)
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// Reads `reader` to the end along the manifest's chunk boundaries and reports which
+    /// chunks no longer match.
+    pub fn verify(&self, mut reader: impl Read) -> io::Result<Changes> {
+        let mut buf = vec![0; 64 * 1024];
+        let mut changes = Changes::default();
+        for (index, chunk) in self.chunks.iter().enumerate() {
+            let (read, crc) = checksum_chunk(&mut reader, chunk.len, &mut buf)?;
+            if read != chunk.len || crc != chunk.crc {
+                changes.changed.push(index);
+            }
+        }
+        changes.appended = io::copy(&mut reader, &mut io::sink())?;
+        Ok(changes)
+    }
+}
+
+/// Reads up to `len` bytes and returns how many there were and their CRC-32C.
+fn checksum_chunk(reader: &mut impl Read, len: u64, buf: &mut [u8]) -> io::Result<(u64, u32)> {
+    let mut crc = Crc32Iscsi::new();
+    let mut read = 0;
+    while read < len {
+        let want = (len - read).min(buf.len() as u64) as usize;
+        let n = read_full(reader, &mut buf[..want])?;
+        crc.update(&buf[..n]);
+        read += n as u64;
+        if n < want {
+            break;
+        }
+    }
+    Ok((read, crc.finalize()))
+}
+
+impl std::fmt::Display for ChunkManifest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", HEADER)?;
+        for chunk in &self.chunks {
+            writeln!(f, "{}\t{}\t{:08x}", chunk.offset, chunk.len, chunk.crc)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses the [`Display`](std::fmt::Display) form. Chunks must follow each other without
+/// gaps, starting at offset zero.
+impl std::str::FromStr for ChunkManifest {
+    type Err = ParseError;
+
+    fn from_str(text: &str) -> Result<Self, ParseError> {
+        let mut chunks = Vec::new();
+        let mut offset = 0;
+        for (index, line) in text.lines().enumerate() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = ParseError { line: index + 1 };
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [offset_field, len, crc] = fields[..] else {
+                return Err(error);
+            };
+            let chunk = Chunk {
+                offset: offset_field.parse().map_err(|_| error.clone())?,
+                len: len.parse().map_err(|_| error.clone())?,
+                crc: u32::from_str_radix(crc, 16).map_err(|_| error.clone())?,
+            };
+            if chunk.offset != offset || chunk.len == 0 {
+                return Err(error);
+            }
+            offset = offset.checked_add(chunk.len).ok_or(error)?;
+            chunks.push(chunk);
+        }
+        Ok(Self { chunks })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunk_manifest_test() {
+        let data: Vec<u8> = (0..10_000u32).map(|i| ((i * 31) >> 2) as u8).collect();
+        let manifest = ChunkManifest::from_reader(&data[..], 4096).unwrap();
+        assert_eq!(manifest.chunks.len(), 3);
+        assert_eq!(manifest.len(), 10_000);
+        assert_eq!(
+            manifest.chunks[2],
+            Chunk {
+                offset: 8192,
+                len: 1808,
+                crc: crc32c(&data[8192..]),
+            }
+        );
+        assert_eq!(
+            ChunkManifest::from_reader(&data[..8192], 4096)
+                .unwrap()
+                .chunks
+                .len(),
+            2
+        );
+        assert!(ChunkManifest::from_reader(&[][..], 4096)
+            .unwrap()
+            .is_empty());
+
+        let custom = ChunkManifest::from_reader_with(&data[..], [100, 900]).unwrap();
+        let lens: Vec<u64> = custom.chunks.iter().map(|chunk| chunk.len).collect();
+        assert_eq!(lens, [100, 900, 9000]);
+        assert_eq!(custom.crc(), crc32c(&data));
+        assert_eq!(manifest.crc(), crc32c(&data));
+        assert_eq!(ChunkManifest::default().crc(), crc32c(&[]));
+
+        let text = manifest.to_string();
+        assert!(text.starts_with(HEADER));
+        assert_eq!(text.parse::<ChunkManifest>(), Ok(manifest));
+        assert_eq!(
+            "0\t10\t0000abcd\n20\t5\t00000000".parse::<ChunkManifest>(),
+            Err(ParseError { line: 2 })
+        );
+        assert_eq!(
+            "0\t18446744073709551615\t0\n18446744073709551615\t1\t0".parse::<ChunkManifest>(),
+            Err(ParseError { line: 2 })
+        );
+    }
+
+    #[test]
+    fn verify_test() {
+        let mut data: Vec<u8> = (0..10_000u32).map(|i| ((i * 31) >> 2) as u8).collect();
+        let manifest = ChunkManifest::from_reader(&data[..], 1000).unwrap();
+        assert!(manifest.verify(&data[..]).unwrap().is_empty());
+
+        data[4321] ^= 0x10;
+        data[9999] ^= 0x01;
+        let changes = manifest.verify(&data[..]).unwrap();
+        assert_eq!(changes.changed, [4, 9]);
+        assert_eq!(changes.appended, 0);
+
+        let changes = manifest.verify(&data[..2500]).unwrap();
+        assert_eq!(changes.changed, [2, 3, 4, 5, 6, 7, 8, 9]);
+        data.extend_from_slice(b"tail");
+        assert_eq!(manifest.verify(&data[..]).unwrap().appended, 4);
+    }
+}
@@ -0,0 +1,82 @@
+//! The checksum of POSIX `cksum`.
+//!
+//! `cksum` runs [CRC-32/CKSUM](crate::catalog::CRC_32_CKSUM) over the data and then over the
+//! data's length in bytes, least significant byte first and without trailing zero bytes. So
+//! its output differs from the catalogue's check value: `printf 123456789 | cksum` prints
+//! `930766865 9`, which [`cksum`] reproduces.
+
+use crate::const_crc::Crc32Cksum;
+use crate::CRC;
+
+/// A running POSIX `cksum` checksum.
+#[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Cksum {
+    crc: Crc32Cksum,
+    len: u64,
+}
+
+impl Cksum {
+    pub const fn new() -> Self {
+        Self {
+            crc: Crc32Cksum::new(),
+            len: 0,
+        }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.crc.update(data);
+        self.len += data.len() as u64;
+    }
+
+    /// The number of bytes fed so far, the second column of `cksum`'s output.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The checksum of the data fed so far, the first column of `cksum`'s output.
+    pub fn finalize(&self) -> u32 {
+        let mut crc = self.crc;
+        let mut len = self.len;
+        while len != 0 {
+            crc.update(&[len as u8]);
+            len >>= 8;
+        }
+        crc.finalize()
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+/// The `cksum` checksum of `data`.
+pub fn cksum(data: &[u8]) -> u32 {
+    let mut state = Cksum::new();
+    state.update(data);
+    state.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cksum_test() {
+        assert_eq!(cksum(b"123456789"), 930_766_865);
+        assert_eq!(cksum(b""), 4_294_967_295);
+        let data = [b'Z'; 300];
+        assert_eq!(cksum(&data), 2_789_565_232);
+        let mut state = Cksum::new();
+        state.update(&data[..100]);
+        state.update(&data[100..]);
+        assert_eq!(state.len(), 300);
+        assert_eq!(state.finalize(), cksum(&data));
+        state.reset();
+        assert!(state.is_empty());
+    }
+}
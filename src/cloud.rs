@@ -0,0 +1,115 @@
+//! Encoding of CRC-32C values in cloud storage checksum headers.
+//!
+//! Both GCS (`x-goog-hash: crc32c=...`) and S3 (`x-amz-checksum-crc32c`) transmit the
+//! checksum as the base64 encoding of its four big-endian bytes.
+
+use crate::base64;
+use crate::oneshot::crc32c;
+use crate::trace;
+
+/// Errors produced while parsing or checking a checksum header.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum HeaderError {
+    /// The value is not valid base64 or does not decode to exactly four bytes.
+    Malformed,
+    /// An `x-goog-hash` header without a `crc32c=` entry.
+    Missing,
+    /// The header parsed but does not match the locally computed checksum.
+    Mismatch { expected: u32, actual: u32 },
+}
+
+impl std::fmt::Display for HeaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Malformed => write!(f, "malformed CRC-32C header value"),
+            Self::Missing => write!(f, "no crc32c entry in header"),
+            Self::Mismatch { expected, actual } => write!(
+                f,
+                "CRC-32C mismatch: header says {:#010X}, data has {:#010X}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+impl std::error::Error for HeaderError {}
+
+/// Encodes `crc` the way both GCS and S3 expect it, e.g. `"yZRlqg=="`.
+pub fn encode_crc32c(crc: u32) -> String {
+    base64::encode(&crc.to_be_bytes())
+}
+
+/// Decodes a base64 CRC-32C value such as the `x-amz-checksum-crc32c` header.
+pub fn decode_crc32c(value: &str) -> Result<u32, HeaderError> {
+    let bytes = base64::decode(value.trim()).ok_or(HeaderError::Malformed)?;
+    let bytes: [u8; 4] = bytes.try_into().map_err(|_| HeaderError::Malformed)?;
+    Ok(u32::from_be_bytes(bytes))
+}
+
+/// Extracts the CRC-32C from an `x-goog-hash` header value like `crc32c=n03x6A==,md5=...`.
+pub fn decode_goog_hash(header: &str) -> Result<u32, HeaderError> {
+    header
+        .split(',')
+        .filter_map(|entry| entry.trim().split_once('='))
+        .find(|(name, _)| name.eq_ignore_ascii_case("crc32c"))
+        .ok_or(HeaderError::Missing)
+        .and_then(|(_, value)| decode_crc32c(value))
+}
+
+/// Formats an `x-goog-hash` entry for `crc`.
+pub fn encode_goog_hash(crc: u32) -> String {
+    format!("crc32c={}", encode_crc32c(crc))
+}
+
+/// Checks a base64 CRC-32C header value against a locally computed checksum.
+pub fn verify_crc32c(value: &str, actual: u32) -> Result<(), HeaderError> {
+    let expected = decode_crc32c(value)?;
+    trace::verified("CRC-32C", "checksum header", expected as u64, actual as u64);
+    if expected == actual {
+        Ok(())
+    } else {
+        Err(HeaderError::Mismatch { expected, actual })
+    }
+}
+
+/// Checksums `data` and checks it against a base64 CRC-32C header value.
+pub fn verify_crc32c_data(value: &str, data: &[u8]) -> Result<(), HeaderError> {
+    verify_crc32c(value, crc32c(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_decode_test() {
+        // GCS documents "hello world" as crc32c=yZRlqg==.
+        let crc = crc32c(b"hello world");
+        assert_eq!(crc, 0xC994_65AA);
+        assert_eq!(encode_crc32c(crc), "yZRlqg==");
+        assert_eq!(decode_crc32c("yZRlqg=="), Ok(crc));
+        assert_eq!(encode_goog_hash(crc), "crc32c=yZRlqg==");
+        assert_eq!(
+            decode_goog_hash("md5=XrY7u+Ae7tCTyyK7j1rNww==, crc32c=yZRlqg=="),
+            Ok(crc)
+        );
+        assert_eq!(
+            decode_goog_hash("md5=XrY7u+Ae7tCTyyK7j1rNww=="),
+            Err(HeaderError::Missing)
+        );
+        assert_eq!(decode_crc32c("aGVsbG8="), Err(HeaderError::Malformed));
+    }
+
+    #[test]
+    fn verify_test() {
+        assert_eq!(verify_crc32c_data("yZRlqg==", b"hello world"), Ok(()));
+        assert_eq!(
+            verify_crc32c_data("yZRlqg==", b"hello world!"),
+            Err(HeaderError::Mismatch {
+                expected: 0xC994_65AA,
+                actual: crc32c(b"hello world!"),
+            })
+        );
+    }
+}
@@ -0,0 +1,186 @@
+//! Lookup tables as C or Rust source.
+//!
+//! [`generate`] prints the 256-entry table of a parameter set, computed by the same code as
+//! the tables this crate uses, for firmware that cannot link Rust:
+//!
+//! ```text
+//! /* width=16 poly=0x1021 init=0x0000 refin=false refout=false xorout=0x0000 check=0x31c3
+//!  * crc = crc16_table[((crc >> 8) ^ byte) & 0xFF] ^ (crc << 8), masked to 16 bits */
+//! static const uint16_t crc16_table[256] = {
+//!     0x0000, 0x1021, 0x2042, 0x3063, 0x4084, 0x50a5, 0x60c6, 0x70e7,
+//!     ...
+//! };
+//! ```
+//!
+//! The header comment holds the parameters and the loop the table is meant for. Entries of
+//! MSB-first algorithms of at least 8 bits are in the low `width` bits; narrower ones are
+//! aligned to the top of a byte, where their register has to be kept for a byte-indexed
+//! table.
+
+use crate::engine::Width;
+use crate::{reveng, table, CrcParams};
+use std::fmt::{self, Write as _};
+
+/// The language of the generated source.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Language {
+    /// A `static const` array of a `<stdint.h>` type.
+    C,
+    /// A `pub static` array.
+    Rust,
+}
+
+/// C has no standard integer type for the entries of a table wider than 64 bits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TooWide {
+    pub width: u32,
+}
+
+impl fmt::Display for TooWide {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "C has no integer type for a {}-bit table", self.width)
+    }
+}
+
+impl std::error::Error for TooWide {}
+
+/// The loop consuming one `byte` with the table called `name`.
+fn update_step<W: Width>(params: &CrcParams<W>, name: &str) -> String {
+    let width = params.width;
+    if params.refin {
+        format!(
+            "crc = {}[(crc ^ byte) & 0xFF] ^ (crc >> 8), starting from init reflected",
+            name
+        )
+    } else if width >= 8 {
+        format!(
+            "crc = {}[((crc >> {}) ^ byte) & 0xFF] ^ (crc << 8), masked to {} bits",
+            name,
+            width - 8,
+            width
+        )
+    } else {
+        format!(
+            "crc = {}[crc ^ byte], starting from init << {} and shifting the result down by {}",
+            name,
+            8 - width,
+            8 - width
+        )
+    }
+}
+
+/// Emits the lookup table of `params` as a `language` array called `name`.
+///
+/// Fails for C tables wider than 64 bits.
+pub fn generate<W: Width>(
+    params: &CrcParams<W>,
+    language: Language,
+    name: &str,
+) -> Result<String, TooWide> {
+    let params = table::checked(*params);
+    let bits = match params.width {
+        0..=8 => 8,
+        9..=16 => 16,
+        17..=32 => 32,
+        33..=64 => 64,
+        _ if language == Language::C => {
+            return Err(TooWide {
+                width: params.width,
+            })
+        }
+        _ => 128,
+    };
+    let model = reveng::Algorithm {
+        residue: None,
+        ..reveng::Algorithm::from(params)
+    };
+    let step = update_step(&params, name);
+
+    let mut source = String::new();
+    let _ = match language {
+        Language::C => writeln!(
+            source,
+            "/* {}\n * {} */\nstatic const uint{}_t {}[256] = {{",
+            model, step, bits, name
+        ),
+        Language::Rust => writeln!(
+            source,
+            "// {}\n// {}\npub static {}: [u{}; 256] = [",
+            model, step, name, bits
+        ),
+    };
+
+    let shift = if params.refin {
+        0
+    } else {
+        W::BITS - params.width.max(8)
+    };
+    let per_line = match bits {
+        128 => 2,
+        64 => 4,
+        _ => 8,
+    };
+    let digits = bits as usize / 4;
+    for row in table::build(&params).chunks(per_line) {
+        source.push_str("   ");
+        for &entry in row {
+            let entry: u128 = (entry >> shift).into();
+            let _ = write!(source, " {:#0w$x},", entry, w = digits + 2);
+        }
+        source.push('\n');
+    }
+    source.push_str(match language {
+        Language::C => "};\n",
+        Language::Rust => "];\n",
+    });
+    Ok(source)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog;
+
+    #[test]
+    fn generate_test() {
+        let source = generate(&catalog::CRC_16_XMODEM.params, Language::C, "crc16_table").unwrap();
+        let mut lines = source.lines();
+        assert_eq!(
+            lines.next(),
+            Some(
+                "/* width=16 poly=0x1021 init=0x0000 refin=false refout=false xorout=0x0000 \
+                 check=0x31c3"
+            )
+        );
+        assert_eq!(
+            lines.nth(1),
+            Some("static const uint16_t crc16_table[256] = {")
+        );
+        assert_eq!(
+            lines.next(),
+            Some("    0x0000, 0x1021, 0x2042, 0x3063, 0x4084, 0x50a5, 0x60c6, 0x70e7,")
+        );
+        assert_eq!(source.lines().count(), 3 + 32 + 1);
+        assert!(source.ends_with("0x1ef0,\n};\n"));
+
+        let source = generate(&catalog::CRC_32_ISO_HDLC.params, Language::Rust, "TABLE").unwrap();
+        assert!(source.contains("pub static TABLE: [u32; 256] = [\n    0x00000000, 0x77073096,"));
+        let source = generate(&catalog::CRC_5_USB.params, Language::Rust, "T").unwrap();
+        assert!(source.contains("[u8; 256]"));
+        let darc = CrcParams {
+            width: 82,
+            poly: 0x308c_0111_0114_0144_0411_u128,
+            init: 0,
+            refin: true,
+            refout: true,
+            xorout: 0,
+        };
+        assert_eq!(
+            generate(&darc, Language::C, "t"),
+            Err(TooWide { width: 82 })
+        );
+        assert!(generate(&darc, Language::Rust, "T")
+            .unwrap()
+            .contains("[u128; 256]"));
+    }
+}
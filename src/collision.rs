@@ -0,0 +1,173 @@
+//! Finding distinct inputs with equal CRCs.
+//!
+//! A CRC is no content hash: for an n-bit CRC, a birthday search over about 2^(n/2) inputs
+//! is expected to turn up a collision. [`find_pairs`] runs that search over short strings
+//! drawn from a given alphabet, so test corpora can include inputs that any system treating
+//! CRC equality as content equality will confuse. This is practical up to 32-bit CRCs;
+//! larger widths need more candidates than fit in memory.
+
+use crate::CRC;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::RangeInclusive;
+
+/// The inputs [`find_pairs`] draws from.
+#[derive(Clone, Debug)]
+pub struct Search<'a> {
+    /// Bytes the inputs may contain, e.g. `b"abcdefghijklmnopqrstuvwxyz"` for inputs that
+    /// survive text-only channels. Bits that are equal across the whole alphabet can never
+    /// differ between two inputs, so small alphabets need longer inputs to collide.
+    pub alphabet: &'a [u8],
+    /// Input lengths to try, shortest first. Two inputs of equal length no longer than the
+    /// CRC never collide, so lengths should extend past the CRC width in bytes.
+    pub lengths: RangeInclusive<usize>,
+    /// How many inputs to checksum at most. Every one is kept in memory.
+    pub max_candidates: usize,
+}
+
+/// Advances `input` to the next string over `alphabet` in lexicographic order, returning
+/// `false` once every string of its length has been visited.
+fn next_input(input: &mut [u8], indices: &mut [usize], alphabet: &[u8]) -> bool {
+    for (byte, index) in input.iter_mut().zip(indices.iter_mut()).rev() {
+        *index += 1;
+        if *index < alphabet.len() {
+            *byte = alphabet[*index];
+            return true;
+        }
+        *index = 0;
+        *byte = alphabet[0];
+    }
+    false
+}
+
+/// A xorshift64 generator, so sampled searches are reproducible.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+/// Searches for up to `count` pairs of distinct inputs whose CRCs, as computed by states
+/// from `create`, are equal.
+///
+/// Lengths are tried in order. Every input of a length is tried while the remaining budget
+/// covers them all; the first length with more inputs than that is sampled at random with
+/// the rest of the budget instead, since enumerating it in order would only ever vary its
+/// last few bytes. The sampling is seeded, so the search finds the same pairs every time.
+pub fn find_pairs<C, N>(
+    search: &Search,
+    count: usize,
+    create: impl Fn() -> C,
+) -> Vec<(Vec<u8>, Vec<u8>)>
+where
+    C: CRC<Width = N>,
+    N: Eq + Hash,
+{
+    let mut pairs = Vec::new();
+    if search.alphabet.is_empty() || count == 0 {
+        return pairs;
+    }
+    let mut seen = HashMap::new();
+    let mut remaining = search.max_candidates;
+    let mut try_input = |input: &[u8], pairs: &mut Vec<_>| {
+        let mut crc = create();
+        crc.update(input);
+        match seen.get(&crc.finalize()) {
+            Some(first) if first != input => pairs.push((Vec::clone(first), input.to_vec())),
+            Some(_) => {}
+            None => {
+                seen.insert(crc.finalize(), input.to_vec());
+            }
+        }
+        pairs.len() == count
+    };
+    for len in search.lengths.clone() {
+        let mut input = vec![search.alphabet[0]; len];
+        let space = u32::try_from(len)
+            .ok()
+            .and_then(|len| search.alphabet.len().checked_pow(len))
+            .filter(|&space| space <= remaining);
+        let Some(space) = space else {
+            let mut rng = Rng(0x9E37_79B9_7F4A_7C15);
+            for _ in 0..remaining {
+                for byte in &mut input {
+                    *byte = search.alphabet[(rng.next() % search.alphabet.len() as u64) as usize];
+                }
+                if try_input(&input, &mut pairs) {
+                    break;
+                }
+            }
+            return pairs;
+        };
+        remaining -= space;
+        let mut indices = vec![0; len];
+        loop {
+            if try_input(&input, &mut pairs) {
+                return pairs;
+            }
+            if !next_input(&mut input, &mut indices, search.alphabet) {
+                break;
+            }
+        }
+    }
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CRC16, CRC32, CRC8};
+
+    fn checksum<C: CRC<Width = N>, N>(data: &[u8]) -> N {
+        let mut crc = C::default();
+        crc.update(data);
+        crc.finalize()
+    }
+
+    #[test]
+    fn find_pairs_test() {
+        let search = Search {
+            alphabet: b"abcdefghijklmnopqrstuvwxyz",
+            lengths: 1..=4,
+            max_candidates: 1 << 20,
+        };
+        let pairs = find_pairs(&search, 3, CRC16::default);
+        assert_eq!(pairs.len(), 3);
+        for (a, b) in &pairs {
+            assert_ne!(a, b);
+            assert!(a.iter().chain(b).all(u8::is_ascii_lowercase));
+            assert_eq!(checksum::<CRC16, _>(a), checksum::<CRC16, _>(b));
+        }
+        assert_eq!(find_pairs(&search, 3, CRC16::default), pairs);
+
+        let search = Search {
+            lengths: 8..=8,
+            max_candidates: 1 << 18,
+            ..search
+        };
+        let (a, b) = &find_pairs(&search, 1, CRC32::default)[0];
+        assert_eq!(checksum::<CRC32, _>(a), checksum::<CRC32, _>(b));
+    }
+
+    #[test]
+    fn exhausted_test() {
+        // Two one-byte inputs always have distinct CRCs, and the budget caps the search.
+        let search = Search {
+            alphabet: b"01",
+            lengths: 1..=1,
+            max_candidates: 100,
+        };
+        assert!(find_pairs(&search, 1, CRC8::default).is_empty());
+        let search = Search {
+            lengths: 1..=16,
+            max_candidates: 3,
+            ..search
+        };
+        assert!(find_pairs(&search, 1, CRC8::default).is_empty());
+    }
+}
@@ -0,0 +1,134 @@
+//! Byte-for-byte comparison of two streams that also checksums both.
+//!
+//! [`compare`] reads both inputs to the end even after they diverge, so the CRC-32C of each
+//! is always available for logging or for a later check against a stored value.
+
+use crate::catalog::CRC_32_ISCSI;
+use crate::Crc;
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// The outcome of [`compare`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Comparison {
+    /// Offset of the first byte that differs, or the length of the shorter input if one is a
+    /// prefix of the other. `None` if both are identical.
+    pub first_difference: Option<u64>,
+    pub len_a: u64,
+    pub len_b: u64,
+    pub crc_a: u32,
+    pub crc_b: u32,
+}
+
+impl Comparison {
+    pub fn is_equal(&self) -> bool {
+        self.first_difference.is_none()
+    }
+}
+
+/// Streams `a` and `b` side by side and reports where they first differ.
+pub fn compare<A: Read, B: Read>(mut a: A, mut b: B) -> io::Result<Comparison> {
+    let (mut buf_a, mut buf_b) = (vec![0; 64 * 1024], vec![0; 64 * 1024]);
+    let crc32c = Crc::<u32>::cached(&CRC_32_ISCSI);
+    let (mut crc_a, mut crc_b) = (crc32c.digest(), crc32c.digest());
+    let (mut len_a, mut len_b) = (0u64, 0u64);
+    let mut first_difference = None;
+    loop {
+        let n_a = read_full(&mut a, &mut buf_a)?;
+        let n_b = read_full(&mut b, &mut buf_b)?;
+        if first_difference.is_none() {
+            let common = n_a.min(n_b);
+            first_difference = match buf_a[..common]
+                .iter()
+                .zip(&buf_b[..common])
+                .position(|(x, y)| x != y)
+            {
+                Some(i) => Some(len_a + i as u64),
+                None if n_a != n_b => Some(len_a + common as u64),
+                None => None,
+            };
+        }
+        crc_a.update(&buf_a[..n_a]);
+        crc_b.update(&buf_b[..n_b]);
+        len_a += n_a as u64;
+        len_b += n_b as u64;
+        if n_a == 0 && n_b == 0 {
+            break;
+        }
+    }
+    Ok(Comparison {
+        first_difference,
+        len_a,
+        len_b,
+        crc_a: crc_a.finalize(),
+        crc_b: crc_b.finalize(),
+    })
+}
+
+/// Compares the files at `a` and `b`, see [`compare`].
+pub fn compare_files(a: &Path, b: &Path) -> io::Result<Comparison> {
+    compare(fs::File::open(a)?, fs::File::open(b)?)
+}
+
+/// Reads until `buf` is full or the reader is exhausted.
+pub(crate) fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::compare;
+    use crate::oneshot::crc32c;
+
+    /// Hands out at most `step` bytes per read to exercise short reads.
+    struct Trickle<'a> {
+        data: &'a [u8],
+        step: usize,
+    }
+
+    impl std::io::Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = self.data.len().min(self.step).min(buf.len());
+            buf[..n].copy_from_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn compare_test() {
+        let data: Vec<u8> = (0..=255).cycle().take(200_000).collect();
+        let equal = compare(
+            &data[..],
+            Trickle {
+                data: &data,
+                step: 7,
+            },
+        )
+        .unwrap();
+        assert!(equal.is_equal());
+        assert_eq!(equal.crc_a, crc32c(&data));
+        assert_eq!(equal.crc_a, equal.crc_b);
+
+        let mut changed = data.clone();
+        changed[150_000] ^= 1;
+        let result = compare(&data[..], &changed[..]).unwrap();
+        assert_eq!(result.first_difference, Some(150_000));
+        assert_eq!(result.crc_b, crc32c(&changed));
+        assert_eq!((result.len_a, result.len_b), (200_000, 200_000));
+
+        let prefix = compare(&data[..100_000], &data[..]).unwrap();
+        assert_eq!(prefix.first_difference, Some(100_000));
+        assert_eq!(prefix.crc_a, crc32c(&data[..100_000]));
+    }
+}
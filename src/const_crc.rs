@@ -0,0 +1,509 @@
+//! CRC state types whose Rocksoft parameters are const generics.
+//!
+//! `ConstCRC16<0x1021, 0xFFFF, false, false, 0x0000>` is CRC-16/IBM-3740 and
+//! `ConstCRC32<0x04C1_1DB7, 0xFFFF_FFFF, true, true, 0xFFFF_FFFF>` is the zlib CRC-32. The
+//! parameters are the polynomial (normal form), init, refin, refout and xorout, as in the
+//! RevEng catalogue. Each instantiation gets its own 256-entry table, built at compile time,
+//! and the branches on the reflection flags fold away, so a custom algorithm costs the same
+//! as a hand-written table-driven one.
+//!
+//! The tables come from [`make_table32`] and its siblings, which are `const fn` and can build
+//! tables for other code too: `const TABLE: [u32; 256] = make_table32(0x04C1_1DB7, false);`
+//! lives in `.rodata`. [`ConstCRC32::checksum`] and friends evaluate whole checksums in const
+//! context.
+//!
+//! For parameters held as a value rather than in the type, `checksum_const` on
+//! [`CrcParams`] and [`catalog::Algorithm`] runs the bitwise algorithm in const context, for
+//! any width, without building a table:
+//! `const HEADER_CRC: u16 = catalog::CRC_16_XMODEM.checksum_const(b"MAGICv1");`.
+//!
+//! The types implement [`CRC`] so they work with every helper taking a CRC state. Since their
+//! parameters are part of the type, [`CRC::create`] only accepts those same parameters.
+//!
+//! The full-width entries of the [`catalog`] have aliases named after them, such as
+//! [`Crc16Modbus`] for [`catalog::CRC_16_MODBUS`].
+
+use crate::engine::Model;
+use crate::{accel, catalog};
+use crate::{CrcParams, CRC};
+
+macro_rules! make_table {
+    ($name:ident, $n:ty) => {
+        #[doc = concat!("Builds the byte-wise lookup table of a ", stringify!($n), " CRC at compile time.")]
+        ///
+        /// `poly` is in normal form. Reflected tables are built LSB-first with the reflected
+        /// polynomial, for algorithms that keep their register reflected, such as zlib's
+        /// CRC-32 with `make_table32(0x04C1_1DB7, true)`.
+        pub const fn $name(poly: $n, reflected: bool) -> [$n; 256] {
+            let mut table = [0; 256];
+            let mut i = 0;
+            while i < 256 {
+                let mut crc = if reflected {
+                    i as $n
+                } else {
+                    (i as $n) << (<$n>::BITS - 8)
+                };
+                let mut bit = 0;
+                while bit < 8 {
+                    crc = if reflected {
+                        (crc >> 1) ^ (poly.reverse_bits() & (crc & 1).wrapping_neg())
+                    } else {
+                        (crc << 1) ^ (poly & (crc >> (<$n>::BITS - 1)).wrapping_neg())
+                    };
+                    bit += 1;
+                }
+                table[i] = crc;
+                i += 1;
+            }
+            table
+        }
+    };
+}
+
+make_table!(make_table8, u8);
+make_table!(make_table16, u16);
+make_table!(make_table32, u32);
+make_table!(make_table64, u64);
+make_table!(make_table128, u128);
+
+macro_rules! const_crc {
+    ($(#[$doc:meta])* $name:ident, $n:ty, $table:ident) => {
+        $(#[$doc])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+        pub struct $name<
+            const POLY: $n,
+            const INIT: $n,
+            const REFIN: bool,
+            const REFOUT: bool,
+            const XOROUT: $n,
+        > {
+            crc: $n,
+        }
+
+        impl<const POLY: $n, const INIT: $n, const REFIN: bool, const REFOUT: bool, const XOROUT: $n>
+            $name<POLY, INIT, REFIN, REFOUT, XOROUT>
+        {
+            const TABLE: [$n; 256] = $table(POLY, REFIN);
+
+            const FOLDING: Option<accel::Folding> = if <$n>::BITS <= 64 {
+                accel::Folding::new(<$n>::BITS, POLY as u64, REFIN)
+            } else {
+                None
+            };
+
+            /// The type's parameters as a value.
+            pub const PARAMS: CrcParams<$n> = CrcParams {
+                width: <$n>::BITS,
+                poly: POLY,
+                init: INIT,
+                refin: REFIN,
+                refout: REFOUT,
+                xorout: XOROUT,
+            };
+
+            pub const fn new() -> Self {
+                Self {
+                    crc: if REFIN { INIT.reverse_bits() } else { INIT },
+                }
+            }
+
+            /// The checksum of `data`, in const context too, e.g.
+            /// `const CHECK: u32 = Crc32IsoHdlc::checksum(b"123456789");`.
+            pub const fn checksum(data: &[u8]) -> $n {
+                let mut crc = Self::new().crc;
+                let mut i = 0;
+                while i < data.len() {
+                    crc = Self::step(crc, data[i]);
+                    i += 1;
+                }
+                Self::finish(crc)
+            }
+
+            const fn step(crc: $n, byte: u8) -> $n {
+                if REFIN {
+                    Self::TABLE[((crc ^ byte as $n) & 0xFF) as usize]
+                        ^ match crc.checked_shr(8) {
+                            Some(rest) => rest,
+                            None => 0,
+                        }
+                } else {
+                    let index = (crc >> (<$n>::BITS - 8)) as u8 ^ byte;
+                    Self::TABLE[index as usize]
+                        ^ match crc.checked_shl(8) {
+                            Some(rest) => rest,
+                            None => 0,
+                        }
+                }
+            }
+
+            const fn finish(crc: $n) -> $n {
+                let crc = if REFIN != REFOUT { crc.reverse_bits() } else { crc };
+                crc ^ XOROUT
+            }
+        }
+
+        impl<const POLY: $n, const INIT: $n, const REFIN: bool, const REFOUT: bool, const XOROUT: $n>
+            Model<$n> for $name<POLY, INIT, REFIN, REFOUT, XOROUT>
+        {
+            fn params(&self) -> CrcParams<$n> {
+                Self::PARAMS
+            }
+        }
+
+        impl<const POLY: $n, const INIT: $n, const REFIN: bool, const REFOUT: bool, const XOROUT: $n>
+            Default for $name<POLY, INIT, REFIN, REFOUT, XOROUT>
+        {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl<const POLY: $n, const INIT: $n, const REFIN: bool, const REFOUT: bool, const XOROUT: $n>
+            CRC for $name<POLY, INIT, REFIN, REFOUT, XOROUT>
+        {
+            type Width = $n;
+
+            /// Creates a fresh state; [`Self::new`] does the same without the check.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `params` differ from the type's [`Self::PARAMS`].
+            fn create(params: impl Into<CrcParams<$n>>) -> Self {
+                assert_eq!(params.into(), Self::PARAMS, "parameters differ from the type's");
+                Self::new()
+            }
+
+            fn update(&mut self, data: &[u8]) {
+                let folding = Self::FOLDING;
+                self.crc = accel::update(&Self::PARAMS, folding.as_ref(), self.crc, data, |crc, data| {
+                    data.iter().fold(crc, |crc, &byte| Self::step(crc, byte))
+                });
+            }
+
+            fn finalize(&self) -> $n {
+                Self::finish(self.crc)
+            }
+
+            fn reset(&mut self) {
+                *self = Self::new();
+            }
+        }
+
+        /// `finish` is the checksum, truncated to 64 bits for the 128-bit type.
+        impl<const POLY: $n, const INIT: $n, const REFIN: bool, const REFOUT: bool, const XOROUT: $n>
+            core::hash::Hasher for $name<POLY, INIT, REFIN, REFOUT, XOROUT>
+        {
+            fn write(&mut self, bytes: &[u8]) {
+                CRC::update(self, bytes);
+            }
+
+            fn finish(&self) -> u64 {
+                CRC::finalize(self) as u64
+            }
+        }
+    };
+}
+
+const_crc!(
+    /// An 8-bit CRC with const generic parameters.
+    ConstCRC8,
+    u8,
+    make_table8
+);
+const_crc!(
+    /// A 16-bit CRC with const generic parameters.
+    ConstCRC16,
+    u16,
+    make_table16
+);
+const_crc!(
+    /// A 32-bit CRC with const generic parameters.
+    ConstCRC32,
+    u32,
+    make_table32
+);
+const_crc!(
+    /// A 64-bit CRC with const generic parameters.
+    ConstCRC64,
+    u64,
+    make_table64
+);
+const_crc!(
+    /// A 128-bit CRC with const generic parameters.
+    ConstCRC128,
+    u128,
+    make_table128
+);
+
+impl<
+        const POLY: u32,
+        const INIT: u32,
+        const REFIN: bool,
+        const REFOUT: bool,
+        const XOROUT: u32,
+    > ConstCRC32<POLY, INIT, REFIN, REFOUT, XOROUT>
+{
+    /// A state whose register, in table layout, is `crc`.
+    pub(crate) const fn with_register(crc: u32) -> Self {
+        Self { crc }
+    }
+}
+
+macro_rules! checksum_const {
+    ($($n:ty),*) => {
+        $(
+            impl CrcParams<$n> {
+                /// The checksum of `data`, bit by bit, in const context too.
+                ///
+                /// # Panics
+                ///
+                /// Panics, or fails to compile in const context, if `width` is zero or wider
+                /// than the register.
+                pub const fn checksum_const(&self, data: &[u8]) -> $n {
+                    assert!(
+                        self.width >= 1 && self.width <= <$n>::BITS,
+                        "CRC width must fit the register type"
+                    );
+                    // MSB-first in the top bits of the register, whatever the width.
+                    let shift = <$n>::BITS - self.width;
+                    let poly = self.poly << shift;
+                    let mut crc = self.init << shift;
+                    let mut i = 0;
+                    while i < data.len() {
+                        let byte = if self.refin {
+                            data[i].reverse_bits()
+                        } else {
+                            data[i]
+                        };
+                        crc ^= (byte as $n) << (<$n>::BITS - 8);
+                        let mut bit = 0;
+                        while bit < 8 {
+                            crc = (crc << 1) ^ (poly & (crc >> (<$n>::BITS - 1)).wrapping_neg());
+                            bit += 1;
+                        }
+                        i += 1;
+                    }
+                    let crc = if self.refout { crc.reverse_bits() } else { crc >> shift };
+                    crc ^ self.xorout
+                }
+            }
+
+            impl catalog::Algorithm<$n> {
+                /// [`CrcParams::checksum_const`] of the entry's parameters.
+                pub const fn checksum_const(&self, data: &[u8]) -> $n {
+                    self.params.checksum_const(data)
+                }
+            }
+        )*
+    };
+}
+
+checksum_const!(u8, u16, u32, u64, u128);
+
+macro_rules! preset {
+    ($alias:ident, $ty:ident, $entry:ident) => {
+        #[doc = concat!("[`catalog::", stringify!($entry), "`] as a type.")]
+        pub type $alias = $ty<
+            { catalog::$entry.params.poly },
+            { catalog::$entry.params.init },
+            { catalog::$entry.params.refin },
+            { catalog::$entry.params.refout },
+            { catalog::$entry.params.xorout },
+        >;
+    };
+}
+
+preset!(Crc8Autosar, ConstCRC8, CRC_8_AUTOSAR);
+preset!(Crc8Bluetooth, ConstCRC8, CRC_8_BLUETOOTH);
+preset!(Crc8MaximDow, ConstCRC8, CRC_8_MAXIM_DOW);
+preset!(Crc8SaeJ1850, ConstCRC8, CRC_8_SAE_J1850);
+preset!(Crc8Smbus, ConstCRC8, CRC_8_SMBUS);
+preset!(Crc16Arc, ConstCRC16, CRC_16_ARC);
+preset!(Crc16DectR, ConstCRC16, CRC_16_DECT_R);
+preset!(Crc16DectX, ConstCRC16, CRC_16_DECT_X);
+preset!(Crc16Dnp, ConstCRC16, CRC_16_DNP);
+preset!(Crc16Ibm3740, ConstCRC16, CRC_16_IBM_3740);
+preset!(Crc16IbmSdlc, ConstCRC16, CRC_16_IBM_SDLC);
+preset!(Crc16Kermit, ConstCRC16, CRC_16_KERMIT);
+preset!(Crc16Modbus, ConstCRC16, CRC_16_MODBUS);
+preset!(Crc16SpiFujitsu, ConstCRC16, CRC_16_SPI_FUJITSU);
+preset!(Crc16T10Dif, ConstCRC16, CRC_16_T10_DIF);
+preset!(Crc16Umts, ConstCRC16, CRC_16_UMTS);
+preset!(Crc16Usb, ConstCRC16, CRC_16_USB);
+preset!(Crc16Xmodem, ConstCRC16, CRC_16_XMODEM);
+preset!(Crc32Autosar, ConstCRC32, CRC_32_AUTOSAR);
+preset!(Crc32Bzip2, ConstCRC32, CRC_32_BZIP2);
+preset!(Crc32Cksum, ConstCRC32, CRC_32_CKSUM);
+preset!(Crc32Iscsi, ConstCRC32, CRC_32_ISCSI);
+preset!(Crc32IsoHdlc, ConstCRC32, CRC_32_ISO_HDLC);
+preset!(Crc32Mpeg2, ConstCRC32, CRC_32_MPEG_2);
+preset!(Crc64Ecma182, ConstCRC64, CRC_64_ECMA_182);
+preset!(Crc64GoIso, ConstCRC64, CRC_64_GO_ISO);
+preset!(Crc64Nvme, ConstCRC64, CRC_64_NVME);
+preset!(Crc64We, ConstCRC64, CRC_64_WE);
+preset!(Crc64Xz, ConstCRC64, CRC_64_XZ);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Params;
+
+    fn checksum<C: CRC<Width = N>, N>(data: &[u8]) -> N {
+        let mut crc = C::default();
+        crc.update(&data[..3]);
+        crc.update(&data[3..]);
+        crc.finalize()
+    }
+
+    #[test]
+    fn catalogue_test() {
+        const CHECK: &[u8] = b"123456789";
+        let check = |algorithm| crc::Crc::<u16>::new(algorithm).checksum(CHECK);
+        assert_eq!(
+            checksum::<ConstCRC16<0x1021, 0xFFFF, false, false, 0>, _>(CHECK),
+            check(&crc::CRC_16_IBM_3740)
+        );
+        assert_eq!(
+            checksum::<ConstCRC16<0x8005, 0, true, true, 0>, _>(CHECK),
+            check(&crc::CRC_16_ARC)
+        );
+        assert_eq!(
+            checksum::<ConstCRC8<0x07, 0, false, false, 0>, _>(CHECK),
+            crc::Crc::<u8>::new(&crc::CRC_8_SMBUS).checksum(CHECK)
+        );
+        assert_eq!(
+            checksum::<ConstCRC32<0x04C1_1DB7, { u32::MAX }, true, true, { u32::MAX }>, _>(CHECK),
+            0xCBF4_3926
+        );
+        assert_eq!(
+            checksum::<ConstCRC64<0x42F0_E1EB_A9EA_3693, { u64::MAX }, true, true, { u64::MAX }>, _>(
+                CHECK
+            ),
+            crc::Crc::<u64>::new(&crc::CRC_64_XZ).checksum(CHECK)
+        );
+        // refin without refout: the register is reflected back before the final XOR.
+        let engine = Params::<u128> {
+            width: 128,
+            poly: 0x1234_5678_9ABC_DEF0_1234_5678_9ABC_DEF1,
+            init: 0x0F,
+            refin: true,
+            refout: false,
+            xorout: 0xAA,
+        };
+        assert_eq!(
+            checksum::<
+                ConstCRC128<0x1234_5678_9ABC_DEF0_1234_5678_9ABC_DEF1, 0x0F, true, false, 0xAA>,
+                _,
+            >(CHECK),
+            engine.checksum(CHECK)
+        );
+        // The crate's own CRC32 is this parameter set.
+        assert_eq!(
+            checksum::<ConstCRC32<0x04C1_1DB7, { u32::MAX }, false, false, { u32::MAX }>, _>(CHECK),
+            checksum::<crate::CRC32, _>(CHECK)
+        );
+    }
+
+    #[test]
+    fn preset_test() {
+        assert_eq!(Crc16Modbus::PARAMS, catalog::CRC_16_MODBUS.params);
+        assert_eq!(checksum::<Crc16Modbus, _>(b"123456789"), 0x4B37);
+        assert_eq!(checksum::<Crc32IsoHdlc, _>(b"123456789"), 0xCBF4_3926);
+        assert_eq!(checksum::<Crc32Iscsi, _>(b"123456789"), 0xE306_9283);
+        assert_eq!(
+            checksum::<Crc64Xz, _>(b"123456789"),
+            catalog::CRC_64_XZ.check
+        );
+        assert_eq!(
+            checksum::<Crc8MaximDow, _>(b"123456789"),
+            catalog::CRC_8_MAXIM_DOW.check
+        );
+        let dect = |algorithm| crc::Crc::<u16>::new(algorithm).checksum(b"123456789");
+        assert_eq!(
+            Crc16DectR::checksum(b"123456789"),
+            dect(&crc::CRC_16_DECT_R)
+        );
+        assert_eq!(
+            Crc16DectX::checksum(b"123456789"),
+            dect(&crc::CRC_16_DECT_X)
+        );
+    }
+
+    #[test]
+    fn ccitt_test() {
+        let check = |algorithm| crc::Crc::<u16>::new(algorithm).checksum(b"123456789");
+        let family = [
+            (
+                checksum::<Crc16Kermit, _>(b"123456789"),
+                &crc::CRC_16_KERMIT,
+            ),
+            (
+                checksum::<Crc16Ibm3740, _>(b"123456789"),
+                &crc::CRC_16_IBM_3740,
+            ),
+            (
+                checksum::<Crc16SpiFujitsu, _>(b"123456789"),
+                &crc::CRC_16_SPI_FUJITSU,
+            ),
+            (
+                checksum::<Crc16IbmSdlc, _>(b"123456789"),
+                &crc::CRC_16_IBM_SDLC,
+            ),
+            (
+                checksum::<Crc16Xmodem, _>(b"123456789"),
+                &crc::CRC_16_XMODEM,
+            ),
+        ];
+        for (i, (value, algorithm)) in family.iter().enumerate() {
+            assert_eq!(*value, check(algorithm));
+            assert!(family[i + 1..].iter().all(|(other, _)| other != value));
+        }
+        assert_eq!(
+            catalog::by_name("CRC-16/AUG-CCITT"),
+            Ok(catalog::AnyAlgorithm::Crc16(catalog::CRC_16_SPI_FUJITSU))
+        );
+    }
+
+    #[test]
+    fn const_fn_test() {
+        const ZLIB: [u32; 256] = make_table32(0x04C1_1DB7, true);
+        const CHECK: u32 = Crc32IsoHdlc::checksum(b"123456789");
+        assert_eq!(ZLIB[1], 0x7707_3096);
+        assert_eq!(ZLIB, crate::table::build(&catalog::CRC_32_ISO_HDLC.params));
+        assert_eq!(
+            make_table16(0x1021, false),
+            crate::table::build(&catalog::CRC_16_XMODEM.params)
+        );
+        assert_eq!(CHECK, 0xCBF4_3926);
+        assert_eq!(Crc16Modbus::checksum(b"123456789"), 0x4B37);
+        assert_eq!(Crc64Xz::checksum(b"123456789"), catalog::CRC_64_XZ.check);
+    }
+
+    #[test]
+    fn checksum_const_test() {
+        const HEADER_CRC: u16 = catalog::CRC_16_XMODEM.checksum_const(b"MAGICv1");
+        assert_eq!(HEADER_CRC, Crc16Xmodem::checksum(b"MAGICv1"));
+        for algorithm in catalog::algorithms() {
+            let value: u64 = match algorithm {
+                catalog::AnyAlgorithm::Crc8(a) => a.checksum_const(b"123456789").into(),
+                catalog::AnyAlgorithm::Crc16(a) => a.checksum_const(b"123456789").into(),
+                catalog::AnyAlgorithm::Crc32(a) => a.checksum_const(b"123456789").into(),
+                catalog::AnyAlgorithm::Crc64(a) => a.checksum_const(b"123456789"),
+            };
+            assert_eq!(value, algorithm.check(), "{}", algorithm.name());
+        }
+        let params = CrcParams::<u128> {
+            width: 128,
+            poly: 0x1234_5678_9ABC_DEF0_1234_5678_9ABC_DEF1,
+            init: 0x0F,
+            refin: true,
+            refout: false,
+            xorout: 0xAA,
+        };
+        assert_eq!(
+            params.checksum_const(b"123456789"),
+            Params::from(params).checksum(b"123456789")
+        );
+    }
+}
@@ -0,0 +1,445 @@
+//! ROM set verification against No-Intro and ClrMamePro DAT files.
+//!
+//! A DAT lists, for every game, the name, size and CRC-32 of each ROM file of a complete
+//! set. [`Dat`] parses both the Logiqx XML format that No-Intro and Redump publish and the
+//! older ClrMamePro text format, and [`verify_dir`] matches a directory of dumps against
+//! it by size and CRC-32, the way ROM managers do: a file with the right contents counts
+//! as good whatever it is called.
+//!
+//! Only the `name`, `size` and `crc` fields are read; ROMs without a CRC, such as entries
+//! marked `nodump`, are left out. Archives are not looked into, so zipped sets have to be
+//! extracted first.
+
+use crate::catalog::CRC_32_ISO_HDLC;
+use crate::compare::read_full;
+use crate::Crc;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// One ROM file listed in a DAT.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DatRom {
+    pub game: String,
+    /// File name of the ROM, which may differ from the game name.
+    pub name: String,
+    pub size: u64,
+    pub crc: u32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Dat {
+    pub roms: Vec<DatRom>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// One-based line number.
+    pub line: usize,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "malformed DAT on line {}", self.line)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+fn line_at(text: &str, offset: usize) -> ParseError {
+    ParseError {
+        line: text[..offset].matches('\n').count() + 1,
+    }
+}
+
+/// Builds a ROM from its fields, skipping ROMs that have no CRC.
+fn rom(game: &str, fields: &HashMap<String, String>) -> Result<Option<DatRom>, ()> {
+    let Some(crc) = fields.get("crc") else {
+        return Ok(None);
+    };
+    Ok(Some(DatRom {
+        game: game.to_string(),
+        name: fields.get("name").ok_or(())?.clone(),
+        size: fields.get("size").ok_or(())?.parse().map_err(|_| ())?,
+        crc: u32::from_str_radix(crc, 16).map_err(|_| ())?,
+    }))
+}
+
+fn unescape_xml(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Parses the attributes of an XML start tag, `tag` being the text after the element name.
+fn xml_attributes(tag: &str) -> Option<HashMap<String, String>> {
+    let mut attributes = HashMap::new();
+    let mut rest = tag.trim_start();
+    while let Some((name, value)) = rest.split_once('=') {
+        let value = value.trim_start();
+        let quote = value.chars().next().filter(|&c| c == '"' || c == '\'')?;
+        let (value, tail) = value[1..].split_once(quote)?;
+        attributes.insert(name.trim().to_string(), unescape_xml(value));
+        rest = tail.trim_start();
+    }
+    rest.trim_end_matches('/')
+        .trim()
+        .is_empty()
+        .then_some(attributes)
+}
+
+fn parse_xml(text: &str) -> Result<Dat, ParseError> {
+    let mut roms = Vec::new();
+    let mut game = String::new();
+    let mut pos = 0;
+    while let Some(start) = text[pos..].find('<').map(|i| pos + i) {
+        let end = start
+            + text[start..]
+                .find('>')
+                .ok_or_else(|| line_at(text, start))?;
+        let tag = &text[start + 1..end];
+        pos = end + 1;
+        let (element, attributes) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+        if !matches!(element, "game" | "machine" | "rom") {
+            continue;
+        }
+        let attributes = xml_attributes(attributes).ok_or_else(|| line_at(text, start))?;
+        if element == "rom" {
+            roms.extend(rom(&game, &attributes).map_err(|_| line_at(text, start))?);
+        } else {
+            game = attributes.get("name").cloned().unwrap_or_default();
+        }
+    }
+    Ok(Dat { roms })
+}
+
+#[derive(Debug)]
+enum Token<'a> {
+    Word(&'a str),
+    Quoted(String),
+    Open,
+    Close,
+}
+
+/// Splits ClrMamePro text into tokens with their byte offsets.
+fn cmp_tokens(text: &str) -> Result<Vec<(usize, Token<'_>)>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '(' => tokens.push((start, Token::Open)),
+            ')' => tokens.push((start, Token::Close)),
+            '"' => {
+                let mut quoted = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) => quoted.extend(chars.next().map(|(_, c)| c)),
+                        Some((_, c)) => quoted.push(c),
+                        None => return Err(line_at(text, start)),
+                    }
+                }
+                tokens.push((start, Token::Quoted(quoted)));
+            }
+            _ => {
+                let mut end = start + c.len_utf8();
+                while let Some(&(i, c)) = chars.peek() {
+                    if c.is_whitespace() || c == '(' || c == ')' {
+                        break;
+                    }
+                    end = i + c.len_utf8();
+                    chars.next();
+                }
+                tokens.push((start, Token::Word(&text[start..end])));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// A ClrMamePro value: a word, a quoted string or a parenthesized block of `key value` pairs.
+enum Value {
+    Scalar(String),
+    /// Pairs with the index of their key token, for error locations.
+    Block(Vec<(usize, String, Value)>),
+}
+
+/// How deeply blocks may nest. Real DATs nest two or three levels; the limit keeps hostile
+/// files from exhausting the stack.
+const MAX_DEPTH: usize = 32;
+
+/// Parses `key value` pairs up to the closing parenthesis, or up to the end of the input at
+/// the top level, `depth` zero. Errors carry the index of the offending token.
+fn cmp_pairs(
+    tokens: &[(usize, Token<'_>)],
+    index: &mut usize,
+    depth: usize,
+) -> Result<Vec<(usize, String, Value)>, usize> {
+    let top = depth == 0;
+    let mut pairs = Vec::new();
+    loop {
+        let start = *index;
+        let key = match tokens.get(*index) {
+            None if top => return Ok(pairs),
+            Some((_, Token::Close)) if !top => {
+                *index += 1;
+                return Ok(pairs);
+            }
+            Some((_, Token::Word(key))) => key.to_ascii_lowercase(),
+            _ => return Err(*index),
+        };
+        *index += 1;
+        let value = match tokens.get(*index) {
+            Some((_, Token::Word(value))) => Value::Scalar(value.to_string()),
+            Some((_, Token::Quoted(value))) => Value::Scalar(value.clone()),
+            Some((_, Token::Open)) if depth < MAX_DEPTH => {
+                *index += 1;
+                let block = cmp_pairs(tokens, index, depth + 1)?;
+                pairs.push((start, key, Value::Block(block)));
+                continue;
+            }
+            _ => return Err(*index),
+        };
+        *index += 1;
+        pairs.push((start, key, value));
+    }
+}
+
+/// The scalar fields of a block; nested blocks are ignored.
+fn scalars(pairs: &[(usize, String, Value)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .filter_map(|(_, key, value)| match value {
+            Value::Scalar(value) => Some((key.clone(), value.clone())),
+            Value::Block(_) => None,
+        })
+        .collect()
+}
+
+fn parse_cmp(text: &str) -> Result<Dat, ParseError> {
+    let tokens = cmp_tokens(text)?;
+    let error = |index: usize| line_at(text, tokens.get(index).map_or(text.len(), |t| t.0));
+    let mut roms = Vec::new();
+    for (_, kind, value) in cmp_pairs(&tokens, &mut 0, 0).map_err(error)? {
+        let Value::Block(fields) = value else {
+            continue;
+        };
+        if !matches!(kind.as_str(), "game" | "machine" | "resource") {
+            continue;
+        }
+        let game = scalars(&fields).remove("name").unwrap_or_default();
+        for (start, key, value) in &fields {
+            if let (Value::Block(rom_fields), "rom") = (value, key.as_str()) {
+                roms.extend(rom(&game, &scalars(rom_fields)).map_err(|_| error(*start))?);
+            }
+        }
+    }
+    Ok(Dat { roms })
+}
+
+impl std::str::FromStr for Dat {
+    type Err = ParseError;
+
+    /// Parses a DAT in either format, telling them apart by the first character.
+    fn from_str(text: &str) -> Result<Self, ParseError> {
+        if text.trim_start().starts_with('<') {
+            parse_xml(text)
+        } else {
+            parse_cmp(text)
+        }
+    }
+}
+
+/// A ROM of the DAT that is present.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Found {
+    pub rom: DatRom,
+    pub path: PathBuf,
+}
+
+/// A file named like a ROM of the DAT but with different contents.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BadDump {
+    pub rom: DatRom,
+    pub path: PathBuf,
+    pub size: u64,
+    pub crc: u32,
+}
+
+/// The result of [`verify_dir`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    pub good: Vec<Found>,
+    pub bad: Vec<BadDump>,
+    /// ROMs with neither a matching file nor a file of their name.
+    pub missing: Vec<DatRom>,
+    /// Files that match no ROM of the DAT.
+    pub unknown: Vec<PathBuf>,
+}
+
+fn crc32_file(path: &Path) -> io::Result<(u64, u32)> {
+    let mut file = File::open(path)?;
+    let mut buf = vec![0; 64 * 1024];
+    let (mut size, mut crc) = (0, Crc::<u32>::cached(&CRC_32_ISO_HDLC).digest());
+    loop {
+        let n = read_full(&mut file, &mut buf)?;
+        crc.update(&buf[..n]);
+        size += n as u64;
+        if n < buf.len() {
+            return Ok((size, crc.finalize()));
+        }
+    }
+}
+
+/// Matches the files directly inside `dir` against `dat`.
+///
+/// Only files whose size occurs in the DAT or whose name is that of a ROM are hashed.
+pub fn verify_dir(dat: &Dat, dir: &Path) -> io::Result<Report> {
+    let mut by_content: HashMap<(u64, u32), Vec<&DatRom>> = HashMap::new();
+    let mut by_name: HashMap<&str, &DatRom> = HashMap::new();
+    for rom in &dat.roms {
+        by_content.entry((rom.size, rom.crc)).or_default().push(rom);
+        by_name.insert(&rom.name, rom);
+    }
+    let sizes: std::collections::HashSet<u64> = dat.roms.iter().map(|rom| rom.size).collect();
+
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<_>>()?;
+    paths.sort();
+
+    let mut report = Report::default();
+    let mut present: std::collections::HashSet<(&str, &str)> = Default::default();
+    for path in paths {
+        let metadata = fs::metadata(&path)?;
+        if !metadata.is_file() {
+            continue;
+        }
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        let named = by_name.get(name.as_str()).copied();
+        if !sizes.contains(&metadata.len()) && named.is_none() {
+            report.unknown.push(path);
+            continue;
+        }
+        let (size, crc) = crc32_file(&path)?;
+        match by_content.get(&(size, crc)) {
+            Some(roms) => {
+                // A file of the right name is the best match among duplicates.
+                let rom = roms.iter().find(|rom| rom.name == name).unwrap_or(&roms[0]);
+                present.insert((&rom.game, &rom.name));
+                report.good.push(Found {
+                    rom: (*rom).clone(),
+                    path,
+                });
+            }
+            None => match named {
+                Some(rom) => {
+                    present.insert((&rom.game, &rom.name));
+                    report.bad.push(BadDump {
+                        rom: rom.clone(),
+                        path,
+                        size,
+                        crc,
+                    });
+                }
+                None => report.unknown.push(path),
+            },
+        }
+    }
+    report.missing = dat
+        .roms
+        .iter()
+        .filter(|rom| !present.contains(&(rom.game.as_str(), rom.name.as_str())))
+        .cloned()
+        .collect();
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const XML: &str = r#"<?xml version="1.0"?>
+<!DOCTYPE datafile PUBLIC "-//Logiqx//DTD ROM Management Datafile//EN" "http://www.logiqx.com/Dats/datafile.dtd">
+<datafile>
+	<header><name>Test - Set</name></header>
+	<game name="Hello &amp; World (World)">
+		<description>Hello &amp; World (World)</description>
+		<rom name="Hello &amp; World (World).bin" size="11" crc="0d4a1185" md5="5eb63bbbe01eeed093cb22bb8f5acdc3"/>
+	</game>
+	<game name="Lost (Japan)">
+		<rom name="Lost (Japan).bin" size="4" crc="DEADBEEF"/>
+		<rom name="Lost (Japan) (Track 2).bin" size="4" status="nodump"/>
+	</game>
+</datafile>
+"#;
+
+    const CMP: &str = r#"clrmamepro (
+	name "Test - Set"
+	version 20240101
+)
+
+game (
+	name "Hello & World (World)"
+	description "Hello & World (World)"
+	rom ( name "Hello & World (World).bin" size 11 crc 0D4A1185 md5 5EB63BBBE01EEED093CB22BB8F5ACDC3 )
+)
+
+game (
+	name "Lost (Japan)"
+	rom ( name "Lost (Japan).bin" size 4 crc deadbeef )
+)
+"#;
+
+    #[test]
+    fn parse_test() {
+        let xml: Dat = XML.parse().unwrap();
+        let cmp: Dat = CMP.parse().unwrap();
+        assert_eq!(xml, cmp);
+        assert_eq!(xml.roms.len(), 2);
+        assert_eq!(xml.roms[0].game, "Hello & World (World)");
+        assert_eq!(xml.roms[0].crc, 0x0D4A_1185);
+        assert_eq!(xml.roms[1].size, 4);
+
+        assert_eq!(
+            "<rom name=\"x\" size=\"no\" crc=\"00\"/>".parse::<Dat>(),
+            Err(ParseError { line: 1 })
+        );
+        assert_eq!(
+            "game (\n\tname \"x\"\n\trom ( name \"y\" size".parse::<Dat>(),
+            Err(ParseError { line: 3 })
+        );
+        let nested = "game ( ".repeat(1_000_000);
+        assert_eq!(nested.parse::<Dat>(), Err(ParseError { line: 1 }));
+    }
+
+    #[test]
+    fn verify_dir_test() {
+        let dir = std::env::temp_dir().join(format!("crclib-dat-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let mut dat: Dat = XML.parse().unwrap();
+        dat.roms[0].crc = crate::oneshot::crc32(b"hello world");
+        // Good under another name, a bad dump of the second ROM and a stray file.
+        fs::write(dir.join("renamed.bin"), b"hello world").unwrap();
+        fs::write(dir.join("Lost (Japan).bin"), b"lost").unwrap();
+        fs::write(dir.join("readme.txt"), b"hi").unwrap();
+
+        let report = verify_dir(&dat, &dir).unwrap();
+        assert_eq!(report.good.len(), 1);
+        assert_eq!(report.good[0].rom, dat.roms[0]);
+        assert_eq!(report.bad.len(), 1);
+        assert_eq!(report.bad[0].rom, dat.roms[1]);
+        assert!(report.missing.is_empty());
+        assert_eq!(report.unknown, [dir.join("readme.txt")]);
+
+        fs::remove_file(dir.join("Lost (Japan).bin")).unwrap();
+        let report = verify_dir(&dat, &dir).unwrap();
+        assert_eq!(report.missing, [dat.roms[1].clone()]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,240 @@
+//! Checksumming encoded payloads while decoding them.
+//!
+//! A [`DecodeReader`] wraps a reader of hex or base64 text, such as a payload embedded in a
+//! log line or a JSON string, and yields the decoded bytes while feeding them to a CRC. The
+//! decoded payload never has to be held in memory as a whole: stream it into
+//! [`io::copy`](std::io::copy) with [`io::sink`](std::io::sink) and compare
+//! [`DecodeReader::checksum`] with the expected value.
+//!
+//! ASCII whitespace between symbols is ignored, so wrapped base64 and spaced hex dumps are
+//! accepted. Malformed input is reported as an [`io::ErrorKind::InvalidData`] error.
+
+use crate::CRC;
+use std::io::{self, Read};
+use std::marker::PhantomData;
+
+/// The text encoding read by a [`DecodeReader`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    /// Pairs of hex digits, upper or lower case.
+    Hex,
+    /// Standard-alphabet base64 with padding (RFC 4648).
+    Base64,
+}
+
+/// Decodes hex or base64 from an inner reader and computes the CRC of the decoded bytes.
+pub struct DecodeReader<R, C, N> {
+    inner: R,
+    crc: C,
+    encoding: Encoding,
+    input: Vec<u8>,
+    input_pos: usize,
+    /// Decoded bits not yet emitted, `symbols` symbols' worth.
+    group: u32,
+    symbols: u8,
+    padding: u8,
+    /// Set once a padded base64 quad was seen; only whitespace may follow.
+    padded: bool,
+    output: [u8; 3],
+    output_pos: usize,
+    output_len: usize,
+    eof: bool,
+    _checksum: PhantomData<N>,
+}
+
+fn invalid(message: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+impl<R, C, N> DecodeReader<R, C, N>
+where
+    R: Read,
+    C: CRC<Width = N>,
+{
+    pub fn new(inner: R, encoding: Encoding, crc: C) -> Self {
+        Self {
+            inner,
+            crc,
+            encoding,
+            input: Vec::with_capacity(8 * 1024),
+            input_pos: 0,
+            group: 0,
+            symbols: 0,
+            padding: 0,
+            padded: false,
+            output: [0; 3],
+            output_pos: 0,
+            output_len: 0,
+            eof: false,
+            _checksum: PhantomData,
+        }
+    }
+
+    pub fn hex(inner: R, crc: C) -> Self {
+        Self::new(inner, Encoding::Hex, crc)
+    }
+
+    pub fn base64(inner: R, crc: C) -> Self {
+        Self::new(inner, Encoding::Base64, crc)
+    }
+
+    /// The CRC of the bytes decoded so far.
+    pub fn checksum(&self) -> N {
+        self.crc.finalize()
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Consumes one input symbol, possibly completing output bytes.
+    fn push(&mut self, symbol: u8) -> io::Result<()> {
+        if symbol.is_ascii_whitespace() {
+            return Ok(());
+        }
+        match self.encoding {
+            Encoding::Hex => {
+                let digit = (symbol as char)
+                    .to_digit(16)
+                    .ok_or_else(|| invalid("invalid hex digit"))?;
+                self.group = self.group << 4 | digit;
+                self.symbols += 1;
+                if self.symbols == 2 {
+                    self.emit(1);
+                }
+            }
+            Encoding::Base64 => {
+                if self.padded {
+                    return Err(invalid("base64 data after padding"));
+                }
+                if symbol == b'=' {
+                    if self.symbols < 2 {
+                        return Err(invalid("misplaced base64 padding"));
+                    }
+                    self.padding += 1;
+                    self.group <<= 6;
+                } else if self.padding > 0 {
+                    return Err(invalid("misplaced base64 padding"));
+                } else {
+                    let value = crate::base64::decode_symbol(symbol)
+                        .ok_or_else(|| invalid("invalid base64 symbol"))?;
+                    self.group = self.group << 6 | value;
+                }
+                self.symbols += 1;
+                if self.symbols == 4 {
+                    self.padded = self.padding > 0;
+                    self.emit(3 - self.padding as usize);
+                    self.padding = 0;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Moves the complete group to the output buffer, most significant byte first.
+    fn emit(&mut self, len: usize) {
+        let bytes = self.group.to_be_bytes();
+        let skip = match self.encoding {
+            Encoding::Hex => 3,
+            Encoding::Base64 => 1,
+        };
+        self.output[..len].copy_from_slice(&bytes[skip..skip + len]);
+        (self.output_pos, self.output_len) = (0, len);
+        (self.group, self.symbols) = (0, 0);
+    }
+}
+
+impl<R, C, N> Read for DecodeReader<R, C, N>
+where
+    R: Read,
+    C: CRC<Width = N>,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut written = 0;
+        while written < buf.len() {
+            if self.output_pos < self.output_len {
+                let n = (self.output_len - self.output_pos).min(buf.len() - written);
+                buf[written..written + n]
+                    .copy_from_slice(&self.output[self.output_pos..self.output_pos + n]);
+                self.output_pos += n;
+                written += n;
+            } else if self.input_pos < self.input.len() {
+                let symbol = self.input[self.input_pos];
+                self.input_pos += 1;
+                self.push(symbol)?;
+            } else if self.eof || written > 0 {
+                // Only block on the inner reader when nothing has been decoded yet.
+                break;
+            } else {
+                self.input.resize(self.input.capacity(), 0);
+                let n = self.inner.read(&mut self.input)?;
+                self.input.truncate(n);
+                self.input_pos = 0;
+                if n == 0 {
+                    self.eof = true;
+                    if self.symbols != 0 {
+                        return Err(invalid("truncated encoded data"));
+                    }
+                }
+            }
+        }
+        self.crc.update(&buf[..written]);
+        Ok(written)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DecodeReader;
+    use crate::{CRC, CRC32};
+    use std::io::{self, Read};
+
+    fn crc32(data: &[u8]) -> u32 {
+        let mut crc = CRC32::default();
+        crc.update(data);
+        crc.finalize()
+    }
+
+    #[test]
+    fn hex_test() {
+        let mut reader = DecodeReader::hex(&b"68656C6c 6f20\n776f726c64"[..], CRC32::default());
+        let mut decoded = Vec::new();
+        reader.read_to_end(&mut decoded).unwrap();
+        assert_eq!(decoded, b"hello world");
+        assert_eq!(reader.checksum(), 0x44F71378);
+
+        let mut reader = DecodeReader::hex(&b"6865x"[..], CRC32::default());
+        let err = reader.read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let mut reader = DecodeReader::hex(&b"686"[..], CRC32::default());
+        assert!(reader.read_to_end(&mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn base64_test() {
+        let data: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        let mut encoded = crate::base64::encode(&data);
+        for at in (76..encoded.len()).step_by(77).rev() {
+            encoded.insert(at, '\n');
+        }
+        let mut reader = DecodeReader::base64(encoded.as_bytes(), CRC32::default());
+        // One byte at a time exercises the carried-over output.
+        let mut byte = [0];
+        let mut decoded = Vec::new();
+        while reader.read(&mut byte).unwrap() == 1 {
+            decoded.push(byte[0]);
+        }
+        assert_eq!(decoded, data);
+        assert_eq!(reader.checksum(), crc32(&data));
+
+        let mut sink = io::sink();
+        let mut reader = DecodeReader::base64(&b"Zm9vYg==\n"[..], CRC32::default());
+        assert_eq!(io::copy(&mut reader, &mut sink).unwrap(), 4);
+        assert_eq!(reader.checksum(), crc32(b"foob"));
+
+        for bad in ["Zm9vYg==Zm9v", "Zg=v", "Z===", "Zm9", "Zm!v"] {
+            let mut reader = DecodeReader::base64(bad.as_bytes(), CRC32::default());
+            assert!(io::copy(&mut reader, &mut sink).is_err(), "{}", bad);
+        }
+    }
+}
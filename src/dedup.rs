@@ -0,0 +1,196 @@
+//! Duplicate file detection by size and CRC-32C.
+//!
+//! Files are first grouped by size, which costs only a `stat` per file. Only files that share
+//! their size with another file are read and grouped by CRC-32C, and with confirmation
+//! enabled each CRC group is split further by comparing contents byte for byte, so a CRC
+//! collision can never report two different files as duplicates.
+//!
+//! [`find_duplicates`] returns an iterator that does the hashing lazily, one size group at a
+//! time, so the first duplicate sets are available long before a large tree is done.
+
+use crate::compare::read_full;
+use crate::manifest::hash_file;
+use std::collections::{BTreeMap, VecDeque};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Files with identical size and CRC-32C, and identical contents if confirmed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DuplicateSet {
+    pub size: u64,
+    pub crc: u32,
+    /// At least two paths, in the order they were given.
+    pub paths: Vec<PathBuf>,
+}
+
+/// A file that could not be examined; it is left out of the results.
+#[derive(Debug)]
+pub struct FileError {
+    pub path: PathBuf,
+    pub error: io::Error,
+}
+
+impl std::fmt::Display for FileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.error)
+    }
+}
+
+impl std::error::Error for FileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Iterator over the duplicate sets among a list of files, see [`find_duplicates`].
+pub struct Duplicates {
+    /// Remaining groups of two or more files of equal size, smallest size first.
+    groups: std::collections::btree_map::IntoIter<u64, Vec<PathBuf>>,
+    confirm: bool,
+    ready: VecDeque<Result<DuplicateSet, FileError>>,
+}
+
+/// Finds the files among `paths` that have identical contents.
+///
+/// Empty files and paths that are not regular files are ignored. With `confirm`, files whose
+/// CRCs match are also compared byte for byte.
+pub fn find_duplicates<I>(paths: I, confirm: bool) -> Duplicates
+where
+    I: IntoIterator<Item = PathBuf>,
+{
+    let mut by_size: BTreeMap<u64, Vec<PathBuf>> = BTreeMap::new();
+    let mut ready = VecDeque::new();
+    for path in paths {
+        match fs::metadata(&path) {
+            Ok(metadata) if metadata.is_file() && metadata.len() > 0 => {
+                by_size.entry(metadata.len()).or_default().push(path)
+            }
+            Ok(_) => {}
+            Err(error) => ready.push_back(Err(FileError { path, error })),
+        }
+    }
+    by_size.retain(|_, paths| paths.len() > 1);
+    Duplicates {
+        groups: by_size.into_iter(),
+        confirm,
+        ready,
+    }
+}
+
+impl Duplicates {
+    fn process(&mut self, size: u64, paths: Vec<PathBuf>) {
+        let mut by_crc: BTreeMap<u32, Vec<PathBuf>> = BTreeMap::new();
+        for path in paths {
+            match hash_file(&path) {
+                // A file that changed size since it was stat'ed is no longer in this group.
+                Ok((actual, crc)) if actual == size => by_crc.entry(crc).or_default().push(path),
+                Ok(_) => {}
+                Err(error) => self.ready.push_back(Err(FileError { path, error })),
+            }
+        }
+        for (crc, paths) in by_crc {
+            let sets = if self.confirm {
+                self.split_by_contents(paths)
+            } else {
+                vec![paths]
+            };
+            for paths in sets.into_iter().filter(|paths| paths.len() > 1) {
+                self.ready.push_back(Ok(DuplicateSet { size, crc, paths }));
+            }
+        }
+    }
+
+    /// Partitions `paths` into sets of files with identical contents.
+    fn split_by_contents(&mut self, paths: Vec<PathBuf>) -> Vec<Vec<PathBuf>> {
+        let mut sets: Vec<Vec<PathBuf>> = Vec::new();
+        'paths: for path in paths {
+            for set in &mut sets {
+                match same_contents(&set[0], &path) {
+                    Ok(true) => {
+                        set.push(path);
+                        continue 'paths;
+                    }
+                    Ok(false) => {}
+                    Err(error) => {
+                        self.ready.push_back(Err(FileError { path, error }));
+                        continue 'paths;
+                    }
+                }
+            }
+            sets.push(vec![path]);
+        }
+        sets
+    }
+}
+
+impl Iterator for Duplicates {
+    type Item = Result<DuplicateSet, FileError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.ready.pop_front() {
+                return Some(item);
+            }
+            let (size, paths) = self.groups.next()?;
+            self.process(size, paths);
+        }
+    }
+}
+
+fn same_contents(a: &Path, b: &Path) -> io::Result<bool> {
+    let (mut a, mut b) = (fs::File::open(a)?, fs::File::open(b)?);
+    let (mut buf_a, mut buf_b) = (vec![0; 64 * 1024], vec![0; 64 * 1024]);
+    loop {
+        let n = read_full(&mut a, &mut buf_a)?;
+        if read_full(&mut b, &mut buf_b[..n.max(1)])? != n || buf_a[..n] != buf_b[..n] {
+            return Ok(false);
+        }
+        if n == 0 {
+            return Ok(true);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_duplicates, same_contents};
+    use std::fs;
+
+    #[test]
+    fn find_duplicates_test() {
+        let root = std::env::temp_dir().join(format!("crclib-dedup-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let files = [
+            ("a", "hello world"),
+            ("b", "hello world"),
+            ("c", "hello there"),
+            ("d", "12345"),
+            ("e", "12345"),
+            ("f", ""),
+            ("g", ""),
+        ];
+        for (name, contents) in files {
+            fs::write(root.join(name), contents).unwrap();
+        }
+        let mut paths: Vec<_> = files.iter().map(|(name, _)| root.join(name)).collect();
+        paths.push(root.join("missing"));
+
+        for confirm in [false, true] {
+            let results: Vec<_> = find_duplicates(paths.clone(), confirm).collect();
+            let sets: Vec<_> = results.iter().filter_map(|r| r.as_ref().ok()).collect();
+            assert_eq!(results.len(), 3);
+            assert!(results[0].is_err());
+            assert_eq!(sets[0].size, 5);
+            assert_eq!(sets[0].paths, [root.join("d"), root.join("e")]);
+            assert_eq!(sets[1].crc, 0xC994_65AA);
+            assert_eq!(sets[1].paths, [root.join("a"), root.join("b")]);
+        }
+
+        assert!(same_contents(&root.join("a"), &root.join("b")).unwrap());
+        assert!(!same_contents(&root.join("a"), &root.join("c")).unwrap());
+        assert!(!same_contents(&root.join("a"), &root.join("f")).unwrap());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,239 @@
+//! DNP3 link-layer frames (IEEE 1815-2012, section 9.2).
+//!
+//! A frame starts with the bytes `05 64`, a length, a control byte and the 16-bit
+//! destination and source addresses, followed by the CRC-16/DNP of those 8 bytes. The user
+//! data that follows is split into blocks of 16 bytes, the last one shorter, and every block
+//! is followed by its own CRC. CRCs and addresses are little-endian.
+//!
+//! The length byte counts the control byte, the addresses and the user data, but none of
+//! the CRCs, so a frame holds at most 250 bytes of user data.
+
+use crate::const_crc::Crc16Dnp;
+use crate::trace;
+
+/// The two bytes every frame starts with.
+pub const START: [u8; 2] = [0x05, 0x64];
+/// Length of the header without its CRC.
+pub const HEADER_LEN: usize = 8;
+/// Length of a full user data block without its CRC.
+pub const BLOCK_LEN: usize = 16;
+/// The most user data a frame carries.
+pub const MAX_DATA_LEN: usize = 250;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Dnp3Error {
+    /// The output buffer cannot hold the `needed` bytes.
+    BufferTooSmall { needed: usize },
+    /// More user data than [`MAX_DATA_LEN`].
+    TooLong,
+    /// The input is shorter than the frame its length byte announces.
+    Truncated,
+    /// The frame does not start with [`START`], or its length is below 5.
+    Malformed,
+    /// The CRC after block `block` is wrong; block 0 is the header.
+    Mismatch {
+        block: usize,
+        expected: u16,
+        actual: u16,
+    },
+}
+
+impl core::fmt::Display for Dnp3Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::BufferTooSmall { needed } => {
+                write!(f, "buffer too small: {} bytes needed", needed)
+            }
+            Self::TooLong => write!(f, "user data does not fit a DNP3 frame"),
+            Self::Truncated => write!(f, "DNP3 frame is truncated"),
+            Self::Malformed => write!(f, "malformed DNP3 frame header"),
+            Self::Mismatch {
+                block,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "DNP3 CRC mismatch in block {}: frame has {:#06X}, computed {:#06X}",
+                block, expected, actual
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Dnp3Error {}
+
+/// The header fields after the length byte.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Header {
+    pub control: u8,
+    pub destination: u16,
+    pub source: u16,
+}
+
+/// The CRC of one block.
+pub fn block_crc(block: &[u8]) -> u16 {
+    Crc16Dnp::checksum(block)
+}
+
+/// Length of a frame carrying `data_len` bytes of user data, CRCs included.
+pub fn frame_len(data_len: usize) -> usize {
+    HEADER_LEN + 2 + data_len + 2 * data_len.div_ceil(BLOCK_LEN)
+}
+
+/// Writes the frame for `header` and `data` to the start of `out`, inserting the block
+/// CRCs, and returns its length.
+pub fn encode_frame(header: Header, data: &[u8], out: &mut [u8]) -> Result<usize, Dnp3Error> {
+    if data.len() > MAX_DATA_LEN {
+        return Err(Dnp3Error::TooLong);
+    }
+    let needed = frame_len(data.len());
+    let frame = out
+        .get_mut(..needed)
+        .ok_or(Dnp3Error::BufferTooSmall { needed })?;
+    let [dest_lo, dest_hi] = header.destination.to_le_bytes();
+    let [src_lo, src_hi] = header.source.to_le_bytes();
+    let head = [
+        START[0],
+        START[1],
+        5 + data.len() as u8,
+        header.control,
+        dest_lo,
+        dest_hi,
+        src_lo,
+        src_hi,
+    ];
+    let mut at = 0;
+    for block in core::iter::once(&head[..]).chain(data.chunks(BLOCK_LEN)) {
+        frame[at..at + block.len()].copy_from_slice(block);
+        at += block.len();
+        frame[at..at + 2].copy_from_slice(&block_crc(block).to_le_bytes());
+        at += 2;
+    }
+    Ok(needed)
+}
+
+/// Checks every CRC of the frame at the start of `input`, which may continue past it, and
+/// copies its user data without the CRCs to the start of `out`.
+///
+/// Returns the header and the length of the user data; the frame itself occupies
+/// [`frame_len`] of that many bytes.
+pub fn decode_frame(input: &[u8], out: &mut [u8]) -> Result<(Header, usize), Dnp3Error> {
+    let head = input.get(..HEADER_LEN + 2).ok_or(Dnp3Error::Truncated)?;
+    if head[..2] != START || head[2] < 5 {
+        return Err(Dnp3Error::Malformed);
+    }
+    let data_len = head[2] as usize - 5;
+    let frame = input
+        .get(..frame_len(data_len))
+        .ok_or(Dnp3Error::Truncated)?;
+    let data = out
+        .get_mut(..data_len)
+        .ok_or(Dnp3Error::BufferTooSmall { needed: data_len })?;
+
+    let blocks = core::iter::once(HEADER_LEN).chain(
+        (0..data_len)
+            .step_by(BLOCK_LEN)
+            .map(|start| BLOCK_LEN.min(data_len - start)),
+    );
+    let (mut at, mut copied) = (0, 0);
+    for (index, len) in blocks.enumerate() {
+        let block = &frame[at..at + len];
+        let expected = u16::from_le_bytes([frame[at + len], frame[at + len + 1]]);
+        let actual = block_crc(block);
+        trace::verified("CRC-16/DNP", "DNP3 block", expected as u64, actual as u64);
+        if expected != actual {
+            return Err(Dnp3Error::Mismatch {
+                block: index,
+                expected,
+                actual,
+            });
+        }
+        if index > 0 {
+            data[copied..copied + len].copy_from_slice(block);
+            copied += len;
+        }
+        at += len + 2;
+    }
+
+    let header = Header {
+        control: head[3],
+        destination: u16::from_le_bytes([head[4], head[5]]),
+        source: u16::from_le_bytes([head[6], head[7]]),
+    };
+    Ok((header, data_len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: Header = Header {
+        control: 0xC4,
+        destination: 0x0001,
+        source: 0x0400,
+    };
+
+    #[test]
+    fn frame_test() {
+        let crc = crc::Crc::<u16>::new(&crc::CRC_16_DNP);
+        assert_eq!(block_crc(b"123456789"), crc.checksum(b"123456789"));
+
+        let data: Vec<u8> = (0..40).collect();
+        let mut frame = [0u8; 300];
+        let len = encode_frame(HEADER, &data, &mut frame).unwrap();
+        assert_eq!(len, 10 + 40 + 2 * 3);
+        assert_eq!(frame[..4], [0x05, 0x64, 45, 0xC4]);
+        assert_eq!(frame[8..10], crc.checksum(&frame[..8]).to_le_bytes());
+        assert_eq!(frame[26..28], crc.checksum(&data[..16]).to_le_bytes());
+        assert_eq!(frame[54..56], crc.checksum(&data[32..]).to_le_bytes());
+
+        let mut out = [0u8; MAX_DATA_LEN];
+        assert_eq!(decode_frame(&frame, &mut out), Ok((HEADER, 40)));
+        assert_eq!(out[..40], data[..]);
+
+        // A header-only reset link states request from outstation 1024 to master 1.
+        let reset = Header {
+            control: 0xC0,
+            ..HEADER
+        };
+        assert_eq!(encode_frame(reset, &[], &mut frame), Ok(10));
+        assert_eq!(
+            frame[..10],
+            [0x05, 0x64, 0x05, 0xC0, 0x01, 0x00, 0x00, 0x04, 0xE9, 0x21]
+        );
+        assert_eq!(decode_frame(&frame[..10], &mut []), Ok((reset, 0)));
+    }
+
+    #[test]
+    fn error_test() {
+        let mut frame = [0u8; 64];
+        let len = encode_frame(HEADER, &[0xAA; 20], &mut frame).unwrap();
+        let mut out = [0u8; 20];
+        assert_eq!(
+            decode_frame(&frame[..len - 1], &mut out),
+            Err(Dnp3Error::Truncated)
+        );
+        assert_eq!(
+            decode_frame(&frame[..len], &mut out[..19]),
+            Err(Dnp3Error::BufferTooSmall { needed: 20 })
+        );
+        frame[30] ^= 0x01;
+        assert!(matches!(
+            decode_frame(&frame, &mut out),
+            Err(Dnp3Error::Mismatch { block: 2, .. })
+        ));
+        frame[0] = 0x06;
+        assert_eq!(decode_frame(&frame, &mut out), Err(Dnp3Error::Malformed));
+        assert_eq!(
+            encode_frame(HEADER, &[0; 251], &mut [0; 400]),
+            Err(Dnp3Error::TooLong)
+        );
+        assert_eq!(
+            encode_frame(HEADER, &[0; 16], &mut frame[..27]),
+            Err(Dnp3Error::BufferTooSmall { needed: 28 })
+        );
+    }
+}
@@ -0,0 +1,357 @@
+//! CRC handling of the AUTOSAR E2E protection profiles 1, 2, 5 and 11.
+//!
+//! These helpers cover how each profile feeds the data ID, the payload and the counter into
+//! its CRC and where the counter and CRC live in the protected data. The receiver-side state
+//! machines (counter jump detection, timeouts) are left to the application.
+//!
+//! Offsets are given in bits, as in the AUTOSAR configuration parameters. CRC offsets must be
+//! multiples of 8, counter and nibble offsets multiples of 4.
+
+use crate::engine::Params;
+
+/// CRC-8/SAE-J1850 as profiles 1 and 11 run it: start value and final XOR both cancel out
+/// to 0x00, per the profiles' use of `Crc_CalculateCRC8` with a start value of 0xFF.
+const CRC_8_E2E_P01: Params<u8> = Params {
+    width: 8,
+    poly: 0x1D,
+    init: 0x00,
+    refin: false,
+    refout: false,
+    xorout: 0x00,
+};
+
+/// CRC-8/AUTOSAR (`Crc_CalculateCRC8H2F`), used by profile 2.
+const CRC_8_AUTOSAR: Params<u8> = Params {
+    width: 8,
+    poly: 0x2F,
+    init: 0xFF,
+    refin: false,
+    refout: false,
+    xorout: 0xFF,
+};
+
+/// CRC-16/IBM-3740, also known as CRC-16/CCITT-FALSE (`Crc_CalculateCRC16`), used by
+/// profile 5.
+const CRC_16_IBM_3740: Params<u16> = Params {
+    width: 16,
+    poly: 0x1021,
+    init: 0xFFFF,
+    refin: false,
+    refout: false,
+    xorout: 0x0000,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum E2eError {
+    /// The data is too short for the configured offsets.
+    Length,
+    Mismatch {
+        expected: u16,
+        actual: u16,
+    },
+}
+
+impl core::fmt::Display for E2eError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Length => write!(f, "data too short for the E2E configuration"),
+            Self::Mismatch { expected, actual } => write!(
+                f,
+                "E2E CRC mismatch: data has {:#X}, computed {:#X}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for E2eError {}
+
+fn read_nibble(data: &[u8], offset: usize) -> Result<u8, E2eError> {
+    let byte = *data.get(offset / 8).ok_or(E2eError::Length)?;
+    Ok(if offset % 8 == 4 {
+        byte >> 4
+    } else {
+        byte & 0x0F
+    })
+}
+
+fn write_nibble(data: &mut [u8], offset: usize, value: u8) -> Result<(), E2eError> {
+    let byte = data.get_mut(offset / 8).ok_or(E2eError::Length)?;
+    *byte = if offset % 8 == 4 {
+        (*byte & 0x0F) | (value << 4)
+    } else {
+        (*byte & 0xF0) | (value & 0x0F)
+    };
+    Ok(())
+}
+
+/// How the 16-bit data ID is included in the CRC of profiles 1 and 11.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DataIdMode {
+    /// Both bytes, low byte first.
+    Both,
+    /// The low byte for even counter values, the high byte for odd ones (profile 1 only).
+    Alt,
+    /// Only the low byte; the high byte must be zero (profile 1 only).
+    Low,
+    /// The low byte followed by a zero byte; the low nibble of the high byte is transmitted
+    /// explicitly in the data at the data ID nibble offset.
+    Nibble,
+}
+
+/// Configuration of profile 1, or of profile 11 (whose CRC is computed the same way).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Profile1 {
+    pub data_id: u16,
+    pub data_id_mode: DataIdMode,
+    pub crc_offset: usize,
+    pub counter_offset: usize,
+    /// Only used in [`DataIdMode::Nibble`].
+    pub data_id_nibble_offset: usize,
+}
+
+impl Profile1 {
+    /// The profile 11 default layout: CRC in byte 0, counter in the low nibble of byte 1 and
+    /// (in nibble mode) the data ID nibble in the high nibble of byte 1.
+    pub fn profile11(data_id: u16, data_id_mode: DataIdMode) -> Self {
+        Self {
+            data_id,
+            data_id_mode,
+            crc_offset: 0,
+            counter_offset: 8,
+            data_id_nibble_offset: 12,
+        }
+    }
+
+    /// Computes the CRC of `data` using the counter (and data ID nibble) already stored in it.
+    pub fn crc(&self, data: &[u8]) -> Result<u8, E2eError> {
+        let crc_byte = self.crc_offset / 8;
+        if crc_byte >= data.len() {
+            return Err(E2eError::Length);
+        }
+        let [low, high] = self.data_id.to_le_bytes();
+        let crc = CRC_8_E2E_P01.start();
+        let crc = match self.data_id_mode {
+            DataIdMode::Both => CRC_8_E2E_P01.update(crc, &[low, high]),
+            DataIdMode::Alt if read_nibble(data, self.counter_offset)? % 2 == 0 => {
+                CRC_8_E2E_P01.update(crc, &[low])
+            }
+            DataIdMode::Alt => CRC_8_E2E_P01.update(crc, &[high]),
+            DataIdMode::Low => CRC_8_E2E_P01.update(crc, &[low]),
+            DataIdMode::Nibble => CRC_8_E2E_P01.update(crc, &[low, 0x00]),
+        };
+        let crc = CRC_8_E2E_P01.update(crc, &data[..crc_byte]);
+        let crc = CRC_8_E2E_P01.update(crc, &data[crc_byte + 1..]);
+        Ok(CRC_8_E2E_P01.finalize(crc))
+    }
+
+    /// Writes the counter (and data ID nibble) into `data`, then its CRC.
+    pub fn protect(&self, data: &mut [u8], counter: u8) -> Result<u8, E2eError> {
+        write_nibble(data, self.counter_offset, counter)?;
+        if self.data_id_mode == DataIdMode::Nibble {
+            write_nibble(data, self.data_id_nibble_offset, (self.data_id >> 8) as u8)?;
+        }
+        let crc = self.crc(data)?;
+        data[self.crc_offset / 8] = crc;
+        Ok(crc)
+    }
+
+    /// Checks the CRC of `data` and returns the received counter.
+    pub fn check(&self, data: &[u8]) -> Result<u8, E2eError> {
+        let actual = self.crc(data)?;
+        let expected = data[self.crc_offset / 8];
+        if expected != actual {
+            return Err(E2eError::Mismatch {
+                expected: expected as u16,
+                actual: actual as u16,
+            });
+        }
+        read_nibble(data, self.counter_offset)
+    }
+}
+
+/// Configuration of profile 2: CRC in byte 0, counter in the low nibble of byte 1, and a data
+/// ID selected from a list by the counter value appended to the CRC input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Profile2 {
+    pub data_id_list: [u8; 16],
+}
+
+impl Profile2 {
+    /// Computes the CRC of `data` using the counter already stored in it.
+    pub fn crc(&self, data: &[u8]) -> Result<u8, E2eError> {
+        let counter = read_nibble(data, 8)?;
+        let crc = CRC_8_AUTOSAR.update(CRC_8_AUTOSAR.start(), &data[1..]);
+        let crc = CRC_8_AUTOSAR.update(crc, &[self.data_id_list[counter as usize]]);
+        Ok(CRC_8_AUTOSAR.finalize(crc))
+    }
+
+    /// Writes the counter into `data`, then its CRC.
+    pub fn protect(&self, data: &mut [u8], counter: u8) -> Result<u8, E2eError> {
+        write_nibble(data, 8, counter)?;
+        let crc = self.crc(data)?;
+        data[0] = crc;
+        Ok(crc)
+    }
+
+    /// Checks the CRC of `data` and returns the received counter.
+    pub fn check(&self, data: &[u8]) -> Result<u8, E2eError> {
+        let actual = self.crc(data)?;
+        if data[0] != actual {
+            return Err(E2eError::Mismatch {
+                expected: data[0] as u16,
+                actual: actual as u16,
+            });
+        }
+        read_nibble(data, 8)
+    }
+}
+
+/// Configuration of profile 5: a little-endian CRC-16 at `offset` followed by an 8-bit
+/// counter, with the data ID appended to the CRC input but never transmitted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Profile5 {
+    pub data_id: u16,
+    pub offset: usize,
+}
+
+impl Profile5 {
+    /// Computes the CRC of `data` using the counter already stored in it.
+    pub fn crc(&self, data: &[u8]) -> Result<u16, E2eError> {
+        let offset = self.offset / 8;
+        if offset + 3 > data.len() {
+            return Err(E2eError::Length);
+        }
+        let crc = CRC_16_IBM_3740.update(CRC_16_IBM_3740.start(), &data[..offset]);
+        let crc = CRC_16_IBM_3740.update(crc, &data[offset + 2..]);
+        let crc = CRC_16_IBM_3740.update(crc, &self.data_id.to_le_bytes());
+        Ok(CRC_16_IBM_3740.finalize(crc))
+    }
+
+    /// Writes the counter into `data`, then its CRC.
+    pub fn protect(&self, data: &mut [u8], counter: u8) -> Result<u16, E2eError> {
+        let offset = self.offset / 8;
+        *data.get_mut(offset + 2).ok_or(E2eError::Length)? = counter;
+        let crc = self.crc(data)?;
+        data[offset..offset + 2].copy_from_slice(&crc.to_le_bytes());
+        Ok(crc)
+    }
+
+    /// Checks the CRC of `data` and returns the received counter.
+    pub fn check(&self, data: &[u8]) -> Result<u8, E2eError> {
+        let actual = self.crc(data)?;
+        let offset = self.offset / 8;
+        let expected = u16::from_le_bytes([data[offset], data[offset + 1]]);
+        if expected != actual {
+            return Err(E2eError::Mismatch { expected, actual });
+        }
+        Ok(data[offset + 2])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc_parameters_test() {
+        // Check values of the AUTOSAR CRC library routines.
+        let sae_j1850 = Params {
+            init: 0xFF,
+            xorout: 0xFF,
+            ..CRC_8_E2E_P01
+        };
+        assert_eq!(sae_j1850.checksum(b"123456789"), 0x4B);
+        assert_eq!(CRC_8_AUTOSAR.checksum(b"123456789"), 0xDF);
+        assert_eq!(CRC_16_IBM_3740.checksum(b"123456789"), 0x29B1);
+    }
+
+    #[test]
+    fn profile1_test() {
+        let config = Profile1 {
+            data_id: 0x0123,
+            data_id_mode: DataIdMode::Both,
+            crc_offset: 0,
+            counter_offset: 8,
+            data_id_nibble_offset: 12,
+        };
+        let mut data = [0x00, 0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66];
+        let crc = config.protect(&mut data, 5).unwrap();
+        assert_eq!(data[0], crc);
+        assert_eq!(data[1], 0x05);
+        assert_eq!(
+            crc,
+            CRC_8_E2E_P01.checksum(&[0x23, 0x01, 0x05, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66])
+        );
+        assert_eq!(config.check(&data), Ok(5));
+
+        data[4] ^= 0x80;
+        assert!(matches!(
+            config.check(&data),
+            Err(E2eError::Mismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn data_id_modes_test() {
+        let data = [0x00, 0x04, 0xAA, 0xBB];
+        let crc = |mode, data: &[u8]| Profile1::profile11(0x0A55, mode).crc(data).unwrap();
+        let rest = [0x04, 0xAA, 0xBB];
+        let expected = |prefix: &[u8]| {
+            CRC_8_E2E_P01.finalize(
+                CRC_8_E2E_P01.update(CRC_8_E2E_P01.update(CRC_8_E2E_P01.start(), prefix), &rest),
+            )
+        };
+        assert_eq!(crc(DataIdMode::Both, &data), expected(&[0x55, 0x0A]));
+        assert_eq!(crc(DataIdMode::Low, &data), expected(&[0x55]));
+        assert_eq!(crc(DataIdMode::Alt, &data), expected(&[0x55]));
+        assert_eq!(crc(DataIdMode::Nibble, &data), expected(&[0x55, 0x00]));
+        let odd = [0x00, 0x05, 0xAA, 0xBB];
+        assert_ne!(crc(DataIdMode::Alt, &odd), crc(DataIdMode::Low, &odd));
+    }
+
+    #[test]
+    fn profile11_nibble_test() {
+        let config = Profile1::profile11(0x0A55, DataIdMode::Nibble);
+        let mut data = [0u8; 8];
+        config.protect(&mut data, 14).unwrap();
+        assert_eq!(data[1], 0xAE);
+        assert_eq!(config.check(&data), Ok(14));
+    }
+
+    #[test]
+    fn profile2_test() {
+        let config = Profile2 {
+            data_id_list: core::array::from_fn(|i| 0x10 + i as u8),
+        };
+        let mut data = [0u8; 8];
+        for counter in 0..16 {
+            config.protect(&mut data, counter).unwrap();
+            assert_eq!(config.check(&data), Ok(counter));
+            let mut expected = data[1..].to_vec();
+            expected.push(0x10 + counter);
+            assert_eq!(data[0], CRC_8_AUTOSAR.checksum(&expected));
+        }
+        data[7] = 1;
+        assert!(config.check(&data).is_err());
+    }
+
+    #[test]
+    fn profile5_test() {
+        let config = Profile5 {
+            data_id: 0x1234,
+            offset: 16,
+        };
+        let mut data = [0xA0, 0xA1, 0, 0, 0, 0xB0, 0xB1, 0xB2];
+        let crc = config.protect(&mut data, 0x42).unwrap();
+        assert_eq!(data[2..5], [crc as u8, (crc >> 8) as u8, 0x42]);
+        assert_eq!(
+            crc,
+            CRC_16_IBM_3740.checksum(&[0xA0, 0xA1, 0x42, 0xB0, 0xB1, 0xB2, 0x34, 0x12])
+        );
+        assert_eq!(config.check(&data), Ok(0x42));
+        assert_eq!(config.check(&data[..4]), Err(E2eError::Length));
+    }
+}
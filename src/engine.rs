@@ -0,0 +1,299 @@
+//! Generic bit-by-bit CRC engine shared by the protocol helpers.
+//!
+//! The register is always kept MSB-first and right-aligned in the low `width` bits. Reflected
+//! algorithms reverse each input byte and the final register instead, which keeps a single
+//! update loop for every parameter set.
+
+use crate::CrcParams;
+use core::ops::{BitAnd, BitXor, Not, Shl, Shr};
+
+/// Unsigned integer types that can hold a CRC register.
+pub trait Width:
+    Copy
+    + Into<u128>
+    + Eq
+    + BitAnd<Output = Self>
+    + BitXor<Output = Self>
+    + Not<Output = Self>
+    + Shl<u32, Output = Self>
+    + Shr<u32, Output = Self>
+{
+    const BITS: u32;
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn reverse_bits(self) -> Self;
+    fn from_byte(byte: u8) -> Self;
+    /// Truncates `value` to the type.
+    fn from_u128(value: u128) -> Self;
+    /// The least significant byte.
+    fn low_byte(self) -> u8;
+}
+
+macro_rules! impl_width {
+    ($($ty:ty),*) => {
+        $(
+            impl Width for $ty {
+                const BITS: u32 = <$ty>::BITS;
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+
+                fn reverse_bits(self) -> Self {
+                    <$ty>::reverse_bits(self)
+                }
+
+                fn from_byte(byte: u8) -> Self {
+                    byte as $ty
+                }
+
+                fn from_u128(value: u128) -> Self {
+                    value as $ty
+                }
+
+                fn low_byte(self) -> u8 {
+                    self as u8
+                }
+            }
+        )*
+    };
+}
+
+impl_width!(u8, u16, u32, u64, u128);
+
+/// A CRC parameter set in the Rocksoft model.
+pub struct Params<W> {
+    pub width: u32,
+    pub poly: W,
+    pub init: W,
+    pub refin: bool,
+    pub refout: bool,
+    pub xorout: W,
+}
+
+impl<W: Width> Params<W> {
+    fn mask(&self) -> W {
+        !W::ZERO >> (W::BITS - self.width)
+    }
+
+    fn top_bit(&self) -> W {
+        W::ONE << (self.width - 1)
+    }
+
+    /// Reverses the low `width` bits of `value`.
+    fn reflect(&self, value: W) -> W {
+        value.reverse_bits() >> (W::BITS - self.width)
+    }
+
+    /// Multiplies the register by `x` modulo the polynomial.
+    fn xmul(&self, value: W) -> W {
+        if value & self.top_bit() != W::ZERO {
+            ((value << 1) ^ self.poly) & self.mask()
+        } else {
+            (value << 1) & self.mask()
+        }
+    }
+
+    /// Multiplies two polynomials modulo the generator polynomial.
+    fn mulmod(&self, a: W, b: W) -> W {
+        let mut product = W::ZERO;
+        for bit in (0..self.width).rev() {
+            product = self.xmul(product);
+            if (b >> bit) & W::ONE != W::ZERO {
+                product = product ^ a;
+            }
+        }
+        product
+    }
+
+    /// Computes `x^(8 * bytes)` modulo the generator polynomial.
+    fn xpow8(&self, mut bytes: u64) -> W {
+        let mut base = (0..8).fold(W::ONE, |value, _| self.xmul(value));
+        let mut result = W::ONE;
+        while bytes != 0 {
+            if bytes & 1 != 0 {
+                result = self.mulmod(result, base);
+            }
+            base = self.mulmod(base, base);
+            bytes >>= 1;
+        }
+        result
+    }
+
+    /// The register value before any data has been processed.
+    pub fn start(&self) -> W {
+        self.init & self.mask()
+    }
+
+    /// Feeds `data` into the register `crc` and returns the new register value.
+    pub fn update(&self, mut crc: W, data: &[u8]) -> W {
+        for &ibyte in data {
+            let ibyte = if self.refin {
+                ibyte.reverse_bits()
+            } else {
+                ibyte
+            };
+            for bit in (0..8).rev() {
+                let feedback = (crc & self.top_bit() != W::ZERO) ^ ((ibyte >> bit) & 1 != 0);
+                crc = (crc << 1) & self.mask();
+                if feedback {
+                    crc = crc ^ self.poly;
+                }
+            }
+        }
+        crc
+    }
+
+    /// Feeds the low `bits` bits of `value` into the register, most significant bit first.
+    ///
+    /// Used by protocols whose CRC covers a bit field rather than whole bytes; no input
+    /// reflection is applied.
+    pub fn update_bits(&self, mut crc: W, value: u64, bits: u32) -> W {
+        for bit in (0..bits).rev() {
+            let feedback = (crc & self.top_bit() != W::ZERO) ^ ((value >> bit) & 1 != 0);
+            crc = (crc << 1) & self.mask();
+            if feedback {
+                crc = crc ^ self.poly;
+            }
+        }
+        crc
+    }
+
+    /// Advances the register over `len` zero bytes without processing them one by one.
+    pub fn update_zeros(&self, crc: W, len: u64) -> W {
+        self.mulmod(crc, self.xpow8(len))
+    }
+
+    /// Turns a register value into the checksum reported to callers.
+    pub fn finalize(&self, crc: W) -> W {
+        let crc = if self.refout { self.reflect(crc) } else { crc };
+        (crc ^ self.xorout) & self.mask()
+    }
+
+    /// Inverse of [`Params::finalize`].
+    pub fn unfinalize(&self, checksum: W) -> W {
+        let crc = (checksum ^ self.xorout) & self.mask();
+        if self.refout {
+            self.reflect(crc)
+        } else {
+            crc
+        }
+    }
+
+    pub fn checksum(&self, data: &[u8]) -> W {
+        self.finalize(self.update(self.start(), data))
+    }
+
+    /// The register after a message followed by its own CRC, sent in the bit order the
+    /// algorithm reads, before the final XOR. It is the same for every message.
+    pub fn residue(&self) -> W {
+        let crc = self.finalize(self.start());
+        let bits: u128 = if self.refin { self.reflect(crc) } else { crc }.into();
+        let mut reg = self.start();
+        if self.width > 64 {
+            reg = self.update_bits(reg, (bits >> 64) as u64, self.width - 64);
+        }
+        reg = self.update_bits(reg, bits as u64, self.width.min(64));
+        if self.refout {
+            self.reflect(reg)
+        } else {
+            reg
+        }
+    }
+
+    /// The checksum of a message extended by `len` zero bytes, from the message's checksum.
+    pub fn extend_zeros(&self, checksum: W, len: u64) -> W {
+        self.finalize(self.update_zeros(self.unfinalize(checksum), len))
+    }
+
+    /// Derives the checksum of `a || b` from `checksum(a)`, `checksum(b)` and the length of `b`.
+    pub fn combine(&self, crc_a: W, crc_b: W, len_b: u64) -> W {
+        let crc_a = self.unfinalize(crc_a) ^ self.start();
+        let crc_b = self.unfinalize(crc_b);
+        self.finalize(self.update_zeros(crc_a, len_b) ^ crc_b)
+    }
+}
+
+/// CRC state types whose parameter set the engine can reproduce, which lets generic helpers
+/// derive things the [`CRC`](crate::CRC) trait has no methods for, such as combined
+/// checksums.
+///
+/// This module is private, so the trait is sealed: it can appear in public bounds, but only
+/// the crate's own state types implement it.
+pub trait Model<W> {
+    fn params(&self) -> CrcParams<W>;
+}
+
+impl<W> From<CrcParams<W>> for Params<W> {
+    fn from(params: CrcParams<W>) -> Self {
+        Self {
+            width: params.width,
+            poly: params.poly,
+            init: params.init,
+            refin: params.refin,
+            refout: params.refout,
+            xorout: params.xorout,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Params;
+    use crate::catalog;
+    use crc as crcl;
+
+    const TEST_DATA: &[u8] = b"hello world";
+
+    #[test]
+    fn crc32c_test() {
+        let expected = crcl::Crc::<u32>::new(&crcl::CRC_32_ISCSI).checksum(TEST_DATA);
+        let iscsi = Params::from(catalog::CRC_32_ISCSI.params);
+        assert_eq!(iscsi.checksum(TEST_DATA), expected);
+        assert_eq!(iscsi.checksum(b"123456789"), 0xE306_9283);
+    }
+
+    #[test]
+    fn crc32_iso_hdlc_test() {
+        let expected = crcl::Crc::<u32>::new(&crcl::CRC_32_ISO_HDLC).checksum(TEST_DATA);
+        let iso_hdlc = Params::from(catalog::CRC_32_ISO_HDLC.params);
+        assert_eq!(iso_hdlc.checksum(TEST_DATA), expected);
+        assert_eq!(iso_hdlc.checksum(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn crc16_t10_dif_test() {
+        let expected = crcl::Crc::<u16>::new(&crcl::CRC_16_T10_DIF).checksum(TEST_DATA);
+        let t10_dif = Params::from(catalog::CRC_16_T10_DIF.params);
+        assert_eq!(t10_dif.checksum(TEST_DATA), expected);
+        assert_eq!(t10_dif.checksum(b"123456789"), 0xD0DB);
+    }
+
+    #[test]
+    fn crc64_nvme_test() {
+        let nvme = Params::from(catalog::CRC_64_NVME.params);
+        assert_eq!(nvme.checksum(b"123456789"), 0xAE8B_1486_0A79_9888);
+    }
+
+    #[test]
+    fn combine_test() {
+        let (a, b) = TEST_DATA.split_at(4);
+        let iscsi = Params::from(catalog::CRC_32_ISCSI.params);
+        let combined = iscsi.combine(iscsi.checksum(a), iscsi.checksum(b), b.len() as u64);
+        assert_eq!(combined, iscsi.checksum(TEST_DATA));
+    }
+
+    #[test]
+    fn update_zeros_test() {
+        for algorithm in [catalog::CRC_32_ISCSI, catalog::CRC_32_ISO_HDLC] {
+            let params = Params::from(algorithm.params);
+            let crc = params.update(params.start(), TEST_DATA);
+            assert_eq!(
+                params.update_zeros(crc, 1000),
+                params.update(crc, &[0; 1000])
+            );
+        }
+        let t10_dif = Params::from(catalog::CRC_16_T10_DIF.params);
+        let crc = t10_dif.update(t10_dif.start(), TEST_DATA);
+        assert_eq!(t10_dif.update_zeros(crc, 3), t10_dif.update(crc, &[0; 3]));
+    }
+}
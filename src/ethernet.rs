@@ -0,0 +1,77 @@
+//! IEEE 802.3 frame check sequences.
+//!
+//! The FCS is the CRC-32/ISO-HDLC of the frame from the destination address to the end of
+//! the payload and padding, sent least significant byte first. Receivers usually do not
+//! compare it but run the CRC over the FCS too and check for the fixed
+//! [residue](crate::catalog::Algorithm::residue), which [`verify_frame_residue`] does.
+//! Captures that kept the FCS (e.g. with `ethtool -K rx-fcs on`) can be checked either way.
+
+use crate::catalog::CRC_32_ISO_HDLC;
+use crate::oneshot::crc32;
+use crate::trace;
+
+/// Length of the FCS.
+pub const FCS_LEN: usize = 4;
+/// The shortest frame checked: destination and source address, EtherType and FCS.
+pub const MIN_FRAME_LEN: usize = 14 + FCS_LEN;
+
+/// The FCS of a frame without it.
+pub fn fcs(frame: &[u8]) -> u32 {
+    crc32(frame)
+}
+
+/// Appends the FCS to `frame`.
+#[cfg(feature = "std")]
+pub fn append_fcs(frame: &mut Vec<u8>) {
+    let fcs = fcs(frame);
+    frame.extend_from_slice(&fcs.to_le_bytes());
+}
+
+/// Recomputes the FCS of a frame ending in one and compares; frames shorter than
+/// [`MIN_FRAME_LEN`] fail.
+pub fn verify_frame(frame: &[u8]) -> bool {
+    if frame.len() < MIN_FRAME_LEN {
+        return false;
+    }
+    let (body, trailer) = frame.split_at(frame.len() - FCS_LEN);
+    let stored = u32::from_le_bytes(trailer.try_into().unwrap());
+    let computed = fcs(body);
+    trace::verified(
+        "CRC-32/ISO-HDLC",
+        "Ethernet frame",
+        stored as u64,
+        computed as u64,
+    );
+    stored == computed
+}
+
+/// Checks a frame ending in its FCS by the residue of the CRC over all of it, as MACs do.
+pub fn verify_frame_residue(frame: &[u8]) -> bool {
+    frame.len() >= MIN_FRAME_LEN
+        && crc32(frame) ^ CRC_32_ISO_HDLC.params.xorout == CRC_32_ISO_HDLC.residue
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fcs_test() {
+        // A broadcast ARP request, padded to the minimum payload.
+        let mut frame = vec![0xFF; 6];
+        frame.extend_from_slice(&[0x00, 0x1B, 0x21, 0x3C, 0x4D, 0x5E, 0x08, 0x06]);
+        frame.resize(60, 0);
+        let expected = fcs(&frame);
+        append_fcs(&mut frame);
+        assert_eq!(frame.len(), 64);
+        assert_eq!(frame[60..], expected.to_le_bytes());
+        assert!(verify_frame(&frame));
+        assert!(verify_frame_residue(&frame));
+
+        frame[20] ^= 0x04;
+        assert!(!verify_frame(&frame));
+        assert!(!verify_frame_residue(&frame));
+        assert!(!verify_frame(&frame[..17]));
+        assert!(!verify_frame_residue(&[0x00, 0x00, 0x00, 0x00]));
+    }
+}
@@ -0,0 +1,118 @@
+//! A C interface for the catalogued algorithms.
+//!
+//! ```c
+//! typedef struct CrclibCrc CrclibCrc;
+//!
+//! CrclibCrc *crclib_new(const char *name);      /* NULL for an unknown name */
+//! void crclib_update(CrclibCrc *crc, const uint8_t *data, size_t len);
+//! uint64_t crclib_finalize(const CrclibCrc *crc);
+//! void crclib_reset(CrclibCrc *crc);
+//! void crclib_free(CrclibCrc *crc);
+//! ```
+//!
+//! Names are those accepted by [`catalog::by_name`], e.g. `"CRC-32C"`. To get a shared
+//! library, build with `cargo rustc --release --features ffi --crate-type cdylib`; the
+//! crate is an rlib otherwise so that Rust users do not pay for the extra artifact.
+
+use crate::catalog;
+use crate::DynCrc;
+use std::ffi::{c_char, CStr};
+
+/// A running checksum owned by C code.
+pub struct CrclibCrc(Box<dyn DynCrc>);
+
+/// Creates a state for the catalogue entry `name`, or returns null if there is none.
+///
+/// # Safety
+///
+/// `name` must be null or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn crclib_new(name: *const c_char) -> *mut CrclibCrc {
+    if name.is_null() {
+        return core::ptr::null_mut();
+    }
+    // SAFETY: the caller passes a valid C string.
+    let Ok(name) = unsafe { CStr::from_ptr(name) }.to_str() else {
+        return core::ptr::null_mut();
+    };
+    let Ok(algorithm) = catalog::by_name(name) else {
+        return core::ptr::null_mut();
+    };
+    Box::into_raw(Box::new(CrclibCrc(algorithm.state())))
+}
+
+/// Feeds `len` bytes at `data`.
+///
+/// # Safety
+///
+/// `crc` must come from [`crclib_new`] and not be freed, and `data` must point to `len`
+/// readable bytes; it may be null if `len` is zero.
+#[no_mangle]
+pub unsafe extern "C" fn crclib_update(crc: *mut CrclibCrc, data: *const u8, len: usize) {
+    if len == 0 {
+        return;
+    }
+    // SAFETY: guaranteed by the caller.
+    let (crc, data) = unsafe { (&mut *crc, core::slice::from_raw_parts(data, len)) };
+    crc.0.dyn_update(data);
+}
+
+/// The checksum of the data fed so far, leaving the state untouched.
+///
+/// # Safety
+///
+/// `crc` must come from [`crclib_new`] and not be freed.
+#[no_mangle]
+pub unsafe extern "C" fn crclib_finalize(crc: *const CrclibCrc) -> u64 {
+    // SAFETY: guaranteed by the caller. Catalogue entries are at most 64 bits wide.
+    unsafe { &*crc }.0.dyn_finalize() as u64
+}
+
+/// Returns to the initial state for the next message.
+///
+/// # Safety
+///
+/// `crc` must come from [`crclib_new`] and not be freed.
+#[no_mangle]
+pub unsafe extern "C" fn crclib_reset(crc: *mut CrclibCrc) {
+    // SAFETY: guaranteed by the caller.
+    unsafe { &mut *crc }.0.dyn_reset();
+}
+
+/// Frees a state; null is ignored.
+///
+/// # Safety
+///
+/// `crc` must be null or come from [`crclib_new`] and not be freed already.
+#[no_mangle]
+pub unsafe extern "C" fn crclib_free(crc: *mut CrclibCrc) {
+    if !crc.is_null() {
+        // SAFETY: the pointer was created by `Box::into_raw` in `crclib_new`.
+        drop(unsafe { Box::from_raw(crc) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ffi_test() {
+        unsafe {
+            let crc = crclib_new(c"crc-32c".as_ptr());
+            assert!(!crc.is_null());
+            crclib_update(crc, b"12345".as_ptr(), 5);
+            crclib_update(crc, core::ptr::null(), 0);
+            crclib_update(crc, b"6789".as_ptr(), 4);
+            assert_eq!(crclib_finalize(crc), 0xE306_9283);
+            crclib_reset(crc);
+            crclib_update(crc, b"123456789".as_ptr(), 9);
+            assert_eq!(crclib_finalize(crc), 0xE306_9283);
+            crclib_free(crc);
+
+            assert!(crclib_new(c"CRC-99".as_ptr()).is_null());
+            assert!(crclib_new(core::ptr::null()).is_null());
+            crclib_free(core::ptr::null_mut());
+        }
+    }
+}
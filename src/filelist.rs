@@ -0,0 +1,228 @@
+//! Streaming file lists, as produced by `find -print0`.
+//!
+//! [`read_list`] splits a reader into paths on newlines or NUL bytes without collecting the
+//! whole list, so trees with millions of files never pass through argv or memory at once.
+//! [`hash_files`] checksums the paths of such a list on a pool of worker threads while the
+//! list is still being read, and [`hash_files_with`] does the same for any catalogued
+//! algorithm. This is the plumbing behind `crcsum --files-from - -0`.
+
+use crate::catalog::AnyAlgorithm;
+use crate::manifest::{hash_file, hash_file_with};
+use std::io::{self, BufRead};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+/// What separates the paths of a list.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Delimiter {
+    /// One path per line; a trailing `\r` is kept as part of the path.
+    Newline,
+    /// NUL-terminated paths, which may contain newlines.
+    Nul,
+}
+
+impl Delimiter {
+    fn byte(self) -> u8 {
+        match self {
+            Self::Newline => b'\n',
+            Self::Nul => 0,
+        }
+    }
+}
+
+/// Iterator over the paths of a list, returned by [`read_list`].
+pub struct FileList<R> {
+    reader: R,
+    delimiter: Delimiter,
+    buf: Vec<u8>,
+}
+
+/// Reads a list of paths separated by `delimiter`. Empty entries are skipped.
+pub fn read_list<R: BufRead>(reader: R, delimiter: Delimiter) -> FileList<R> {
+    FileList {
+        reader,
+        delimiter,
+        buf: Vec::new(),
+    }
+}
+
+#[cfg(unix)]
+fn to_path(bytes: Vec<u8>) -> io::Result<PathBuf> {
+    use std::os::unix::ffi::OsStringExt;
+    Ok(std::ffi::OsString::from_vec(bytes).into())
+}
+
+#[cfg(not(unix))]
+fn to_path(bytes: Vec<u8>) -> io::Result<PathBuf> {
+    String::from_utf8(bytes)
+        .map(PathBuf::from)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+impl<R: BufRead> Iterator for FileList<R> {
+    type Item = io::Result<PathBuf>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.buf.clear();
+            let delimiter = self.delimiter.byte();
+            match self.reader.read_until(delimiter, &mut self.buf) {
+                Ok(0) => return None,
+                Ok(_) => {}
+                Err(err) => return Some(Err(err)),
+            }
+            if self.buf.last() == Some(&delimiter) {
+                self.buf.pop();
+            }
+            if !self.buf.is_empty() {
+                return Some(to_path(std::mem::take(&mut self.buf)));
+            }
+        }
+    }
+}
+
+/// The outcome of checksumming one listed file.
+#[derive(Debug)]
+pub struct Hashed<T = u32> {
+    pub path: PathBuf,
+    /// The file's length and checksum: the CRC-32C as from [`hash_file`], or for
+    /// [`hash_files_with`] the checksum widened to `u64` as from [`hash_file_with`].
+    pub result: io::Result<(u64, T)>,
+}
+
+/// Iterator over the files hashed by [`hash_files`] or [`hash_files_with`].
+pub struct HashFiles<T = u32> {
+    results: mpsc::Receiver<io::Result<Hashed<T>>>,
+}
+
+impl<T> Iterator for HashFiles<T> {
+    type Item = io::Result<Hashed<T>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.results.recv().ok()
+    }
+}
+
+/// Checksums every path of `paths` on `threads` worker threads, yielding results in
+/// completion order.
+///
+/// Workers pull paths from `paths` as they become free, so reading the list overlaps with
+/// hashing. An error reading the list itself is yielded once and ends the iteration after
+/// the files already taken have been hashed. Dropping the iterator stops the workers once
+/// they finish their current file.
+pub fn hash_files<I>(paths: I, threads: usize) -> HashFiles
+where
+    I: Iterator<Item = io::Result<PathBuf>> + Send + 'static,
+{
+    hash_files_by(paths, threads, hash_file)
+}
+
+/// [`hash_files`] under `algorithm`.
+pub fn hash_files_with<I>(paths: I, threads: usize, algorithm: AnyAlgorithm) -> HashFiles<u64>
+where
+    I: Iterator<Item = io::Result<PathBuf>> + Send + 'static,
+{
+    hash_files_by(paths, threads, move |path| hash_file_with(path, algorithm))
+}
+
+fn hash_files_by<I, T, F>(paths: I, threads: usize, hash: F) -> HashFiles<T>
+where
+    I: Iterator<Item = io::Result<PathBuf>> + Send + 'static,
+    T: Send + 'static,
+    F: Fn(&Path) -> io::Result<(u64, T)> + Copy + Send + 'static,
+{
+    let threads = threads.max(1);
+    let paths = Arc::new(Mutex::new(Some(paths)));
+    let (sender, results) = mpsc::sync_channel(2 * threads);
+    for _ in 0..threads {
+        let (paths, sender) = (Arc::clone(&paths), sender.clone());
+        thread::spawn(move || loop {
+            let next = {
+                let mut paths = paths.lock().unwrap_or_else(|err| err.into_inner());
+                let next = paths.as_mut().and_then(Iterator::next);
+                if !matches!(next, Some(Ok(_))) {
+                    *paths = None;
+                }
+                next
+            };
+            let item = match next {
+                Some(Ok(path)) => {
+                    let result = hash(&path);
+                    Ok(Hashed { path, result })
+                }
+                Some(Err(err)) => Err(err),
+                None => break,
+            };
+            if sender.send(item).is_err() {
+                break;
+            }
+        });
+    }
+    HashFiles { results }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog;
+    use crate::oneshot::{crc32, crc32c};
+    use std::fs;
+
+    #[test]
+    fn read_list_test() {
+        let list = b"a\0b\nc\0\0d";
+        let paths: Vec<_> = read_list(&list[..], Delimiter::Nul)
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(paths, ["a", "b\nc", "d"].map(PathBuf::from));
+        let paths: Vec<_> = read_list(&list[..], Delimiter::Newline)
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(paths, [PathBuf::from("a\0b"), PathBuf::from("c\0\0d")]);
+    }
+
+    #[test]
+    fn hash_files_test() {
+        let dir = std::env::temp_dir().join(format!("crclib-filelist-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let mut list = Vec::new();
+        for i in 0..20 {
+            let path = dir.join(format!("file {}", i));
+            fs::write(&path, vec![i as u8; i * 100]).unwrap();
+            list.extend_from_slice(path.to_str().unwrap().as_bytes());
+            list.push(0);
+        }
+        list.extend_from_slice(dir.join("missing").to_str().unwrap().as_bytes());
+
+        let paths = read_list(io::Cursor::new(list), Delimiter::Nul);
+        let mut hashed: Vec<_> = hash_files(paths, 4).map(Result::unwrap).collect();
+        hashed.sort_by(|a, b| a.path.cmp(&b.path));
+        assert_eq!(hashed.len(), 21);
+        assert!(hashed.pop().unwrap().result.is_err());
+        for entry in hashed {
+            let contents = fs::read(&entry.path).unwrap();
+            let expected = (contents.len() as u64, crc32c(&contents));
+            assert_eq!(entry.result.unwrap(), expected);
+        }
+
+        let paths = read_list(
+            io::Cursor::new(b"file 3\nfile 7\n".to_vec()),
+            Delimiter::Newline,
+        )
+        .map({
+            let dir = dir.clone();
+            move |path| path.map(|path| dir.join(path))
+        });
+        let algorithm = AnyAlgorithm::Crc32(catalog::CRC_32_ISO_HDLC);
+        let mut hashed: Vec<_> = hash_files_with(paths, 2, algorithm)
+            .map(Result::unwrap)
+            .collect();
+        hashed.sort_by(|a, b| a.path.cmp(&b.path));
+        assert_eq!(
+            hashed[1].result.as_ref().unwrap(),
+            &(700, crc32(&[7; 700]) as u64)
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,266 @@
+//! Adler-32 and the Fletcher checksums, the position-dependent sums that often sit next to a
+//! CRC: zlib streams end in an Adler-32, and OSI transport, ISO 8473 and some file systems
+//! use Fletcher.
+//!
+//! They are not CRCs and have no [`CrcParams`](crate::CrcParams), so they do not implement
+//! [`CRC`](crate::CRC), but they do implement [`DynCrc`]. Code that is generic over
+//! `impl DynCrc` or stores a `Box<dyn DynCrc>`, such as [`MultiDigest`](crate::multi::MultiDigest), takes
+//! them like any CRC state, and with `std` [`by_name`] picks either kind from a name.
+//!
+//! Fletcher-16 sums bytes modulo 255. Fletcher-32 sums 16-bit little-endian words modulo
+//! 65535, padding an odd trailing byte with zero. Adler-32 sums bytes modulo the prime 65521,
+//! starting the first sum at 1.
+
+use crate::DynCrc;
+
+const ADLER_BASE: u32 = 65_521;
+/// The most bytes whose sums fit a `u32` before reducing, as in zlib.
+const ADLER_NMAX: usize = 5552;
+/// The most 16-bit words Fletcher-32 can sum before reducing.
+const FLETCHER32_NMAX: usize = 359;
+
+/// A running Adler-32.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Adler32 {
+    a: u32,
+    b: u32,
+}
+
+impl Adler32 {
+    pub const fn new() -> Self {
+        Self { a: 1, b: 0 }
+    }
+
+    /// Continues from a previous checksum, like passing it back to zlib's `adler32()`.
+    pub const fn from_checksum(adler: u32) -> Self {
+        Self {
+            a: adler & 0xFFFF,
+            b: adler >> 16,
+        }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        for chunk in data.chunks(ADLER_NMAX) {
+            for &byte in chunk {
+                self.a += byte as u32;
+                self.b += self.a;
+            }
+            self.a %= ADLER_BASE;
+            self.b %= ADLER_BASE;
+        }
+    }
+
+    pub fn finalize(&self) -> u32 {
+        self.b << 16 | self.a
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl Default for Adler32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A running Fletcher-16.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Fletcher16 {
+    sum1: u32,
+    sum2: u32,
+}
+
+impl Fletcher16 {
+    pub const fn new() -> Self {
+        Self { sum1: 0, sum2: 0 }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        for chunk in data.chunks(ADLER_NMAX) {
+            for &byte in chunk {
+                self.sum1 += byte as u32;
+                self.sum2 += self.sum1;
+            }
+            self.sum1 %= 255;
+            self.sum2 %= 255;
+        }
+    }
+
+    pub fn finalize(&self) -> u16 {
+        (self.sum2 << 8 | self.sum1) as u16
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+/// A running Fletcher-32.
+///
+/// Updates may split a word; its first byte is kept until the next update.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Fletcher32 {
+    sum1: u32,
+    sum2: u32,
+    pending: Option<u8>,
+}
+
+impl Fletcher32 {
+    pub const fn new() -> Self {
+        Self {
+            sum1: 0,
+            sum2: 0,
+            pending: None,
+        }
+    }
+
+    pub fn update(&mut self, mut data: &[u8]) {
+        if let Some(low) = self.pending {
+            let Some((&high, rest)) = data.split_first() else {
+                return;
+            };
+            self.add_words(&[u16::from_le_bytes([low, high])]);
+            self.pending = None;
+            data = rest;
+        }
+        let mut words = data.chunks_exact(2);
+        let mut block = [0; FLETCHER32_NMAX];
+        loop {
+            let mut len = 0;
+            for (slot, word) in block.iter_mut().zip(&mut words) {
+                *slot = u16::from_le_bytes([word[0], word[1]]);
+                len += 1;
+            }
+            if len == 0 {
+                break;
+            }
+            self.add_words(&block[..len]);
+        }
+        self.pending = words.remainder().first().copied();
+    }
+
+    /// At most [`FLETCHER32_NMAX`] words.
+    fn add_words(&mut self, words: &[u16]) {
+        for &word in words {
+            self.sum1 += word as u32;
+            self.sum2 += self.sum1;
+        }
+        self.sum1 %= 65_535;
+        self.sum2 %= 65_535;
+    }
+
+    /// The checksum so far, with a pending odd byte padded with zero.
+    pub fn finalize(&self) -> u32 {
+        let mut state = *self;
+        if let Some(low) = state.pending.take() {
+            state.add_words(&[low as u16]);
+        }
+        state.sum2 << 16 | state.sum1
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+macro_rules! impl_dyn_crc {
+    ($($checksum:ty),*) => {
+        $(
+            impl DynCrc for $checksum {
+                fn dyn_update(&mut self, data: &[u8]) {
+                    self.update(data)
+                }
+
+                fn dyn_finalize(&self) -> u128 {
+                    self.finalize().into()
+                }
+
+                fn dyn_reset(&mut self) {
+                    self.reset()
+                }
+            }
+        )*
+    };
+}
+
+impl_dyn_crc!(Adler32, Fletcher16, Fletcher32);
+
+/// A fresh state for `"ADLER-32"`, `"FLETCHER-16"`, `"FLETCHER-32"` or any name
+/// [`catalog::by_name`](crate::catalog::by_name) knows, ignoring ASCII case.
+#[cfg(feature = "std")]
+pub fn by_name(name: &str) -> Result<Box<dyn DynCrc>, crate::catalog::UnknownAlgorithm> {
+    Ok(match name.to_ascii_uppercase().as_str() {
+        "ADLER-32" | "ADLER32" => Box::new(Adler32::new()),
+        "FLETCHER-16" | "FLETCHER16" => Box::new(Fletcher16::new()),
+        "FLETCHER-32" | "FLETCHER32" => Box::new(Fletcher32::new()),
+        _ => crate::catalog::by_name(name)?.state(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checksum(mut state: impl DynCrc, chunks: &[&[u8]]) -> u128 {
+        for chunk in chunks {
+            state.dyn_update(chunk);
+        }
+        state.dyn_finalize()
+    }
+
+    #[test]
+    fn fletcher_test() {
+        assert_eq!(checksum(Adler32::new(), &[b"Wikipedia"]), 0x11E6_0398);
+        assert_eq!(checksum(Adler32::new(), &[b"123456789"]), 0x091E_01DE);
+        assert_eq!(checksum(Fletcher16::new(), &[b"abcde"]), 0xC8F0);
+        assert_eq!(checksum(Fletcher16::new(), &[b"abcdefgh"]), 0x0627);
+        assert_eq!(checksum(Fletcher32::new(), &[b"abcde"]), 0xF04F_C729);
+        assert_eq!(checksum(Fletcher32::new(), &[b"abcdef"]), 0x5650_2D2A);
+        assert_eq!(
+            checksum(Fletcher32::new(), &[b"abc", b"", b"d", b"efgh"]),
+            0xEBE1_9591
+        );
+
+        // Long inputs cross the reduction blocks.
+        let data: Vec<u8> = (0..20_000u32).map(|i| (i * 7 + i / 13) as u8).collect();
+        let (a, b) = data.iter().fold((1u64, 0u64), |(a, b), &byte| {
+            let a = (a + byte as u64) % 65_521;
+            (a, (b + a) % 65_521)
+        });
+        assert_eq!(checksum(Adler32::new(), &[&data]), (b << 16 | a) as u128);
+        let (a, b) = data.chunks(2).fold((0u64, 0u64), |(a, b), word| {
+            let a = (a + u16::from_le_bytes([word[0], word[1]]) as u64) % 65_535;
+            (a, (b + a) % 65_535)
+        });
+        let split: Vec<&[u8]> = data.chunks(1001).collect();
+        assert_eq!(checksum(Fletcher32::new(), &split), (b << 16 | a) as u128);
+        let mut adler = Adler32::new();
+        adler.update(&data[..777]);
+        let mut resumed = Adler32::from_checksum(adler.finalize());
+        resumed.update(&data[777..]);
+        assert_eq!(
+            resumed.finalize() as u128,
+            checksum(Adler32::new(), &[&data])
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn by_name_test() {
+        let mut states: Vec<_> = ["adler-32", "Fletcher-16", "CRC-32"]
+            .into_iter()
+            .map(|name| by_name(name).unwrap())
+            .collect();
+        for state in &mut states {
+            state.dyn_update(b"123456789");
+        }
+        let checksums: Vec<u128> = states.iter().map(|state| state.dyn_finalize()).collect();
+        assert_eq!(checksums, [0x091E_01DE, 0x1EDE, 0xCBF4_3926]);
+        assert!(by_name("FLETCHER-64").is_err());
+    }
+}
@@ -0,0 +1,308 @@
+//! FlexRay header and frame CRCs (FlexRay Communications System Protocol Specification 2.1,
+//! section 4.5).
+//!
+//! The 11-bit header CRC covers only 20 bits of the header: the sync and startup frame
+//! indicators, the frame ID and the payload length. The 24-bit frame CRC covers the whole
+//! 5-byte header and the payload and uses a different init value on each channel. Both are
+//! sent most significant bit first, the frame CRC as a 3-byte trailer.
+
+use crate::engine::Params;
+
+/// Length of the frame header.
+pub const HEADER_LEN: usize = 5;
+/// Length of the frame CRC trailer.
+pub const CRC_LEN: usize = 3;
+
+const CRC_11_FLEXRAY: Params<u16> = Params {
+    width: 11,
+    poly: 0x385,
+    init: 0x01A,
+    refin: false,
+    refout: false,
+    xorout: 0x000,
+};
+
+const CRC_24_FLEXRAY_A: Params<u32> = Params {
+    width: 24,
+    poly: 0x5D_6DCB,
+    init: 0xFE_DCBA,
+    refin: false,
+    refout: false,
+    xorout: 0x00_0000,
+};
+
+const CRC_24_FLEXRAY_B: Params<u32> = Params {
+    init: 0xAB_CDEF,
+    ..CRC_24_FLEXRAY_A
+};
+
+/// The channel a frame is sent on, which selects the frame CRC init value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Channel {
+    A,
+    B,
+}
+
+impl Channel {
+    fn params(self) -> &'static Params<u32> {
+        match self {
+            Self::A => &CRC_24_FLEXRAY_A,
+            Self::B => &CRC_24_FLEXRAY_B,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FlexRayError {
+    /// The frame is shorter than its header, payload length and trailer require.
+    Truncated,
+    HeaderMismatch {
+        expected: u16,
+        actual: u16,
+    },
+    FrameMismatch {
+        expected: u32,
+        actual: u32,
+    },
+}
+
+impl core::fmt::Display for FlexRayError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Truncated => write!(f, "frame is truncated"),
+            Self::HeaderMismatch { expected, actual } => write!(
+                f,
+                "header CRC mismatch: header has {:#05X}, computed {:#05X}",
+                expected, actual
+            ),
+            Self::FrameMismatch { expected, actual } => write!(
+                f,
+                "frame CRC mismatch: trailer has {:#08X}, computed {:#08X}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FlexRayError {}
+
+/// The fields of a frame header, excluding the header CRC.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Header {
+    pub payload_preamble: bool,
+    /// Set for frames carrying data; cleared for null frames.
+    pub null_frame_indicator: bool,
+    pub sync: bool,
+    pub startup: bool,
+    /// 11-bit frame ID.
+    pub frame_id: u16,
+    /// 7-bit payload length in two-byte words.
+    pub payload_length: u8,
+    /// 6-bit cycle counter.
+    pub cycle_count: u8,
+}
+
+impl Header {
+    /// The header CRC of this header's sync and startup indicators, frame ID and payload length.
+    pub fn crc(&self) -> u16 {
+        header_crc(self.sync, self.startup, self.frame_id, self.payload_length)
+    }
+
+    /// Encodes the header, including its header CRC.
+    pub fn to_bytes(&self) -> [u8; HEADER_LEN] {
+        let bits = (self.payload_preamble as u64) << 38
+            | (self.null_frame_indicator as u64) << 37
+            | (self.sync as u64) << 36
+            | (self.startup as u64) << 35
+            | (self.frame_id as u64 & 0x7FF) << 24
+            | (self.payload_length as u64 & 0x7F) << 17
+            | (self.crc() as u64) << 6
+            | self.cycle_count as u64 & 0x3F;
+        bits.to_be_bytes()[3..].try_into().unwrap()
+    }
+
+    /// Decodes a header and returns it together with the header CRC it carries.
+    pub fn from_bytes(bytes: &[u8; HEADER_LEN]) -> (Self, u16) {
+        let bits = bytes.iter().fold(0u64, |bits, &b| bits << 8 | b as u64);
+        let flag = |bit: u32| (bits >> bit) & 1 != 0;
+        let header = Self {
+            payload_preamble: flag(38),
+            null_frame_indicator: flag(37),
+            sync: flag(36),
+            startup: flag(35),
+            frame_id: (bits >> 24) as u16 & 0x7FF,
+            payload_length: (bits >> 17) as u8 & 0x7F,
+            cycle_count: bits as u8 & 0x3F,
+        };
+        (header, (bits >> 6) as u16 & 0x7FF)
+    }
+
+    /// Length of the payload in bytes.
+    pub fn payload_len(&self) -> usize {
+        (self.payload_length & 0x7F) as usize * 2
+    }
+}
+
+/// The 11-bit header CRC over the 20 header bits it covers.
+///
+/// Only the low 11 bits of `frame_id` and 7 bits of `payload_length` are used.
+pub fn header_crc(sync: bool, startup: bool, frame_id: u16, payload_length: u8) -> u16 {
+    let bits = (sync as u64) << 19
+        | (startup as u64) << 18
+        | (frame_id as u64 & 0x7FF) << 7
+        | payload_length as u64 & 0x7F;
+    CRC_11_FLEXRAY.finalize(CRC_11_FLEXRAY.update_bits(CRC_11_FLEXRAY.start(), bits, 20))
+}
+
+/// The 24-bit frame CRC over `frame`, the header followed by the payload.
+pub fn frame_crc(channel: Channel, frame: &[u8]) -> u32 {
+    channel.params().checksum(frame)
+}
+
+/// Encodes a complete frame: header, payload and frame CRC trailer.
+///
+/// The header's payload length is taken from `payload`, which must be an even number of at
+/// most 254 bytes.
+#[cfg(feature = "std")]
+pub fn encode_frame(channel: Channel, header: Header, payload: &[u8]) -> Vec<u8> {
+    assert!(
+        payload.len().is_multiple_of(2) && payload.len() <= 254,
+        "invalid FlexRay payload length {}",
+        payload.len()
+    );
+    let header = Header {
+        payload_length: (payload.len() / 2) as u8,
+        ..header
+    };
+    let mut frame = header.to_bytes().to_vec();
+    frame.extend_from_slice(payload);
+    let crc = frame_crc(channel, &frame);
+    frame.extend_from_slice(&crc.to_be_bytes()[1..]);
+    frame
+}
+
+/// Verifies the header CRC and frame CRC of the frame at the start of `frame`.
+///
+/// Returns the decoded header; the frame occupies `HEADER_LEN + header.payload_len() +
+/// CRC_LEN` bytes.
+pub fn verify_frame(channel: Channel, frame: &[u8]) -> Result<Header, FlexRayError> {
+    let bytes: &[u8; HEADER_LEN] = frame
+        .get(..HEADER_LEN)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(FlexRayError::Truncated)?;
+    let (header, expected) = Header::from_bytes(bytes);
+    let actual = header.crc();
+    if expected != actual {
+        return Err(FlexRayError::HeaderMismatch { expected, actual });
+    }
+
+    let covered_len = HEADER_LEN + header.payload_len();
+    let trailer = frame
+        .get(covered_len..covered_len + CRC_LEN)
+        .ok_or(FlexRayError::Truncated)?;
+    let expected = u32::from_be_bytes([0, trailer[0], trailer[1], trailer[2]]);
+    let actual = frame_crc(channel, &frame[..covered_len]);
+    if expected != actual {
+        return Err(FlexRayError::FrameMismatch { expected, actual });
+    }
+    Ok(header)
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "std")]
+    use super::{encode_frame, verify_frame, FlexRayError};
+    use super::{frame_crc, header_crc, Channel, Header, CRC_11_FLEXRAY, HEADER_LEN};
+    use crc as crcl;
+
+    #[test]
+    fn check_values_test() {
+        assert_eq!(CRC_11_FLEXRAY.checksum(b"123456789"), 0x5A3);
+        assert_eq!(frame_crc(Channel::A, b"123456789"), 0x79_79BD);
+        assert_eq!(frame_crc(Channel::B, b"123456789"), 0x1F_23B8);
+    }
+
+    #[test]
+    fn header_crc_test() {
+        // With a zero init, leading zero bits leave the register unchanged, so the 20-bit
+        // field can be checked byte-wise once the init value is folded into its top bits.
+        const ZERO_INIT: crcl::Algorithm<u16> = crcl::Algorithm {
+            init: 0,
+            ..crcl::CRC_11_FLEXRAY
+        };
+        let crc11 = crcl::Crc::<u16>::new(&ZERO_INIT);
+        for (sync, startup, frame_id, payload_length) in [
+            (false, false, 0, 0),
+            (true, false, 1, 8),
+            (false, true, 0x7FF, 0x7F),
+        ] {
+            let bits = (sync as u32) << 19
+                | (startup as u32) << 18
+                | (frame_id as u32) << 7
+                | payload_length as u32;
+            let padded = bits ^ 0x01A << 9;
+            assert_eq!(
+                header_crc(sync, startup, frame_id, payload_length),
+                crc11.checksum(&padded.to_be_bytes()[1..])
+            );
+        }
+        // Bits beyond the field widths are ignored.
+        assert_eq!(
+            header_crc(true, true, 0xFFFF, 0xFF),
+            header_crc(true, true, 0x7FF, 0x7F)
+        );
+    }
+
+    #[test]
+    fn header_roundtrip_test() {
+        let header = Header {
+            payload_preamble: false,
+            null_frame_indicator: true,
+            sync: true,
+            startup: false,
+            frame_id: 0x123,
+            payload_length: 4,
+            cycle_count: 37,
+        };
+        let bytes = header.to_bytes();
+        assert_eq!(Header::from_bytes(&bytes), (header, header.crc()));
+        assert_eq!(bytes.len(), HEADER_LEN);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn verify_frame_test() {
+        let header = Header {
+            null_frame_indicator: true,
+            frame_id: 42,
+            cycle_count: 5,
+            ..Header::default()
+        };
+        let mut frame = encode_frame(Channel::A, header, b"hello world!");
+        let decoded = verify_frame(Channel::A, &frame).unwrap();
+        assert_eq!(decoded.payload_length, 6);
+        assert!(matches!(
+            verify_frame(Channel::B, &frame),
+            Err(FlexRayError::FrameMismatch { .. })
+        ));
+        assert_eq!(
+            verify_frame(Channel::A, &frame[..frame.len() - 1]),
+            Err(FlexRayError::Truncated)
+        );
+
+        // The cycle count is not covered by the header CRC, only by the frame CRC.
+        frame[4] ^= 0x01;
+        assert!(matches!(
+            verify_frame(Channel::A, &frame),
+            Err(FlexRayError::FrameMismatch { .. })
+        ));
+        frame[1] ^= 0x01;
+        assert!(matches!(
+            verify_frame(Channel::A, &frame),
+            Err(FlexRayError::HeaderMismatch { .. })
+        ));
+    }
+}
@@ -0,0 +1,187 @@
+//! Checking the trailing CRC of every frame a decoder yields.
+//!
+//! Serial protocol decoders tend to end the same way: split the byte stream into frames,
+//! check each frame's trailer and pass the payload on. [`ValidateCrc::validate_crc`] is that
+//! last step as an iterator adapter over borrowed (`&[u8]`) or, with `std`, owned
+//! (`Vec<u8>`) frames:
+//!
+//! ```text
+//! let x25 = Crc::new(&catalog::CRC_16_IBM_SDLC);
+//! for payload in frames.validate_crc(&x25, Endian::Little) {
+//!     match payload {
+//!         Ok(payload) => handle(payload),
+//!         Err(bad) => log::warn!("frame {} dropped: {}", bad.index, bad.error),
+//!     }
+//! }
+//! ```
+//!
+//! Trailers are laid out as [`Crc::append_checksum`] writes them, `ceil(width / 8)` bytes in
+//! the given byte order.
+
+use crate::algorithm::CrcError;
+use crate::engine::Width;
+use crate::framing::Endian;
+use crate::strategy::Strategy;
+use crate::Crc;
+use core::fmt;
+use core::iter::FusedIterator;
+
+/// A frame type whose trailer can be cut off.
+pub trait Frame: AsRef<[u8]> {
+    /// The first `len` bytes of the frame.
+    fn truncated(self, len: usize) -> Self;
+}
+
+impl Frame for &[u8] {
+    fn truncated(self, len: usize) -> Self {
+        &self[..len]
+    }
+}
+
+/// Truncates in place.
+#[cfg(feature = "std")]
+impl Frame for Vec<u8> {
+    fn truncated(mut self, len: usize) -> Self {
+        self.truncate(len);
+        self
+    }
+}
+
+/// A frame rejected by [`ValidatedFrames`], returned whole so that it can be logged or
+/// retried.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct InvalidFrame<F> {
+    /// Position of the frame in the input, counting from zero.
+    pub index: usize,
+    pub frame: F,
+    pub error: CrcError,
+}
+
+impl<F> fmt::Display for InvalidFrame<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "frame {}: {}", self.index, self.error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<F: fmt::Debug> std::error::Error for InvalidFrame<F> {}
+
+/// Adds [`validate_crc`](ValidateCrc::validate_crc) to iterators over frames.
+pub trait ValidateCrc: Iterator + Sized
+where
+    Self::Item: Frame,
+{
+    /// Checks the trailing CRC of each frame and yields its payload, or an [`InvalidFrame`]
+    /// for frames that are too short or whose CRC does not match.
+    fn validate_crc<W: Width, S: Strategy>(
+        self,
+        crc: &Crc<W, S>,
+        endian: Endian,
+    ) -> ValidatedFrames<'_, Self, W, S> {
+        ValidatedFrames {
+            frames: self,
+            crc,
+            endian,
+            index: 0,
+        }
+    }
+}
+
+impl<I: Iterator> ValidateCrc for I where I::Item: Frame {}
+
+/// The iterator returned by [`ValidateCrc::validate_crc`].
+pub struct ValidatedFrames<'c, I, W: Width, S: Strategy> {
+    frames: I,
+    crc: &'c Crc<W, S>,
+    endian: Endian,
+    index: usize,
+}
+
+impl<I, W, S> Iterator for ValidatedFrames<'_, I, W, S>
+where
+    I: Iterator,
+    I::Item: Frame,
+    W: Width,
+    S: Strategy,
+{
+    type Item = Result<I::Item, InvalidFrame<I::Item>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let frame = self.frames.next()?;
+        let index = self.index;
+        self.index += 1;
+        let payload = self.crc.validate_and_strip(frame.as_ref(), self.endian);
+        Some(match payload.map(<[u8]>::len) {
+            Ok(len) => Ok(frame.truncated(len)),
+            Err(error) => Err(InvalidFrame {
+                index,
+                frame,
+                error,
+            }),
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.frames.size_hint()
+    }
+}
+
+impl<I, W, S> FusedIterator for ValidatedFrames<'_, I, W, S>
+where
+    I: FusedIterator,
+    I::Item: Frame,
+    W: Width,
+    S: Strategy,
+{
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::catalog;
+
+    #[test]
+    fn validate_crc_test() {
+        let x25 = Crc::new(&catalog::CRC_16_IBM_SDLC);
+        let frames: Vec<Vec<u8>> = [&b"hello"[..], b"", b"world"]
+            .iter()
+            .map(|payload| {
+                let mut frame = payload.to_vec();
+                x25.append_checksum(&mut frame, Endian::Little);
+                frame
+            })
+            .collect();
+        let mut corrupted = frames.clone();
+        corrupted[2][0] ^= 1;
+        corrupted.push(vec![0x7E]);
+
+        let payloads: Vec<_> = frames
+            .iter()
+            .map(Vec::as_slice)
+            .validate_crc(&x25, Endian::Little)
+            .collect();
+        assert_eq!(payloads, [Ok(&b"hello"[..]), Ok(b""), Ok(b"world")]);
+
+        let results: Vec<_> = corrupted
+            .clone()
+            .into_iter()
+            .validate_crc(&x25, Endian::Little)
+            .collect();
+        assert_eq!(results[0], Ok(b"hello".to_vec()));
+        let Err(bad) = &results[2] else {
+            panic!("corrupted frame accepted");
+        };
+        assert_eq!(bad.index, 2);
+        assert_eq!(bad.frame, corrupted[2]);
+        assert!(matches!(bad.error, CrcError::Mismatch { .. }));
+        assert_eq!(
+            results[3],
+            Err(InvalidFrame {
+                index: 3,
+                frame: vec![0x7E],
+                error: CrcError::Truncated,
+            })
+        );
+    }
+}
@@ -0,0 +1,339 @@
+//! Declarative, transport-agnostic CRC framing.
+//!
+//! Many serial and IPC protocols share one frame shape: optional sync bytes, an optional
+//! length field, the payload and a CRC in front of or behind it. A [`Framing`] describes
+//! where each part goes and what the CRC covers, and [`encode_frame`] and [`decode_frame`]
+//! build and check frames from that single description, so the two directions cannot drift
+//! apart. Everything works on caller-provided slices and needs no allocator.
+//!
+//! The layout is `sync | length | crc | payload` for [`CrcPosition::Header`] and
+//! `sync | length | payload | crc` for [`CrcPosition::Trailer`]. The CRC is as wide as the
+//! CRC type's value, e.g. four bytes for [`CRC32`](crate::CRC32).
+
+use crate::CRC;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Endian {
+    Big,
+    Little,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CrcPosition {
+    /// Between the length field and the payload.
+    Header,
+    /// After the payload.
+    Trailer,
+}
+
+/// A length field preceding the payload.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LengthField {
+    /// Width of the field in bytes, 1 to 8.
+    pub len: usize,
+    pub endian: Endian,
+    /// Whether the value counts the CRC bytes as well as the payload.
+    pub includes_crc: bool,
+}
+
+/// The frame layout of a protocol.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Framing<'a> {
+    /// Fixed bytes every frame starts with; may be empty.
+    pub sync: &'a [u8],
+    pub length: Option<LengthField>,
+    pub crc_position: CrcPosition,
+    pub crc_endian: Endian,
+    /// Whether the CRC covers the sync bytes.
+    pub crc_covers_sync: bool,
+    /// Whether the CRC covers the length field.
+    pub crc_covers_length: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FramingError {
+    /// The output buffer cannot hold a frame of `needed` bytes.
+    BufferTooSmall {
+        needed: usize,
+    },
+    /// The frame is shorter than its layout or length field requires.
+    Truncated,
+    /// The frame does not start with the sync bytes.
+    Sync,
+    /// The payload length does not fit the length field.
+    Length,
+    Mismatch {
+        expected: u128,
+        actual: u128,
+    },
+}
+
+impl core::fmt::Display for FramingError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::BufferTooSmall { needed } => {
+                write!(f, "buffer too small: frame needs {} bytes", needed)
+            }
+            Self::Truncated => write!(f, "frame is truncated"),
+            Self::Sync => write!(f, "frame does not start with the sync bytes"),
+            Self::Length => write!(f, "payload length does not fit the length field"),
+            Self::Mismatch { expected, actual } => write!(
+                f,
+                "frame CRC mismatch: frame has {:#X}, computed {:#X}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FramingError {}
+
+fn write_uint(out: &mut [u8], value: u128, endian: Endian) {
+    let len = out.len();
+    match endian {
+        Endian::Big => out.copy_from_slice(&value.to_be_bytes()[16 - len..]),
+        Endian::Little => out.copy_from_slice(&value.to_le_bytes()[..len]),
+    }
+}
+
+pub(crate) fn read_uint(bytes: &[u8], endian: Endian) -> u128 {
+    let fold = |value: u128, &byte: &u8| value << 8 | byte as u128;
+    match endian {
+        Endian::Big => bytes.iter().fold(0, fold),
+        Endian::Little => bytes.iter().rev().fold(0, fold),
+    }
+}
+
+impl Framing<'_> {
+    fn header_len(&self, crc_len: usize) -> usize {
+        let crc = match self.crc_position {
+            CrcPosition::Header => crc_len,
+            CrcPosition::Trailer => 0,
+        };
+        self.sync.len() + self.length.map_or(0, |length| length.len) + crc
+    }
+
+    /// Length of the frame for a payload of `payload_len` bytes and a `crc_len`-byte CRC.
+    pub fn frame_len(&self, payload_len: usize, crc_len: usize) -> usize {
+        self.sync.len() + self.length.map_or(0, |length| length.len) + payload_len + crc_len
+    }
+
+    /// Feeds the covered parts of a frame whose length field is `length` to `crc`.
+    fn crc<C, N>(&self, mut crc: C, length: &[u8], payload: &[u8]) -> u128
+    where
+        C: CRC<Width = N>,
+        N: Into<u128>,
+    {
+        if self.crc_covers_sync {
+            crc.update(self.sync);
+        }
+        if self.crc_covers_length {
+            crc.update(length);
+        }
+        crc.update(payload);
+        crc.finalize().into()
+    }
+}
+
+/// Writes a frame carrying `payload` to the start of `out` and returns its length.
+///
+/// `crc` is the initial CRC state, e.g. `CRC16::default()`.
+pub fn encode_frame<C, N>(
+    framing: &Framing<'_>,
+    crc: C,
+    payload: &[u8],
+    out: &mut [u8],
+) -> Result<usize, FramingError>
+where
+    C: CRC<Width = N>,
+    N: Into<u128>,
+{
+    let crc_len = core::mem::size_of::<N>();
+    let needed = framing.frame_len(payload.len(), crc_len);
+    if out.len() < needed {
+        return Err(FramingError::BufferTooSmall { needed });
+    }
+    let out = &mut out[..needed];
+    out[..framing.sync.len()].copy_from_slice(framing.sync);
+    let mut pos = framing.sync.len();
+    if let Some(length) = framing.length {
+        let value = payload.len() + if length.includes_crc { crc_len } else { 0 };
+        if length.len < 8 && (value as u64) >> (8 * length.len) != 0 {
+            return Err(FramingError::Length);
+        }
+        write_uint(
+            &mut out[pos..pos + length.len],
+            value as u128,
+            length.endian,
+        );
+        pos += length.len;
+    }
+    let (head, rest) = out.split_at_mut(pos);
+    let (crc_bytes, body) = match framing.crc_position {
+        CrcPosition::Header => rest.split_at_mut(crc_len),
+        CrcPosition::Trailer => {
+            let (body, crc_bytes) = rest.split_at_mut(payload.len());
+            (crc_bytes, body)
+        }
+    };
+    body.copy_from_slice(payload);
+    let value = framing.crc(crc, &head[framing.sync.len()..], payload);
+    write_uint(crc_bytes, value, framing.crc_endian);
+    Ok(needed)
+}
+
+/// Checks the frame at the start of `frame` and returns its payload and total length.
+///
+/// With a length field, bytes after the frame are ignored, so a receive buffer can be
+/// advanced by the returned length; without one the whole of `frame` is the frame.
+pub fn decode_frame<'a, C, N>(
+    framing: &Framing<'_>,
+    crc: C,
+    frame: &'a [u8],
+) -> Result<(&'a [u8], usize), FramingError>
+where
+    C: CRC<Width = N>,
+    N: Into<u128>,
+{
+    let crc_len = core::mem::size_of::<N>();
+    let header_len = framing.header_len(crc_len);
+    if frame.len() < framing.frame_len(0, crc_len) {
+        return Err(FramingError::Truncated);
+    }
+    if !frame.starts_with(framing.sync) {
+        return Err(FramingError::Sync);
+    }
+    let length_bytes = &frame[framing.sync.len()..][..framing.length.map_or(0, |l| l.len)];
+    let payload_len = match framing.length {
+        Some(length) => {
+            let value = read_uint(length_bytes, length.endian);
+            let crc = if length.includes_crc { crc_len } else { 0 };
+            usize::try_from(value)
+                .ok()
+                .and_then(|value| value.checked_sub(crc))
+                .ok_or(FramingError::Length)?
+        }
+        None => frame.len() - framing.frame_len(0, crc_len),
+    };
+    let frame_len = framing
+        .frame_len(0, crc_len)
+        .checked_add(payload_len)
+        .ok_or(FramingError::Length)?;
+    let frame = frame.get(..frame_len).ok_or(FramingError::Truncated)?;
+    let (crc_bytes, payload) = match framing.crc_position {
+        CrcPosition::Header => (
+            &frame[header_len - crc_len..header_len],
+            &frame[header_len..],
+        ),
+        CrcPosition::Trailer => (
+            &frame[frame_len - crc_len..],
+            &frame[header_len..frame_len - crc_len],
+        ),
+    };
+    let expected = read_uint(crc_bytes, framing.crc_endian);
+    let actual = framing.crc(crc, length_bytes, payload);
+    if expected != actual {
+        return Err(FramingError::Mismatch { expected, actual });
+    }
+    Ok((payload, frame_len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        decode_frame, encode_frame, CrcPosition, Endian, Framing, FramingError, LengthField,
+    };
+    use crate::{CRC, CRC16, CRC32};
+
+    /// Sync byte, big-endian 16-bit length of the payload, payload, little-endian CRC.
+    const SERIAL: Framing<'static> = Framing {
+        sync: &[0x7E],
+        length: Some(LengthField {
+            len: 2,
+            endian: Endian::Big,
+            includes_crc: false,
+        }),
+        crc_position: CrcPosition::Trailer,
+        crc_endian: Endian::Little,
+        crc_covers_sync: false,
+        crc_covers_length: true,
+    };
+
+    #[test]
+    fn trailer_test() {
+        let mut buf = [0; 32];
+        let len = encode_frame(&SERIAL, CRC16::default(), b"hello world", &mut buf).unwrap();
+        assert_eq!(len, 16);
+        assert_eq!(buf[..3], [0x7E, 0x00, 0x0B]);
+        let mut crc = CRC16::default();
+        crc.update(&buf[1..14]);
+        assert_eq!(buf[14..16], crc.finalize().to_le_bytes());
+
+        // Trailing bytes belong to the next frame.
+        buf[16] = 0x7E;
+        let (payload, frame_len) = decode_frame(&SERIAL, CRC16::default(), &buf).unwrap();
+        assert_eq!((payload, frame_len), (&b"hello world"[..], 16));
+
+        assert_eq!(
+            decode_frame(&SERIAL, CRC16::default(), &buf[..15]),
+            Err(FramingError::Truncated)
+        );
+        buf[5] ^= 1;
+        assert!(matches!(
+            decode_frame(&SERIAL, CRC16::default(), &buf),
+            Err(FramingError::Mismatch { .. })
+        ));
+        buf[0] = 0;
+        assert_eq!(
+            decode_frame(&SERIAL, CRC16::default(), &buf),
+            Err(FramingError::Sync)
+        );
+        assert_eq!(
+            encode_frame(&SERIAL, CRC16::default(), b"hello world", &mut buf[..15]),
+            Err(FramingError::BufferTooSmall { needed: 16 })
+        );
+    }
+
+    #[test]
+    fn header_test() {
+        let framing = Framing {
+            sync: &[],
+            length: Some(LengthField {
+                len: 1,
+                endian: Endian::Big,
+                includes_crc: true,
+            }),
+            crc_position: CrcPosition::Header,
+            crc_endian: Endian::Big,
+            crc_covers_sync: false,
+            crc_covers_length: false,
+        };
+        let mut buf = [0; 16];
+        let len = encode_frame(&framing, CRC32::default(), b"hello", &mut buf).unwrap();
+        assert_eq!(buf[0], 9);
+        let mut crc = CRC32::default();
+        crc.update(b"hello");
+        assert_eq!(buf[1..5], crc.finalize().to_be_bytes());
+        assert_eq!(
+            decode_frame(&framing, CRC32::default(), &buf[..len]),
+            Ok((&b"hello"[..], 10))
+        );
+        assert_eq!(
+            encode_frame(&framing, CRC32::default(), &[0; 252], &mut [0; 300]),
+            Err(FramingError::Length)
+        );
+
+        // Without a length field the whole buffer is the frame.
+        let bare = Framing {
+            length: None,
+            ..framing
+        };
+        let len = encode_frame(&bare, CRC32::default(), b"hello", &mut buf).unwrap();
+        assert_eq!(
+            decode_frame(&bare, CRC32::default(), &buf[..len]),
+            Ok((&b"hello"[..], 9))
+        );
+    }
+}
@@ -0,0 +1,396 @@
+//! Polynomial arithmetic over GF(2), the algebra behind every CRC.
+//!
+//! Polynomials are bit patterns with the coefficient of `x^i` in bit `i`. A [`Polynomial`] is
+//! a CRC generator polynomial, kept in the same normal form as
+//! [`CrcParams::poly`](crate::CrcParams::poly), without its `x^width` term, and reduces
+//! products modulo itself. Combining checksums comes
+//! down to [`Polynomial::pow_x`]: appending `n` bits to a message multiplies its register by
+//! `x^n`.
+//!
+//! The polynomial tables in the literature use several representations of the same
+//! generator: normal form (`0x04C11DB7` for CRC-32), the reversed form LSB-first
+//! implementations shift with (`0xEDB88320`), and Koopman's notation, the full polynomial
+//! without its `+1` term (`0x82608EDB`). Passing one where another is expected silently gives
+//! wrong checksums, so a [`Polynomial`] is built from a named representation, e.g.
+//! [`Polynomial::from_koopman`], and handed to [`CrcBuilder::polynomial`](crate::CrcBuilder::polynomial).
+//! The reciprocal polynomial ([`Polynomial::reciprocal`], `0xDB710641`) has the same
+//! error-detection properties.
+//!
+//! [`factors`] splits a generator of up to 128 bits into irreducible factors, which is how
+//! typos and placeholder constants show up: a well-chosen CRC polynomial is irreducible or
+//! `x + 1` times an irreducible one, while a random constant usually has several factors.
+
+/// The carry-less product of `a` and `b`.
+pub const fn clmul(a: u64, b: u64) -> u128 {
+    let mut product = 0u128;
+    let mut bit = 0;
+    while bit < 64 {
+        if b >> bit & 1 != 0 {
+            product ^= (a as u128) << bit;
+        }
+        bit += 1;
+    }
+    product
+}
+
+/// Reverses the low `width` bits of `value`.
+///
+/// # Panics
+///
+/// Panics if `width` is 0 or above 64.
+pub const fn reflect(value: u64, width: u32) -> u64 {
+    assert!(width >= 1 && width <= 64, "width out of range");
+    value.reverse_bits() >> (64 - width)
+}
+
+/// A generator polynomial `x^width + poly` of degree 1 to 64.
+///
+/// `new` takes the normal form; the other constructors convert from the other
+/// representations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Polynomial {
+    width: u32,
+    poly: u64,
+}
+
+impl Polynomial {
+    /// # Panics
+    ///
+    /// Panics if `width` is 0 or above 64, or `poly` has bits set at or above `width`.
+    pub const fn new(width: u32, poly: u64) -> Self {
+        assert!(width >= 1 && width <= 64, "width out of range");
+        assert!(
+            width == 64 || poly >> width == 0,
+            "poly does not fit the width"
+        );
+        Self { width, poly }
+    }
+
+    /// From the reversed representation of a `width`-bit polynomial, e.g. `0xEDB88320`.
+    ///
+    /// # Panics
+    ///
+    /// As for [`Polynomial::new`].
+    pub const fn from_reversed(width: u32, reversed: u64) -> Self {
+        Self::new(width, reflect(reversed, width))
+    }
+
+    /// From Koopman's notation, whose highest set bit gives the width, e.g. `0x82608EDB`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `koopman` is zero.
+    pub const fn from_koopman(koopman: u64) -> Self {
+        assert!(koopman != 0, "zero is no polynomial in Koopman's notation");
+        let width = 64 - koopman.leading_zeros();
+        let mask = u64::MAX >> (64 - width);
+        Self::new(width, (koopman << 1 | 1) & mask)
+    }
+
+    pub const fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The normal form, without the `x^width` term.
+    pub const fn normal(&self) -> u64 {
+        self.poly
+    }
+
+    /// Koopman's notation: the full polynomial without the `+1` term, shifted right by one.
+    pub const fn koopman(&self) -> u64 {
+        1 << (self.width - 1) | self.poly >> 1
+    }
+
+    /// The full polynomial, `x^width` included.
+    pub const fn full(&self) -> u128 {
+        1 << self.width | self.poly as u128
+    }
+
+    /// `value` modulo the generator.
+    pub const fn reduce(&self, mut value: u128) -> u64 {
+        let full = self.full();
+        let mut bit = 127;
+        while bit >= self.width {
+            if value >> bit & 1 != 0 {
+                value ^= full << (bit - self.width);
+            }
+            bit -= 1;
+        }
+        value as u64
+    }
+
+    /// The product of `a` and `b` modulo the generator.
+    pub const fn mul(&self, a: u64, b: u64) -> u64 {
+        self.reduce(clmul(self.reduce(a as u128), self.reduce(b as u128)))
+    }
+
+    /// `x^n` modulo the generator, in time logarithmic in `n`.
+    pub const fn pow_x(&self, mut n: u64) -> u64 {
+        let mut base = self.reduce(2);
+        let mut result = self.reduce(1);
+        while n != 0 {
+            if n & 1 != 0 {
+                result = self.mul(result, base);
+            }
+            base = self.mul(base, base);
+            n >>= 1;
+        }
+        result
+    }
+
+    /// The reversed representation, as shifted by LSB-first implementations.
+    pub const fn reversed(&self) -> u64 {
+        reflect(self.poly, self.width)
+    }
+
+    /// The reciprocal polynomial `x^width * P(1/x)`, in normal form.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the polynomial has no `+1` term, as its reciprocal would be of lower degree.
+    pub const fn reciprocal(&self) -> Self {
+        assert!(self.poly & 1 != 0, "polynomial has no +1 term");
+        let mask = u64::MAX >> (64 - self.width);
+        Self {
+            width: self.width,
+            poly: (self.reversed() << 1 | 1) & mask,
+        }
+    }
+}
+
+/// The degrees of the irreducible factors of a generator, with multiplicity, in ascending
+/// order.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Factors {
+    /// Distinct degrees and how many factors have each. Their sum is at most 128, so there
+    /// are at most 15.
+    counts: [(u8, u8); 15],
+    len: u8,
+}
+
+impl Factors {
+    pub fn degrees(&self) -> impl Iterator<Item = u32> + '_ {
+        self.counts[..self.len as usize]
+            .iter()
+            .flat_map(|&(degree, count)| core::iter::repeat_n(degree as u32, count as usize))
+    }
+
+    pub fn is_irreducible(&self) -> bool {
+        self.len == 1 && self.counts[0].1 == 1
+    }
+
+    fn push(&mut self, degree: u32, count: u32) {
+        self.counts[self.len as usize] = (degree as u8, count as u8);
+        self.len += 1;
+    }
+}
+
+impl core::fmt::Debug for Factors {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.degrees()).finish()
+    }
+}
+
+/// Factors the generator `x^width + poly`, `poly` in normal form, by distinct-degree
+/// factorization.
+///
+/// # Panics
+///
+/// Panics if `width` is 0 or above 128, or `poly` has bits set at or above `width`.
+pub fn factors(width: u32, poly: u128) -> Factors {
+    assert!((1..=128).contains(&width), "width out of range");
+    assert!(
+        width == 128 || poly >> width == 0,
+        "poly does not fit the width"
+    );
+    let x = Wide::from(2);
+    let mut factors = Factors {
+        counts: [(0, 0); 15],
+        len: 0,
+    };
+    let mut f = Wide::from(poly).xor(Wide::ONE.shl(width));
+    // x^(2^i) mod f: each factor of degree i divides x^(2^i) - x.
+    let mut h = x.rem(f);
+    let mut i = 1;
+    while f.degree() >= Some(2 * i) {
+        h = h.mul_mod(h, f);
+        let mut g = f.gcd(h.xor(x));
+        // g is the product of the distinct factors of degree i; divide them out until none
+        // is left to count their multiplicity.
+        let mut count = 0;
+        while g.degree() > Some(0) {
+            count += g.degree().unwrap() / i;
+            f = f.div_rem(g).0;
+            h = h.rem(f);
+            g = f.gcd(g);
+        }
+        if count > 0 {
+            factors.push(i, count);
+        }
+        i += 1;
+    }
+    if let Some(degree @ 1..) = f.degree() {
+        factors.push(degree, 1);
+    }
+    factors
+}
+
+/// A polynomial of degree up to 128, with `hi` the coefficient of `x^128`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Wide {
+    hi: bool,
+    lo: u128,
+}
+
+impl Wide {
+    const ZERO: Self = Self::from(0);
+    const ONE: Self = Self::from(1);
+
+    const fn from(lo: u128) -> Self {
+        Self { hi: false, lo }
+    }
+
+    fn degree(self) -> Option<u32> {
+        match (self.hi, self.lo) {
+            (true, _) => Some(128),
+            (false, 0) => None,
+            (false, lo) => Some(127 - lo.leading_zeros()),
+        }
+    }
+
+    fn xor(self, other: Self) -> Self {
+        Self {
+            hi: self.hi ^ other.hi,
+            lo: self.lo ^ other.lo,
+        }
+    }
+
+    /// Multiplies by `x^n`; the product must be of degree 128 at most.
+    fn shl(self, n: u32) -> Self {
+        match n {
+            0 => self,
+            128 => Self {
+                hi: self.lo & 1 != 0,
+                lo: 0,
+            },
+            _ => Self {
+                hi: self.lo >> (128 - n) & 1 != 0,
+                lo: self.lo << n,
+            },
+        }
+    }
+
+    fn div_rem(self, divisor: Self) -> (Self, Self) {
+        let degree = divisor.degree().expect("division by zero");
+        let (mut quotient, mut rem) = (Self::ZERO, self);
+        while let Some(shift) = rem.degree().and_then(|d| d.checked_sub(degree)) {
+            quotient = quotient.xor(Self::ONE.shl(shift));
+            rem = rem.xor(divisor.shl(shift));
+        }
+        (quotient, rem)
+    }
+
+    fn rem(self, divisor: Self) -> Self {
+        self.div_rem(divisor).1
+    }
+
+    fn gcd(self, other: Self) -> Self {
+        let (mut a, mut b) = (self, other);
+        while b != Self::ZERO {
+            (a, b) = (b, a.rem(b));
+        }
+        a
+    }
+
+    /// The product modulo `m`, for `self` and `other` already reduced modulo `m`.
+    fn mul_mod(self, other: Self, m: Self) -> Self {
+        let degree = m.degree();
+        let mut product = Self::ZERO;
+        for bit in (0..128).rev() {
+            product = product.shl(1);
+            if product.degree() == degree {
+                product = product.xor(m);
+            }
+            if other.lo >> bit & 1 != 0 {
+                product = product.xor(self);
+            }
+        }
+        product
+    }
+}
+
+impl Polynomial {
+    /// The irreducible factors of the generator; see [`factors`].
+    pub fn factors(&self) -> Factors {
+        factors(self.width, self.poly as u128)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Params;
+
+    const CRC32: Polynomial = Polynomial::new(32, 0x04C1_1DB7);
+
+    #[test]
+    fn representation_test() {
+        assert_eq!(clmul(0b101, 0b11), 0b1111);
+        assert_eq!(clmul(u64::MAX, 2), (u64::MAX as u128) << 1);
+        assert_eq!(CRC32.reversed(), 0xEDB8_8320);
+        assert_eq!(CRC32.reciprocal().normal(), 0xDB71_0641);
+        assert_eq!(CRC32.reciprocal().reciprocal(), CRC32);
+        assert_eq!(reflect(0b0011, 4), 0b1100);
+        assert_eq!(Polynomial::new(64, 0x1B).full(), 1 << 64 | 0x1B);
+
+        assert_eq!(Polynomial::from_koopman(0x8260_8EDB), CRC32);
+        assert_eq!(Polynomial::from_reversed(32, 0xEDB8_8320), CRC32);
+        assert_eq!(CRC32.koopman(), 0x8260_8EDB);
+        let ecma = Polynomial::new(64, 0x42F0_E1EB_A9EA_3693);
+        assert_eq!(Polynomial::from_koopman(ecma.koopman()), ecma);
+        assert_eq!(Polynomial::from_koopman(0x62CC).normal(), 0x4599);
+    }
+
+    #[test]
+    fn arithmetic_test() {
+        assert_eq!(CRC32.pow_x(31), 1 << 31);
+        assert_eq!(CRC32.pow_x(32), 0x04C1_1DB7);
+        assert_eq!(
+            CRC32.mul(CRC32.pow_x(100), CRC32.pow_x(23)),
+            CRC32.pow_x(123)
+        );
+        assert_eq!(
+            CRC32.reduce(clmul(0xDEAD_BEEF, 0x1234_5678)),
+            CRC32.mul(0xDEAD_BEEF, 0x1234_5678)
+        );
+
+        // Appending zero bytes multiplies the register by x^8 per byte.
+        let bzip2 = Params::<u32>::from(crate::catalog::CRC_32_BZIP2.params);
+        let register = bzip2.update(bzip2.start(), b"123456789");
+        let extended = bzip2.update(register, &[0; 5]);
+        assert_eq!(extended as u64, CRC32.mul(register as u64, CRC32.pow_x(40)));
+
+        let ecma = Polynomial::new(64, 0x42F0_E1EB_A9EA_3693);
+        assert_eq!(ecma.mul(ecma.pow_x(64), 1), 0x42F0_E1EB_A9EA_3693);
+    }
+
+    #[test]
+    fn factors_test() {
+        assert!(CRC32.factors().is_irreducible());
+        assert!(Polynomial::new(64, 0x1B).factors().is_irreducible());
+        // CRC-16/ARC is (x + 1) times a primitive polynomial of degree 15.
+        assert!(Polynomial::new(16, 0x8005).factors().degrees().eq([1, 15]));
+        // x^8 + x^2 + x + 1 = (x + 1)(x^7 + x^6 + x^5 + x^4 + x^3 + x^2 + 1).
+        assert!(Polynomial::new(8, 0x07).factors().degrees().eq([1, 7]));
+        // x^4 = x * x * x * x, and (x^2 + x + 1)^2 = x^4 + x^2 + 1.
+        assert!(Polynomial::new(4, 0).factors().degrees().eq([1, 1, 1, 1]));
+        assert!(Polynomial::new(4, 0b101).factors().degrees().eq([2, 2]));
+        // x^128 + x^7 + x^2 + x + 1, the GHASH polynomial, is irreducible.
+        assert!(factors(128, 0x87).is_irreducible());
+        assert!(factors(128, 0).degrees().eq([1; 128]));
+        // x^128 + 1 = (x + 1)^128.
+        assert!(factors(128, 1).degrees().eq([1; 128]));
+    }
+}
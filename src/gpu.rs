@@ -0,0 +1,336 @@
+//! Experimental GPU backend for checksumming many buffers at once.
+//!
+//! [`GpuCrc32c`] computes the CRC-32C of a batch of independent buffers with a `wgpu` compute
+//! shader. This pays off for bulk work such as scrubbing thousands of multi-megabyte objects
+//! from object storage, not for single small buffers, where the upload alone costs more than
+//! hashing on the CPU.
+//!
+//! Every buffer is cut into 16 KiB chunks and one shader invocation hashes one chunk; the
+//! chunk CRCs are then joined on the CPU by CRC combination. Batches larger than the device's
+//! storage buffer limit are split into several dispatches.
+//!
+//! By default every GPU result is also computed on the CPU and compared, so that a driver or
+//! shader bug surfaces as [`GpuError::Mismatch`] rather than as a wrong checksum. Turn
+//! [`GpuCrc32c::verify`] off once the backend has proven itself on the hardware in use.
+
+use crate::catalog::CRC_32_ISCSI;
+use crate::Crc;
+use std::sync::mpsc;
+use wgpu::util::DeviceExt;
+
+const CHUNK_LEN: usize = 16 * 1024;
+const WORKGROUP_SIZE: u32 = 64;
+
+const SHADER: &str = r#"
+@group(0) @binding(0) var<storage, read> table: array<u32, 256>;
+@group(0) @binding(1) var<storage, read> data: array<u32>;
+// (first word, length in bytes) of each chunk.
+@group(0) @binding(2) var<storage, read> chunks: array<vec2<u32>>;
+@group(0) @binding(3) var<storage, read_write> crcs: array<u32>;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = id.x;
+    if index >= arrayLength(&chunks) {
+        return;
+    }
+    let chunk = chunks[index];
+    var crc = 0xFFFFFFFFu;
+    for (var i = 0u; i < chunk.y; i++) {
+        let byte = (data[chunk.x + i / 4u] >> ((i % 4u) * 8u)) & 0xFFu;
+        crc = table[(crc ^ byte) & 0xFFu] ^ (crc >> 8u);
+    }
+    crcs[index] = crc ^ 0xFFFFFFFFu;
+}
+"#;
+
+#[derive(Debug)]
+pub enum GpuError {
+    /// No suitable GPU adapter was found.
+    NoAdapter(wgpu::RequestAdapterError),
+    Device(wgpu::RequestDeviceError),
+    /// Reading the results back from the GPU failed.
+    Readback(String),
+    /// The GPU and CPU disagree on the CRC of buffer `index`.
+    Mismatch {
+        index: usize,
+        expected: u32,
+        actual: u32,
+    },
+}
+
+impl std::fmt::Display for GpuError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoAdapter(err) => write!(f, "no GPU adapter: {}", err),
+            Self::Device(err) => write!(f, "failed to open GPU device: {}", err),
+            Self::Readback(err) => write!(f, "failed to read GPU results: {}", err),
+            Self::Mismatch {
+                index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "GPU CRC of buffer {} is {:#010X}, CPU computed {:#010X}",
+                index, actual, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GpuError {}
+
+/// A chunk of one of the input buffers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Chunk {
+    buffer: usize,
+    offset: usize,
+    len: usize,
+}
+
+/// Splits `buffers` into chunks, grouped into dispatches of at most `max_bytes` each.
+fn plan(buffers: &[&[u8]], max_bytes: usize) -> Vec<Vec<Chunk>> {
+    let per_dispatch = (max_bytes / CHUNK_LEN).max(1);
+    let mut dispatches = vec![Vec::new()];
+    for (buffer, data) in buffers.iter().enumerate() {
+        for offset in (0..data.len()).step_by(CHUNK_LEN) {
+            if dispatches.last().unwrap().len() == per_dispatch {
+                dispatches.push(Vec::new());
+            }
+            dispatches.last_mut().unwrap().push(Chunk {
+                buffer,
+                offset,
+                len: CHUNK_LEN.min(data.len() - offset),
+            });
+        }
+    }
+    dispatches.retain(|chunks| !chunks.is_empty());
+    dispatches
+}
+
+/// A GPU device set up for CRC-32C.
+pub struct GpuCrc32c {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::ComputePipeline,
+    table: wgpu::Buffer,
+    max_bytes: usize,
+    /// Whether to check every result against the CPU; on by default.
+    pub verify: bool,
+}
+
+impl GpuCrc32c {
+    /// Opens the default GPU adapter, blocking until it is ready.
+    pub fn new() -> Result<Self, GpuError> {
+        pollster::block_on(Self::new_async())
+    }
+
+    pub async fn new_async() -> Result<Self, GpuError> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::new_without_display_handle());
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::HighPerformance,
+                ..Default::default()
+            })
+            .await
+            .map_err(GpuError::NoAdapter)?;
+        let limits = adapter.limits();
+        let (device, queue) = adapter
+            .request_device(&wgpu::DeviceDescriptor {
+                label: Some("crclib"),
+                required_limits: limits.clone(),
+                ..Default::default()
+            })
+            .await
+            .map_err(GpuError::Device)?;
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("crc32c"),
+            source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("crc32c"),
+            layout: None,
+            module: &module,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+        let table: Vec<u8> = (0..=255u32)
+            .flat_map(|byte| {
+                (0..8)
+                    .fold(byte, |crc, _| {
+                        (crc >> 1) ^ (0x82F6_3B78 & (crc & 1).wrapping_neg())
+                    })
+                    .to_le_bytes()
+            })
+            .collect();
+        let table = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("crc32c table"),
+            contents: &table,
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+
+        let max_bytes = (limits.max_storage_buffer_binding_size as u64)
+            .min(limits.max_buffer_size)
+            .min(
+                WORKGROUP_SIZE as u64
+                    * limits.max_compute_workgroups_per_dimension as u64
+                    * CHUNK_LEN as u64,
+            ) as usize;
+        Ok(Self {
+            device,
+            queue,
+            pipeline,
+            table,
+            max_bytes,
+            verify: true,
+        })
+    }
+
+    /// Computes the CRC-32C of each of `buffers`.
+    pub fn checksum_batch(&self, buffers: &[&[u8]]) -> Result<Vec<u32>, GpuError> {
+        let crc32c = Crc::<u32>::cached(&CRC_32_ISCSI);
+        let mut crcs = vec![crc32c.checksum(&[]); buffers.len()];
+        for chunks in plan(buffers, self.max_bytes) {
+            for (chunk, crc) in chunks.iter().zip(self.dispatch(buffers, &chunks)?) {
+                crcs[chunk.buffer] = crc32c.combine(crcs[chunk.buffer], crc, chunk.len as u64);
+            }
+        }
+        if self.verify {
+            for (index, (data, &actual)) in buffers.iter().zip(&crcs).enumerate() {
+                let expected = crc32c.checksum(data);
+                if expected != actual {
+                    return Err(GpuError::Mismatch {
+                        index,
+                        expected,
+                        actual,
+                    });
+                }
+            }
+        }
+        Ok(crcs)
+    }
+
+    /// Hashes `chunks` in a single dispatch, returning one CRC per chunk.
+    fn dispatch(&self, buffers: &[&[u8]], chunks: &[Chunk]) -> Result<Vec<u32>, GpuError> {
+        // Chunks start on word boundaries so the shader can index them as `u32`s.
+        let mut data = Vec::with_capacity(chunks.len() * CHUNK_LEN);
+        let mut ranges = Vec::with_capacity(chunks.len() * 8);
+        for chunk in chunks {
+            ranges.extend_from_slice(&((data.len() / 4) as u32).to_le_bytes());
+            ranges.extend_from_slice(&(chunk.len as u32).to_le_bytes());
+            data.extend_from_slice(&buffers[chunk.buffer][chunk.offset..][..chunk.len]);
+            data.resize(data.len().next_multiple_of(4), 0);
+        }
+
+        let storage = |label, contents: &[u8]| {
+            self.device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some(label),
+                    contents,
+                    usage: wgpu::BufferUsages::STORAGE,
+                })
+        };
+        let data = storage("crc32c data", &data);
+        let ranges = storage("crc32c chunks", &ranges);
+        let size = chunks.len() as u64 * 4;
+        let output = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("crc32c output"),
+            size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("crc32c readback"),
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("crc32c"),
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &[&self.table, &data, &ranges, &output]
+                .iter()
+                .enumerate()
+                .map(|(binding, buffer)| wgpu::BindGroupEntry {
+                    binding: binding as u32,
+                    resource: buffer.as_entire_binding(),
+                })
+                .collect::<Vec<_>>(),
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups((chunks.len() as u32).div_ceil(WORKGROUP_SIZE), 1, 1);
+        }
+        encoder.copy_buffer_to_buffer(&output, 0, &readback, 0, size);
+        self.queue.submit([encoder.finish()]);
+
+        let (sender, receiver) = mpsc::channel();
+        readback.map_async(wgpu::MapMode::Read, .., move |result| {
+            let _ = sender.send(result);
+        });
+        self.device
+            .poll(wgpu::PollType::wait_indefinitely())
+            .map_err(|err| GpuError::Readback(err.to_string()))?;
+        receiver
+            .recv()
+            .map_err(|err| GpuError::Readback(err.to_string()))?
+            .map_err(|err| GpuError::Readback(err.to_string()))?;
+        let view = readback
+            .get_mapped_range(..)
+            .map_err(|err| GpuError::Readback(err.to_string()))?;
+        Ok(view
+            .chunks_exact(4)
+            .map(|word| u32::from_le_bytes(word.try_into().unwrap()))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{plan, GpuCrc32c, CHUNK_LEN, CRC_32_ISCSI};
+    use crate::Crc;
+
+    #[test]
+    fn plan_test() {
+        let (a, b, c) = (vec![1u8; 3 * CHUNK_LEN + 5], vec![], vec![2u8; 10]);
+        let dispatches = plan(&[&a, &b, &c], 2 * CHUNK_LEN);
+        assert_eq!(dispatches.len(), 3);
+        assert_eq!(dispatches[1][1].offset, 3 * CHUNK_LEN);
+        assert_eq!(dispatches[1][1].len, 5);
+        assert_eq!(dispatches[2][0].buffer, 2);
+
+        // Combining the chunk CRCs as `checksum_batch` does gives the whole-buffer CRC.
+        let crc32c = Crc::<u32>::cached(&CRC_32_ISCSI);
+        let crc = dispatches.iter().flatten().filter(|c| c.buffer == 0).fold(
+            crc32c.checksum(&[]),
+            |crc, chunk| {
+                let part = crc32c.checksum(&a[chunk.offset..][..chunk.len]);
+                crc32c.combine(crc, part, chunk.len as u64)
+            },
+        );
+        assert_eq!(crc, crc32c.checksum(&a));
+        assert!(plan(&[&b], 1 << 20).is_empty());
+    }
+
+    #[test]
+    fn gpu_test() {
+        // Most CI machines have no usable adapter.
+        let Ok(gpu) = GpuCrc32c::new() else {
+            return;
+        };
+        let data: Vec<u8> = (0..=255).cycle().take(100_000).collect();
+        let buffers: Vec<&[u8]> = vec![b"hello world", &[], &data, &data[1..]];
+        // `verify` makes this compare every result with the CPU.
+        let crcs = gpu.checksum_batch(&buffers).unwrap();
+        assert_eq!(crcs[0], 0xC994_65AA);
+        assert_eq!(crcs[1], 0);
+    }
+}
@@ -0,0 +1,120 @@
+//! `BuildHasher`s for hash maps and sets that bucket the same way on every run and platform.
+//!
+//! `std`'s `RandomState` seeds every map differently, which is right for maps fed untrusted
+//! keys but makes iteration order and bucket assignment change from run to run. A
+//! [`CrcBuildHasher`] hashes with a plain CRC instead, so tools that shard or sort by hash get
+//! the same result everywhere, and it needs neither `std` nor a source of randomness:
+//!
+//! ```text
+//! let mut map: HashMap<&str, u32, CrcBuildHasher> = HashMap::default();
+//! ```
+//!
+//! [`SeededCrcBuildHasher`] mixes in a fixed seed, for separate maps that should not bucket
+//! alike. Neither resists keys chosen to collide. `Hash` writes integers in native byte
+//! order, so integer keys hash alike only on platforms of the same endianness; strings and
+//! byte slices hash alike everywhere.
+//!
+//! Both default to CRC-64/XZ through [`Crc64Xz`], whose tables are built at compile time, so
+//! starting a hasher costs nothing. Any CRC state that implements `Hasher` works, but maps
+//! such as `hashbrown` take bits from the top of the 64-bit hash, which a CRC-32 leaves at
+//! zero.
+
+use crate::const_crc::Crc64Xz;
+use core::hash::{BuildHasher, Hasher};
+use core::marker::PhantomData;
+
+/// Hashes with a fresh CRC state of type `C`.
+pub struct CrcBuildHasher<C = Crc64Xz>(PhantomData<fn() -> C>);
+
+impl<C> CrcBuildHasher<C> {
+    pub const fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<C> Default for CrcBuildHasher<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C> Clone for CrcBuildHasher<C> {
+    fn clone(&self) -> Self {
+        Self::new()
+    }
+}
+
+impl<C> core::fmt::Debug for CrcBuildHasher<C> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("CrcBuildHasher")
+    }
+}
+
+impl<C: Hasher + Default> BuildHasher for CrcBuildHasher<C> {
+    type Hasher = C;
+
+    fn build_hasher(&self) -> C {
+        C::default()
+    }
+}
+
+/// Hashes with a CRC state of type `C` that has already read `seed`.
+#[derive(Clone, Debug)]
+pub struct SeededCrcBuildHasher<C = Crc64Xz> {
+    start: C,
+}
+
+impl<C: Hasher + Default> SeededCrcBuildHasher<C> {
+    pub fn new(seed: u64) -> Self {
+        let mut start = C::default();
+        start.write(&seed.to_le_bytes());
+        Self { start }
+    }
+}
+
+impl<C: Hasher + Clone> BuildHasher for SeededCrcBuildHasher<C> {
+    type Hasher = C;
+
+    fn build_hasher(&self) -> C {
+        self.start.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::const_crc::Crc32Iscsi;
+    use core::hash::Hash;
+    use std::collections::HashMap;
+
+    #[test]
+    fn build_hasher_test() {
+        let build = CrcBuildHasher::<Crc64Xz>::new();
+        let mut expected = Crc64Xz::new();
+        "key".hash(&mut expected);
+        assert_eq!(build.hash_one("key"), expected.finish());
+        assert_eq!(
+            build.hash_one(42u32),
+            CrcBuildHasher::<Crc64Xz>::new().hash_one(42u32)
+        );
+
+        let seeded = SeededCrcBuildHasher::<Crc64Xz>::new(7);
+        assert_eq!(
+            seeded.hash_one("key"),
+            SeededCrcBuildHasher::<Crc64Xz>::new(7).hash_one("key")
+        );
+        assert_ne!(seeded.hash_one("key"), build.hash_one("key"));
+        assert_ne!(
+            seeded.hash_one("key"),
+            SeededCrcBuildHasher::<Crc64Xz>::new(8).hash_one("key")
+        );
+
+        let mut map: HashMap<&str, usize, CrcBuildHasher> = HashMap::default();
+        for (i, word) in ["alpha", "beta", "gamma"].into_iter().enumerate() {
+            map.insert(word, i);
+        }
+        assert_eq!(map["beta"], 1);
+        let crc32 = CrcBuildHasher::<Crc32Iscsi>::new();
+        assert!(crc32.hash_one("beta") <= u32::MAX as u64);
+    }
+}
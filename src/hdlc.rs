@@ -0,0 +1,89 @@
+//! HDLC frame check sequences, as used by PPP (RFC 1662), AX.25 and other HDLC-framed links.
+//!
+//! The FCS-16 is CRC-16/IBM-SDLC, the X.25 CRC, and the FCS-32 is CRC-32/ISO-HDLC. Both
+//! cover the unescaped frame from the address field to the end of the information field and
+//! are sent least significant byte first; flag removal and byte or bit stuffing are up to
+//! the caller. Receivers run the CRC over the FCS as well and compare the register with
+//! [`GOOD_FCS16`] or [`GOOD_FCS32`], the "good FCS" values of RFC 1662, which is what
+//! [`verify_fcs16`] and [`verify_fcs32`] do.
+
+use crate::catalog::{CRC_16_IBM_SDLC, CRC_32_ISO_HDLC};
+use crate::const_crc::Crc16IbmSdlc;
+use crate::oneshot::crc32;
+
+/// Length of the FCS-16.
+pub const FCS16_LEN: usize = 2;
+/// Length of the FCS-32.
+pub const FCS32_LEN: usize = 4;
+
+/// The FCS-16 register, before the final complement, after a frame followed by its FCS.
+pub const GOOD_FCS16: u16 = 0xF0B8;
+/// The FCS-32 register, before the final complement, after a frame followed by its FCS.
+pub const GOOD_FCS32: u32 = 0xDEBB_20E3;
+
+/// The FCS-16 of a frame without it.
+pub fn fcs16(frame: &[u8]) -> u16 {
+    Crc16IbmSdlc::checksum(frame)
+}
+
+/// The FCS-32 of a frame without it.
+pub fn fcs32(frame: &[u8]) -> u32 {
+    crc32(frame)
+}
+
+/// Appends the FCS-16 to `frame`.
+#[cfg(feature = "std")]
+pub fn append_fcs16(frame: &mut Vec<u8>) {
+    let fcs = fcs16(frame);
+    frame.extend_from_slice(&fcs.to_le_bytes());
+}
+
+/// Appends the FCS-32 to `frame`.
+#[cfg(feature = "std")]
+pub fn append_fcs32(frame: &mut Vec<u8>) {
+    let fcs = fcs32(frame);
+    frame.extend_from_slice(&fcs.to_le_bytes());
+}
+
+/// Checks a frame ending in its FCS-16; frames too short to hold one fail.
+pub fn verify_fcs16(frame: &[u8]) -> bool {
+    frame.len() >= FCS16_LEN && fcs16(frame) ^ CRC_16_IBM_SDLC.params.xorout == GOOD_FCS16
+}
+
+/// Checks a frame ending in its FCS-32; frames too short to hold one fail.
+pub fn verify_fcs32(frame: &[u8]) -> bool {
+    frame.len() >= FCS32_LEN && fcs32(frame) ^ CRC_32_ISO_HDLC.params.xorout == GOOD_FCS32
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fcs_test() {
+        assert_eq!(GOOD_FCS16, CRC_16_IBM_SDLC.residue);
+        assert_eq!(GOOD_FCS32, CRC_32_ISO_HDLC.residue);
+        assert_eq!(fcs16(b"123456789"), 0x906E);
+        assert_eq!(fcs32(b"123456789"), 0xCBF4_3926);
+
+        // An LCP Configure-Request with the address and control fields.
+        let lcp = [0xFF, 0x03, 0xC0, 0x21, 0x01, 0x01, 0x00, 0x04];
+        let mut frame = lcp.to_vec();
+        append_fcs16(&mut frame);
+        assert_eq!(frame[lcp.len()..], fcs16(&lcp).to_le_bytes());
+        assert!(verify_fcs16(&frame));
+        frame[3] ^= 0x80;
+        assert!(!verify_fcs16(&frame));
+
+        let mut frame = lcp.to_vec();
+        append_fcs32(&mut frame);
+        assert_eq!(frame.len(), lcp.len() + FCS32_LEN);
+        assert!(verify_fcs32(&frame));
+        assert!(!verify_fcs16(&frame));
+        frame.pop();
+        assert!(!verify_fcs32(&frame));
+
+        assert!(!verify_fcs16(&[0xFF]));
+        assert!(!verify_fcs32(&[0x00; 3]));
+    }
+}
@@ -0,0 +1,105 @@
+//! Parsing expected checksums from hex text.
+//!
+//! Manifests, command lines and ASCII protocol fields carry checksums as hex strings in
+//! either case, with or without a `0x` prefix and with or without leading zeros.
+//! [`FromHex`] parses them into the integer type [`CRC::finalize`](crate::CRC::finalize)
+//! returns, rejecting strings with more digits than that type's width.
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum HexError {
+    /// There are no digits after the optional prefix.
+    Empty,
+    /// A character that is not a hex digit, at this byte offset.
+    InvalidDigit(usize),
+    /// More digits than the checksum width allows.
+    TooLong { max_digits: usize },
+}
+
+impl core::fmt::Display for HexError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "empty hex checksum"),
+            Self::InvalidDigit(offset) => write!(f, "invalid hex digit at offset {}", offset),
+            Self::TooLong { max_digits } => {
+                write!(f, "hex checksum longer than {} digits", max_digits)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for HexError {}
+
+/// Checksum types that can be parsed from hex.
+pub trait FromHex: Sized {
+    /// Parses `text`, e.g. `"CBF43926"`, `"0xcbf43926"` or `"c814"`.
+    ///
+    /// Leading zeros may be omitted, but a leading zero still counts towards the width:
+    /// `"000ABC"` is too long for a `u16` even though its value would fit.
+    fn from_hex_str(text: &str) -> Result<Self, HexError>;
+}
+
+macro_rules! impl_from_hex {
+    ($($n:ty),*) => {
+        $(
+            impl FromHex for $n {
+                fn from_hex_str(text: &str) -> Result<Self, HexError> {
+                    let (offset, digits) = match text.as_bytes() {
+                        [b'0', b'x' | b'X', ..] => (2, &text[2..]),
+                        _ => (0, text),
+                    };
+                    let max_digits = <$n>::BITS as usize / 4;
+                    if digits.is_empty() {
+                        return Err(HexError::Empty);
+                    }
+                    if digits.len() > max_digits {
+                        return Err(HexError::TooLong { max_digits });
+                    }
+                    digits.bytes().enumerate().try_fold(0, |value: $n, (i, digit)| {
+                        let nibble = (digit as char)
+                            .to_digit(16)
+                            .ok_or(HexError::InvalidDigit(offset + i))?;
+                        Ok(value << 4 | nibble as $n)
+                    })
+                }
+            }
+        )*
+    };
+}
+
+impl_from_hex!(u8, u16, u32, u64, u128);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_hex_test() {
+        assert_eq!(u32::from_hex_str("CBF43926"), Ok(0xCBF4_3926));
+        assert_eq!(u32::from_hex_str("0xcbf43926"), Ok(0xCBF4_3926));
+        assert_eq!(u16::from_hex_str("c814"), Ok(0xC814));
+        assert_eq!(u16::from_hex_str("94"), Ok(0x94));
+        assert_eq!(u8::from_hex_str("FF"), Ok(0xFF));
+        assert_eq!(
+            u128::from_hex_str("0123456789abcdefABCDEF0123456789"),
+            Ok(0x0123_4567_89AB_CDEF_ABCD_EF01_2345_6789)
+        );
+
+        assert_eq!(
+            u16::from_hex_str("000ABC"),
+            Err(HexError::TooLong { max_digits: 4 })
+        );
+        assert_eq!(u32::from_hex_str("0x"), Err(HexError::Empty));
+        assert_eq!(u32::from_hex_str(""), Err(HexError::Empty));
+        assert_eq!(
+            u32::from_hex_str("0xCBF4392G"),
+            Err(HexError::InvalidDigit(9))
+        );
+        assert_eq!(
+            u32::from_hex_str("+CBF4392"),
+            Err(HexError::InvalidDigit(0))
+        );
+        assert_eq!(u32::from_hex_str("é"), Err(HexError::InvalidDigit(0)));
+    }
+}
@@ -0,0 +1,106 @@
+use crate::CRC;
+use http_body::{Body, Frame, SizeHint};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+pin_project_lite::pin_project! {
+    /// Wraps an HTTP [`Body`] and computes the CRC of its data frames as they are forwarded.
+    ///
+    /// Frames are passed through untouched, so the wrapper can sit between a client or
+    /// server and whatever consumes the payload. Once the inner body reports end of
+    /// stream the finalized digest becomes available through [`CrcBody::checksum`].
+    pub struct CrcBody<B, C, N> {
+        #[pin]
+        inner: B,
+        crc: C,
+        checksum: Option<N>,
+    }
+}
+
+impl<B, C, N> CrcBody<B, C, N>
+where
+    C: CRC<Width = N>,
+    N: Copy,
+{
+    pub fn new(inner: B, crc: C) -> Self {
+        Self {
+            inner,
+            crc,
+            checksum: None,
+        }
+    }
+
+    /// The CRC of the complete payload, or `None` while the body has not reached EOF.
+    pub fn checksum(&self) -> Option<N> {
+        self.checksum
+    }
+
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+}
+
+impl<B, C, N> Body for CrcBody<B, C, N>
+where
+    B: Body,
+    B::Data: AsRef<[u8]>,
+    C: CRC<Width = N>,
+    N: Copy,
+{
+    type Data = B::Data;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        let frame = std::task::ready!(this.inner.poll_frame(cx));
+        match &frame {
+            Some(Ok(frame)) => {
+                if let Some(data) = frame.data_ref() {
+                    this.crc.update(data.as_ref());
+                }
+            }
+            Some(Err(_)) => {}
+            None => *this.checksum = Some(this.crc.finalize()),
+        }
+        Poll::Ready(frame)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CrcBody;
+    use crate::{CRC, CRC32};
+    use bytes::Bytes;
+    use futures::executor::block_on;
+    use http_body_util::{BodyExt, Full};
+
+    const TEST_DATA: &[u8] = b"hello world";
+
+    #[test]
+    fn crc_body_test() {
+        let mut body = CrcBody::new(Full::new(Bytes::from_static(TEST_DATA)), CRC32::default());
+        assert_eq!(body.checksum(), None);
+
+        let mut forwarded = Vec::new();
+        while let Some(frame) = block_on(body.frame()) {
+            let frame = frame.expect("body should not fail");
+            forwarded.extend_from_slice(frame.data_ref().expect("data frame"));
+        }
+
+        let mut expected = CRC32::default();
+        expected.update(TEST_DATA);
+        assert_eq!(forwarded, TEST_DATA);
+        assert_eq!(body.checksum(), Some(expected.finalize()));
+    }
+}
@@ -0,0 +1,219 @@
+//! iSCSI HeaderDigest and DataDigest (RFC 7143, section 13.1).
+//!
+//! Both digests are CRC-32C. The header digest covers the 48-byte basic header segment plus
+//! any additional header segments; the data digest covers the data segment including its
+//! zero padding to a multiple of four bytes. Digests are sent in little-endian byte order.
+
+use crate::const_crc::Crc32Iscsi;
+use crate::oneshot::crc32c;
+use crate::trace;
+use crate::CRC;
+
+/// Length of the basic header segment.
+pub const BHS_LEN: usize = 48;
+/// Length of a digest on the wire.
+pub const DIGEST_LEN: usize = 4;
+
+/// Errors produced while verifying the digests of a PDU.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DigestError {
+    /// The buffer is shorter than the lengths in the basic header segment require.
+    Truncated,
+    HeaderMismatch {
+        expected: u32,
+        actual: u32,
+    },
+    DataMismatch {
+        expected: u32,
+        actual: u32,
+    },
+}
+
+impl core::fmt::Display for DigestError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Truncated => write!(f, "PDU is truncated"),
+            Self::HeaderMismatch { expected, actual } => write!(
+                f,
+                "header digest mismatch: PDU has {:#010X}, computed {:#010X}",
+                expected, actual
+            ),
+            Self::DataMismatch { expected, actual } => write!(
+                f,
+                "data digest mismatch: PDU has {:#010X}, computed {:#010X}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DigestError {}
+
+/// Length of the header covered by the header digest (BHS plus AHS), read from `bhs`.
+pub fn header_len(bhs: &[u8; BHS_LEN]) -> usize {
+    BHS_LEN + bhs[4] as usize * 4
+}
+
+/// Length of the data segment excluding padding, read from `bhs`.
+pub fn data_segment_len(bhs: &[u8; BHS_LEN]) -> usize {
+    u32::from_be_bytes([0, bhs[5], bhs[6], bhs[7]]) as usize
+}
+
+/// The digest over a complete header (BHS followed by any AHS).
+pub fn header_digest(header: &[u8]) -> u32 {
+    crc32c(header)
+}
+
+/// The digest over a data segment, padded with zeros to a multiple of four bytes.
+///
+/// `data` may be given with or without its padding; the result is the same.
+pub fn data_digest(data: &[u8]) -> u32 {
+    let padding = data.len().next_multiple_of(4) - data.len();
+    let mut crc = Crc32Iscsi::new();
+    crc.update(data);
+    crc.update(&[0; 3][..padding]);
+    crc.finalize()
+}
+
+/// Verifies the enabled digests of the PDU at the start of `pdu`.
+///
+/// Returns the total length of the PDU including padding and digests, so that the caller
+/// can advance to the next one.
+pub fn verify_pdu(
+    pdu: &[u8],
+    header_digest: bool,
+    data_digest: bool,
+) -> Result<usize, DigestError> {
+    let bhs: &[u8; BHS_LEN] = pdu
+        .get(..BHS_LEN)
+        .and_then(|bhs| bhs.try_into().ok())
+        .ok_or(DigestError::Truncated)?;
+    let header_len = header_len(bhs);
+    let data_len = data_segment_len(bhs);
+    let mut pos = header_len;
+
+    if header_digest {
+        let header = pdu.get(..header_len).ok_or(DigestError::Truncated)?;
+        let expected = read_digest(pdu, pos)?;
+        let actual = self::header_digest(header);
+        trace::verified(
+            "CRC-32C",
+            "iSCSI header digest",
+            expected as u64,
+            actual as u64,
+        );
+        if expected != actual {
+            return Err(DigestError::HeaderMismatch { expected, actual });
+        }
+        pos += DIGEST_LEN;
+    }
+
+    if data_len > 0 {
+        let padded_len = data_len.next_multiple_of(4);
+        let data = pdu
+            .get(pos..pos + padded_len)
+            .ok_or(DigestError::Truncated)?;
+        pos += padded_len;
+        if data_digest {
+            let expected = read_digest(pdu, pos)?;
+            let actual = self::data_digest(data);
+            trace::verified(
+                "CRC-32C",
+                "iSCSI data digest",
+                expected as u64,
+                actual as u64,
+            );
+            if expected != actual {
+                return Err(DigestError::DataMismatch { expected, actual });
+            }
+            pos += DIGEST_LEN;
+        }
+    }
+    Ok(pos)
+}
+
+fn read_digest(pdu: &[u8], pos: usize) -> Result<u32, DigestError> {
+    let digest = pdu
+        .get(pos..pos + DIGEST_LEN)
+        .ok_or(DigestError::Truncated)?;
+    Ok(u32::from_le_bytes(digest.try_into().unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{data_digest, header_digest, verify_pdu, DigestError, BHS_LEN};
+
+    /// The SCSI read command from RFC 3720, appendix B.4.
+    const READ_PDU: [u8; BHS_LEN] = [
+        0x01, 0xC0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x14, 0x00, 0x00, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x00, 0x14, 0x00, 0x00,
+        0x00, 0x18, 0x28, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00,
+    ];
+
+    #[test]
+    fn rfc3720_vectors_test() {
+        let incrementing: Vec<u8> = (0..32).collect();
+        let decrementing: Vec<u8> = (0..32).rev().collect();
+        assert_eq!(
+            data_digest(&[0; 32]).to_le_bytes(),
+            [0xAA, 0x36, 0x91, 0x8A]
+        );
+        assert_eq!(
+            data_digest(&[0xFF; 32]).to_le_bytes(),
+            [0x43, 0xAB, 0xA8, 0x62]
+        );
+        assert_eq!(
+            data_digest(&incrementing).to_le_bytes(),
+            [0x4E, 0x79, 0xDD, 0x46]
+        );
+        assert_eq!(
+            data_digest(&decrementing).to_le_bytes(),
+            [0x5C, 0xDB, 0x3F, 0x11]
+        );
+        assert_eq!(
+            header_digest(&READ_PDU).to_le_bytes(),
+            [0x56, 0x3A, 0x96, 0xD9]
+        );
+    }
+
+    #[test]
+    fn padding_test() {
+        assert_eq!(data_digest(b"hello"), data_digest(b"hello\0\0\0"));
+        assert_ne!(data_digest(b"hello"), data_digest(b"hello\0\0\0\0"));
+    }
+
+    fn pdu(data: &[u8]) -> Vec<u8> {
+        let mut pdu = READ_PDU.to_vec();
+        pdu[5..8].copy_from_slice(&(data.len() as u32).to_be_bytes()[1..]);
+        let digest = header_digest(&pdu);
+        pdu.extend_from_slice(&digest.to_le_bytes());
+        pdu.extend_from_slice(data);
+        pdu.resize(pdu.len().next_multiple_of(4), 0);
+        pdu.extend_from_slice(&data_digest(data).to_le_bytes());
+        pdu
+    }
+
+    #[test]
+    fn verify_pdu_test() {
+        let mut pdu = pdu(b"hello world");
+        assert_eq!(verify_pdu(&pdu, true, true), Ok(pdu.len()));
+        assert_eq!(
+            verify_pdu(&pdu[..pdu.len() - 1], true, true),
+            Err(DigestError::Truncated)
+        );
+
+        pdu[BHS_LEN + 4] ^= 1;
+        assert!(matches!(
+            verify_pdu(&pdu, true, true),
+            Err(DigestError::DataMismatch { .. })
+        ));
+        pdu[0] ^= 1;
+        assert!(matches!(
+            verify_pdu(&pdu, true, true),
+            Err(DigestError::HeaderMismatch { .. })
+        ));
+    }
+}
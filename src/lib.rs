@@ -1,214 +1,1103 @@
-pub trait CRC<N>: Default
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+mod accel;
+pub mod algorithm;
+pub mod archive;
+pub mod autosar;
+pub mod backend;
+pub mod bluetooth;
+pub mod bzip2;
+pub mod can;
+pub mod catalog;
+pub mod checksum;
+pub mod cksum;
+pub mod const_crc;
+pub mod dnp3;
+pub mod e2e;
+mod engine;
+pub mod ethernet;
+pub mod fletcher;
+pub mod flexray;
+pub mod frames;
+pub mod framing;
+pub mod gf2;
+pub mod hash;
+pub mod hdlc;
+pub mod hex;
+pub mod iscsi;
+pub mod modbus;
+pub mod mpegts;
+pub mod nvme;
+pub mod oneshot;
+pub mod onewire;
+pub mod parquet;
+pub mod record;
+pub mod resync;
+pub mod rolling;
+pub mod rom;
+pub mod sctp;
+pub mod selftest;
+pub mod smbus;
+pub mod state;
+pub mod strategy;
+mod table;
+pub mod test_vectors;
+mod trace;
+pub mod usb;
+pub mod xmodem;
+pub mod zlib;
+pub mod zmodem;
+
+#[cfg(feature = "std")]
+pub mod analysis;
+#[cfg(feature = "std")]
+pub mod armor;
+#[cfg(feature = "std")]
+mod base64;
+#[cfg(feature = "std")]
+pub mod blocks;
+#[cfg(feature = "std")]
+pub mod checkpoint;
+#[cfg(feature = "std")]
+pub mod chunks;
+#[cfg(feature = "std")]
+pub mod cloud;
+#[cfg(feature = "std")]
+pub mod codegen;
+#[cfg(feature = "std")]
+pub mod collision;
+#[cfg(feature = "std")]
+pub mod compare;
+#[cfg(feature = "std")]
+pub mod dat;
+#[cfg(feature = "std")]
+pub mod decode;
+#[cfg(feature = "std")]
+pub mod dedup;
+#[cfg(feature = "std")]
+pub mod filelist;
+#[cfg(feature = "std")]
+pub mod log;
+#[cfg(feature = "std")]
+pub mod manifest;
+#[cfg(feature = "std")]
+pub mod multi;
+#[cfg(feature = "std")]
+pub mod multipart;
+#[cfg(feature = "std")]
+pub mod reader;
+#[cfg(feature = "std")]
+pub mod reveng;
+#[cfg(feature = "std")]
+pub mod scrub;
+#[cfg(feature = "std")]
+pub mod sfv;
+#[cfg(feature = "std")]
+pub mod writer;
+
+#[cfg(feature = "digest")]
+mod rustcrypto;
+
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+#[cfg(feature = "futures")]
+pub mod stream;
+
+#[cfg(feature = "gpu")]
+pub mod gpu;
+
+#[cfg(feature = "http")]
+pub mod http;
+
+#[cfg(feature = "mmap")]
+pub mod mmap;
+
+#[cfg(feature = "postcard")]
+pub mod postcard_frame;
+
+#[cfg(feature = "python")]
+pub mod python;
+
+#[cfg(feature = "serde")]
+mod serde_state;
+
+#[cfg(feature = "sparse")]
+pub mod sparse;
+
+#[cfg(feature = "tokio")]
+pub mod async_io;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use algorithm::{Crc, CrcBuilder, CrcError, Digest, MultiStreamDigest};
+pub use checksum::{Checksum, ChecksumBytes};
+pub use const_crc::{
+    Crc16Arc, Crc16DectR, Crc16DectX, Crc16Dnp, Crc16Ibm3740, Crc16IbmSdlc, Crc16Kermit,
+    Crc16Modbus, Crc16SpiFujitsu, Crc16T10Dif, Crc16Umts, Crc16Usb, Crc16Xmodem, Crc32Bzip2,
+    Crc32Cksum, Crc32Iscsi, Crc32IsoHdlc, Crc32Mpeg2, Crc64Ecma182, Crc64GoIso, Crc64Nvme, Crc64We,
+    Crc64Xz, Crc8Autosar, Crc8Bluetooth, Crc8MaximDow, Crc8SaeJ1850, Crc8Smbus,
+};
+pub use frames::ValidateCrc;
+pub use hash::{CrcBuildHasher, SeededCrcBuildHasher};
+pub use oneshot::{
+    crc16_ibm_3740, crc16_kermit, crc16_modbus, crc16_xmodem, crc32, crc32_combine, crc32c,
+    crc32c_combine, crc64_nvme, crc64_xz, crc8,
+};
+#[cfg(feature = "std")]
+pub use selftest::self_test;
+
+/// A CRC algorithm in the Rocksoft model, as listed in the RevEng catalogue.
+///
+/// `poly` is in normal (MSB-first) form and `init` is the register value before the first
+/// input bit, unreflected, e.g. CRC-16/ARC is
+/// `CrcParams { width: 16, poly: 0x8005, init: 0, refin: true, refout: true, xorout: 0 }`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CrcParams<N> {
+    /// The CRC width in bits, at most the width of `N`.
+    pub width: u32,
+    pub poly: N,
+    pub init: N,
+    /// Whether input bytes are processed least significant bit first.
+    pub refin: bool,
+    /// Whether the register is reflected before the final XOR.
+    pub refout: bool,
+    pub xorout: N,
+}
+
+macro_rules! impl_params_from_poly {
+    ($($n:ty),*) => {
+        $(
+            /// The parameters this crate has always used for a bare polynomial: an all-ones
+            /// init and final XOR, without reflection.
+            impl From<$n> for CrcParams<$n> {
+                fn from(poly: $n) -> Self {
+                    Self {
+                        width: <$n>::BITS,
+                        poly,
+                        init: <$n>::MAX,
+                        refin: false,
+                        refout: false,
+                        xorout: <$n>::MAX,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_params_from_poly!(u8, u16, u32, u64, u128);
+
+/// A CRC state of a statically known algorithm.
+///
+/// The trait is generic over the checksum type and so cannot be made into an object; use
+/// [`DynCrc`] to pick algorithms at runtime.
+pub trait CRC: Default {
+    /// The integer type of the checksum.
+    type Width: Copy;
+
+    /// Creates a state for the algorithm `params`, or for a bare polynomial with this
+    /// crate's classic parameters (see [`CrcParams::from`]).
+    fn create(params: impl Into<CrcParams<Self::Width>>) -> Self;
+
+    /// Creates a state for a catalogued algorithm, e.g. [`catalog::CRC_16_MODBUS`].
+    fn from_algorithm(algorithm: &catalog::Algorithm<Self::Width>) -> Self {
+        Self::create(algorithm.params)
+    }
+    fn update(&mut self, data: &[u8]);
+    /// The checksum of the data fed so far. The state is left untouched, so updating can
+    /// continue as if `finalize` had not been called.
+    fn finalize(&self) -> Self::Width;
+    /// Returns to the initial state, so the state can be reused for the next message.
+    fn reset(&mut self);
+
+    /// Finalizes and resets in one go.
+    fn finalize_reset(&mut self) -> Self::Width {
+        let crc = self.finalize();
+        self.reset();
+        crc
+    }
+
+    /// The running checksum, e.g. for progress reports while streaming. Same as
+    /// [`CRC::finalize`], which never consumes or alters the state either; the separate name
+    /// only makes the intent explicit at call sites.
+    fn peek(&self) -> Self::Width {
+        self.finalize()
+    }
+
+    /// An independent copy of the state. Updating either one does not affect the other,
+    /// which allows checksumming several messages sharing a prefix from one snapshot.
+    fn clone_state(&self) -> Self
+    where
+        Self: Clone,
+    {
+        self.clone()
+    }
+
+    /// Processes at most `max_bytes` bytes from the front of `data` and returns how many
+    /// bytes were consumed.
+    ///
+    /// The implementations do a fixed amount of work per byte (one table lookup, or eight
+    /// shift and conditional XOR steps), so the worst-case time of a call is proportional to
+    /// `max_bytes`. This lets interrupt handlers bound the time spent per invocation and
+    /// resume with `&data[consumed..]` on the next one.
+    fn update_bounded(&mut self, data: &[u8], max_bytes: usize) -> usize {
+        let consumed = data.len().min(max_bytes);
+        self.update(&data[..consumed]);
+        consumed
+    }
+
+    /// Feeds `data` with the bytes in `field` replaced by zeros.
+    ///
+    /// Formats whose checksum covers a header including its own, zeroed, CRC field can be
+    /// checked in place this way without copying the header to clear the field first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `field` is out of bounds of `data`.
+    fn update_zeroing(&mut self, data: &[u8], field: core::ops::Range<usize>) {
+        const ZEROS: [u8; 16] = [0; 16];
+        self.update(&data[..field.start]);
+        let mut remaining = data[field.clone()].len();
+        while remaining > 0 {
+            let n = remaining.min(ZEROS.len());
+            self.update(&ZEROS[..n]);
+            remaining -= n;
+        }
+        self.update(&data[field.end..]);
+    }
+
+    /// Feeds `data` except for the bytes in `field`, for formats whose checksum leaves out
+    /// the CRC field.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `field` is out of bounds of `data`.
+    fn update_skipping(&mut self, data: &[u8], field: core::ops::Range<usize>) {
+        let _ = &data[field.clone()];
+        self.update(&data[..field.start]);
+        self.update(&data[field.end..]);
+    }
+
+    /// Like [`CRC::update`] for anything that views as bytes, such as `&str`, `Vec<u8>` or
+    /// arrays.
+    fn update_ref(&mut self, data: impl AsRef<[u8]>) {
+        self.update(data.as_ref());
+    }
+
+    /// Feeds a single byte, e.g. a header's type or length field.
+    fn update_u8(&mut self, value: u8) {
+        self.update(&[value]);
+    }
+
+    /// Feeds `value` as two bytes, least significant byte first.
+    fn update_u16_le(&mut self, value: u16) {
+        self.update(&value.to_le_bytes());
+    }
+
+    /// Feeds `value` as two bytes, most significant byte first.
+    fn update_u16_be(&mut self, value: u16) {
+        self.update(&value.to_be_bytes());
+    }
+
+    /// Feeds `value` as four bytes, least significant byte first.
+    fn update_u32_le(&mut self, value: u32) {
+        self.update(&value.to_le_bytes());
+    }
+
+    /// Feeds `value` as four bytes, most significant byte first.
+    fn update_u32_be(&mut self, value: u32) {
+        self.update(&value.to_be_bytes());
+    }
+
+    /// Feeds `value` as eight bytes, least significant byte first.
+    fn update_u64_le(&mut self, value: u64) {
+        self.update(&value.to_le_bytes());
+    }
+
+    /// Feeds `value` as eight bytes, most significant byte first.
+    fn update_u64_be(&mut self, value: u64) {
+        self.update(&value.to_be_bytes());
+    }
+
+    /// Feeds every word of `words` in `endian` byte order, e.g. a DMA buffer of ADC samples,
+    /// without copying it into bytes first.
+    fn update_u16_slice(&mut self, words: &[u16], endian: framing::Endian) {
+        self.update_iter(words.iter().flat_map(|word| match endian {
+            framing::Endian::Big => word.to_be_bytes(),
+            framing::Endian::Little => word.to_le_bytes(),
+        }));
+    }
+
+    /// Like [`CRC::update_u16_slice`] for 32-bit words.
+    fn update_u32_slice(&mut self, words: &[u32], endian: framing::Endian) {
+        self.update_iter(words.iter().flat_map(|word| match endian {
+            framing::Endian::Big => word.to_be_bytes(),
+            framing::Endian::Little => word.to_le_bytes(),
+        }));
+    }
+
+    /// Feeds every word of `words` as with [`CRC::update_u16_le`].
+    fn update_u16_slice_le(&mut self, words: &[u16]) {
+        self.update_u16_slice(words, framing::Endian::Little);
+    }
+
+    /// Feeds every word of `words` as with [`CRC::update_u16_be`].
+    fn update_u16_slice_be(&mut self, words: &[u16]) {
+        self.update_u16_slice(words, framing::Endian::Big);
+    }
+
+    /// Feeds every word of `words` as with [`CRC::update_u32_le`].
+    fn update_u32_slice_le(&mut self, words: &[u32]) {
+        self.update_u32_slice(words, framing::Endian::Little);
+    }
+
+    /// Feeds every word of `words` as with [`CRC::update_u32_be`].
+    fn update_u32_slice_be(&mut self, words: &[u32]) {
+        self.update_u32_slice(words, framing::Endian::Big);
+    }
+
+    /// Feeds each buffer of `bufs` in turn, as if they were one contiguous message.
+    fn update_vectored(&mut self, bufs: &[&[u8]]) {
+        for buf in bufs {
+            self.update(buf);
+        }
+    }
+
+    /// Like [`CRC::update_vectored`] for any sequence of segments, such as the chunks of a
+    /// rope or `io_slices.iter().map(|slice| &**slice)`.
+    fn update_segments<'a>(&mut self, segments: impl IntoIterator<Item = &'a [u8]>) {
+        for segment in segments {
+            self.update(segment);
+        }
+    }
+
+    /// Feeds bytes produced lazily, e.g. by a decoder, without collecting them first. They
+    /// are gathered into small blocks so the table or hardware path still sees slices.
+    fn update_iter(&mut self, bytes: impl IntoIterator<Item = u8>) {
+        let mut block = [0; 64];
+        let mut len = 0;
+        for byte in bytes {
+            block[len] = byte;
+            len += 1;
+            if len == block.len() {
+                self.update(&block);
+                len = 0;
+            }
+        }
+        self.update(&block[..len]);
+    }
+
+    /// Feeds the in-memory representation of a plain-old-data value, such as a `#[repr(C)]`
+    /// telemetry record deriving `bytemuck::Pod`. The byte order of its fields is the
+    /// target's native one.
+    #[cfg(feature = "bytemuck")]
+    fn update_pod<T: bytemuck::NoUninit>(&mut self, value: &T) {
+        self.update(bytemuck::bytes_of(value));
+    }
+
+    /// Feeds the in-memory representation of a slice of plain-old-data values.
+    #[cfg(feature = "bytemuck")]
+    fn update_pod_slice<T: bytemuck::NoUninit>(&mut self, values: &[T]) {
+        self.update(bytemuck::cast_slice(values));
+    }
+}
+
+/// Object-safe counterpart of [`CRC`] with the checksum widened to `u128`, e.g. for
+/// `Box<dyn DynCrc>` chosen from a configuration file.
+///
+/// Every [`CRC`] state with a checksum of at most 128 bits implements it, as does
+/// [`Digest`]. The methods carry a `dyn_` prefix so that both traits can be in scope
+/// without making every `update` call ambiguous.
+pub trait DynCrc {
+    fn dyn_update(&mut self, data: &[u8]);
+    fn dyn_finalize(&self) -> u128;
+    fn dyn_reset(&mut self);
+}
+
+impl<C> DynCrc for C
 where
-    N: std::ops::Shl + std::ops::ShlAssign + std::ops::BitXor + std::ops::BitXorAssign,
+    C: CRC,
+    C::Width: Into<u128>,
 {
-    fn create(polynomial: N) -> Self;
-    fn update(&mut self, data: &[u8]);
-    fn finalize(&self) -> N;
+    fn dyn_update(&mut self, data: &[u8]) {
+        CRC::update(self, data)
+    }
+
+    fn dyn_finalize(&self) -> u128 {
+        CRC::finalize(self).into()
+    }
+
+    fn dyn_reset(&mut self) {
+        CRC::reset(self)
+    }
+}
+
+/// Creates a state for `params` with its lookup table, or without one for the bitwise
+/// `CRC128` loop on limbs.
+///
+/// The register uses the layout described in [`table`], which the bitwise loop shares.
+macro_rules! create {
+    ($params:expr) => {{
+        let params = table::checked($params.into());
+        Self {
+            crc: table::start(&params),
+            table: table::build(&params),
+            params,
+        }
+    }};
+    ($params:expr, bitwise) => {{
+        let params = table::checked($params.into());
+        Self {
+            crc: table::start(&params),
+            params,
+        }
+    }};
 }
 
+#[derive(Clone)]
 pub struct CRC8 {
     crc: u8,
-    polynomial: u8,
+    params: CrcParams<u8>,
+    table: [u8; 256],
 }
 
-impl CRC<u8> for CRC8 {
+impl CRC for CRC8 {
+    type Width = u8;
+
     fn update(&mut self, data: &[u8]) {
-        for ibyte in data {
-            self.crc ^= *ibyte;
-            for _bit in 0..8u8 {
-                if self.crc & 0x80 != 0 {
-                    // MSB is set so shift + XOR polynomial
-                    self.crc = (self.crc << 1) ^ self.polynomial;
-                } else {
-                    // MSB is not set so just shift
-                    self.crc <<= 1;
-                }
-            }
-        }
+        self.crc = table::update(&self.table, self.params.refin, self.crc, data);
     }
 
     fn finalize(&self) -> u8 {
-        self.crc ^ u8::MAX
+        table::finalize(&self.params, self.crc)
     }
 
-    fn create(polynomial: u8) -> Self {
-        Self {
-            crc: u8::MAX,
-            polynomial,
-        }
+    fn create(params: impl Into<CrcParams<u8>>) -> Self {
+        create!(params)
+    }
+
+    fn reset(&mut self) {
+        self.crc = table::start(&self.params);
     }
 }
 
-impl std::default::Default for CRC8 {
+impl core::default::Default for CRC8 {
     fn default() -> Self {
         // TODO: check default polynomial
         Self::create(0b00000111)
     }
 }
 
+#[derive(Clone)]
 pub struct CRC16 {
     crc: u16,
-    polynomial: u16,
+    params: CrcParams<u16>,
+    table: [u16; 256],
 }
 
-impl CRC<u16> for CRC16 {
+impl CRC for CRC16 {
+    type Width = u16;
+
     fn update(&mut self, data: &[u8]) {
-        for ibyte in data {
-            self.crc ^= (*ibyte as u16) << 8;
-            for _bit in 0..8u8 {
-                if self.crc & 0x8000 != 0 {
-                    // MSB is set so shift + XOR polynomial
-                    self.crc = (self.crc << 1) ^ self.polynomial;
-                } else {
-                    // MSB is not set so just shift
-                    self.crc <<= 1;
-                }
-            }
-        }
+        self.crc = table::update(&self.table, self.params.refin, self.crc, data);
     }
 
     fn finalize(&self) -> u16 {
-        self.crc ^ u16::MAX
+        table::finalize(&self.params, self.crc)
     }
 
-    fn create(polynomial: u16) -> Self {
-        Self {
-            crc: u16::MAX,
-            polynomial,
-        }
+    fn create(params: impl Into<CrcParams<u16>>) -> Self {
+        create!(params)
+    }
+
+    fn reset(&mut self) {
+        self.crc = table::start(&self.params);
     }
 }
 
-impl std::default::Default for CRC16 {
+/// The `0x8005` polynomial MSB-first with init and xorout `0xFFFF`, kept for compatibility.
+/// It is none of the CCITT variants; see [`catalog::CRC_16_IBM_3740`] for those.
+impl core::default::Default for CRC16 {
     fn default() -> Self {
-        // TODO: check default polynomial
         Self::create(0b1000_0000_0000_0101)
     }
 }
 
-struct CRC32 {
+#[derive(Clone)]
+pub struct CRC32 {
     crc: u32,
-    polynomial: u32,
+    params: CrcParams<u32>,
+    table: [u32; 256],
 }
 
-impl CRC<u32> for CRC32 {
+impl CRC for CRC32 {
+    type Width = u32;
+
     fn update(&mut self, data: &[u8]) {
-        for ibyte in data {
-            self.crc ^= (*ibyte as u32) << 24;
-            for _bit in 0..8u8 {
-                if self.crc & 0x8000_0000 != 0 {
-                    // MSB is set so shift + XOR polynomial
-                    self.crc = (self.crc << 1) ^ self.polynomial;
-                } else {
-                    // MSB is not set so just shift
-                    self.crc <<= 1;
-                }
-            }
-        }
+        let refin = self.params.refin;
+        self.crc = accel::update(&self.params, None, self.crc, data, |crc, data| {
+            table::update(&self.table, refin, crc, data)
+        });
     }
 
     fn finalize(&self) -> u32 {
-        self.crc ^ u32::MAX
+        table::finalize(&self.params, self.crc)
     }
 
-    fn create(polynomial: u32) -> Self {
-        Self {
-            crc: u32::MAX,
-            polynomial,
-        }
+    fn create(params: impl Into<CrcParams<u32>>) -> Self {
+        create!(params)
+    }
+
+    fn reset(&mut self) {
+        self.crc = table::start(&self.params);
     }
 }
 
-impl std::default::Default for CRC32 {
+impl core::default::Default for CRC32 {
     fn default() -> Self {
         // TODO: check default polynomial
         Self::create(0b0000_0100_1100_0001_0001_1101_1011_0111)
     }
 }
 
-struct CRC64 {
+#[derive(Clone)]
+pub struct CRC64 {
     crc: u64,
-    polynomial: u64,
+    params: CrcParams<u64>,
+    table: [u64; 256],
 }
 
-impl CRC<u64> for CRC64 {
+impl CRC for CRC64 {
+    type Width = u64;
+
     fn update(&mut self, data: &[u8]) {
-        for ibyte in data {
-            self.crc ^= (*ibyte as u64) << 56;
-            for _bit in 0..8u8 {
-                if self.crc & 0x8000_0000_0000_0000 != 0 {
-                    // MSB is set so shift + XOR polynomial
-                    self.crc = (self.crc << 1) ^ self.polynomial;
-                } else {
-                    // MSB is not set so just shift
-                    self.crc <<= 1;
-                }
-            }
-        }
+        self.crc = table::update(&self.table, self.params.refin, self.crc, data);
     }
 
     fn finalize(&self) -> u64 {
-        self.crc ^ u64::MAX
+        table::finalize(&self.params, self.crc)
     }
 
-    fn create(polynomial: u64) -> Self {
-        Self {
-            crc: u64::MAX,
-            polynomial,
-        }
+    fn create(params: impl Into<CrcParams<u64>>) -> Self {
+        create!(params)
+    }
+
+    fn reset(&mut self) {
+        self.crc = table::start(&self.params);
     }
 }
 
-impl std::default::Default for CRC64 {
+/// CRC-64/WE, kept for compatibility. It matches neither xz nor NVMe; use
+/// `CRC64::from_algorithm(&catalog::CRC_64_XZ)` or [`catalog::CRC_64_NVME`] for those.
+impl core::default::Default for CRC64 {
     fn default() -> Self {
-        // TODO: check default polynomial
         Self::create(
             0b0100_0010_1111_0000_1110_0001_1110_1011_1010_1001_1110_1010_0011_0110_1001_0011,
         )
     }
 }
 
-struct CRC128 {
+#[derive(Clone)]
+pub struct CRC128 {
     crc: u128,
-    polynomial: u128,
+    params: CrcParams<u128>,
+    #[cfg(not(feature = "crc128-u64-limbs"))]
+    table: [u128; 256],
 }
 
-impl CRC<u128> for CRC128 {
-    fn update(&mut self, data: &[u8]) {
-        for ibyte in data {
-            self.crc ^= (*ibyte as u128) << 120;
-            for _bit in 0..8u8 {
-                if self.crc & 0x8000_0000_0000_0000_0000_0000_0000_0000 != 0 {
-                    // MSB is set so shift + XOR polynomial
-                    self.crc = (self.crc << 1) ^ self.polynomial;
-                } else {
-                    // MSB is not set so just shift
-                    self.crc <<= 1;
-                }
+/// The plain bitwise loop, which the limbs variant is tested against.
+#[cfg(test)]
+fn crc128_update(mut crc: u128, polynomial: u128, data: &[u8]) -> u128 {
+    for ibyte in data {
+        crc ^= (*ibyte as u128) << 120;
+        for _bit in 0..8u8 {
+            if crc & 0x8000_0000_0000_0000_0000_0000_0000_0000 != 0 {
+                // MSB is set so shift + XOR polynomial
+                crc = (crc << 1) ^ polynomial;
+            } else {
+                // MSB is not set so just shift
+                crc <<= 1;
             }
         }
     }
+    crc
+}
 
-    fn finalize(&self) -> u128 {
-        self.crc ^ u128::MAX
+/// Same as `crc128_update`, but with the register split into two `u64` limbs. On 32-bit
+/// targets this avoids the much slower `u128` emulation in the inner loop.
+#[cfg(any(feature = "crc128-u64-limbs", test))]
+fn crc128_update_limbs(crc: u128, polynomial: u128, data: &[u8]) -> u128 {
+    let (mut hi, mut lo) = ((crc >> 64) as u64, crc as u64);
+    let (poly_hi, poly_lo) = ((polynomial >> 64) as u64, polynomial as u64);
+    for ibyte in data {
+        hi ^= (*ibyte as u64) << 56;
+        for _bit in 0..8u8 {
+            let msb = hi & 0x8000_0000_0000_0000 != 0;
+            // Shift both limbs, carrying the top bit of the low limb into the high limb
+            hi = (hi << 1) | (lo >> 63);
+            lo <<= 1;
+            if msb {
+                hi ^= poly_hi;
+                lo ^= poly_lo;
+            }
+        }
     }
+    (hi as u128) << 64 | lo as u128
+}
 
-    fn create(polynomial: u128) -> Self {
-        Self {
-            crc: u128::MAX,
-            polynomial,
+/// The plain bitwise loop for reflected algorithms, with the register and the reflected
+/// polynomial in the low bits.
+#[cfg(test)]
+fn crc128_update_reflected(mut crc: u128, polynomial: u128, data: &[u8]) -> u128 {
+    for ibyte in data {
+        crc ^= *ibyte as u128;
+        for _bit in 0..8u8 {
+            let lsb = crc & 1 != 0;
+            crc >>= 1;
+            if lsb {
+                crc ^= polynomial;
+            }
         }
     }
+    crc
 }
 
-impl std::default::Default for CRC128 {
+/// Same as `crc128_update_reflected`, but on two `u64` limbs like `crc128_update_limbs`.
+#[cfg(any(feature = "crc128-u64-limbs", test))]
+fn crc128_update_reflected_limbs(crc: u128, polynomial: u128, data: &[u8]) -> u128 {
+    let (mut hi, mut lo) = ((crc >> 64) as u64, crc as u64);
+    let (poly_hi, poly_lo) = ((polynomial >> 64) as u64, polynomial as u64);
+    for ibyte in data {
+        lo ^= *ibyte as u64;
+        for _bit in 0..8u8 {
+            let lsb = lo & 1 != 0;
+            // Shift both limbs, carrying the low bit of the high limb into the low limb
+            lo = (lo >> 1) | (hi << 63);
+            hi >>= 1;
+            if lsb {
+                hi ^= poly_hi;
+                lo ^= poly_lo;
+            }
+        }
+    }
+    (hi as u128) << 64 | lo as u128
+}
+
+impl CRC for CRC128 {
+    type Width = u128;
+
+    #[cfg(not(feature = "crc128-u64-limbs"))]
+    fn update(&mut self, data: &[u8]) {
+        self.crc = table::update(&self.table, self.params.refin, self.crc, data);
+    }
+
+    #[cfg(feature = "crc128-u64-limbs")]
+    fn update(&mut self, data: &[u8]) {
+        if self.params.refin {
+            let poly = self.params.poly.reverse_bits() >> (u128::BITS - self.params.width);
+            self.crc = crc128_update_reflected_limbs(self.crc, poly, data);
+        } else {
+            let poly = self.params.poly << (u128::BITS - self.params.width);
+            self.crc = crc128_update_limbs(self.crc, poly, data);
+        }
+    }
+
+    fn finalize(&self) -> u128 {
+        table::finalize(&self.params, self.crc)
+    }
+
+    #[cfg(not(feature = "crc128-u64-limbs"))]
+    fn create(params: impl Into<CrcParams<u128>>) -> Self {
+        create!(params)
+    }
+
+    #[cfg(feature = "crc128-u64-limbs")]
+    fn create(params: impl Into<CrcParams<u128>>) -> Self {
+        create!(params, bitwise)
+    }
+
+    fn reset(&mut self) {
+        self.crc = table::start(&self.params);
+    }
+}
+
+impl core::default::Default for CRC128 {
     fn default() -> Self {
         // TODO: check default polynomial
         Self::create(0b1110_0011_1100_0011_1101_0101_1010_0111_1110_1001_1111_0111_1101_0100_1110_0001_1111_0011_1111_0000_1111_1011_1010_1011_0110_0101_1100_0111_1000_1001_0001)
     }
 }
 
+/// Implements `Debug` (parameters and running checksum in hex) and `PartialEq`/`Eq` for a CRC
+/// state. The lookup table follows from the parameters, so it is neither shown nor compared.
+macro_rules! impl_std_traits {
+    ($($crc:ident),*) => {
+        $(
+            impl core::fmt::Debug for $crc {
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    f.debug_struct(stringify!($crc))
+                        .field("params", &checksum::HexParams(&self.params))
+                        .field("checksum", &checksum::Hex::new(self.finalize(), self.params.width))
+                        .finish_non_exhaustive()
+                }
+            }
+
+            impl PartialEq for $crc {
+                fn eq(&self, other: &Self) -> bool {
+                    self.params == other.params && self.crc == other.crc
+                }
+            }
+
+            impl Eq for $crc {}
+        )*
+    };
+}
+
+impl_std_traits!(CRC8, CRC16, CRC32, CRC64, CRC128);
+
+// Every state, digest and parameter type is plain data and can be shared across threads.
+const _: fn() = || {
+    fn send_sync<T: Send + Sync>() {}
+    send_sync::<CrcParams<u128>>();
+    send_sync::<catalog::Algorithm<u64>>();
+    send_sync::<CRC8>();
+    send_sync::<CRC16>();
+    send_sync::<CRC32>();
+    send_sync::<CRC64>();
+    send_sync::<CRC128>();
+    send_sync::<Crc<u32, strategy::Sliced<16>>>();
+    send_sync::<Digest<'static, u64, strategy::NoTable>>();
+};
+
+/// Formats a CRC state for defmt as its register and polynomial in hex.
+#[cfg(feature = "defmt")]
+macro_rules! impl_defmt_format {
+    ($crc:ty, $fmt:literal) => {
+        impl defmt::Format for $crc {
+            fn format(&self, f: defmt::Formatter) {
+                defmt::write!(f, $fmt, self.crc, self.params.poly);
+            }
+        }
+    };
+}
+
+#[cfg(feature = "defmt")]
+impl_defmt_format!(CRC8, "CRC8 {{ crc: {=u8:#x}, polynomial: {=u8:#x} }}");
+#[cfg(feature = "defmt")]
+impl_defmt_format!(CRC16, "CRC16 {{ crc: {=u16:#x}, polynomial: {=u16:#x} }}");
+#[cfg(feature = "defmt")]
+impl_defmt_format!(CRC32, "CRC32 {{ crc: {=u32:#x}, polynomial: {=u32:#x} }}");
+#[cfg(feature = "defmt")]
+impl_defmt_format!(CRC64, "CRC64 {{ crc: {=u64:#x}, polynomial: {=u64:#x} }}");
+#[cfg(feature = "defmt")]
+impl_defmt_format!(
+    CRC128,
+    "CRC128 {{ crc: {=u128:#x}, polynomial: {=u128:#x} }}"
+);
+
+/// Writes `value` as exactly `digits` uppercase hex digits.
+#[cfg(feature = "ufmt")]
+fn write_hex<W: ufmt::uWrite + ?Sized>(
+    f: &mut ufmt::Formatter<'_, W>,
+    value: u128,
+    digits: usize,
+) -> Result<(), W::Error> {
+    let mut buf = [0u8; 32];
+    for (i, digit) in buf[..digits].iter_mut().rev().enumerate() {
+        *digit = b"0123456789ABCDEF"[(value >> (4 * i)) as usize & 0xF];
+    }
+    f.write_str(core::str::from_utf8(&buf[..digits]).unwrap())
+}
+
+/// Implements `uDisplay` (the finalized checksum as zero-padded hex) and `uDebug` (register
+/// and polynomial) for a CRC state.
+#[cfg(feature = "ufmt")]
+macro_rules! impl_ufmt {
+    ($crc:ident, $n:ty) => {
+        impl ufmt::uDisplay for $crc {
+            fn fmt<W: ufmt::uWrite + ?Sized>(
+                &self,
+                f: &mut ufmt::Formatter<'_, W>,
+            ) -> Result<(), W::Error> {
+                write_hex(f, self.finalize() as u128, <$n>::BITS as usize / 4)
+            }
+        }
+
+        impl ufmt::uDebug for $crc {
+            fn fmt<W: ufmt::uWrite + ?Sized>(
+                &self,
+                f: &mut ufmt::Formatter<'_, W>,
+            ) -> Result<(), W::Error> {
+                f.write_str(concat!(stringify!($crc), " { crc: 0x"))?;
+                write_hex(f, self.crc as u128, <$n>::BITS as usize / 4)?;
+                f.write_str(", polynomial: 0x")?;
+                write_hex(f, self.params.poly as u128, <$n>::BITS as usize / 4)?;
+                f.write_str(" }")
+            }
+        }
+    };
+}
+
+#[cfg(feature = "ufmt")]
+impl_ufmt!(CRC8, u8);
+#[cfg(feature = "ufmt")]
+impl_ufmt!(CRC16, u16);
+#[cfg(feature = "ufmt")]
+impl_ufmt!(CRC32, u32);
+#[cfg(feature = "ufmt")]
+impl_ufmt!(CRC64, u64);
+#[cfg(feature = "ufmt")]
+impl_ufmt!(CRC128, u128);
+
+/// Implements `Extend<u8>`, `Extend<&u8>` and `FromIterator<u8>` for a CRC state so that
+/// iterator pipelines can feed it directly, e.g. `bytes.into_iter().collect::<CRC32>()`.
+macro_rules! impl_iter_traits {
+    ($crc:ty) => {
+        impl Extend<u8> for $crc {
+            fn extend<I: IntoIterator<Item = u8>>(&mut self, iter: I) {
+                self.update_iter(iter);
+            }
+        }
+
+        impl<'a> Extend<&'a u8> for $crc {
+            fn extend<I: IntoIterator<Item = &'a u8>>(&mut self, iter: I) {
+                self.extend(iter.into_iter().copied());
+            }
+        }
+
+        impl FromIterator<u8> for $crc {
+            fn from_iter<I: IntoIterator<Item = u8>>(iter: I) -> Self {
+                let mut crc = Self::default();
+                crc.extend(iter);
+                crc
+            }
+        }
+
+        impl<'a> FromIterator<&'a u8> for $crc {
+            fn from_iter<I: IntoIterator<Item = &'a u8>>(iter: I) -> Self {
+                iter.into_iter().copied().collect()
+            }
+        }
+    };
+}
+
+impl_iter_traits!(CRC8);
+impl_iter_traits!(CRC16);
+impl_iter_traits!(CRC32);
+impl_iter_traits!(CRC64);
+impl_iter_traits!(CRC128);
+
+/// Implements `Hasher` for a CRC state: `write` is [`CRC::update`] and `finish` the checksum,
+/// truncated to its low 64 bits for CRC128. `Hash::hash(&value, &mut crc)` then checksums
+/// anything that derives `Hash`, though the bytes `Hash` writes for a type are only stable
+/// within one compiler version.
+macro_rules! impl_hasher {
+    ($crc:ty) => {
+        impl core::hash::Hasher for $crc {
+            fn write(&mut self, bytes: &[u8]) {
+                self.update(bytes);
+            }
+
+            fn finish(&self) -> u64 {
+                self.finalize() as u64
+            }
+        }
+    };
+}
+
+impl_hasher!(CRC8);
+impl_hasher!(CRC16);
+impl_hasher!(CRC32);
+impl_hasher!(CRC64);
+impl_hasher!(CRC128);
+
+/// Implements `io::Write` for a CRC state, so `io::copy(&mut file, &mut crc)` checksums a
+/// file. Writes never fail and always take the whole buffer.
+#[cfg(feature = "std")]
+macro_rules! impl_write {
+    ($crc:ty) => {
+        impl std::io::Write for $crc {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.update(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+    };
+}
+
+#[cfg(feature = "std")]
+impl_write!(CRC8);
+#[cfg(feature = "std")]
+impl_write!(CRC16);
+#[cfg(feature = "std")]
+impl_write!(CRC32);
+#[cfg(feature = "std")]
+impl_write!(CRC64);
+#[cfg(feature = "std")]
+impl_write!(CRC128);
+
+/// Adds `checksum_batch` to a CRC state.
+///
+/// Messages are processed eight at a time, with the eight registers going through the lookup
+/// table in lockstep over the group's common prefix, so the CPU overlaps the lookups that a
+/// single message has to wait for one by one. What is left of each message once the shortest
+/// one in its group ends is finished one message at a time. Algorithms the CPU has a CRC
+/// instruction for skip the interleaving, since the instruction outruns the table anyway.
+/// States without a table, CRC128 on limbs, update each message in turn.
+#[cfg(feature = "std")]
+macro_rules! impl_checksum_batch {
+    ($crc:ty, $n:ty) => {
+        impl $crc {
+            /// Computes the checksum of each of `messages` with this state's algorithm,
+            /// starting every message from a fresh register.
+            ///
+            /// Equivalent to creating, updating and finalizing one state per message, but
+            /// faster for large numbers of short messages of similar length.
+            pub fn checksum_batch(&self, messages: &[&[u8]]) -> Vec<$n> {
+                const LANES: usize = 8;
+                let refin = self.params.refin;
+                let software = |crc, data: &[u8]| table::update(&self.table, refin, crc, data);
+                let interleave = !accel::available(&self.params, None);
+                let mut crcs = Vec::with_capacity(messages.len());
+                for group in messages.chunks(LANES) {
+                    let mut lanes = [table::start(&self.params); LANES];
+                    let mut common = 0;
+                    if interleave {
+                        common = group.iter().map(|message| message.len()).min().unwrap_or(0);
+                        // A short last group fills its spare lanes with its first message.
+                        let mut data = [group[0]; LANES];
+                        data[..group.len()].copy_from_slice(group);
+                        table::update_interleaved(&self.table, refin, &mut lanes, data, common);
+                    }
+                    for (&lane, message) in lanes.iter().zip(group) {
+                        let crc =
+                            accel::update(&self.params, None, lane, &message[common..], software);
+                        crcs.push(table::finalize(&self.params, crc));
+                    }
+                }
+                crcs
+            }
+        }
+    };
+    ($crc:ty, $n:ty, bitwise) => {
+        impl $crc {
+            /// Computes the checksum of each of `messages` with this state's algorithm,
+            /// starting every message from a fresh register.
+            pub fn checksum_batch(&self, messages: &[&[u8]]) -> Vec<$n> {
+                messages
+                    .iter()
+                    .map(|message| {
+                        let mut crc = self.clone();
+                        crc.reset();
+                        crc.update(message);
+                        crc.finalize()
+                    })
+                    .collect()
+            }
+        }
+    };
+}
+
+#[cfg(feature = "std")]
+impl_checksum_batch!(CRC8, u8);
+#[cfg(feature = "std")]
+impl_checksum_batch!(CRC16, u16);
+#[cfg(feature = "std")]
+impl_checksum_batch!(CRC32, u32);
+#[cfg(feature = "std")]
+impl_checksum_batch!(CRC64, u64);
+#[cfg(all(feature = "std", not(feature = "crc128-u64-limbs")))]
+impl_checksum_batch!(CRC128, u128);
+#[cfg(all(feature = "std", feature = "crc128-u64-limbs"))]
+impl_checksum_batch!(CRC128, u128, bitwise);
+
+macro_rules! impl_model {
+    ($crc:ty, $n:ty) => {
+        impl engine::Model<$n> for $crc {
+            fn params(&self) -> CrcParams<$n> {
+                self.params
+            }
+        }
+    };
+}
+
+impl_model!(CRC8, u8);
+impl_model!(CRC16, u16);
+impl_model!(CRC32, u32);
+impl_model!(CRC64, u64);
+impl_model!(CRC128, u128);
+
+/// Implements `unwind` and `from_register`, which run the register backwards over known data
+/// and resume from the result.
+///
+/// Stepping back works because the polynomial's constant term is set: after a shift in which
+/// the polynomial was XORed in, bit 0 of the register is always one, and otherwise zero.
+macro_rules! impl_unwind {
+    ($crc:ty, $n:ty) => {
+        impl $crc {
+            /// Recovers the register value this state's algorithm must have held before
+            /// `trailing` was processed for the final checksum to come out as `checksum`.
+            ///
+            /// With a whole message as `trailing`, this is the initial value the device that
+            /// produced `checksum` started from, which need not be the `init` of this
+            /// state's parameters. The polynomial must be odd, as every CRC polynomial in
+            /// use is.
+            pub fn unwind(&self, checksum: $n, trailing: &[u8]) -> $n {
+                let width = self.params.width;
+                let crc = checksum ^ self.params.xorout;
+                let mut crc = if self.params.refout {
+                    crc.reverse_bits() >> (<$n>::BITS - width)
+                } else {
+                    crc
+                };
+                for &byte in trailing.iter().rev() {
+                    let byte = if self.params.refin {
+                        byte.reverse_bits()
+                    } else {
+                        byte
+                    };
+                    // Bits went in most significant first, so the last one is bit 0.
+                    for bit in 0..8 {
+                        let xored = (crc & 1).wrapping_neg();
+                        let top = (crc & 1) ^ ((byte >> bit) & 1) as $n;
+                        crc = ((crc ^ (self.params.poly & xored)) >> 1) | (top << (width - 1));
+                    }
+                }
+                crc
+            }
+
+            /// Creates a state that continues from `register`, e.g. an initial value recovered
+            /// with [`Self::unwind`].
+            pub fn from_register(params: impl Into<CrcParams<$n>>, register: $n) -> Self {
+                let state = Self::create(params);
+                let shift = <$n>::BITS - state.params.width;
+                Self {
+                    crc: if state.params.refin {
+                        register.reverse_bits() >> shift
+                    } else {
+                        register << shift
+                    },
+                    ..state
+                }
+            }
+        }
+    };
+}
+
+impl_unwind!(CRC8, u8);
+impl_unwind!(CRC16, u16);
+impl_unwind!(CRC32, u32);
+impl_unwind!(CRC64, u64);
+impl_unwind!(CRC128, u128);
+
 #[cfg(test)]
 mod tests {
     use super::{CRC, CRC128, CRC16, CRC32, CRC64, CRC8};
@@ -246,6 +1135,12 @@ mod tests {
         crc.update(TEST_DATA);
         let crc = crc.finalize();
         assert!(crc == 0xC287020321943B9D, "{:#X}", crc);
+
+        let mut crc = CRC64::default();
+        crc.update(b"123456789");
+        assert_eq!(crc.finalize(), crate::catalog::CRC_64_WE.check);
+        assert_ne!(crc.finalize(), crate::catalog::CRC_64_XZ.check);
+        assert_ne!(crc.finalize(), crate::catalog::CRC_64_NVME.check);
     }
 
     #[test]
@@ -256,6 +1151,45 @@ mod tests {
         assert!(crc == 0x1B004A91C7EF19134E779C0AC320AD8C, "{:#X}", crc);
     }
 
+    #[test]
+    fn crc128_limbs_test() {
+        let data: Vec<u8> = (0..=255u8).map(|i| i.wrapping_mul(167) ^ 0x5A).collect();
+        let polynomial = CRC128::default().params.poly;
+        for start in [0, u128::MAX, 0x0123_4567_89AB_CDEF_FEDC_BA98_7654_3210] {
+            for len in [0, 1, 7, 8, 9, 64, 256] {
+                assert_eq!(
+                    super::crc128_update(start, polynomial, &data[..len]),
+                    super::crc128_update_limbs(start, polynomial, &data[..len]),
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn crc128_reflected_limbs_test() {
+        let data: Vec<u8> = (0..=255u8).map(|i| i.wrapping_mul(167) ^ 0x5A).collect();
+        let params = super::CrcParams {
+            refin: true,
+            refout: true,
+            init: u128::MAX,
+            xorout: u128::MAX,
+            ..CRC128::default().params
+        };
+        let polynomial = params.poly.reverse_bits();
+        let table = CRC128::create(params);
+        for len in [0, 1, 7, 8, 9, 64, 256] {
+            let start = super::table::start(&params);
+            let limbs = super::crc128_update_reflected_limbs(start, polynomial, &data[..len]);
+            assert_eq!(
+                limbs,
+                super::crc128_update_reflected(start, polynomial, &data[..len])
+            );
+            let mut expected = table.clone();
+            expected.update(&data[..len]);
+            assert_eq!(super::table::finalize(&params, limbs), expected.finalize());
+        }
+    }
+
     #[test]
     fn sanity_test() {
         const CCRC: crcl::Algorithm<u16> = crcl::Algorithm {
@@ -277,4 +1211,469 @@ mod tests {
         let b = crc.finalize();
         assert_eq!(a, b, "LIB: {:#X} != CRATE: {:#X}", a, b);
     }
+
+    #[test]
+    fn hasher_test() {
+        use core::hash::{Hash, Hasher};
+
+        #[derive(Hash)]
+        struct Header {
+            id: u16,
+            name: &'static str,
+        }
+
+        let mut crc = CRC32::default();
+        crc.write(TEST_DATA);
+        assert_eq!(crc.finish(), 0x44F71378);
+
+        let mut derived = CRC64::default();
+        Header { id: 7, name: "x" }.hash(&mut derived);
+        let mut manual = CRC64::default();
+        manual.write_u16(7);
+        "x".hash(&mut manual);
+        assert_eq!(derived.finish(), manual.finalize());
+
+        let crc = crate::Crc::new(&crate::catalog::CRC_32_ISO_HDLC);
+        let mut digest = crc.digest();
+        digest.write(b"123456789");
+        assert_eq!(digest.finish(), 0xCBF43926);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn write_test() {
+        let mut crc = CRC32::default();
+        let copied = std::io::copy(&mut &TEST_DATA[..], &mut crc).unwrap();
+        assert_eq!(copied, TEST_DATA.len() as u64);
+        assert_eq!(crc.finalize(), 0x44F71378);
+
+        let crc = crate::Crc::new(&crate::catalog::CRC_32_ISO_HDLC);
+        let mut digest = crc.digest();
+        std::io::Write::write_all(&mut digest, b"123456789").unwrap();
+        assert_eq!(digest.finalize(), 0xCBF43926);
+    }
+
+    #[test]
+    fn update_bounded_test() {
+        let mut crc = CRC32::default();
+        let mut remaining = TEST_DATA;
+        while !remaining.is_empty() {
+            let consumed = crc.update_bounded(remaining, 4);
+            assert!(consumed <= 4);
+            remaining = &remaining[consumed..];
+        }
+        assert_eq!(crc.update_bounded(remaining, 4), 0);
+        let crc = crc.finalize();
+        assert!(crc == 0x44F71378, "{:#X}", crc);
+    }
+
+    #[test]
+    fn update_words_test() {
+        let bytes = [0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE, 0xF0];
+        let mut expected = CRC32::default();
+        expected.update(&bytes);
+        let expected = expected.finalize();
+
+        let mut crc = CRC32::default();
+        crc.update_u16_be(0x1234);
+        crc.update_u16_le(0x7856);
+        crc.update_u32_be(0x9ABC_DEF0);
+        assert_eq!(crc.finalize(), expected);
+
+        let mut crc = CRC32::default();
+        crc.update_u32_slice_le(&[0x7856_3412, 0xF0DE_BC9A]);
+        assert_eq!(crc.finalize(), expected);
+
+        let mut crc = CRC32::default();
+        crc.update_u16_slice_be(&[0x1234, 0x5678, 0x9ABC, 0xDEF0]);
+        assert_eq!(crc.finalize(), expected);
+
+        let mut crc = CRC32::default();
+        crc.update_u16_slice_le(&[0x3412, 0x7856]);
+        crc.update_u32_slice_be(&[0x9ABC_DEF0]);
+        assert_eq!(crc.finalize(), expected);
+
+        let mut crc = CRC32::default();
+        crc.update_u16_slice(&[0x3412, 0x7856, 0xBC9A], crate::framing::Endian::Little);
+        crc.update_u16_slice(&[0xDEF0], crate::framing::Endian::Big);
+        assert_eq!(crc.finalize(), expected);
+        let mut crc = CRC32::default();
+        crc.update_u32_slice(&[0x1234_5678, 0x9ABC_DEF0], crate::framing::Endian::Big);
+        assert_eq!(crc.finalize(), expected);
+
+        let mut crc = CRC32::default();
+        crc.update_u8(0x12);
+        crc.update_ref([0x34, 0x56, 0x78]);
+        crc.update_ref(vec![0x9A, 0xBC]);
+        crc.update_ref(&bytes[6..]);
+        assert_eq!(crc.finalize(), expected);
+
+        let mut crc = CRC32::default();
+        crc.update_u64_be(0x1234_5678_9ABC_DEF0);
+        assert_eq!(crc.finalize(), expected);
+        let mut crc = CRC32::default();
+        crc.update_u64_le(0xF0DE_BC9A_7856_3412);
+        assert_eq!(crc.finalize(), expected);
+
+        let mut crc = CRC32::default();
+        crc.update_ref("hello world");
+        assert!(crc.finalize() == 0x44F71378);
+    }
+
+    #[cfg(feature = "bytemuck")]
+    #[test]
+    fn update_pod_test() {
+        #[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+        #[repr(C)]
+        struct Record {
+            id: u32,
+            value: u16,
+            flags: u16,
+        }
+
+        let record = Record {
+            id: 0x0102_0304,
+            value: 0x0506,
+            flags: 0x0708,
+        };
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&record.id.to_ne_bytes());
+        bytes.extend_from_slice(&record.value.to_ne_bytes());
+        bytes.extend_from_slice(&record.flags.to_ne_bytes());
+
+        let mut expected = CRC32::default();
+        expected.update(&bytes);
+        expected.update(&bytes);
+
+        let mut crc = CRC32::default();
+        crc.update_pod(&record);
+        crc.update_pod_slice(&[record]);
+        assert_eq!(crc.finalize(), expected.finalize());
+    }
+
+    #[cfg(feature = "ufmt")]
+    #[test]
+    fn ufmt_test() {
+        let mut crc = CRC16::default();
+        crc.update(TEST_DATA);
+        let mut out = String::new();
+        ufmt::uwrite!(out, "{}", crc).unwrap();
+        assert_eq!(out, "C814");
+
+        let mut out = String::new();
+        ufmt::uwrite!(out, "{:?}", CRC8::default()).unwrap();
+        assert_eq!(out, "CRC8 { crc: 0xFF, polynomial: 0x07 }");
+    }
+
+    #[test]
+    fn iter_test() {
+        let crc = TEST_DATA.iter().collect::<CRC32>().finalize();
+        assert!(crc == 0x44F71378, "{:#X}", crc);
+
+        let mut crc = CRC16::default();
+        crc.extend(TEST_DATA[..5].iter().copied());
+        crc.extend(&TEST_DATA[5..]);
+        let crc = crc.finalize();
+        assert!(crc == 0xC814, "{:#X}", crc);
+
+        let lazy = (0..200u8).map(|i| i.wrapping_mul(31));
+        let bytes: Vec<u8> = lazy.clone().collect();
+        let mut crc = CRC64::default();
+        crc.update_iter(lazy.clone());
+        let mut expected = CRC64::default();
+        expected.update(&bytes);
+        assert_eq!(crc.finalize(), expected.finalize());
+
+        let algorithm = crate::Crc::new(&crate::catalog::CRC_32_ISCSI);
+        let mut digest = algorithm.digest();
+        digest.extend(lazy);
+        assert_eq!(digest.finalize(), algorithm.checksum(&bytes));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn checksum_batch_test() {
+        let data: Vec<u8> = (0..=255u8).map(|i| i.wrapping_mul(167) ^ 0x5A).collect();
+        let messages: Vec<&[u8]> = (0..21).map(|i| &data[i..64 + i % 5]).collect();
+        fn each<N, C: CRC<Width = N>>(messages: &[&[u8]]) -> Vec<N> {
+            messages
+                .iter()
+                .map(|message| {
+                    let mut crc = C::default();
+                    crc.update(message);
+                    crc.finalize()
+                })
+                .collect()
+        }
+        assert_eq!(
+            CRC8::default().checksum_batch(&messages),
+            each::<_, CRC8>(&messages)
+        );
+        assert_eq!(
+            CRC16::default().checksum_batch(&messages),
+            each::<_, CRC16>(&messages)
+        );
+        assert_eq!(
+            CRC32::default().checksum_batch(&messages),
+            each::<_, CRC32>(&messages)
+        );
+        assert_eq!(
+            CRC64::default().checksum_batch(&messages),
+            each::<_, CRC64>(&messages)
+        );
+        assert_eq!(
+            CRC128::default().checksum_batch(&messages),
+            each::<_, CRC128>(&messages)
+        );
+        assert_eq!(
+            CRC32::default().checksum_batch(&[b"", TEST_DATA]),
+            [0, 0x44F71378]
+        );
+        assert!(CRC32::default().checksum_batch(&[]).is_empty());
+    }
+
+    #[test]
+    fn field_mask_test() {
+        let mut header = *b"HDR\x12\x34\x56\x78payload";
+        let mut crc = CRC32::default();
+        crc.update_zeroing(&header, 3..7);
+        let zeroing = crc.finalize();
+        let mut crc = CRC32::default();
+        crc.update_skipping(&header, 3..7);
+        let skipping = crc.finalize();
+
+        header[3..7].fill(0);
+        let mut crc = CRC32::default();
+        crc.update(&header);
+        assert_eq!(zeroing, crc.finalize());
+        let mut crc = CRC32::default();
+        crc.update(b"HDRpayload");
+        assert_eq!(skipping, crc.finalize());
+
+        // An empty field is the same as a plain update.
+        let mut crc = CRC16::default();
+        crc.update_zeroing(TEST_DATA, 4..4);
+        assert_eq!(crc.finalize(), 0xC814);
+    }
+
+    #[test]
+    fn unwind_test() {
+        // A device that seeds its CRC-16 with a secret value instead of all ones.
+        let secret = 0x1D0F;
+        let mut device = CRC16::from_register(0x1021, secret);
+        device.update(TEST_DATA);
+        let checksum = device.finalize();
+        assert_eq!(CRC16::create(0x1021).unwind(checksum, TEST_DATA), secret);
+
+        // Unwinding part of the message yields the register at that point.
+        let mut crc = CRC64::default();
+        crc.update(&TEST_DATA[..5]);
+        let register = crc.crc;
+        crc.update(&TEST_DATA[5..]);
+        assert_eq!(crc.unwind(crc.finalize(), &TEST_DATA[5..]), register);
+        assert_eq!(CRC8::default().unwind(0x94, TEST_DATA), u8::MAX);
+        assert_eq!(CRC128::default().unwind(0, b""), u128::MAX);
+    }
+
+    #[test]
+    fn params_test() {
+        use super::CrcParams;
+        let arc = CrcParams {
+            width: 16,
+            poly: 0x8005,
+            init: 0,
+            refin: true,
+            refout: true,
+            xorout: 0,
+        };
+        let mut crc = CRC16::create(arc);
+        crc.update(&TEST_DATA[..4]);
+        crc.update(&TEST_DATA[4..]);
+        let arc_crc = crcl::Crc::<u16>::new(&crcl::CRC_16_ARC);
+        assert_eq!(crc.finalize(), arc_crc.checksum(TEST_DATA));
+
+        let mpeg2 = CrcParams {
+            refin: false,
+            refout: false,
+            xorout: 0,
+            ..CrcParams::from(0x04C1_1DB7u32)
+        };
+        let mut crc = CRC32::create(mpeg2);
+        crc.update(TEST_DATA);
+        let mpeg2_crc = crcl::Crc::<u32>::new(&crcl::CRC_32_MPEG_2);
+        assert_eq!(crc.finalize(), mpeg2_crc.checksum(TEST_DATA));
+
+        let xz = CrcParams {
+            refin: true,
+            refout: true,
+            ..CrcParams::from(0x42F0_E1EB_A9EA_3693u64)
+        };
+        let mut crc = CRC64::create(xz);
+        crc.update(TEST_DATA);
+        let xz_crc = crcl::Crc::<u64>::new(&crcl::CRC_64_XZ);
+        assert_eq!(crc.finalize(), xz_crc.checksum(TEST_DATA));
+        assert_eq!(
+            CRC64::create(xz).unwind(crc.finalize(), TEST_DATA),
+            u64::MAX
+        );
+    }
+
+    #[test]
+    fn reflected_test() {
+        use super::{catalog, CrcParams};
+        let mut crc = CRC16::from_algorithm(&catalog::CRC_16_MODBUS);
+        crc.update(&TEST_DATA[..3]);
+        crc.update(&TEST_DATA[3..]);
+        let modbus = crcl::Crc::<u16>::new(&crcl::CRC_16_MODBUS);
+        assert_eq!(crc.finalize(), modbus.checksum(TEST_DATA));
+        let mut crc = CRC32::from_algorithm(&catalog::CRC_32_ISO_HDLC);
+        crc.update(TEST_DATA);
+        assert_eq!(crc.finalize(), 0x0D4A_1185);
+        let mut crc = CRC8::from_algorithm(&catalog::CRC_8_MAXIM_DOW);
+        crc.update(TEST_DATA);
+        let maxim = crcl::Crc::<u8>::new(&crcl::CRC_8_MAXIM_DOW);
+        assert_eq!(crc.finalize(), maxim.checksum(TEST_DATA));
+
+        // refin without refout, against the generic engine.
+        let params = CrcParams {
+            width: 128,
+            poly: CRC128::default().params.poly,
+            init: 0x1234,
+            refin: true,
+            refout: false,
+            xorout: 0x5678,
+        };
+        let mut crc = CRC128::create(params);
+        crc.update(TEST_DATA);
+        let expected = super::engine::Params::from(params).checksum(TEST_DATA);
+        assert_eq!(crc.finalize(), expected);
+        assert_eq!(CRC128::create(params).unwind(expected, TEST_DATA), 0x1234);
+        let mut resumed = CRC128::from_register(params, 0x1234);
+        resumed.update(TEST_DATA);
+        assert_eq!(resumed.finalize(), expected);
+
+        #[cfg(feature = "std")]
+        {
+            let zlib = crcl::Crc::<u32>::new(&crcl::CRC_32_ISO_HDLC);
+            let messages: Vec<&[u8]> = vec![TEST_DATA, &TEST_DATA[1..], b"", &TEST_DATA[..4]];
+            let crcs = CRC32::from_algorithm(&catalog::CRC_32_ISO_HDLC).checksum_batch(&messages);
+            let expected: Vec<_> = messages.iter().map(|m| zlib.checksum(m)).collect();
+            assert_eq!(crcs, expected);
+        }
+    }
+
+    #[test]
+    fn narrow_width_test() {
+        use super::{catalog, CrcParams};
+        let darc = CrcParams {
+            width: 82,
+            poly: 0x0_308C_0111_0114_0144_0411,
+            init: 0,
+            refin: true,
+            refout: true,
+            xorout: 0,
+        };
+        let mut crc = CRC128::create(darc);
+        crc.update(b"12345");
+        crc.update(b"6789");
+        assert_eq!(crc.finalize(), 0x0_9EA8_3F62_5023_801F_D612);
+
+        let openpgp = catalog::CRC_24_OPENPGP;
+        let mut crc = CRC32::from_algorithm(&openpgp);
+        crc.update(TEST_DATA);
+        let checksum = crc.finalize();
+        let openpgp_crc = crcl::Crc::<u32>::new(&crcl::CRC_24_OPENPGP);
+        assert_eq!(checksum, openpgp_crc.checksum(TEST_DATA));
+        let crc = CRC32::from_algorithm(&openpgp);
+        assert_eq!(crc.unwind(checksum, TEST_DATA), openpgp.params.init);
+        let mut resumed = CRC32::from_register(openpgp.params, 0x12_3456);
+        resumed.update(TEST_DATA);
+        assert_eq!(crc.unwind(resumed.finalize(), TEST_DATA), 0x12_3456);
+
+        // refin without refout on a register with spare bits.
+        let umts = catalog::CRC_12_UMTS;
+        let crc = CRC16::from_algorithm(&umts);
+        assert_eq!(crc.unwind(umts.check, b"123456789"), 0);
+        #[cfg(feature = "std")]
+        {
+            let usb = crcl::Crc::<u8>::new(&crcl::CRC_5_USB);
+            let messages: [&[u8]; 3] = [TEST_DATA, b"", b"123456789"];
+            let crcs = CRC8::from_algorithm(&catalog::CRC_5_USB).checksum_batch(&messages);
+            assert_eq!(crcs, messages.map(|m| usb.checksum(m)));
+        }
+    }
+
+    #[test]
+    fn reset_test() {
+        let mut crc = CRC16::from_algorithm(&super::catalog::CRC_16_MODBUS);
+        for _ in 0..2 {
+            crc.update(b"123456789");
+            assert_eq!(crc.finalize_reset(), 0x4B37);
+        }
+        crc.update(b"partial");
+        crc.reset();
+        crc.update(b"123456789");
+        assert_eq!(crc.finalize(), 0x4B37);
+
+        let mut crc = crate::const_crc::ConstCRC8::<0x07, 0, false, false, 0>::new();
+        crc.update(b"junk");
+        crc.reset();
+        crc.update(b"123456789");
+        assert_eq!(crc.finalize_reset(), 0xF4);
+        assert_eq!(crc.finalize(), 0);
+    }
+
+    #[test]
+    fn snapshot_test() {
+        let mut crc = CRC32::default();
+        crc.update(&TEST_DATA[..6]);
+        let snapshot = crc.clone_state();
+        assert_eq!(crc.peek(), crc.finalize());
+        crc.update(&TEST_DATA[6..]);
+        assert_eq!(crc.finalize(), 0x44F71378);
+        let mut other = snapshot.clone_state();
+        other.update(b"there");
+        let mut expected = CRC32::default();
+        expected.update(b"hello there");
+        assert_eq!(other.finalize(), expected.finalize());
+        assert_eq!(snapshot.peek(), {
+            let mut crc = CRC32::default();
+            crc.update(b"hello ");
+            crc.finalize()
+        });
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn dyn_crc_test() {
+        use super::{catalog, Crc, DynCrc};
+        let modbus = Crc::new(&catalog::CRC_16_MODBUS);
+        let mut crcs: Vec<Box<dyn DynCrc>> = vec![
+            Box::new(CRC8::from_algorithm(&catalog::CRC_8_SMBUS)),
+            Box::new(CRC32::from_algorithm(&catalog::CRC_32_ISO_HDLC)),
+            Box::new(CRC64::from_algorithm(&catalog::CRC_64_XZ)),
+            Box::new(modbus.digest()),
+        ];
+        for crc in &mut crcs {
+            crc.dyn_update(b"1234");
+            crc.dyn_update(b"56789");
+        }
+        let checks: Vec<u128> = crcs.iter().map(|crc| crc.dyn_finalize()).collect();
+        assert_eq!(checks, [0xF4, 0xCBF4_3926, 0x995D_C9BB_DF19_39FA, 0x4B37]);
+        crcs[1].dyn_reset();
+        assert_eq!(crcs[1].dyn_finalize(), 0);
+    }
+
+    #[test]
+    fn vectored_test() {
+        let mut whole = CRC32::default();
+        whole.update(b"hello world");
+        let mut crc = CRC32::default();
+        crc.update_vectored(&[b"hel", b"", b"lo wor", b"ld"]);
+        assert_eq!(crc.finalize(), whole.finalize());
+        let rope = ["hello", " ", "world"];
+        let mut crc = CRC32::default();
+        crc.update_segments(rope.iter().map(|part| part.as_bytes()));
+        assert_eq!(crc.finalize(), whole.finalize());
+    }
 }
@@ -0,0 +1,299 @@
+//! Integrity framing for append-only logs.
+//!
+//! Every record is written as a one-byte kind, its length as `u32` little-endian, the
+//! payload, and the CRC-32C of those bytes, so a torn write at the tail is always detected.
+//! [`LogWriter`] also keeps a running CRC-32C of the record headers and payloads it has
+//! written, and every so many bytes or records appends a checkpoint record holding that CRC.
+//! A checkpoint that verifies proves the whole log up to it is intact, not just each record
+//! on its own, which catches lost or reordered blocks that individually look fine.
+//!
+//! After a crash, [`verify`] reads the log from the start and reports where the valid prefix
+//! ends, which is where the file should be truncated before appending resumes with
+//! [`LogWriter::resume`].
+
+use crate::compare::read_full;
+use crate::const_crc::Crc32Iscsi;
+use crate::{trace, CRC};
+use std::io::{self, Read, Write};
+
+const DATA: u8 = 0;
+const CHECKPOINT: u8 = 1;
+const HEADER_LEN: usize = 5;
+const CRC_LEN: usize = 4;
+/// Payload of a checkpoint: the offset it was written at and the running CRC up to there.
+const CHECKPOINT_LEN: usize = 12;
+
+/// When [`LogWriter`] appends checkpoints on its own. A checkpoint is due once either limit
+/// has been reached since the previous one; `None` disables that limit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Interval {
+    pub bytes: Option<u64>,
+    pub records: Option<u64>,
+}
+
+/// Appends framed records and checkpoints to a writer.
+pub struct LogWriter<W> {
+    inner: W,
+    interval: Interval,
+    offset: u64,
+    crc: Crc32Iscsi,
+    since_bytes: u64,
+    since_records: u64,
+}
+
+impl<W: Write> LogWriter<W> {
+    /// Starts a new, empty log.
+    pub fn new(inner: W, interval: Interval) -> Self {
+        Self {
+            inner,
+            interval,
+            offset: 0,
+            crc: Crc32Iscsi::new(),
+            since_bytes: 0,
+            since_records: 0,
+        }
+    }
+
+    /// Continues a log that [`verify`] has checked, with `inner` positioned at
+    /// [`Recovery::valid_len`] (and the file truncated there).
+    pub fn resume(inner: W, interval: Interval, recovery: &Recovery) -> Self {
+        Self {
+            inner,
+            interval,
+            offset: recovery.valid_len,
+            crc: recovery.crc,
+            since_bytes: 0,
+            since_records: 0,
+        }
+    }
+
+    fn write_record(&mut self, kind: u8, payload: &[u8]) -> io::Result<u64> {
+        let len = u32::try_from(payload.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "log record too large"))?;
+        let mut header = [kind, 0, 0, 0, 0];
+        header[1..].copy_from_slice(&len.to_le_bytes());
+        let mut crc = Crc32Iscsi::new();
+        crc.update_vectored(&[&header, payload]);
+        let crc = crc.finalize().to_le_bytes();
+        // The running CRC leaves out the record CRCs: a record followed by its own CRC
+        // always leaves the register in the same state, so including them would make the
+        // running CRC blind to swapped records of equal length.
+        self.crc.update_vectored(&[&header, payload]);
+        for part in [&header[..], payload, &crc] {
+            self.inner.write_all(part)?;
+        }
+        let offset = self.offset;
+        self.offset += (HEADER_LEN + payload.len() + CRC_LEN) as u64;
+        Ok(offset)
+    }
+
+    /// Appends `record` and returns its offset, followed by a checkpoint if one is due.
+    pub fn append(&mut self, record: &[u8]) -> io::Result<u64> {
+        let offset = self.write_record(DATA, record)?;
+        self.since_bytes += self.offset - offset;
+        self.since_records += 1;
+        let due = |limit: Option<u64>, count| limit.is_some_and(|limit| count >= limit);
+        if due(self.interval.bytes, self.since_bytes)
+            || due(self.interval.records, self.since_records)
+        {
+            self.checkpoint()?;
+        }
+        Ok(offset)
+    }
+
+    /// Appends a checkpoint now and flushes the writer.
+    pub fn checkpoint(&mut self) -> io::Result<()> {
+        let mut payload = [0; CHECKPOINT_LEN];
+        payload[..8].copy_from_slice(&self.offset.to_le_bytes());
+        payload[8..].copy_from_slice(&self.crc.finalize().to_le_bytes());
+        self.write_record(CHECKPOINT, &payload)?;
+        self.since_bytes = 0;
+        self.since_records = 0;
+        self.inner.flush()
+    }
+
+    /// The length of the log written so far.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+/// Why [`verify`] stopped before the end of the log.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Damage {
+    /// The log ends in the middle of a record, as after a torn write.
+    Truncated,
+    /// The record at this offset has an unknown kind or a bad CRC.
+    Record { offset: u64 },
+    /// The checkpoint at this offset is intact but disagrees with the log before it.
+    Checkpoint { offset: u64 },
+}
+
+impl std::fmt::Display for Damage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Truncated => write!(f, "log ends in a partial record"),
+            Self::Record { offset } => write!(f, "corrupt log record at offset {}", offset),
+            Self::Checkpoint { offset } => {
+                write!(f, "log checkpoint at offset {} does not match", offset)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Damage {}
+
+/// The result of [`verify`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Recovery {
+    /// Length of the valid prefix: where to truncate before appending.
+    pub valid_len: u64,
+    /// End of the last checkpoint within the valid prefix, zero if there is none. Records
+    /// up to here are covered by the running CRC as well as their own.
+    pub checkpointed_len: u64,
+    /// Data records in the valid prefix.
+    pub records: u64,
+    /// What ended the valid prefix, or `None` if the whole log is valid.
+    pub damage: Option<Damage>,
+    crc: Crc32Iscsi,
+}
+
+/// Reads a log from the start and calls `record` with the offset and payload of every data
+/// record in its valid prefix.
+pub fn verify<R: Read>(mut reader: R, mut record: impl FnMut(u64, &[u8])) -> io::Result<Recovery> {
+    let mut recovery = Recovery {
+        valid_len: 0,
+        checkpointed_len: 0,
+        records: 0,
+        damage: None,
+        crc: Crc32Iscsi::new(),
+    };
+    let mut buf = Vec::new();
+    loop {
+        let offset = recovery.valid_len;
+        let mut header = [0; HEADER_LEN];
+        match read_full(&mut reader, &mut header)? {
+            0 => return Ok(recovery),
+            HEADER_LEN => {}
+            _ => break recovery.damage = Some(Damage::Truncated),
+        }
+        let len = u32::from_le_bytes(header[1..].try_into().unwrap()) as usize;
+        if !matches!(header[0], DATA | CHECKPOINT) {
+            break recovery.damage = Some(Damage::Record { offset });
+        }
+        buf.resize(len + CRC_LEN, 0);
+        if read_full(&mut reader, &mut buf)? < buf.len() {
+            break recovery.damage = Some(Damage::Truncated);
+        }
+        let (payload, stored) = buf.split_at(len);
+        let expected = u32::from_le_bytes(stored.try_into().unwrap());
+        let mut crc = Crc32Iscsi::new();
+        crc.update_vectored(&[&header, payload]);
+        if crc.finalize() != expected {
+            break recovery.damage = Some(Damage::Record { offset });
+        }
+        if header[0] == CHECKPOINT {
+            let actual = recovery.crc.finalize();
+            let valid = len == CHECKPOINT_LEN
+                && u64::from_le_bytes(payload[..8].try_into().unwrap()) == offset
+                && u32::from_le_bytes(payload[8..].try_into().unwrap()) == actual;
+            if len == CHECKPOINT_LEN {
+                let stored = u32::from_le_bytes(payload[8..].try_into().unwrap());
+                trace::verified("CRC-32C", "log checkpoint", stored as u64, actual as u64);
+            }
+            if !valid {
+                break recovery.damage = Some(Damage::Checkpoint { offset });
+            }
+        } else {
+            record(offset, payload);
+            recovery.records += 1;
+        }
+        recovery.crc.update_vectored(&[&header, payload]);
+        recovery.valid_len += (HEADER_LEN + buf.len()) as u64;
+        if header[0] == CHECKPOINT {
+            recovery.checkpointed_len = recovery.valid_len;
+        }
+    }
+    Ok(recovery)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_log(records: &[&[u8]]) -> Vec<u8> {
+        let interval = Interval {
+            bytes: None,
+            records: Some(2),
+        };
+        let mut log = LogWriter::new(Vec::new(), interval);
+        for record in records {
+            log.append(record).unwrap();
+        }
+        log.into_inner()
+    }
+
+    #[test]
+    fn verify_test() {
+        let log = write_log(&[b"one", b"two", b"three"]);
+        // Three records of 9 bytes plus framing and one checkpoint after the second.
+        let checkpoint = 2 * (HEADER_LEN + 3 + CRC_LEN) + HEADER_LEN + CHECKPOINT_LEN + CRC_LEN;
+        assert_eq!(log.len(), checkpoint + HEADER_LEN + 5 + CRC_LEN);
+
+        let mut seen = Vec::new();
+        let recovery = verify(&log[..], |offset, payload| {
+            seen.push((offset, payload.to_vec()))
+        })
+        .unwrap();
+        assert_eq!(recovery.damage, None);
+        assert_eq!(recovery.valid_len, log.len() as u64);
+        assert_eq!(recovery.checkpointed_len, checkpoint as u64);
+        assert_eq!(recovery.records, 3);
+        assert_eq!(seen[2], (checkpoint as u64, b"three".to_vec()));
+
+        // A torn write of the last record.
+        let recovery = verify(&log[..log.len() - 2], |_, _| {}).unwrap();
+        assert_eq!(recovery.damage, Some(Damage::Truncated));
+        assert_eq!(recovery.valid_len, checkpoint as u64);
+        assert_eq!(recovery.records, 2);
+
+        let mut corrupt = log.clone();
+        corrupt[HEADER_LEN] ^= 1;
+        let recovery = verify(&corrupt[..], |_, _| {}).unwrap();
+        assert_eq!(recovery.damage, Some(Damage::Record { offset: 0 }));
+        assert_eq!(recovery.valid_len, 0);
+    }
+
+    #[test]
+    fn checkpoint_and_resume_test() {
+        // Swapping two intact records passes the per-record CRCs but not the checkpoint.
+        let log = write_log(&[b"one", b"two"]);
+        let record = HEADER_LEN + 3 + CRC_LEN;
+        let mut swapped = log[record..2 * record].to_vec();
+        swapped.extend_from_slice(&log[..record]);
+        swapped.extend_from_slice(&log[2 * record..]);
+        let recovery = verify(&swapped[..], |_, _| {}).unwrap();
+        assert_eq!(
+            recovery.damage,
+            Some(Damage::Checkpoint {
+                offset: 2 * record as u64
+            })
+        );
+        assert_eq!(recovery.valid_len, 2 * record as u64);
+
+        let mut torn = write_log(&[b"one", b"two", b"three"]);
+        torn.truncate(torn.len() - 1);
+        let recovery = verify(&torn[..], |_, _| {}).unwrap();
+        torn.truncate(recovery.valid_len as usize);
+        let mut log = LogWriter::resume(torn, Interval::default(), &recovery);
+        log.append(b"three").unwrap();
+        log.checkpoint().unwrap();
+        let recovery = verify(&log.into_inner()[..], |_, _| {}).unwrap();
+        assert_eq!(recovery.damage, None);
+        assert_eq!(recovery.records, 3);
+    }
+}
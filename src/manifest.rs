@@ -0,0 +1,863 @@
+//! CRC manifests of directory trees.
+//!
+//! [`create`] walks a directory, checksums every regular file with the algorithm chosen in
+//! [`Options::algorithm`], CRC-32C by default, and returns a [`Manifest`]
+//! whose entries are sorted by path, so two walks of identical trees produce identical
+//! manifests regardless of the order the file system lists directories in. Paths are
+//! relative to the root and always use `/` as separator.
+//!
+//! A manifest serializes to a line-based text format (a header naming the algorithm, then
+//! one tab-separated entry per line) via [`Display`](std::fmt::Display) and back via
+//! [`FromStr`](std::str::FromStr), and two manifests can be compared with [`Manifest::diff`].
+//!
+//! File entries also record the modification time, which lets [`refresh`] reuse the stored
+//! checksum of every file whose size and modification time are unchanged instead of reading
+//! it again.
+//!
+//! [`Manifest::tree_digest`] rolls a whole manifest up into a single CRC, Merkle style: a
+//! directory's digest is the CRC-32C over its children sorted by name, each encoded as its
+//! name, a NUL, a type tag and its own digest (`f` plus size and file checksum for files, `l`
+//! plus the CRC of the target for links, `d` plus the subdirectory digest). The file checksum
+//! takes as many big-endian bytes as the manifest's algorithm is wide. Replicas with equal tree
+//! digests hold the same files; [`Manifest::directory_digests`] narrows down where unequal
+//! ones differ. Modification times are not part of the digest.
+
+use crate::catalog::{self, AnyAlgorithm};
+use crate::engine::Width;
+use crate::trace;
+use crate::Crc;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::{Instant, UNIX_EPOCH};
+
+const HEADER: &str = "# crclib manifest v2";
+/// The default algorithm, written as `crc32c` in the header.
+const CRC_32C: AnyAlgorithm = AnyAlgorithm::Crc32(catalog::CRC_32_ISCSI);
+
+/// What to do with symbolic links found while walking.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SymlinkPolicy {
+    /// Leave links out of the manifest.
+    Skip,
+    /// Record the link target without following it.
+    #[default]
+    Record,
+    /// Hash whatever the link points to as if it were at the link's location.
+    Follow,
+}
+
+/// Settings for [`create`].
+#[derive(Clone, Debug)]
+pub struct Options {
+    /// Glob patterns (`*`, `?`) of entries to leave out. A pattern without `/` is matched
+    /// against every file and directory name; one with `/` against the whole relative path.
+    /// Ignored directories are not descended into.
+    pub ignore: Vec<String>,
+    pub symlinks: SymlinkPolicy,
+    /// The algorithm files are checksummed with.
+    pub algorithm: AnyAlgorithm,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            ignore: Vec::new(),
+            symlinks: SymlinkPolicy::default(),
+            algorithm: CRC_32C,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EntryKind {
+    /// A regular file and its checksum under the manifest's algorithm, widened to `u64`.
+    File {
+        size: u64,
+        crc: u64,
+    },
+    Symlink {
+        target: String,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    pub path: String,
+    pub kind: EntryKind,
+    /// Modification time in nanoseconds since the Unix epoch, if known. Not compared by
+    /// [`Manifest::diff`].
+    pub mtime: Option<u64>,
+}
+
+/// A sorted list of manifest entries and the algorithm their checksums were made with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Manifest {
+    algorithm: AnyAlgorithm,
+    entries: Vec<Entry>,
+}
+
+impl Default for Manifest {
+    fn default() -> Self {
+        Self {
+            algorithm: CRC_32C,
+            entries: Vec::new(),
+        }
+    }
+}
+
+/// The differences between two manifests, each list sorted by path.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Diff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub modified: Vec<String>,
+}
+
+impl Diff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// A manifest line that could not be parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// One-based line number.
+    pub line: usize,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "malformed manifest entry on line {}", self.line)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl Manifest {
+    /// Builds a CRC-32C manifest from entries in any order.
+    ///
+    /// If several entries share a path, the last one wins.
+    pub fn from_entries(mut entries: Vec<Entry>) -> Self {
+        entries.reverse();
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        entries.dedup_by(|a, b| a.path == b.path);
+        Self {
+            algorithm: CRC_32C,
+            entries,
+        }
+    }
+
+    /// Marks the file checksums as made with `algorithm`.
+    pub fn with_algorithm(self, algorithm: AnyAlgorithm) -> Self {
+        Self { algorithm, ..self }
+    }
+
+    pub fn algorithm(&self) -> AnyAlgorithm {
+        self.algorithm
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    pub fn get(&self, path: &str) -> Option<&Entry> {
+        self.entries
+            .binary_search_by(|entry| entry.path.as_str().cmp(path))
+            .ok()
+            .map(|index| &self.entries[index])
+    }
+
+    /// Lists the entries of `newer` that are not in `self`, those of `self` that are gone
+    /// from `newer`, and those whose checksum, size or link target differs.
+    ///
+    /// Checksums are compared as they are, so against a manifest made with another algorithm
+    /// nearly every file shows up as modified.
+    pub fn diff(&self, newer: &Manifest) -> Diff {
+        let mut diff = Diff::default();
+        let (mut old, mut new) = (
+            self.entries.iter().peekable(),
+            newer.entries.iter().peekable(),
+        );
+        loop {
+            match (old.peek(), new.peek()) {
+                (None, None) => break,
+                (Some(a), Some(b)) if a.path == b.path => {
+                    if a.kind != b.kind {
+                        diff.modified.push(a.path.clone());
+                    }
+                    old.next();
+                    new.next();
+                }
+                (Some(a), Some(b)) if a.path < b.path => {
+                    diff.removed.push(a.path.clone());
+                    old.next();
+                }
+                (Some(a), None) => {
+                    diff.removed.push(a.path.clone());
+                    old.next();
+                }
+                (_, Some(b)) => {
+                    diff.added.push(b.path.clone());
+                    new.next();
+                }
+            }
+        }
+        diff
+    }
+
+    /// The rolled-up digest of the whole tree.
+    pub fn tree_digest(&self) -> u32 {
+        digest_dir(
+            &build_tree(&self.entries),
+            self.crc_bytes(),
+            &mut Vec::new(),
+            &mut |_, _| (),
+        )
+    }
+
+    /// The rolled-up digest of every directory, keyed by path (`""` for the root).
+    ///
+    /// Only directories that contain at least one entry appear in a manifest.
+    pub fn directory_digests(&self) -> BTreeMap<String, u32> {
+        let mut digests = BTreeMap::new();
+        digest_dir(
+            &build_tree(&self.entries),
+            self.crc_bytes(),
+            &mut Vec::new(),
+            &mut |path, digest| {
+                digests.insert(path.join("/"), digest);
+            },
+        );
+        digests
+    }
+
+    /// How many bytes a file checksum takes in the tree digest.
+    fn crc_bytes(&self) -> usize {
+        self.algorithm.width().div_ceil(8) as usize
+    }
+}
+
+enum Node<'a> {
+    Leaf(&'a EntryKind),
+    Dir(Children<'a>),
+}
+
+type Children<'a> = BTreeMap<&'a str, Node<'a>>;
+
+fn build_tree(entries: &[Entry]) -> Children<'_> {
+    let mut root = Children::new();
+    for entry in entries {
+        let (parents, name) = match entry.path.rsplit_once('/') {
+            Some((parents, name)) => (Some(parents), name),
+            None => (None, entry.path.as_str()),
+        };
+        let mut dir = &mut root;
+        for component in parents.into_iter().flat_map(|parents| parents.split('/')) {
+            let node = dir.entry(component).or_insert(Node::Dir(Children::new()));
+            // A hand-written manifest may list a path both as a file and as a directory;
+            // the directory wins.
+            if let Node::Leaf(_) = node {
+                *node = Node::Dir(Children::new());
+            }
+            let Node::Dir(children) = node else {
+                unreachable!()
+            };
+            dir = children;
+        }
+        dir.entry(name).or_insert(Node::Leaf(&entry.kind));
+    }
+    root
+}
+
+/// Digests a directory, calling `visit` with the components and digest of it and every
+/// directory below it.
+fn digest_dir<'a>(
+    children: &Children<'a>,
+    crc_bytes: usize,
+    path: &mut Vec<&'a str>,
+    visit: &mut impl FnMut(&[&str], u32),
+) -> u32 {
+    let crc32c = Crc::<u32>::cached(&catalog::CRC_32_ISCSI);
+    let mut crc = crc32c.digest();
+    for (&name, child) in children {
+        crc.update(name.as_bytes());
+        crc.update(&[0]);
+        match child {
+            Node::Leaf(EntryKind::File {
+                size,
+                crc: file_crc,
+            }) => {
+                crc.update(b"f");
+                crc.update(&size.to_be_bytes());
+                crc.update(&file_crc.to_be_bytes()[8 - crc_bytes..]);
+            }
+            Node::Leaf(EntryKind::Symlink { target }) => {
+                let target_crc = crc32c.checksum(target.as_bytes());
+                crc.update(b"l");
+                crc.update(&target_crc.to_be_bytes());
+            }
+            Node::Dir(grandchildren) => {
+                path.push(name);
+                let dir_crc = digest_dir(grandchildren, crc_bytes, path, visit);
+                path.pop();
+                crc.update(b"d");
+                crc.update(&dir_crc.to_be_bytes());
+            }
+        }
+    }
+    let digest = crc.finalize();
+    visit(path, digest);
+    digest
+}
+
+impl std::fmt::Display for Manifest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.algorithm {
+            CRC_32C => writeln!(f, "{} crc32c", HEADER)?,
+            algorithm => writeln!(f, "{} {}", HEADER, algorithm.name())?,
+        }
+        let digits = self.algorithm.width().div_ceil(4) as usize;
+        for entry in &self.entries {
+            match &entry.kind {
+                EntryKind::File { size, crc } => {
+                    write!(f, "file\t{:0digits$x}\t{}\t", crc, size)?;
+                    match entry.mtime {
+                        Some(mtime) => write!(f, "{}", mtime)?,
+                        None => write!(f, "-")?,
+                    }
+                    writeln!(f, "\t{}", escape(&entry.path))?
+                }
+                EntryKind::Symlink { target } => {
+                    writeln!(f, "link\t{}\t{}", escape(target), escape(&entry.path))?
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for Manifest {
+    type Err = ParseError;
+
+    /// Manifests without a header are taken to be CRC-32C.
+    fn from_str(text: &str) -> Result<Self, ParseError> {
+        let mut algorithm = CRC_32C;
+        let mut entries = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let error = ParseError { line: index + 1 };
+            if let Some(rest) = line.strip_prefix("# crclib manifest v") {
+                algorithm = match rest.split_once(' ') {
+                    Some((_, "crc32c")) => CRC_32C,
+                    Some((_, name)) => catalog::by_name(name).map_err(|_| error)?,
+                    None => return Err(error),
+                };
+                continue;
+            }
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split('\t').collect();
+            // Version 1 manifests have no modification time column.
+            let entry = match fields[..] {
+                ["file", crc, size, path] | ["file", crc, size, "-", path] => Entry {
+                    path: unescape(path).ok_or(error.clone())?,
+                    kind: EntryKind::File {
+                        size: size.parse().map_err(|_| error.clone())?,
+                        crc: u64::from_str_radix(crc, 16).map_err(|_| error.clone())?,
+                    },
+                    mtime: None,
+                },
+                ["file", crc, size, mtime, path] => Entry {
+                    path: unescape(path).ok_or(error.clone())?,
+                    kind: EntryKind::File {
+                        size: size.parse().map_err(|_| error.clone())?,
+                        crc: u64::from_str_radix(crc, 16).map_err(|_| error.clone())?,
+                    },
+                    mtime: Some(mtime.parse().map_err(|_| error.clone())?),
+                },
+                ["link", target, path] => Entry {
+                    path: unescape(path).ok_or(error.clone())?,
+                    kind: EntryKind::Symlink {
+                        target: unescape(target).ok_or(error.clone())?,
+                    },
+                    mtime: None,
+                },
+                _ => return Err(error),
+            };
+            entries.push(entry);
+        }
+        Ok(Self::from_entries(entries).with_algorithm(algorithm))
+    }
+}
+
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn unescape(s: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        unescaped.push(match chars.next()? {
+            '\\' => '\\',
+            't' => '\t',
+            'n' => '\n',
+            'r' => '\r',
+            _ => return None,
+        });
+    }
+    Some(unescaped)
+}
+
+/// Matches `name` against a glob pattern supporting `*` and `?`.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let (pattern, name): (Vec<char>, Vec<char>) =
+        (pattern.chars().collect(), name.chars().collect());
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    n = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+impl Options {
+    fn is_ignored(&self, path: &str, name: &str) -> bool {
+        self.ignore.iter().any(|pattern| {
+            if pattern.contains('/') {
+                glob_match(pattern, path)
+            } else {
+                glob_match(pattern, name)
+            }
+        })
+    }
+}
+
+/// Computes the CRC-32C and length of the file at `path`.
+pub fn hash_file(path: &Path) -> io::Result<(u64, u32)> {
+    let (size, crc) = hash_file_with(path, CRC_32C)?;
+    Ok((size, crc as u32))
+}
+
+/// Computes the checksum under `algorithm`, widened to `u64`, and length of the file at
+/// `path`.
+pub fn hash_file_with(path: &Path, algorithm: AnyAlgorithm) -> io::Result<(u64, u64)> {
+    let _span = trace::file_span("hash_file", path);
+    let started = Instant::now();
+    let (size, crc) = match algorithm {
+        AnyAlgorithm::Crc8(algorithm) => hash_file_crc(path, Crc::cached(&algorithm)),
+        AnyAlgorithm::Crc16(algorithm) => hash_file_crc(path, Crc::cached(&algorithm)),
+        AnyAlgorithm::Crc32(algorithm) => hash_file_crc(path, Crc::cached(&algorithm)),
+        AnyAlgorithm::Crc64(algorithm) => hash_file_crc(path, Crc::cached(&algorithm)),
+    }?;
+    trace::hashed(algorithm.name(), size, started);
+    Ok((size, crc))
+}
+
+fn hash_file_crc<W: Width>(path: &Path, crc: &Crc<W>) -> io::Result<(u64, u64)> {
+    let mut digest = crc.digest();
+    let size = read_file(path, |data| digest.update(data))?;
+    Ok((size, Into::<u128>::into(digest.finalize()) as u64))
+}
+
+/// Feeds the file at `path` to `update` and returns its length.
+fn read_file(path: &Path, mut update: impl FnMut(&[u8])) -> io::Result<u64> {
+    let mut file = fs::File::open(path)?;
+    let mut buf = vec![0; 64 * 1024];
+    let mut size = 0;
+    loop {
+        let n = match file.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+        update(&buf[..n]);
+        size += n as u64;
+    }
+    Ok(size)
+}
+
+/// The modification time of a file in nanoseconds since the Unix epoch, if available.
+pub(crate) fn mtime_ns(metadata: &fs::Metadata) -> Option<u64> {
+    let since = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    u64::try_from(since.as_nanos()).ok()
+}
+
+/// Walks the directory tree at `root` and checksums every regular file in it.
+///
+/// Entries other than regular files, directories and symbolic links (sockets, FIFOs,
+/// devices) are skipped. Names that are not valid UTF-8 and, with
+/// [`SymlinkPolicy::Follow`], links that form a cycle fail with
+/// [`io::ErrorKind::InvalidData`].
+pub fn create(root: &Path, options: &Options) -> io::Result<Manifest> {
+    Ok(Walk::new(options, None).run(root)?.0)
+}
+
+/// The result of [`refresh`].
+#[derive(Clone, Debug)]
+pub struct Refresh {
+    pub manifest: Manifest,
+    /// Changes from the stored manifest to the current tree.
+    pub diff: Diff,
+    /// Number of files that were read; the others kept their stored checksum.
+    pub rehashed: usize,
+}
+
+/// Re-walks `root` and rehashes only files that are new or whose size or modification time
+/// differs from `stored`. If `stored` was made with another algorithm than the one in
+/// `options`, every file is rehashed.
+///
+/// A file rewritten in place with the same size that also had its modification time reset
+/// is not detected; use [`create`] for a full verification.
+pub fn refresh(root: &Path, stored: &Manifest, options: &Options) -> io::Result<Refresh> {
+    let (manifest, rehashed) = Walk::new(options, Some(stored)).run(root)?;
+    Ok(Refresh {
+        diff: stored.diff(&manifest),
+        manifest,
+        rehashed,
+    })
+}
+
+struct Walk<'a> {
+    options: &'a Options,
+    stored: Option<&'a Manifest>,
+    ancestors: Vec<PathBuf>,
+    entries: Vec<Entry>,
+    rehashed: usize,
+}
+
+impl<'a> Walk<'a> {
+    fn new(options: &'a Options, stored: Option<&'a Manifest>) -> Self {
+        Self {
+            options,
+            stored,
+            ancestors: Vec::new(),
+            entries: Vec::new(),
+            rehashed: 0,
+        }
+    }
+
+    fn run(mut self, root: &Path) -> io::Result<(Manifest, usize)> {
+        self.ancestors.push(fs::canonicalize(root)?);
+        self.walk(root, "")?;
+        let manifest = Manifest::from_entries(self.entries).with_algorithm(self.options.algorithm);
+        Ok((manifest, self.rehashed))
+    }
+
+    /// The stored entry for `path` if it recorded the same size and modification time.
+    fn unchanged(&self, path: &str, size: u64, mtime: Option<u64>) -> Option<Entry> {
+        let stored = self
+            .stored
+            .filter(|stored| stored.algorithm == self.options.algorithm)?;
+        let entry = stored.get(path)?;
+        match entry.kind {
+            EntryKind::File { size: stored, .. }
+                if stored == size && mtime.is_some() && entry.mtime == mtime =>
+            {
+                Some(entry.clone())
+            }
+            _ => None,
+        }
+    }
+
+    fn walk(&mut self, dir: &Path, prefix: &str) -> io::Result<()> {
+        let options = self.options;
+        for dir_entry in fs::read_dir(dir)? {
+            let dir_entry = dir_entry?;
+            let name = dir_entry.file_name();
+            let name = name.to_str().ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, "file name is not valid UTF-8")
+            })?;
+            let path = if prefix.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}/{}", prefix, name)
+            };
+            if options.is_ignored(&path, name) {
+                continue;
+            }
+
+            let full_path = dir_entry.path();
+            let mut file_type = dir_entry.file_type()?;
+            if file_type.is_symlink() {
+                match options.symlinks {
+                    SymlinkPolicy::Skip => continue,
+                    SymlinkPolicy::Record => {
+                        let target = fs::read_link(&full_path)?;
+                        let target = target.to_str().ok_or_else(|| {
+                            io::Error::new(
+                                io::ErrorKind::InvalidData,
+                                "link target is not valid UTF-8",
+                            )
+                        })?;
+                        self.entries.push(Entry {
+                            path,
+                            kind: EntryKind::Symlink {
+                                target: target.replace(std::path::MAIN_SEPARATOR, "/"),
+                            },
+                            mtime: None,
+                        });
+                        continue;
+                    }
+                    SymlinkPolicy::Follow => file_type = fs::metadata(&full_path)?.file_type(),
+                }
+            }
+
+            if file_type.is_dir() {
+                let canonical = fs::canonicalize(&full_path)?;
+                if self.ancestors.contains(&canonical) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("symbolic link cycle at {}", path),
+                    ));
+                }
+                self.ancestors.push(canonical);
+                self.walk(&full_path, &path)?;
+                self.ancestors.pop();
+            } else if file_type.is_file() {
+                let metadata = fs::metadata(&full_path)?;
+                let mtime = mtime_ns(&metadata);
+                let entry = match self.unchanged(&path, metadata.len(), mtime) {
+                    Some(entry) => entry,
+                    None => {
+                        let (size, crc) = hash_file_with(&full_path, options.algorithm)?;
+                        self.rehashed += 1;
+                        Entry {
+                            path,
+                            kind: EntryKind::File { size, crc },
+                            mtime,
+                        }
+                    }
+                };
+                self.entries.push(entry);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{create, glob_match, refresh, Entry, EntryKind, Manifest, Options, SymlinkPolicy};
+    use crate::catalog;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn tree(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("crclib-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("sub/target")).unwrap();
+        fs::write(root.join("hello.txt"), "hello world").unwrap();
+        fs::write(root.join("sub/b.bin"), [0u8; 100]).unwrap();
+        fs::write(root.join("sub/a.tmp"), "scratch").unwrap();
+        fs::write(root.join("sub/target/c"), "").unwrap();
+        root
+    }
+
+    #[test]
+    fn create_test() {
+        let root = tree("create");
+        let options = Options {
+            ignore: vec!["*.tmp".into(), "sub/target".into()],
+            ..Options::default()
+        };
+        let manifest = create(&root, &options).unwrap();
+        let paths: Vec<&str> = manifest.entries().iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, ["hello.txt", "sub/b.bin"]);
+        assert_eq!(
+            manifest.get("hello.txt").unwrap().kind,
+            EntryKind::File {
+                size: 11,
+                crc: 0xC994_65AA
+            }
+        );
+        assert_eq!(create(&root, &options).unwrap(), manifest);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn algorithm_test() {
+        let root = tree("algorithm");
+        let options = Options {
+            algorithm: catalog::by_name("CRC-64/XZ").unwrap(),
+            ..Options::default()
+        };
+        let manifest = create(&root, &options).unwrap();
+        assert_eq!(
+            manifest.get("hello.txt").unwrap().kind,
+            EntryKind::File {
+                size: 11,
+                crc: options.algorithm.checksum(b"hello world")
+            }
+        );
+        let text = manifest.to_string();
+        assert!(text.starts_with("# crclib manifest v2 CRC-64/XZ\n"));
+        assert_eq!(text.parse::<Manifest>(), Ok(manifest.clone()));
+        assert!("# crclib manifest v2 CRC-99\n".parse::<Manifest>().is_err());
+
+        // A CRC-32C manifest cannot vouch for CRC-64 checksums.
+        let stored = create(&root, &Options::default()).unwrap();
+        assert!(stored
+            .to_string()
+            .starts_with("# crclib manifest v2 crc32c\n"));
+        let refreshed = refresh(&root, &stored, &options).unwrap();
+        assert_eq!(refreshed.rehashed, 4);
+        assert_eq!(refreshed.manifest, manifest);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_test() {
+        let root = tree("symlink");
+        std::os::unix::fs::symlink("sub/target", root.join("link")).unwrap();
+        let with = |symlinks| {
+            create(
+                &root,
+                &Options {
+                    symlinks,
+                    ..Options::default()
+                },
+            )
+        };
+        assert!(with(SymlinkPolicy::Skip).unwrap().get("link").is_none());
+        assert_eq!(
+            with(SymlinkPolicy::Record)
+                .unwrap()
+                .get("link")
+                .unwrap()
+                .kind,
+            EntryKind::Symlink {
+                target: "sub/target".into()
+            }
+        );
+        assert!(with(SymlinkPolicy::Follow).unwrap().get("link/c").is_some());
+
+        std::os::unix::fs::symlink("..", root.join("sub/target/up")).unwrap();
+        assert!(with(SymlinkPolicy::Follow).is_err());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn serialize_diff_test() {
+        let file = |path: &str, crc| Entry {
+            path: path.into(),
+            kind: EntryKind::File { size: 1, crc },
+            mtime: Some(crc * 1_000_000_007),
+        };
+        let old = Manifest::from_entries(vec![file("b", 2), file("a\tb", 1), file("c", 3)]);
+        let text = old.to_string();
+        assert_eq!(text.parse::<Manifest>(), Ok(old.clone()));
+        assert!("file\tzz\t1\tx".parse::<Manifest>().is_err());
+        let v1: Manifest = "file\t00000001\t1\ta".parse().unwrap();
+        assert_eq!(v1.get("a").unwrap().mtime, None);
+
+        let new = Manifest::from_entries(vec![file("a\tb", 1), file("c", 4), file("d", 5)]);
+        let diff = old.diff(&new);
+        assert_eq!(diff.added, ["d"]);
+        assert_eq!(diff.removed, ["b"]);
+        assert_eq!(diff.modified, ["c"]);
+        assert!(new.diff(&new).is_empty());
+
+        assert!(glob_match("*.t?p", "a.tmp"));
+        assert!(!glob_match("*.tmp", "a.tmp.bak"));
+    }
+
+    #[test]
+    fn refresh_test() {
+        let root = tree("refresh");
+        let stored = create(&root, &Options::default()).unwrap();
+        let unchanged = refresh(&root, &stored, &Options::default()).unwrap();
+        assert_eq!(unchanged.rehashed, 0);
+        assert!(unchanged.diff.is_empty());
+        assert_eq!(unchanged.manifest, stored);
+
+        // Same size, new content: only detected because the modification time moves.
+        let file = fs::File::options()
+            .write(true)
+            .open(root.join("hello.txt"))
+            .unwrap();
+        fs::write(root.join("hello.txt"), "HELLO WORLD").unwrap();
+        file.set_modified(UNIX_EPOCH + Duration::from_secs(1_000_000_000))
+            .unwrap();
+        fs::remove_file(root.join("sub/a.tmp")).unwrap();
+        fs::write(root.join("new"), "new").unwrap();
+
+        let refreshed = refresh(&root, &stored, &Options::default()).unwrap();
+        assert_eq!(refreshed.rehashed, 2);
+        assert_eq!(refreshed.diff.added, ["new"]);
+        assert_eq!(refreshed.diff.removed, ["sub/a.tmp"]);
+        assert_eq!(refreshed.diff.modified, ["hello.txt"]);
+        assert_eq!(
+            refreshed.manifest,
+            create(&root, &Options::default()).unwrap()
+        );
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn tree_digest_test() {
+        let file = |path: &str, crc| Entry {
+            path: path.into(),
+            kind: EntryKind::File { size: 4, crc },
+            mtime: None,
+        };
+        let tree = Manifest::from_entries(vec![file("a/x", 1), file("a-b", 2), file("c/y", 3)]);
+        let reordered =
+            Manifest::from_entries(vec![file("c/y", 3), file("a/x", 1), file("a-b", 2)]);
+        assert_eq!(tree.tree_digest(), reordered.tree_digest());
+
+        let digests = tree.directory_digests();
+        assert_eq!(
+            digests.keys().map(String::as_str).collect::<Vec<_>>(),
+            ["", "a", "c"]
+        );
+        assert_eq!(digests[""], tree.tree_digest());
+
+        let changed = Manifest::from_entries(vec![file("a/x", 1), file("a-b", 2), file("c/y", 4)]);
+        let changed_digests = changed.directory_digests();
+        assert_ne!(changed.tree_digest(), tree.tree_digest());
+        assert_eq!(changed_digests["a"], digests["a"]);
+        assert_ne!(changed_digests["c"], digests["c"]);
+
+        // Moving a file changes the digest even though the multiset of checksums is equal.
+        let moved = Manifest::from_entries(vec![file("a/x", 1), file("a-b", 2), file("a/y", 3)]);
+        assert_ne!(moved.tree_digest(), tree.tree_digest());
+    }
+}
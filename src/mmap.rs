@@ -0,0 +1,137 @@
+//! Checksums of memory-mapped files.
+//!
+//! [`checksum_file_mmap`] maps the file a window at a time and hands each window to a
+//! [`Crc`] as one slice, so the data goes from the page cache straight into the hardware or
+//! sliced-table path without being copied into a read buffer. Windows are 1 GiB on 64-bit
+//! targets, so files larger than RAM or address space are fine. On non-Unix targets the file
+//! is read in 1 MiB chunks instead.
+//!
+//! Mapping a file that another process truncates meanwhile makes the kernel raise `SIGBUS`
+//! when the vanished pages are touched; only map files that are not being modified.
+
+use crate::engine::Width;
+use crate::strategy::Strategy;
+use crate::{trace, Crc};
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+/// Computes the checksum of the file at `path` with `crc`.
+pub fn checksum_file_mmap<W: Width, S: Strategy>(crc: &Crc<W, S>, path: &Path) -> io::Result<W> {
+    let _span = trace::file_span("checksum_file_mmap", path);
+    let file = File::open(path)?;
+    let mut digest = crc.digest();
+    update_file(&mut |data| digest.update(data), &file, WINDOW)?;
+    Ok(digest.finalize())
+}
+
+const WINDOW: u64 = if cfg!(target_pointer_width = "64") {
+    1 << 30
+} else {
+    1 << 26
+};
+
+/// Feeds the file to `update` in mappings of `window` bytes, a multiple of the page size.
+#[cfg(unix)]
+fn update_file(update: &mut dyn FnMut(&[u8]), file: &File, window: u64) -> io::Result<()> {
+    let size = file.metadata()?.len();
+    let mut offset = 0;
+    while offset < size {
+        let len = (size - offset).min(window) as usize;
+        let mapping = Mapping::new(file, offset, len)?;
+        update(mapping.bytes());
+        offset += len as u64;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn update_file(update: &mut dyn FnMut(&[u8]), mut file: &File, _window: u64) -> io::Result<()> {
+    let mut buf = vec![0; 1 << 20];
+    loop {
+        let n = crate::compare::read_full(&mut file, &mut buf)?;
+        if n == 0 {
+            return Ok(());
+        }
+        update(&buf[..n]);
+    }
+}
+
+/// A read-only mapping of part of a file, unmapped on drop.
+#[cfg(unix)]
+struct Mapping {
+    ptr: *mut libc::c_void,
+    len: usize,
+}
+
+#[cfg(unix)]
+impl Mapping {
+    /// Maps `len` bytes, `len` being non-zero, from `offset`, which must be a multiple of
+    /// the page size.
+    fn new(file: &File, offset: u64, len: usize) -> io::Result<Self> {
+        use std::os::unix::io::AsRawFd;
+
+        // SAFETY: a fresh private read-only mapping at an address of the kernel's choosing
+        // cannot alias any Rust object.
+        let ptr = unsafe {
+            libc::mmap(
+                core::ptr::null_mut(),
+                len,
+                libc::PROT_READ,
+                libc::MAP_PRIVATE,
+                file.as_raw_fd(),
+                offset as libc::off_t,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: only a hint about the mapping created above; failure is harmless.
+        unsafe { libc::posix_madvise(ptr, len, libc::POSIX_MADV_SEQUENTIAL) };
+        Ok(Self { ptr, len })
+    }
+
+    fn bytes(&self) -> &[u8] {
+        // SAFETY: the mapping is readable and `len` bytes long until `self` is dropped.
+        unsafe { core::slice::from_raw_parts(self.ptr.cast(), self.len) }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        // SAFETY: unmaps exactly the mapping created in `new`, which no slice outlives.
+        unsafe { libc::munmap(self.ptr, self.len) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog;
+    use std::fs;
+
+    #[test]
+    fn mmap_test() {
+        let path = std::env::temp_dir().join(format!("crclib-mmap-{}", std::process::id()));
+        let contents: Vec<u8> = (0..3_000_001u32)
+            .map(|i| (i * 7 + i / 4099) as u8)
+            .collect();
+        fs::write(&path, &contents).unwrap();
+        let crc = Crc::new(&catalog::CRC_64_XZ);
+        assert_eq!(
+            checksum_file_mmap(&crc, &path).unwrap(),
+            crc.checksum(&contents)
+        );
+        // Several windows, 64 KiB being a multiple of every common page size.
+        let mut digest = crc.digest();
+        let file = File::open(&path).unwrap();
+        update_file(&mut |data| digest.update(data), &file, 1 << 16).unwrap();
+        assert_eq!(digest.finalize(), crc.checksum(&contents));
+
+        fs::write(&path, b"").unwrap();
+        assert_eq!(checksum_file_mmap(&crc, &path).unwrap(), crc.checksum(b""));
+        fs::remove_file(&path).unwrap();
+        assert!(checksum_file_mmap(&crc, &path).is_err());
+    }
+}
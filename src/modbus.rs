@@ -0,0 +1,60 @@
+//! Modbus RTU frame checks.
+//!
+//! An RTU frame is the slave address, the function code and its data, followed by the
+//! CRC-16/MODBUS of those bytes with the low byte first. The reflected parameters are those
+//! of [`catalog::CRC_16_MODBUS`](crate::catalog::CRC_16_MODBUS); ASCII mode frames use an
+//! LRC instead and are not covered here.
+
+use crate::oneshot::crc16_modbus;
+use crate::trace;
+
+/// The shortest valid frame: address, function code and CRC.
+pub const MIN_FRAME_LEN: usize = 4;
+
+/// The CRC of a frame without its trailer.
+pub fn crc(frame: &[u8]) -> u16 {
+    crc16_modbus(frame)
+}
+
+/// Appends the CRC trailer to `frame`.
+#[cfg(feature = "std")]
+pub fn append_crc(frame: &mut Vec<u8>) {
+    let crc = crc(frame);
+    frame.extend_from_slice(&crc.to_le_bytes());
+}
+
+/// Checks the CRC trailer of a received frame; frames shorter than [`MIN_FRAME_LEN`] fail.
+pub fn verify_frame(frame: &[u8]) -> bool {
+    if frame.len() < MIN_FRAME_LEN {
+        return false;
+    }
+    let (body, trailer) = frame.split_at(frame.len() - 2);
+    let stored = u16::from_le_bytes([trailer[0], trailer[1]]);
+    let computed = crc(body);
+    trace::verified(
+        "CRC-16/MODBUS",
+        "Modbus RTU frame",
+        stored as u64,
+        computed as u64,
+    );
+    stored == computed
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modbus_test() {
+        // Read ten holding registers from slave 1.
+        let mut frame = vec![0x01, 0x03, 0x00, 0x00, 0x00, 0x0A];
+        assert_eq!(crc(&frame), 0xCDC5);
+        append_crc(&mut frame);
+        assert_eq!(frame[6..], [0xC5, 0xCD]);
+        assert!(verify_frame(&frame));
+
+        frame[3] ^= 0x01;
+        assert!(!verify_frame(&frame));
+        assert!(!verify_frame(&[0x01, 0x03, 0x00]));
+    }
+}
@@ -0,0 +1,144 @@
+//! The `CRC_32` field of MPEG-2 transport stream PSI/SI sections.
+//!
+//! Sections with `section_syntax_indicator` set (PAT, PMT, NIT, SDT, EIT, ...) end with the
+//! CRC-32/MPEG-2 of everything before it, stored big-endian. The section length is taken
+//! from the 12-bit `section_length` field, so a slice may extend past the section, e.g.
+//! into the `0xFF` stuffing that fills the rest of the transport packet.
+
+use crate::engine::Params;
+use crate::trace;
+
+const CRC_32_MPEG_2: Params<u32> = Params {
+    width: 32,
+    poly: 0x04C1_1DB7,
+    init: 0xFFFF_FFFF,
+    refin: false,
+    refout: false,
+    xorout: 0,
+};
+
+/// Bytes up to and including the `section_length` field.
+const HEADER_LEN: usize = 3;
+const CRC_LEN: usize = 4;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SectionError {
+    /// The slice is shorter than the section's `section_length` says.
+    Truncated,
+    /// `section_syntax_indicator` is clear, so the section carries no CRC (e.g. a TDT).
+    NoCrc,
+    Mismatch {
+        expected: u32,
+        actual: u32,
+    },
+}
+
+impl core::fmt::Display for SectionError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Truncated => write!(f, "section is truncated"),
+            Self::NoCrc => write!(f, "section has no CRC_32 field"),
+            Self::Mismatch { expected, actual } => write!(
+                f,
+                "section CRC_32 mismatch: field has {:#010X}, computed {:#010X}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SectionError {}
+
+/// The length of the section at the start of `data`, including its three header bytes.
+pub fn section_len(data: &[u8]) -> Result<usize, SectionError> {
+    let [_, hi, lo, ..] = *data else {
+        return Err(SectionError::Truncated);
+    };
+    let len = HEADER_LEN + (u16::from_be_bytes([hi, lo]) & 0x0FFF) as usize;
+    if data.len() < len {
+        return Err(SectionError::Truncated);
+    }
+    Ok(len)
+}
+
+/// Splits the section at the start of `data` into the bytes covered by the CRC and the CRC
+/// field.
+fn split(data: &[u8]) -> Result<(&[u8], &[u8]), SectionError> {
+    let len = section_len(data)?;
+    if data[1] & 0x80 == 0 {
+        return Err(SectionError::NoCrc);
+    }
+    let covered = len.checked_sub(CRC_LEN).ok_or(SectionError::Truncated)?;
+    Ok(data[..len].split_at(covered))
+}
+
+/// Computes the CRC_32 the section at the start of `data` should carry.
+pub fn section_crc(data: &[u8]) -> Result<u32, SectionError> {
+    let (covered, _) = split(data)?;
+    Ok(CRC_32_MPEG_2.checksum(covered))
+}
+
+/// Checks the CRC_32 field of the section at the start of `data`.
+pub fn verify_section(data: &[u8]) -> Result<(), SectionError> {
+    let (covered, field) = split(data)?;
+    let expected = u32::from_be_bytes(field.try_into().unwrap());
+    let actual = CRC_32_MPEG_2.checksum(covered);
+    trace::verified(
+        "CRC-32/MPEG-2",
+        "PSI section",
+        expected as u64,
+        actual as u64,
+    );
+    if expected != actual {
+        return Err(SectionError::Mismatch { expected, actual });
+    }
+    Ok(())
+}
+
+/// Recomputes and stores the CRC_32 field of the section at the start of `data`, e.g. after
+/// rewriting a PMT, and returns it.
+pub fn fix_section(data: &mut [u8]) -> Result<u32, SectionError> {
+    let crc = section_crc(data)?;
+    let end = section_len(data)?;
+    data[end - CRC_LEN..end].copy_from_slice(&crc.to_be_bytes());
+    Ok(crc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A single-program PAT as found at the start of many transport streams.
+    const PAT: [u8; 16] = [
+        0x00, 0xB0, 0x0D, 0x00, 0x01, 0xC1, 0x00, 0x00, 0x00, 0x01, 0xF0, 0x00, 0x2A, 0xB1, 0x04,
+        0xB2,
+    ];
+
+    #[test]
+    fn section_test() {
+        let check = crc::Crc::<u32>::new(&crc::CRC_32_MPEG_2).checksum(b"123456789");
+        assert_eq!(CRC_32_MPEG_2.checksum(b"123456789"), check);
+
+        let mut packet = [0xFF; 24];
+        packet[..16].copy_from_slice(&PAT);
+        assert_eq!(section_len(&packet), Ok(16));
+        assert_eq!(verify_section(&packet), Ok(()));
+        assert_eq!(section_crc(&packet), Ok(0x2AB1_04B2));
+
+        packet[9] = 0x02;
+        assert!(matches!(
+            verify_section(&packet),
+            Err(SectionError::Mismatch { .. })
+        ));
+        assert_ne!(fix_section(&mut packet), Ok(0x2AB1_04B2));
+        assert_eq!(verify_section(&packet), Ok(()));
+        assert_eq!(packet[16], 0xFF);
+
+        assert_eq!(verify_section(&PAT[..15]), Err(SectionError::Truncated));
+        // A TDT: section_syntax_indicator clear, no CRC.
+        let tdt = [0x70, 0x70, 0x05, 0xE8, 0x2A, 0x12, 0x00, 0x00];
+        assert_eq!(verify_section(&tdt), Err(SectionError::NoCrc));
+    }
+}
@@ -0,0 +1,100 @@
+//! Several checksums of the same data in one pass.
+//!
+//! A [`MultiDigest`] feeds every [`update`](MultiDigest::update) to all of its digests,
+//! 4 KiB at a time, so each block is still in L1 cache when the next algorithm reads it and
+//! the data is only streamed from memory once however many algorithms there are.
+
+use crate::DynCrc;
+
+/// Bytes handed to each digest in turn.
+const BLOCK: usize = 4096;
+
+/// Digests of any widths and algorithms that are updated together.
+///
+/// ```text
+/// let crc32 = Crc::new(&catalog::CRC_32_ISO_HDLC);
+/// let mut multi = MultiDigest::new()
+///     .with(crc32.digest())
+///     .with(CRC64::from_algorithm(&catalog::CRC_64_XZ));
+/// multi.update(artifact);
+/// let [crc32, crc64] = multi.finalize()[..] else { unreachable!() };
+/// ```
+#[derive(Default)]
+pub struct MultiDigest<'a> {
+    digests: Vec<Box<dyn DynCrc + 'a>>,
+}
+
+impl<'a> MultiDigest<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `digest`, e.g. a [`Digest`](crate::Digest) or a state such as
+    /// [`CRC32`](crate::CRC32).
+    pub fn with(mut self, digest: impl DynCrc + 'a) -> Self {
+        self.push(digest);
+        self
+    }
+
+    pub fn push(&mut self, digest: impl DynCrc + 'a) {
+        self.digests.push(Box::new(digest));
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        for block in data.chunks(BLOCK) {
+            for digest in &mut self.digests {
+                digest.dyn_update(block);
+            }
+        }
+    }
+
+    /// The checksums so far, in the order the digests were added.
+    pub fn finalize(&self) -> Vec<u128> {
+        self.digests
+            .iter()
+            .map(|digest| digest.dyn_finalize())
+            .collect()
+    }
+
+    /// Resets every digest for the next message.
+    pub fn reset(&mut self) {
+        for digest in &mut self.digests {
+            digest.dyn_reset();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{catalog, Crc, CRC, CRC64};
+
+    #[test]
+    fn multi_test() {
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i * 3 + i / 251) as u8).collect();
+        let crc32 = Crc::new(&catalog::CRC_32_ISO_HDLC);
+        let xz = CRC64::from_algorithm(&catalog::CRC_64_XZ);
+        let mut multi = MultiDigest::new().with(crc32.digest()).with(xz.clone());
+        multi.update(&data[..5000]);
+        multi.update(&data[5000..]);
+        let mut expected = xz;
+        expected.update(&data);
+        assert_eq!(
+            multi.finalize(),
+            [
+                u128::from(crc32.checksum(&data)),
+                expected.finalize().into()
+            ]
+        );
+        multi.reset();
+        multi.update(b"123456789");
+        assert_eq!(
+            multi.finalize(),
+            [
+                u128::from(catalog::CRC_32_ISO_HDLC.check),
+                catalog::CRC_64_XZ.check.into()
+            ]
+        );
+        assert!(MultiDigest::new().finalize().is_empty());
+    }
+}
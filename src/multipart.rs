@@ -0,0 +1,100 @@
+//! CRC-32C bookkeeping for multipart uploads.
+//!
+//! Object stores such as GCS (compose) and S3 (full-object checksums) report the CRC-32C of
+//! the final object rather than of its parts. [`MultipartCrc32c`] records the checksum and
+//! length of every part as it is uploaded and derives the object checksum with a CRC
+//! combine, so the expected value is known before the upload is completed.
+//!
+//! Note that S3's `COMPOSITE` checksum type is a checksum of the part checksums and is not
+//! what [`MultipartCrc32c::object_checksum`] computes.
+
+use crate::oneshot::{crc32c, crc32c_combine};
+
+/// The CRC-32C and length of a single uploaded part.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Part {
+    pub crc: u32,
+    pub length: u64,
+}
+
+impl Part {
+    /// Checksums `data` as a part.
+    pub fn from_data(data: &[u8]) -> Self {
+        Self {
+            crc: crc32c(data),
+            length: data.len() as u64,
+        }
+    }
+}
+
+/// Collects the parts of a multipart upload in order.
+#[derive(Clone, Debug, Default)]
+pub struct MultipartCrc32c {
+    parts: Vec<Part>,
+}
+
+impl MultipartCrc32c {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Checksums `data` as the next part and returns its checksum.
+    pub fn add_part(&mut self, data: &[u8]) -> Part {
+        let part = Part::from_data(data);
+        self.parts.push(part);
+        part
+    }
+
+    /// Records a part whose checksum was computed elsewhere, e.g. by another worker.
+    pub fn push_part(&mut self, part: Part) {
+        self.parts.push(part);
+    }
+
+    pub fn parts(&self) -> &[Part] {
+        &self.parts
+    }
+
+    /// Total length of the composed object.
+    pub fn length(&self) -> u64 {
+        self.parts.iter().map(|part| part.length).sum()
+    }
+
+    /// The CRC-32C of the object made by concatenating all parts in order.
+    pub fn object_checksum(&self) -> u32 {
+        compose(&self.parts)
+    }
+}
+
+/// Composes the CRC-32C of the concatenation of `parts`.
+pub fn compose(parts: &[Part]) -> u32 {
+    parts.iter().fold(crc32c(&[]), |crc, part| {
+        crc32c_combine(crc, part.crc, part.length)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compose, crc32c, MultipartCrc32c, Part};
+
+    const TEST_DATA: &[u8] = b"hello world, this is a multipart upload";
+
+    #[test]
+    fn object_checksum_test() {
+        let mut upload = MultipartCrc32c::new();
+        for chunk in TEST_DATA.chunks(7) {
+            upload.add_part(chunk);
+        }
+        assert_eq!(upload.parts().len(), 6);
+        assert_eq!(upload.length(), TEST_DATA.len() as u64);
+        assert_eq!(upload.object_checksum(), crc32c(TEST_DATA));
+    }
+
+    #[test]
+    fn compose_test() {
+        assert_eq!(compose(&[]), crc32c(&[]));
+
+        let (a, b) = TEST_DATA.split_at(10);
+        let parts = [Part::from_data(a), Part::from_data(b)];
+        assert_eq!(compose(&parts), crc32c(TEST_DATA));
+    }
+}
@@ -0,0 +1,256 @@
+//! NVMe end-to-end protection information (PI) guard tags.
+//!
+//! The 16-bit guard format is the T10 DIF compatible layout (CRC-16/T10-DIF guard, 16-bit
+//! application tag, 32-bit reference tag in 8 bytes); the 64-bit guard format carries a
+//! CRC-64/NVME guard, a 16-bit application tag and a 48-bit reference tag in 16 bytes. All
+//! fields are big-endian.
+//!
+//! The PI occupies either the first or the last bytes of each block's metadata. When it is
+//! last, the guard also covers the metadata bytes in front of it.
+
+use crate::const_crc::{Crc16T10Dif, Crc64Nvme};
+use crate::trace;
+use crate::CRC;
+
+/// The guard tag type of a protection information format.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Guard {
+    Crc16,
+    Crc64,
+}
+
+/// Where the protection information sits within the metadata of a block.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PiLocation {
+    First,
+    Last,
+}
+
+/// The decoded protection information of a block.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ProtectionInfo {
+    pub guard: u64,
+    pub app_tag: u16,
+    pub ref_tag: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PiError {
+    /// The metadata buffer is smaller than the format's metadata size, or the metadata size
+    /// cannot hold the protection information.
+    MetadataTooSmall,
+    GuardMismatch {
+        expected: u64,
+        actual: u64,
+    },
+}
+
+impl core::fmt::Display for PiError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::MetadataTooSmall => write!(f, "metadata too small for protection information"),
+            Self::GuardMismatch { expected, actual } => write!(
+                f,
+                "guard tag mismatch: metadata has {:#X}, computed {:#X}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PiError {}
+
+/// The CRC-16/T10-DIF guard over `data`.
+pub fn guard16(data: &[u8]) -> u16 {
+    guard::<Crc16T10Dif>(&[data])
+}
+
+/// The CRC-64/NVME guard over `data`.
+pub fn guard64(data: &[u8]) -> u64 {
+    guard::<Crc64Nvme>(&[data])
+}
+
+fn guard<C: CRC>(parts: &[&[u8]]) -> C::Width {
+    let mut crc = C::default();
+    for part in parts {
+        crc.update(part);
+    }
+    crc.finalize()
+}
+
+/// Describes the metadata layout of a formatted namespace.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PiFormat {
+    pub guard: Guard,
+    /// Metadata bytes per logical block, including the protection information.
+    pub metadata_size: usize,
+    pub location: PiLocation,
+}
+
+impl PiFormat {
+    /// Length of the protection information in bytes.
+    pub fn pi_len(&self) -> usize {
+        match self.guard {
+            Guard::Crc16 => 8,
+            Guard::Crc64 => 16,
+        }
+    }
+
+    fn pi_range(&self, metadata: &[u8]) -> Result<core::ops::Range<usize>, PiError> {
+        if self.metadata_size < self.pi_len() || metadata.len() < self.metadata_size {
+            return Err(PiError::MetadataTooSmall);
+        }
+        Ok(match self.location {
+            PiLocation::First => 0..self.pi_len(),
+            PiLocation::Last => self.metadata_size - self.pi_len()..self.metadata_size,
+        })
+    }
+
+    /// Computes the guard tag of a logical block and its metadata.
+    pub fn guard(&self, block: &[u8], metadata: &[u8]) -> Result<u64, PiError> {
+        let covered = &metadata[..self.pi_range(metadata)?.start];
+        Ok(match self.guard {
+            Guard::Crc16 => guard::<Crc16T10Dif>(&[block, covered]) as u64,
+            Guard::Crc64 => guard::<Crc64Nvme>(&[block, covered]),
+        })
+    }
+
+    /// Writes the protection information for `block` into `metadata`.
+    ///
+    /// The reference tag is truncated to the format's 32 or 48 bits.
+    pub fn insert(
+        &self,
+        block: &[u8],
+        metadata: &mut [u8],
+        app_tag: u16,
+        ref_tag: u64,
+    ) -> Result<ProtectionInfo, PiError> {
+        let guard = self.guard(block, metadata)?;
+        let range = self.pi_range(metadata)?;
+        let pi = &mut metadata[range];
+        match self.guard {
+            Guard::Crc16 => {
+                pi[..2].copy_from_slice(&(guard as u16).to_be_bytes());
+                pi[2..4].copy_from_slice(&app_tag.to_be_bytes());
+                pi[4..8].copy_from_slice(&(ref_tag as u32).to_be_bytes());
+            }
+            Guard::Crc64 => {
+                pi[..8].copy_from_slice(&guard.to_be_bytes());
+                pi[8..10].copy_from_slice(&app_tag.to_be_bytes());
+                pi[10..16].copy_from_slice(&ref_tag.to_be_bytes()[2..]);
+            }
+        }
+        self.read(metadata)
+    }
+
+    /// Decodes the protection information stored in `metadata` without verifying it.
+    pub fn read(&self, metadata: &[u8]) -> Result<ProtectionInfo, PiError> {
+        let pi = &metadata[self.pi_range(metadata)?];
+        let be = |bytes: &[u8]| bytes.iter().fold(0u64, |value, &b| value << 8 | b as u64);
+        Ok(match self.guard {
+            Guard::Crc16 => ProtectionInfo {
+                guard: be(&pi[..2]),
+                app_tag: be(&pi[2..4]) as u16,
+                ref_tag: be(&pi[4..8]),
+            },
+            Guard::Crc64 => ProtectionInfo {
+                guard: be(&pi[..8]),
+                app_tag: be(&pi[8..10]) as u16,
+                ref_tag: be(&pi[10..16]),
+            },
+        })
+    }
+
+    /// Checks the guard tag stored in `metadata` against `block`.
+    pub fn verify(&self, block: &[u8], metadata: &[u8]) -> Result<ProtectionInfo, PiError> {
+        let pi = self.read(metadata)?;
+        let actual = self.guard(block, metadata)?;
+        let algorithm = match self.guard {
+            Guard::Crc16 => "CRC-16/T10-DIF",
+            Guard::Crc64 => "CRC-64/NVME",
+        };
+        trace::verified(algorithm, "protection information guard", pi.guard, actual);
+        if pi.guard != actual {
+            return Err(PiError::GuardMismatch {
+                expected: pi.guard,
+                actual,
+            });
+        }
+        Ok(pi)
+    }
+
+    /// Splits an extended logical block (data immediately followed by its metadata).
+    ///
+    /// Returns `None` if `extended` is shorter than the metadata size.
+    pub fn split_extended<'a>(&self, extended: &'a [u8]) -> Option<(&'a [u8], &'a [u8])> {
+        let data_len = extended.len().checked_sub(self.metadata_size)?;
+        Some(extended.split_at(data_len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{guard16, guard64, Guard, PiError, PiFormat, PiLocation};
+
+    #[test]
+    fn guard_test() {
+        assert_eq!(guard16(b"123456789"), 0xD0DB);
+        assert_eq!(guard64(b"123456789"), 0xAE8B_1486_0A79_9888);
+        // 4 KiB of zeros, as written for a freshly formatted block.
+        assert_eq!(guard64(&[0; 4096]), 0x6482_D367_EB22_B64E);
+    }
+
+    #[test]
+    fn insert_verify_test() {
+        let block: Vec<u8> = (0..=255).cycle().take(512).collect();
+        for guard in [Guard::Crc16, Guard::Crc64] {
+            for location in [PiLocation::First, PiLocation::Last] {
+                let format = PiFormat {
+                    guard,
+                    metadata_size: 32,
+                    location,
+                };
+                let mut extended = block.clone();
+                extended.extend_from_slice(&[0xA5; 32]);
+                let (data, metadata) = extended.split_at_mut(512);
+                let pi = format.insert(data, metadata, 0x1234, 0xABCD).unwrap();
+                assert_eq!(pi.app_tag, 0x1234);
+                assert_eq!(pi.ref_tag, 0xABCD);
+
+                let (data, metadata) = format.split_extended(&extended).unwrap();
+                assert_eq!(format.verify(data, metadata), Ok(pi));
+
+                // Metadata in front of a trailing PI is covered by the guard, metadata
+                // after a leading PI is not.
+                match location {
+                    PiLocation::First => extended[512 + format.pi_len()] ^= 1,
+                    PiLocation::Last => extended[512] ^= 1,
+                }
+                let (data, metadata) = format.split_extended(&extended).unwrap();
+                let result = format.verify(data, metadata);
+                match location {
+                    PiLocation::First => assert_eq!(result, Ok(pi)),
+                    PiLocation::Last => {
+                        assert!(matches!(result, Err(PiError::GuardMismatch { .. })))
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn metadata_too_small_test() {
+        let format = PiFormat {
+            guard: Guard::Crc64,
+            metadata_size: 8,
+            location: PiLocation::First,
+        };
+        assert_eq!(
+            format.guard(&[0; 512], &[0; 8]),
+            Err(PiError::MetadataTooSmall)
+        );
+    }
+}
@@ -0,0 +1,140 @@
+//! One-shot checksums for the popular algorithms.
+//!
+//! `crc32(data)` replaces creating, updating and finalizing a state when the whole message
+//! is at hand. The functions use the compile-time tables of the [`const_crc`](crate::const_crc)
+//! presets, so they cost nothing up front.
+
+use crate::const_crc::{
+    Crc16Ibm3740, Crc16Kermit, Crc16Modbus, Crc16Xmodem, Crc32Iscsi, Crc32IsoHdlc, Crc64Nvme,
+    Crc64Xz, Crc8Smbus,
+};
+use crate::engine::Params;
+use crate::CRC;
+
+fn checksum<C: CRC>(data: &[u8]) -> C::Width {
+    let mut crc = C::default();
+    crc.update(data);
+    crc.finalize()
+}
+
+/// CRC-8/SMBUS.
+pub fn crc8(data: &[u8]) -> u8 {
+    checksum::<Crc8Smbus>(data)
+}
+
+/// CRC-16/IBM-3740, also known as CRC-16/CCITT-FALSE.
+pub fn crc16_ibm_3740(data: &[u8]) -> u16 {
+    checksum::<Crc16Ibm3740>(data)
+}
+
+/// CRC-16/KERMIT.
+pub fn crc16_kermit(data: &[u8]) -> u16 {
+    checksum::<Crc16Kermit>(data)
+}
+
+/// CRC-16/MODBUS.
+pub fn crc16_modbus(data: &[u8]) -> u16 {
+    checksum::<Crc16Modbus>(data)
+}
+
+/// CRC-16/XMODEM.
+pub fn crc16_xmodem(data: &[u8]) -> u16 {
+    checksum::<Crc16Xmodem>(data)
+}
+
+/// CRC-32/ISO-HDLC, as in zlib, gzip and PNG.
+pub fn crc32(data: &[u8]) -> u32 {
+    checksum::<Crc32IsoHdlc>(data)
+}
+
+/// CRC-32/ISCSI, the Castagnoli CRC-32C.
+pub fn crc32c(data: &[u8]) -> u32 {
+    checksum::<Crc32Iscsi>(data)
+}
+
+/// CRC-64/NVME.
+pub fn crc64_nvme(data: &[u8]) -> u64 {
+    checksum::<Crc64Nvme>(data)
+}
+
+/// CRC-64/XZ.
+pub fn crc64_xz(data: &[u8]) -> u64 {
+    checksum::<Crc64Xz>(data)
+}
+
+/// The [`crc32`] of `a` followed by `b`, given `crc32(a)`, `crc32(b)` and the length of `b`,
+/// as zlib's `crc32_combine`.
+pub fn crc32_combine(crc_a: u32, crc_b: u32, len_b: u64) -> u32 {
+    Params::from(Crc32IsoHdlc::PARAMS).combine(crc_a, crc_b, len_b)
+}
+
+/// [`crc32_combine`] for [`crc32c`].
+pub fn crc32c_combine(crc_a: u32, crc_b: u32, len_b: u64) -> u32 {
+    Params::from(Crc32Iscsi::PARAMS).combine(crc_a, crc_b, len_b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog;
+
+    #[test]
+    fn oneshot_test() {
+        const CHECK: &[u8] = b"123456789";
+        assert_eq!(crc8(CHECK), catalog::CRC_8_SMBUS.check);
+        assert_eq!(crc16_ibm_3740(CHECK), 0x29B1);
+        assert_eq!(crc16_kermit(CHECK), catalog::CRC_16_KERMIT.check);
+        assert_eq!(crc16_modbus(CHECK), 0x4B37);
+        assert_eq!(crc16_xmodem(CHECK), catalog::CRC_16_XMODEM.check);
+        assert_eq!(crc32(CHECK), 0xCBF4_3926);
+        assert_eq!(crc32c(CHECK), 0xE306_9283);
+        assert_eq!(crc64_nvme(CHECK), catalog::CRC_64_NVME.check);
+        assert_eq!(crc64_xz(CHECK), catalog::CRC_64_XZ.check);
+        assert_eq!(crc32(b""), 0);
+        let zlib = crc::Crc::<u32>::new(&crc::CRC_32_ISO_HDLC);
+        assert_eq!(crc32(b"hello world"), zlib.checksum(b"hello world"));
+        let large: Vec<u8> = (0..100_000u32).map(|i| (i ^ i >> 9) as u8).collect();
+        assert_eq!(crc32(&large), zlib.checksum(&large));
+        let xz = crc::Crc::<u64>::new(&crc::CRC_64_XZ);
+        assert_eq!(crc64_xz(&large), xz.checksum(&large));
+        let (a, b) = large.split_at(12_345);
+        assert_eq!(
+            crc32_combine(crc32(a), crc32(b), b.len() as u64),
+            crc32(&large)
+        );
+        assert_eq!(
+            crc32c_combine(crc32c(a), crc32c(b), b.len() as u64),
+            crc32c(&large)
+        );
+    }
+
+    #[test]
+    fn crc32c_test() {
+        use crate::{Crc, CRC32};
+
+        // RFC 3720, appendix B.4.
+        let ascending: Vec<u8> = (0..32).collect();
+        let descending: Vec<u8> = (0..32).rev().collect();
+        let vectors: [(&[u8], u32); 4] = [
+            (&[0x00; 32], 0x8A91_36AA),
+            (&[0xFF; 32], 0x62A8_AB43),
+            (&ascending, 0x46DD_794E),
+            (&descending, 0x113F_DB5C),
+        ];
+        let crc = Crc::new(&catalog::CRC_32_ISCSI);
+        for (data, expected) in vectors {
+            assert_eq!(crc32c(data), expected);
+            assert_eq!(crc.checksum(data), expected);
+            let mut state = CRC32::from_algorithm(&catalog::CRC_32_ISCSI);
+            state.update(data);
+            assert_eq!(state.finalize(), expected);
+        }
+        // Long enough for the CRC instructions and carry-less folding to kick in.
+        let large: Vec<u8> = (0..65_537u32)
+            .map(|i| ((i * 17) ^ (i >> 7)) as u8)
+            .collect();
+        let reference = crc::Crc::<u32>::new(&crc::CRC_32_ISCSI);
+        assert_eq!(crc32c(&large), reference.checksum(&large));
+        assert_eq!(crc.checksum(&large[3..]), reference.checksum(&large[3..]));
+    }
+}
@@ -0,0 +1,45 @@
+//! Dallas/Maxim 1-Wire CRCs.
+//!
+//! 1-Wire devices protect their 64-bit ROM code and most memory reads with
+//! [CRC-8/MAXIM-DOW](crate::catalog::CRC_8_MAXIM_DOW): polynomial `0x31`, processed
+//! LSB-first (`0x8C` reflected) from a zero register. The CRC byte comes last, so the CRC
+//! over a whole intact block including it is zero.
+
+use crate::const_crc::Crc8MaximDow;
+
+/// The CRC-8/MAXIM-DOW of `bytes`.
+pub const fn crc(bytes: &[u8]) -> u8 {
+    Crc8MaximDow::checksum(bytes)
+}
+
+/// Checks a ROM code as read with Read ROM or Search ROM: family code, 48-bit serial number
+/// and the CRC of both.
+pub const fn verify_rom_code(rom: &[u8; 8]) -> bool {
+    crc(rom) == 0
+}
+
+/// Checks a DS18B20-style 9-byte scratchpad whose last byte is the CRC of the other eight.
+pub const fn verify_scratchpad(scratchpad: &[u8; 9]) -> bool {
+    crc(scratchpad) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn onewire_test() {
+        // The ROM code of Maxim application note 27.
+        let mut rom = [0x02, 0x1C, 0xB8, 0x01, 0x00, 0x00, 0x00, 0xA2];
+        assert_eq!(crc(&rom[..7]), 0xA2);
+        assert!(verify_rom_code(&rom));
+        rom[3] ^= 0x10;
+        assert!(!verify_rom_code(&rom));
+
+        // A DS18B20 scratchpad after power-up: 85 °C.
+        let mut scratchpad = [0x50, 0x05, 0x4B, 0x46, 0x7F, 0xFF, 0x0C, 0x10, 0x1C];
+        assert!(verify_scratchpad(&scratchpad));
+        scratchpad[8] = 0;
+        assert!(!verify_scratchpad(&scratchpad));
+    }
+}
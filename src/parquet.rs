@@ -0,0 +1,337 @@
+//! Verification of the optional CRC-32 stored in Parquet page headers.
+//!
+//! A Parquet page is a Thrift compact-protocol `PageHeader` followed by
+//! `compressed_page_size` bytes of page data. When the writer filled in the header's `crc`
+//! field it holds the CRC-32 (as used by gzip) of exactly those page bytes. Only the handful
+//! of header fields needed for the check are decoded; everything else is skipped.
+
+use crate::oneshot::crc32;
+use crate::trace;
+
+/// Errors produced while verifying a page.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PageError {
+    /// The buffer ends before the header or page data does.
+    Truncated,
+    /// The header is not a valid Thrift compact-protocol `PageHeader`.
+    Malformed,
+    /// The stored CRC does not match the page data.
+    Mismatch { expected: u32, actual: u32 },
+}
+
+impl core::fmt::Display for PageError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Truncated => write!(f, "page is truncated"),
+            Self::Malformed => write!(f, "malformed page header"),
+            Self::Mismatch { expected, actual } => write!(
+                f,
+                "page CRC mismatch: header says {:#010X}, data has {:#010X}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PageError {}
+
+/// The fields of a `PageHeader` relevant to integrity checking.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PageHeader {
+    pub page_type: i32,
+    pub uncompressed_page_size: i32,
+    pub compressed_page_size: i32,
+    /// The stored CRC-32, if the writer provided one.
+    pub crc: Option<u32>,
+    /// Length of the encoded header in bytes; the page data follows immediately.
+    pub header_len: usize,
+}
+
+/// Checks the page data against the `crc` value from its header.
+///
+/// Thrift stores the field as a signed `i32`, so it is accepted here as-is.
+pub fn verify_page_crc(crc: i32, page_data: &[u8]) -> Result<(), PageError> {
+    let expected = crc as u32;
+    let actual = crc32(page_data);
+    trace::verified("CRC-32", "Parquet page", expected as u64, actual as u64);
+    if expected == actual {
+        Ok(())
+    } else {
+        Err(PageError::Mismatch { expected, actual })
+    }
+}
+
+/// Parses the page header at the start of `bytes` and verifies the page data following it.
+///
+/// Pages without a stored CRC are returned with `crc: None`, since there is nothing to
+/// check; it is up to the caller whether that counts as a failure.
+pub fn verify_page(bytes: &[u8]) -> Result<PageHeader, PageError> {
+    let header = parse_page_header(bytes)?;
+    let size = usize::try_from(header.compressed_page_size).map_err(|_| PageError::Malformed)?;
+    let data = bytes[header.header_len..]
+        .get(..size)
+        .ok_or(PageError::Truncated)?;
+    if let Some(crc) = header.crc {
+        verify_page_crc(crc as i32, data)?;
+    }
+    Ok(header)
+}
+
+/// Decodes the page header at the start of `bytes`.
+pub fn parse_page_header(bytes: &[u8]) -> Result<PageHeader, PageError> {
+    let mut reader = Reader {
+        bytes,
+        pos: 0,
+        depth: 0,
+    };
+    let mut page_type = None;
+    let mut uncompressed_page_size = None;
+    let mut compressed_page_size = None;
+    let mut crc = None;
+    let mut field_id = 0i16;
+    loop {
+        let header = reader.byte()?;
+        if header == STOP {
+            break;
+        }
+        let field_type = header & 0x0F;
+        field_id = match header >> 4 {
+            0 => reader.varint().map(zigzag)? as i16,
+            delta => field_id
+                .checked_add(delta as i16)
+                .ok_or(PageError::Malformed)?,
+        };
+        match (field_id, field_type) {
+            (1, I32) => page_type = Some(reader.i32()?),
+            (2, I32) => uncompressed_page_size = Some(reader.i32()?),
+            (3, I32) => compressed_page_size = Some(reader.i32()?),
+            (4, I32) => crc = Some(reader.i32()? as u32),
+            _ => reader.skip(field_type)?,
+        }
+    }
+    Ok(PageHeader {
+        page_type: page_type.ok_or(PageError::Malformed)?,
+        uncompressed_page_size: uncompressed_page_size.ok_or(PageError::Malformed)?,
+        compressed_page_size: compressed_page_size.ok_or(PageError::Malformed)?,
+        crc,
+        header_len: reader.pos,
+    })
+}
+
+const STOP: u8 = 0;
+const BOOL_TRUE: u8 = 1;
+const BOOL_FALSE: u8 = 2;
+const BYTE: u8 = 3;
+const I16: u8 = 4;
+const I32: u8 = 5;
+const I64: u8 = 6;
+const DOUBLE: u8 = 7;
+const BINARY: u8 = 8;
+const LIST: u8 = 9;
+const SET: u8 = 10;
+const MAP: u8 = 11;
+const STRUCT: u8 = 12;
+
+fn zigzag(value: u64) -> i64 {
+    (value >> 1) as i64 ^ -((value & 1) as i64)
+}
+
+/// How deeply structs, lists and maps may nest in a skipped value. Page headers nest a few
+/// levels at most; the limit keeps hostile input from exhausting the stack.
+const MAX_DEPTH: usize = 64;
+
+/// A cursor over Thrift compact-protocol data.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    /// How many containers the value being skipped is nested in.
+    depth: usize,
+}
+
+impl Reader<'_> {
+    fn byte(&mut self) -> Result<u8, PageError> {
+        let byte = *self.bytes.get(self.pos).ok_or(PageError::Truncated)?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    fn advance(&mut self, len: usize) -> Result<(), PageError> {
+        if self.bytes.len() - self.pos < len {
+            return Err(PageError::Truncated);
+        }
+        self.pos += len;
+        Ok(())
+    }
+
+    fn varint(&mut self) -> Result<u64, PageError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7F) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(PageError::Malformed)
+    }
+
+    fn i32(&mut self) -> Result<i32, PageError> {
+        i32::try_from(zigzag(self.varint()?)).map_err(|_| PageError::Malformed)
+    }
+
+    fn length(&mut self) -> Result<usize, PageError> {
+        usize::try_from(self.varint()?).map_err(|_| PageError::Malformed)
+    }
+
+    /// Skips a value of the given compact type.
+    fn skip(&mut self, value_type: u8) -> Result<(), PageError> {
+        if !matches!(value_type, LIST | SET | MAP | STRUCT) {
+            return self.skip_value(value_type);
+        }
+        if self.depth == MAX_DEPTH {
+            return Err(PageError::Malformed);
+        }
+        self.depth += 1;
+        let result = self.skip_value(value_type);
+        self.depth -= 1;
+        result
+    }
+
+    fn skip_value(&mut self, value_type: u8) -> Result<(), PageError> {
+        match value_type {
+            // Inside a struct a boolean is encoded in the field type itself.
+            BOOL_TRUE | BOOL_FALSE => Ok(()),
+            BYTE => self.advance(1),
+            I16 | I32 | I64 => self.varint().map(drop),
+            DOUBLE => self.advance(8),
+            BINARY => {
+                let len = self.length()?;
+                self.advance(len)
+            }
+            LIST | SET => {
+                let header = self.byte()?;
+                let len = match header >> 4 {
+                    15 => self.length()?,
+                    len => len as usize,
+                };
+                self.skip_elements(header & 0x0F, len)
+            }
+            MAP => {
+                let len = self.length()?;
+                if len == 0 {
+                    return Ok(());
+                }
+                let types = self.byte()?;
+                for _ in 0..len {
+                    self.skip_elements(types >> 4, 1)?;
+                    self.skip_elements(types & 0x0F, 1)?;
+                }
+                Ok(())
+            }
+            STRUCT => loop {
+                let header = self.byte()?;
+                if header == STOP {
+                    return Ok(());
+                }
+                if header >> 4 == 0 {
+                    self.varint()?;
+                }
+                self.skip(header & 0x0F)?;
+            },
+            _ => Err(PageError::Malformed),
+        }
+    }
+
+    /// Skips `len` collection elements; unlike struct fields, booleans take a byte here.
+    fn skip_elements(&mut self, element_type: u8, len: usize) -> Result<(), PageError> {
+        for _ in 0..len {
+            match element_type {
+                BOOL_TRUE | BOOL_FALSE => self.advance(1)?,
+                element_type => self.skip(element_type)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_page_header, verify_page, PageError};
+    use crate::oneshot::crc32;
+
+    fn zigzag_varint(value: i32, out: &mut Vec<u8>) {
+        let mut value = ((value << 1) ^ (value >> 31)) as u32;
+        while value >= 0x80 {
+            out.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        out.push(value as u8);
+    }
+
+    /// Encodes a data page header with a nested `DataPageHeader` struct.
+    fn page(data: &[u8], crc: Option<u32>) -> Vec<u8> {
+        let mut page = Vec::new();
+        page.push(0x15);
+        zigzag_varint(0, &mut page);
+        page.push(0x15);
+        zigzag_varint(data.len() as i32, &mut page);
+        page.push(0x15);
+        zigzag_varint(data.len() as i32, &mut page);
+        if let Some(crc) = crc {
+            page.push(0x15);
+            zigzag_varint(crc as i32, &mut page);
+            page.push(0x1C);
+        } else {
+            page.push(0x2C);
+        }
+        // DataPageHeader { num_values: 3, encoding: 0, ..., statistics: { max: "z" } }
+        page.extend_from_slice(&[0x15, 0x06, 0x15, 0x00, 0x15, 0x00, 0x15, 0x00]);
+        page.extend_from_slice(&[0x1C, 0x18, 0x01, b'z', 0x00]);
+        page.push(0x00);
+        page.push(0x00);
+        page.extend_from_slice(data);
+        page
+    }
+
+    #[test]
+    fn verify_page_test() {
+        let data = b"hello world";
+        let crc = crc32(data);
+        let header = verify_page(&page(data, Some(crc))).expect("valid page");
+        assert_eq!(header.crc, Some(crc));
+        assert_eq!(header.compressed_page_size, data.len() as i32);
+
+        let header = verify_page(&page(data, None)).expect("valid page");
+        assert_eq!(header.crc, None);
+
+        let mut corrupt = page(data, Some(crc));
+        *corrupt.last_mut().unwrap() ^= 1;
+        assert!(matches!(
+            verify_page(&corrupt),
+            Err(PageError::Mismatch { expected, .. }) if expected == crc
+        ));
+    }
+
+    #[test]
+    fn truncated_test() {
+        let data = b"hello world";
+        let page = page(data, Some(crc32(data)));
+        assert_eq!(
+            verify_page(&page[..page.len() - 1]),
+            Err(PageError::Truncated)
+        );
+        assert_eq!(parse_page_header(&page[..5]), Err(PageError::Truncated));
+    }
+
+    #[test]
+    fn malformed_test() {
+        // Structs nested a million deep, and field ids counting past `i16::MAX`.
+        let mut nested = vec![0x15, 0x00];
+        nested.resize(1_000_002, 0x1C);
+        assert_eq!(parse_page_header(&nested), Err(PageError::Malformed));
+        assert_eq!(parse_page_header(&[0xF1; 5000]), Err(PageError::Malformed));
+    }
+}
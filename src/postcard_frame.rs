@@ -0,0 +1,150 @@
+//! Postcard encoding with an appended CRC-32 for message buses.
+//!
+//! A frame is the postcard serialization of a value followed by the CRC-32/ISO-HDLC of
+//! those bytes in little-endian byte order (matching postcard's own little-endian layout).
+
+use crate::oneshot::crc32;
+use serde::{Deserialize, Serialize};
+
+/// Length of the CRC trailer.
+pub const CRC_LEN: usize = 4;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FrameError {
+    Postcard(postcard::Error),
+    /// The frame is too short to contain a CRC trailer, or the buffer is too small for it.
+    Truncated,
+    Mismatch {
+        expected: u32,
+        actual: u32,
+    },
+}
+
+impl core::fmt::Display for FrameError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Postcard(err) => write!(f, "postcard: {}", err),
+            Self::Truncated => write!(f, "frame is truncated"),
+            Self::Mismatch { expected, actual } => write!(
+                f,
+                "frame CRC mismatch: trailer says {:#010X}, payload has {:#010X}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+/// postcard's error only implements `defmt::Format` with its own `use-defmt` feature, so it
+/// goes through `Debug`.
+#[cfg(feature = "defmt")]
+impl defmt::Format for FrameError {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            Self::Postcard(err) => defmt::write!(f, "Postcard({})", defmt::Debug2Format(err)),
+            Self::Truncated => defmt::write!(f, "Truncated"),
+            Self::Mismatch { expected, actual } => defmt::write!(
+                f,
+                "Mismatch {{ expected: {=u32:#010x}, actual: {=u32:#010x} }}",
+                expected,
+                actual
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FrameError {}
+
+impl From<postcard::Error> for FrameError {
+    fn from(err: postcard::Error) -> Self {
+        Self::Postcard(err)
+    }
+}
+
+/// Serializes `value` into `buf` followed by its CRC and returns the used part of `buf`.
+pub fn to_slice_crc<'a, T: Serialize + ?Sized>(
+    value: &T,
+    buf: &'a mut [u8],
+) -> Result<&'a mut [u8], FrameError> {
+    let capacity = buf
+        .len()
+        .checked_sub(CRC_LEN)
+        .ok_or(FrameError::Truncated)?;
+    let len = postcard::to_slice(value, &mut buf[..capacity])?.len();
+    let crc = crc32(&buf[..len]);
+    buf[len..len + CRC_LEN].copy_from_slice(&crc.to_le_bytes());
+    Ok(&mut buf[..len + CRC_LEN])
+}
+
+/// Serializes `value` into a new vector followed by its CRC.
+#[cfg(feature = "std")]
+pub fn to_vec_crc<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, FrameError> {
+    let mut frame = postcard::to_allocvec(value)?;
+    let crc = crc32(&frame);
+    frame.extend_from_slice(&crc.to_le_bytes());
+    Ok(frame)
+}
+
+/// Checks the CRC trailer of `frame` and deserializes the payload in front of it.
+pub fn from_bytes_crc<'a, T: Deserialize<'a>>(frame: &'a [u8]) -> Result<T, FrameError> {
+    let split = frame
+        .len()
+        .checked_sub(CRC_LEN)
+        .ok_or(FrameError::Truncated)?;
+    let (payload, trailer) = frame.split_at(split);
+    let expected = u32::from_le_bytes(trailer.try_into().unwrap());
+    let actual = crc32(payload);
+    if expected != actual {
+        return Err(FrameError::Mismatch { expected, actual });
+    }
+    Ok(postcard::from_bytes(payload)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_bytes_crc, to_slice_crc, to_vec_crc, FrameError};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Telemetry<'a> {
+        id: u16,
+        temperature: i32,
+        label: &'a str,
+    }
+
+    const MESSAGE: Telemetry<'static> = Telemetry {
+        id: 7,
+        temperature: -40,
+        label: "boiler",
+    };
+
+    #[test]
+    fn roundtrip_test() {
+        let frame = to_vec_crc(&MESSAGE).unwrap();
+        assert_eq!(from_bytes_crc::<Telemetry>(&frame), Ok(MESSAGE));
+
+        let mut buf = [0u8; 32];
+        let slice = to_slice_crc(&MESSAGE, &mut buf).unwrap();
+        assert_eq!(slice, &frame[..]);
+    }
+
+    #[test]
+    fn corrupt_test() {
+        let mut frame = to_vec_crc(&MESSAGE).unwrap();
+        frame[1] ^= 0x10;
+        assert!(matches!(
+            from_bytes_crc::<Telemetry>(&frame),
+            Err(FrameError::Mismatch { .. })
+        ));
+        assert_eq!(
+            from_bytes_crc::<Telemetry>(&frame[..3]),
+            Err(FrameError::Truncated)
+        );
+
+        let mut buf = [0u8; 4];
+        assert!(matches!(
+            to_slice_crc(&MESSAGE, &mut buf),
+            Err(FrameError::Postcard(_))
+        ));
+    }
+}
@@ -0,0 +1,196 @@
+//! Python bindings through PyO3.
+//!
+//! ```text
+//! import crclib
+//!
+//! crc = crclib.Crc("CRC-32C")
+//! with open(path, "rb") as f:
+//!     while chunk := f.read(1 << 20):
+//!         crc.update(chunk)
+//! assert crc.finalize() == expected
+//! assert crclib.crc32(memoryview(frame)[4:]) == crclib.checksum("CRC-32", frame[4:])
+//! ```
+//!
+//! Data is taken through the buffer protocol, so `bytes`, `bytearray`, `memoryview`, `mmap`
+//! and byte-typed numpy arrays all work, and contiguous buffers are read in place. Checksums
+//! come back as Python ints and run the same code as [`CRC32`](crate::CRC32) and friends in
+//! the Rust services. To get an extension module, build with
+//! `cargo rustc --release --features python,pyo3/extension-module --crate-type cdylib` and
+//! rename the library to `crclib.so` (`crclib.pyd` on Windows).
+
+use crate::checksum::Checksum;
+use crate::{catalog, oneshot, DynCrc};
+use pyo3::buffer::PyBuffer;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+/// Runs `f` on the bytes of `data`, copying them only if the buffer is not contiguous.
+fn with_bytes<R>(py: Python<'_>, data: &PyBuffer<u8>, f: impl FnOnce(&[u8]) -> R) -> PyResult<R> {
+    if !data.is_c_contiguous() {
+        return Ok(f(&data.to_vec(py)?));
+    }
+    if data.len_bytes() == 0 {
+        return Ok(f(&[]));
+    }
+    // SAFETY: the exporter keeps a contiguous buffer of `len_bytes` bytes alive for as long as
+    // `data` holds it, and nothing can run Python code that resizes it while we hold the GIL.
+    let bytes =
+        unsafe { core::slice::from_raw_parts(data.buf_ptr() as *const u8, data.len_bytes()) };
+    Ok(f(bytes))
+}
+
+fn lookup(name: &str) -> PyResult<catalog::AnyAlgorithm> {
+    catalog::by_name(name).map_err(|err| PyValueError::new_err(err.to_string()))
+}
+
+/// A streaming checksum of a catalogued algorithm.
+#[pyclass(name = "Crc", module = "crclib")]
+pub struct PyCrc {
+    state: Box<dyn DynCrc + Send + Sync>,
+    /// The catalogue name of the algorithm.
+    #[pyo3(get)]
+    name: &'static str,
+    /// The width of the checksum in bits.
+    #[pyo3(get)]
+    width: u32,
+}
+
+#[pymethods]
+impl PyCrc {
+    /// Raises `ValueError` for a name that is not in the catalogue.
+    #[new]
+    fn new(name: &str) -> PyResult<Self> {
+        let algorithm = lookup(name)?;
+        Ok(Self {
+            state: algorithm.state(),
+            name: algorithm.name(),
+            width: algorithm.width(),
+        })
+    }
+
+    fn update(&mut self, py: Python<'_>, data: PyBuffer<u8>) -> PyResult<()> {
+        with_bytes(py, &data, |bytes| self.state.dyn_update(bytes))
+    }
+
+    /// The checksum so far.
+    fn finalize(&self) -> u128 {
+        self.state.dyn_finalize()
+    }
+
+    /// The checksum so far as upper-case hex, zero-padded to the width.
+    fn hex(&self) -> String {
+        Checksum::new(self.finalize() as u64, self.width).to_string()
+    }
+
+    fn reset(&mut self) {
+        self.state.dyn_reset();
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Crc({:?}, checksum=0x{})", self.name, self.hex())
+    }
+}
+
+/// The names of every catalogued algorithm, as accepted by `Crc(name)`.
+#[pyfunction]
+fn algorithms() -> Vec<&'static str> {
+    catalog::algorithms()
+        .map(|algorithm| algorithm.name())
+        .collect()
+}
+
+/// The checksum of `data` with the catalogued algorithm `name`.
+#[pyfunction]
+fn checksum(py: Python<'_>, name: &str, data: PyBuffer<u8>) -> PyResult<u128> {
+    let mut state = lookup(name)?.state();
+    with_bytes(py, &data, |bytes| state.dyn_update(bytes))?;
+    Ok(state.dyn_finalize())
+}
+
+macro_rules! oneshot_functions {
+    ($($name:ident: $n:ty),* $(,)?) => {
+        $(
+            #[doc = concat!("See [`oneshot::", stringify!($name), "`].")]
+            #[pyfunction]
+            fn $name(py: Python<'_>, data: PyBuffer<u8>) -> PyResult<$n> {
+                with_bytes(py, &data, oneshot::$name)
+            }
+        )*
+
+        fn add_oneshot_functions(module: &Bound<'_, PyModule>) -> PyResult<()> {
+            $(module.add_function(wrap_pyfunction!($name, module)?)?;)*
+            Ok(())
+        }
+    };
+}
+
+oneshot_functions!(
+    crc8: u8,
+    crc16_ibm_3740: u16,
+    crc16_kermit: u16,
+    crc16_modbus: u16,
+    crc16_xmodem: u16,
+    crc32: u32,
+    crc32c: u32,
+    crc64_nvme: u64,
+    crc64_xz: u64,
+);
+
+#[pymodule]
+fn crclib(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyCrc>()?;
+    module.add_function(wrap_pyfunction!(algorithms, module)?)?;
+    module.add_function(wrap_pyfunction!(checksum, module)?)?;
+    add_oneshot_functions(module)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::call::PyCallArgs;
+    use pyo3::types::{PyByteArray, PyBytes, PyMemoryView, PySlice};
+
+    fn call<'py>(module: &Bound<'py, PyModule>, name: &str, args: impl PyCallArgs<'py>) -> u128 {
+        let result = module.getattr(name).unwrap().call1(args).unwrap();
+        result.extract().unwrap()
+    }
+
+    #[test]
+    fn python_test() {
+        Python::initialize();
+        Python::attach(|py| {
+            let module = PyModule::new(py, "crclib").unwrap();
+            crclib(&module).unwrap();
+            let data = PyBytes::new(py, b"123456789");
+            assert_eq!(call(&module, "crc32", (&data,)), 0xCBF4_3926);
+            assert_eq!(call(&module, "crc32c", (&data,)), 0xE306_9283);
+            let array = PyByteArray::new(py, b"123456789");
+            assert_eq!(call(&module, "checksum", ("CRC-16/MODBUS", &array)), 0x4B37);
+
+            // A strided view is copied first.
+            let strided = PyBytes::new(py, b"1x2x3x4x5x6x7x8x9x");
+            let view = PyMemoryView::from(strided.as_any()).unwrap();
+            let every_other = view.get_item(PySlice::new(py, 0, 18, 2)).unwrap();
+            assert_eq!(
+                call(&module, "crc64_xz", (&every_other,)),
+                0x995D_C9BB_DF19_39FA
+            );
+
+            let crc = module.getattr("Crc").unwrap().call1(("crc-32c",)).unwrap();
+            crc.call_method1("update", (PyBytes::new(py, b"12345"),))
+                .unwrap();
+            crc.call_method1("update", (PyByteArray::new(py, b"6789"),))
+                .unwrap();
+            let hex: String = crc.call_method0("hex").unwrap().extract().unwrap();
+            assert_eq!(hex, "E3069283");
+            let name: String = crc.getattr("name").unwrap().extract().unwrap();
+            assert_eq!(name, "CRC-32/ISCSI");
+            crc.call_method0("reset").unwrap();
+            let crc: u128 = crc.call_method0("finalize").unwrap().extract().unwrap();
+            assert_eq!(crc, 0);
+
+            let error = module.getattr("Crc").unwrap().call1(("CRC-3000",));
+            assert!(error.unwrap_err().is_instance_of::<PyValueError>(py));
+        });
+    }
+}
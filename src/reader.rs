@@ -0,0 +1,101 @@
+//! Checksumming data as it is read.
+//!
+//! A [`CrcReader`] wraps a download, file or decompressor input and computes the CRC of
+//! everything read through it, so the data can be verified while it is consumed rather
+//! than in a second pass.
+
+use crate::CRC;
+use std::io::{self, BufRead, Read};
+use std::marker::PhantomData;
+
+/// Forwards reads from an inner reader and computes the CRC of the bytes returned.
+pub struct CrcReader<R, C, N> {
+    inner: R,
+    crc: C,
+    _checksum: PhantomData<N>,
+}
+
+impl<R, C, N> CrcReader<R, C, N>
+where
+    R: Read,
+    C: CRC<Width = N>,
+{
+    pub fn new(inner: R, crc: C) -> Self {
+        Self {
+            inner,
+            crc,
+            _checksum: PhantomData,
+        }
+    }
+
+    /// The CRC of the bytes read so far, that of the whole stream once a read returned 0.
+    pub fn checksum(&self) -> N {
+        self.crc.finalize()
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// The inner reader. Reading from it directly bypasses the CRC.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R, C, N> Read for CrcReader<R, C, N>
+where
+    R: Read,
+    C: CRC<Width = N>,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.crc.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+/// Bytes count once they are consumed, not when `fill_buf` returns them.
+impl<R, C, N> BufRead for CrcReader<R, C, N>
+where
+    R: BufRead,
+    C: CRC<Width = N>,
+{
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        if let Ok(buf) = self.inner.fill_buf() {
+            self.crc.update(&buf[..amt]);
+        }
+        self.inner.consume(amt);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CrcReader;
+    use crate::{CRC, CRC32};
+    use std::io::{self, BufRead, Read};
+
+    #[test]
+    fn reader_test() {
+        let mut reader = CrcReader::new(&b"hello world"[..], CRC32::default());
+        let mut head = [0; 4];
+        reader.read_exact(&mut head).unwrap();
+        assert_eq!(io::copy(&mut reader, &mut io::sink()).unwrap(), 7);
+        assert_eq!(reader.checksum(), 0x44F71378);
+
+        let mut reader = CrcReader::new(&b"hello\nworld"[..], CRC32::default());
+        let lines: Vec<String> = reader.by_ref().lines().map(Result::unwrap).collect();
+        assert_eq!(lines, ["hello", "world"]);
+        let mut crc = CRC32::default();
+        crc.update(b"hello\nworld");
+        assert_eq!(reader.checksum(), crc.finalize());
+    }
+}
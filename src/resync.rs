@@ -0,0 +1,146 @@
+//! Recovering frame boundaries from raw captures by their trailing CRC.
+//!
+//! Sniffed UART and RS-485 traffic often has no reliable sync bytes: idle gaps are lost,
+//! captures start mid-frame, and line noise inserts or drops bytes. A [`Scanner`] instead
+//! tries every frame length in a range at every offset and reports the spans whose last
+//! bytes are the CRC of the rest. Each hit is a likely frame, not a certain one: an n-bit
+//! CRC matches random data with probability 2^-n per length tried, so wide length ranges
+//! over 8- or 16-bit CRCs will report some noise as frames.
+
+use crate::framing::{read_uint, Endian};
+use crate::CRC;
+use core::marker::PhantomData;
+use core::ops::RangeInclusive;
+
+/// A span of the capture whose trailing CRC matches.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Candidate {
+    pub offset: usize,
+    /// Length of the frame including its CRC.
+    pub len: usize,
+}
+
+impl Candidate {
+    /// The frame's bytes within `data`, the capture that was scanned.
+    pub fn frame<'a>(&self, data: &'a [u8]) -> &'a [u8] {
+        &data[self.offset..self.offset + self.len]
+    }
+}
+
+/// Iterator over the likely frames in a capture.
+///
+/// At each offset, the lengths in range are tried shortest first. After a hit the scan
+/// continues behind the frame; otherwise it moves on by one byte, skipping it as noise.
+pub struct Scanner<'a, F, N> {
+    data: &'a [u8],
+    pos: usize,
+    lengths: RangeInclusive<usize>,
+    crc_endian: Endian,
+    create: F,
+    _checksum: PhantomData<N>,
+}
+
+impl<'a, F, N> Scanner<'a, F, N> {
+    /// Scans `data` for frames whose total length, CRC included, is in `lengths`, using
+    /// CRC states from `create`. Lengths shorter than the CRC itself are skipped.
+    pub fn new(
+        data: &'a [u8],
+        lengths: RangeInclusive<usize>,
+        crc_endian: Endian,
+        create: F,
+    ) -> Self {
+        Self {
+            data,
+            pos: 0,
+            lengths,
+            crc_endian,
+            create,
+            _checksum: PhantomData,
+        }
+    }
+}
+
+impl<F, C, N> Iterator for Scanner<'_, F, N>
+where
+    F: Fn() -> C,
+    C: CRC<Width = N>,
+    N: Into<u128>,
+{
+    type Item = Candidate;
+
+    fn next(&mut self) -> Option<Candidate> {
+        let crc_len = core::mem::size_of::<N>();
+        let min_len = (*self.lengths.start()).max(crc_len);
+        while self.pos + min_len <= self.data.len() {
+            let rest = &self.data[self.pos..];
+            let max_len = (*self.lengths.end()).min(rest.len());
+            // Covered bytes are fed once as the candidate length grows.
+            let mut crc = (self.create)();
+            crc.update(&rest[..min_len - crc_len]);
+            for len in min_len..=max_len {
+                let expected = read_uint(&rest[len - crc_len..len], self.crc_endian);
+                if crc.finalize().into() == expected {
+                    let candidate = Candidate {
+                        offset: self.pos,
+                        len,
+                    };
+                    self.pos += len;
+                    return Some(candidate);
+                }
+                crc.update(&rest[len - crc_len..len - crc_len + 1]);
+            }
+            self.pos += 1;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CRC16, CRC32};
+
+    fn frame(payload: &[u8]) -> Vec<u8> {
+        let mut crc = CRC32::default();
+        crc.update(payload);
+        [payload, &crc.finalize().to_le_bytes()].concat()
+    }
+
+    #[test]
+    fn scan_test() {
+        let (a, b) = (
+            frame(b"\x01\x03\x00\x10\x00\x02"),
+            frame(b"\x01\x03\x04ABCD"),
+        );
+        // A capture joined mid-frame, with a noise byte between the frames.
+        let capture = [&a[2..], &a[..], &[0x55][..], &b[..]].concat();
+        let found: Vec<_> =
+            Scanner::new(&capture, 6..=32, Endian::Little, CRC32::default).collect();
+        assert_eq!(
+            found,
+            [
+                Candidate { offset: 8, len: 10 },
+                Candidate {
+                    offset: 19,
+                    len: 11
+                },
+            ]
+        );
+        assert_eq!(found[1].frame(&capture), &b[..]);
+
+        // Lengths the range does not allow are passed over.
+        assert_eq!(
+            Scanner::new(&capture, 11..=11, Endian::Little, CRC32::default).count(),
+            1
+        );
+        assert_eq!(
+            Scanner::new(&capture, 6..=32, Endian::Big, CRC32::default).count(),
+            0
+        );
+        assert_eq!(
+            Scanner::new(b"\x00", 0..=4, Endian::Big, CRC16::default).next(),
+            None
+        );
+    }
+}
@@ -0,0 +1,712 @@
+//! CRC algorithm definitions in the CRC RevEng catalogue format.
+//!
+//! The catalogue, and `reveng` itself, describe an algorithm as one line of `key=value`
+//! pairs:
+//!
+//! ```text
+//! width=16 poly=0x1021 init=0xffff refin=false refout=false xorout=0x0000 check=0x29b1 residue=0x0000 name="CRC-16/IBM-3740"
+//! ```
+//!
+//! [`Algorithm`] parses such lines via [`FromStr`] and formats them back via
+//! [`Display`](fmt::Display). [`CrcParams`] parse from the same lines, and convert into an
+//! [`Algorithm`] to be printed. `width`, `poly`, `init`, `refin`, `refout` and `xorout` are
+//! required; `check`, `residue` and `name` may be left out, as in `reveng` search output.
+//! A `check` value that is present must match the algorithm, which catches most copy and
+//! paste mistakes.
+//!
+//! [`search`] does what `reveng -s` does: given messages and their checksums from an
+//! undocumented protocol, it finds the algorithms that produce them. XORing two samples of
+//! the same length cancels `init` and `xorout`, leaving a multiple of the generator
+//! polynomial, so the polynomial divides the GCD of all such differences; `init` and
+//! `xorout` then follow from a linear system. Samples of several lengths are needed to tell
+//! `init` from `xorout`; when they are all the same length the conventional values are
+//! reported.
+
+use crate::catalog::{self, AnyAlgorithm};
+use crate::engine::{Params, Width};
+use crate::CrcParams;
+use std::fmt;
+use std::str::FromStr;
+
+/// A CRC algorithm of up to 128 bits.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Algorithm {
+    pub width: u32,
+    pub poly: u128,
+    pub init: u128,
+    pub refin: bool,
+    pub refout: bool,
+    pub xorout: u128,
+    /// The checksum of `"123456789"`.
+    pub check: Option<u128>,
+    /// The register after processing a codeword, before `xorout`.
+    pub residue: Option<u128>,
+    pub name: Option<String>,
+}
+
+impl Algorithm {
+    fn params(&self) -> Params<u128> {
+        Params {
+            width: self.width,
+            poly: self.poly,
+            init: self.init,
+            refin: self.refin,
+            refout: self.refout,
+            xorout: self.xorout,
+        }
+    }
+
+    pub fn checksum(&self, data: &[u8]) -> u128 {
+        self.params().checksum(data)
+    }
+
+    /// Whether the parameters, ignoring `check`, `residue` and `name`, are the same.
+    fn same_model(&self, other: &Algorithm) -> bool {
+        (
+            self.width,
+            self.poly,
+            self.init,
+            self.refin,
+            self.refout,
+            self.xorout,
+        ) == (
+            other.width,
+            other.poly,
+            other.init,
+            other.refin,
+            other.refout,
+            other.xorout,
+        )
+    }
+}
+
+fn from_entry<W: Width>(entry: &catalog::Algorithm<W>) -> Algorithm {
+    Algorithm {
+        width: entry.params.width,
+        poly: entry.params.poly.into(),
+        init: entry.params.init.into(),
+        refin: entry.params.refin,
+        refout: entry.params.refout,
+        xorout: entry.params.xorout.into(),
+        check: Some(entry.check.into()),
+        residue: Some(entry.residue.into()),
+        name: Some(entry.name.to_string()),
+    }
+}
+
+/// The model of `params`, with its check value and residue and without a name.
+impl<W: Width> From<CrcParams<W>> for Algorithm {
+    fn from(params: CrcParams<W>) -> Self {
+        let engine = Params::from(params);
+        Algorithm {
+            width: params.width,
+            poly: params.poly.into(),
+            init: params.init.into(),
+            refin: params.refin,
+            refout: params.refout,
+            xorout: params.xorout.into(),
+            check: Some(engine.checksum(b"123456789").into()),
+            residue: Some(engine.residue().into()),
+            name: None,
+        }
+    }
+}
+
+impl From<AnyAlgorithm> for Algorithm {
+    fn from(entry: AnyAlgorithm) -> Self {
+        match entry {
+            AnyAlgorithm::Crc8(entry) => from_entry(&entry),
+            AnyAlgorithm::Crc16(entry) => from_entry(&entry),
+            AnyAlgorithm::Crc32(entry) => from_entry(&entry),
+            AnyAlgorithm::Crc64(entry) => from_entry(&entry),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// A token is not a `key=value` pair, or a quoted value is not terminated.
+    Syntax,
+    UnknownKey(String),
+    DuplicateKey(String),
+    MissingKey(&'static str),
+    /// The value of this key is malformed or does not fit the width.
+    InvalidValue(String),
+    /// The stated `check` differs from the algorithm's actual check value.
+    CheckMismatch {
+        stated: u128,
+        actual: u128,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax => write!(f, "expected key=value pairs"),
+            Self::UnknownKey(key) => write!(f, "unknown key `{}`", key),
+            Self::DuplicateKey(key) => write!(f, "duplicate key `{}`", key),
+            Self::MissingKey(key) => write!(f, "missing key `{}`", key),
+            Self::InvalidValue(key) => write!(f, "invalid value for `{}`", key),
+            Self::CheckMismatch { stated, actual } => write!(
+                f,
+                "check value {:#x} does not match the algorithm's {:#x}",
+                stated, actual
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// The error returned by [`search`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SearchError {
+    /// The width is 0 or above 128.
+    InvalidWidth,
+    /// A sample's checksum does not fit the width.
+    ValueTooWide,
+    /// The samples do not pin down the polynomial: there are no two different messages of
+    /// the same length, or too few to narrow the candidates to a searchable number.
+    Underdetermined,
+}
+
+impl fmt::Display for SearchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidWidth => write!(f, "CRC width out of range"),
+            Self::ValueTooWide => write!(f, "checksum does not fit the width"),
+            Self::Underdetermined => write!(f, "not enough samples to find the polynomial"),
+        }
+    }
+}
+
+impl std::error::Error for SearchError {}
+
+/// A polynomial over GF(2), the coefficient of `x^i` in bit `i`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Gf2(Vec<u64>);
+
+impl Gf2 {
+    /// `x^width` plus the low `width` bits of `low`.
+    fn monic(width: u32, low: u128) -> Self {
+        let mut poly = Gf2(vec![low as u64, (low >> 64) as u64, 0]);
+        poly.flip(width as usize);
+        poly
+    }
+
+    /// `message * x^width + crc`, with the message read MSB-first like an unreflected CRC.
+    fn codeword(message: &[u8], width: u32, crc: u128) -> Self {
+        let len = message.len() * 8 + width as usize;
+        let mut poly = Gf2(vec![0; len / 64 + 3]);
+        poly.0[0] = crc as u64;
+        poly.0[1] = (crc >> 64) as u64;
+        for (i, &byte) in message.iter().enumerate() {
+            for bit in 0..8 {
+                if byte >> bit & 1 != 0 {
+                    poly.flip(len - 8 * (i + 1) + bit);
+                }
+            }
+        }
+        poly
+    }
+
+    fn degree(&self) -> Option<usize> {
+        let (index, word) = self.0.iter().enumerate().rev().find(|(_, &w)| w != 0)?;
+        Some(index * 64 + 63 - word.leading_zeros() as usize)
+    }
+
+    fn flip(&mut self, bit: usize) {
+        if self.0.len() <= bit / 64 {
+            self.0.resize(bit / 64 + 1, 0);
+        }
+        self.0[bit / 64] ^= 1 << (bit % 64);
+    }
+
+    /// Adds `other * x^shift`.
+    fn add_shifted(&mut self, other: &Gf2, shift: usize) {
+        let (words, bits) = (shift / 64, shift % 64);
+        if self.0.len() < other.0.len() + words + 1 {
+            self.0.resize(other.0.len() + words + 1, 0);
+        }
+        for (i, &word) in other.0.iter().enumerate() {
+            self.0[i + words] ^= word << bits;
+            if bits != 0 {
+                self.0[i + words + 1] ^= word >> (64 - bits);
+            }
+        }
+    }
+
+    /// Reduces `self` modulo `divisor` and returns the quotient.
+    fn div_rem(&mut self, divisor: &Gf2) -> Gf2 {
+        let degree = divisor.degree().expect("division by zero");
+        let mut quotient = Gf2(Vec::new());
+        while let Some(top) = self.degree().filter(|&top| top >= degree) {
+            self.add_shifted(divisor, top - degree);
+            quotient.flip(top - degree);
+        }
+        quotient
+    }
+
+    fn gcd(mut a: Gf2, mut b: Gf2) -> Gf2 {
+        while b.degree().is_some() {
+            a.div_rem(&b);
+            std::mem::swap(&mut a, &mut b);
+        }
+        a
+    }
+
+    /// The low 128 coefficients.
+    fn low(&self) -> u128 {
+        let word = |i: usize| self.0.get(i).copied().unwrap_or(0) as u128;
+        word(0) | word(1) << 64
+    }
+}
+
+/// The highest degree of candidate factors [`search`] enumerates when the GCD of the
+/// differences has a higher degree than the width.
+const MAX_FACTOR_BITS: usize = 16;
+
+/// The generator polynomials of degree `width` that divide `gcd`, in normal form.
+fn divisors(gcd: &Gf2, width: u32) -> Result<Vec<u128>, SearchError> {
+    let degree = gcd.degree().ok_or(SearchError::Underdetermined)?;
+    let width = width as usize;
+    if degree < width {
+        return Ok(Vec::new());
+    }
+    let extra = degree - width;
+    if extra == 0 {
+        return Ok(vec![gcd.low() & low_mask(width as u32)]);
+    }
+    // Try whichever side of the factorization is smaller.
+    let (bits, cofactor) = if extra <= width {
+        (extra, true)
+    } else {
+        (width, false)
+    };
+    if bits > MAX_FACTOR_BITS {
+        return Err(SearchError::Underdetermined);
+    }
+    let mut found = Vec::new();
+    for low in 0..1u128 << bits {
+        let factor = Gf2::monic(bits as u32, low);
+        let mut rem = gcd.clone();
+        let quotient = rem.div_rem(&factor);
+        if rem.degree().is_none() {
+            let generator = if cofactor { quotient } else { factor };
+            found.push(generator.low() & low_mask(width as u32));
+        }
+    }
+    found.sort_unstable();
+    found.dedup();
+    Ok(found)
+}
+
+fn low_mask(width: u32) -> u128 {
+    u128::MAX >> (128 - width)
+}
+
+/// Solves a linear system over GF(2) given as rows of coefficient bits and a right-hand
+/// side. Returns one solution, with free unknowns zero, and whether it is the only one.
+fn solve(mut rows: Vec<(u128, bool)>, unknowns: u32) -> Option<(u128, bool)> {
+    let mut pivots = Vec::new();
+    for bit in 0..unknowns {
+        let rank = pivots.len();
+        let Some(found) = (rank..rows.len()).find(|&i| rows[i].0 >> bit & 1 != 0) else {
+            continue;
+        };
+        rows.swap(rank, found);
+        let pivot = rows[rank];
+        for (i, row) in rows.iter_mut().enumerate() {
+            if i != rank && row.0 >> bit & 1 != 0 {
+                row.0 ^= pivot.0;
+                row.1 ^= pivot.1;
+            }
+        }
+        pivots.push(bit);
+    }
+    if rows[pivots.len()..].iter().any(|row| row.1) {
+        return None;
+    }
+    let solution = pivots
+        .iter()
+        .zip(&rows)
+        .filter(|(_, row)| row.1)
+        .fold(0, |solution, (&bit, _)| solution | 1 << bit);
+    Some((solution, pivots.len() == unknowns as usize))
+}
+
+/// The `init` values for `poly` consistent with the samples, given the reflection.
+fn inits(params: &Params<u128>, samples: &[(&[u8], u128)]) -> Vec<u128> {
+    let width = params.width;
+    // With zero init and xorout, a checksum is linear in the message; init adds
+    // `T_len(init)`, the checksum of `len` zero bytes, and xorout is added on top.
+    let shifted = |init: u128, len: usize| {
+        params.finalize(params.update_zeros(init & low_mask(width), len as u64))
+    };
+    let offset = |(data, checksum): &(&[u8], u128)| checksum ^ params.checksum(data);
+    let (first, rest) = samples.split_first().expect("no samples");
+    let mut rows = Vec::new();
+    for sample in rest {
+        let rhs = offset(first) ^ offset(sample);
+        let columns: Vec<u128> = (0..width)
+            .map(|j| shifted(1 << j, first.0.len()) ^ shifted(1 << j, sample.0.len()))
+            .collect();
+        for k in 0..width {
+            let row = (0..width).fold(0, |row, j| row | (columns[j as usize] >> k & 1) << j);
+            rows.push((row, rhs >> k & 1 != 0));
+        }
+    }
+    match solve(rows, width) {
+        Some((init, true)) => vec![init],
+        Some((init, false)) => vec![0, low_mask(width), init],
+        None => Vec::new(),
+    }
+}
+
+/// Finds the `width`-bit algorithms that give each message in `samples` its checksum.
+///
+/// Every result reproduces all samples and carries its check value, residue and, if it is
+/// in the [catalogue](crate::catalog), its name. At least two different messages of the same
+/// length are needed to find the polynomial; more samples, and samples of different
+/// lengths, narrow the results down. For polynomials divisible by `x + 1` no set of samples
+/// separates every `init` from a matching `xorout`; there the results are limited to the
+/// conventional `init` values of all zeros and all ones and one other solution.
+pub fn search(width: u32, samples: &[(&[u8], u128)]) -> Result<Vec<Algorithm>, SearchError> {
+    if !(1..=128).contains(&width) {
+        return Err(SearchError::InvalidWidth);
+    }
+    if samples
+        .iter()
+        .any(|&(_, checksum)| checksum & !low_mask(width) != 0)
+    {
+        return Err(SearchError::ValueTooWide);
+    }
+    let mut found: Vec<Algorithm> = Vec::new();
+    let mut underdetermined = true;
+    for (refin, refout) in [(false, false), (true, true), (true, false), (false, true)] {
+        let mut gcd = Gf2(Vec::new());
+        for (i, &(data, checksum)) in samples.iter().enumerate() {
+            let Some(&(other, other_checksum)) = samples[..i]
+                .iter()
+                .find(|(other, _)| other.len() == data.len())
+            else {
+                continue;
+            };
+            let message: Vec<u8> = data
+                .iter()
+                .zip(other)
+                .map(|(a, b)| if refin { (a ^ b).reverse_bits() } else { a ^ b })
+                .collect();
+            let mut crc = checksum ^ other_checksum;
+            if refout {
+                crc = crc.reverse_bits() >> (128 - width);
+            }
+            gcd = Gf2::gcd(gcd, Gf2::codeword(&message, width, crc));
+        }
+        let polys = match divisors(&gcd, width) {
+            Ok(polys) => polys,
+            Err(_) => continue,
+        };
+        underdetermined = false;
+        for poly in polys {
+            let mut params = Params {
+                width,
+                poly,
+                init: 0,
+                refin,
+                refout,
+                xorout: 0,
+            };
+            for init in inits(&params, samples) {
+                params.init = init;
+                params.xorout = 0;
+                let (data, checksum) = samples[0];
+                params.xorout = checksum ^ params.checksum(data);
+                let candidate = Algorithm {
+                    width,
+                    poly,
+                    init,
+                    refin,
+                    refout,
+                    xorout: params.xorout,
+                    check: Some(params.checksum(b"123456789")),
+                    residue: Some(params.residue()),
+                    name: None,
+                };
+                let matches = samples
+                    .iter()
+                    .all(|&(data, checksum)| candidate.checksum(data) == checksum);
+                if matches && !found.iter().any(|known| known.same_model(&candidate)) {
+                    found.push(candidate);
+                }
+            }
+        }
+    }
+    if underdetermined {
+        return Err(SearchError::Underdetermined);
+    }
+    for candidate in &mut found {
+        candidate.name = catalog::algorithms()
+            .map(Algorithm::from)
+            .find(|entry| entry.same_model(candidate))
+            .and_then(|entry| entry.name);
+    }
+    Ok(found)
+}
+
+/// Splits a catalogue line into its `key=value` pairs, unquoting values.
+fn pairs(line: &str) -> Result<Vec<(&str, &str)>, ParseError> {
+    let mut pairs = Vec::new();
+    let mut rest = line.trim();
+    while !rest.is_empty() {
+        let (key, value) = rest.split_once('=').ok_or(ParseError::Syntax)?;
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            return Err(ParseError::Syntax);
+        }
+        let (value, tail) = match value.strip_prefix('"') {
+            Some(quoted) => quoted.split_once('"').ok_or(ParseError::Syntax)?,
+            None => value.split_once(char::is_whitespace).unwrap_or((value, "")),
+        };
+        pairs.push((key, value));
+        rest = tail.trim_start();
+    }
+    Ok(pairs)
+}
+
+fn parse_value(key: &str, value: &str, width: u32) -> Result<u128, ParseError> {
+    let invalid = || ParseError::InvalidValue(key.to_string());
+    let value = match value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+    {
+        Some(hex) => u128::from_str_radix(hex, 16),
+        None => value.parse(),
+    }
+    .map_err(|_| invalid())?;
+    if width < 128 && value >> width != 0 {
+        return Err(invalid());
+    }
+    Ok(value)
+}
+
+impl FromStr for Algorithm {
+    type Err = ParseError;
+
+    fn from_str(line: &str) -> Result<Self, ParseError> {
+        const KEYS: [&str; 9] = [
+            "width", "poly", "init", "refin", "refout", "xorout", "check", "residue", "name",
+        ];
+        let mut values: [Option<&str>; 9] = [None; 9];
+        for (key, value) in pairs(line)? {
+            let index = KEYS
+                .iter()
+                .position(|&known| known == key)
+                .ok_or_else(|| ParseError::UnknownKey(key.to_string()))?;
+            if values[index].replace(value).is_some() {
+                return Err(ParseError::DuplicateKey(key.to_string()));
+            }
+        }
+        let required = |index: usize| values[index].ok_or(ParseError::MissingKey(KEYS[index]));
+        let width = required(0)?
+            .parse()
+            .ok()
+            .filter(|width| (1..=128).contains(width))
+            .ok_or_else(|| ParseError::InvalidValue("width".to_string()))?;
+        let number = |index: usize| parse_value(KEYS[index], required(index)?, width);
+        let flag = |index: usize| match required(index)? {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            _ => Err(ParseError::InvalidValue(KEYS[index].to_string())),
+        };
+        let optional = |index: usize| values[index].map(|_| number(index)).transpose();
+        let algorithm = Algorithm {
+            width,
+            poly: number(1)?,
+            init: number(2)?,
+            refin: flag(3)?,
+            refout: flag(4)?,
+            xorout: number(5)?,
+            check: optional(6)?,
+            residue: optional(7)?,
+            name: values[8].map(str::to_string),
+        };
+        if let Some(stated) = algorithm.check {
+            let actual = algorithm.checksum(b"123456789");
+            if stated != actual {
+                return Err(ParseError::CheckMismatch { stated, actual });
+            }
+        }
+        Ok(algorithm)
+    }
+}
+
+/// Parses a catalogue line as for [`Algorithm`], rejecting widths that do not fit `W`.
+impl<W: Width> FromStr for CrcParams<W> {
+    type Err = ParseError;
+
+    fn from_str(line: &str) -> Result<Self, ParseError> {
+        let algorithm: Algorithm = line.parse()?;
+        if algorithm.width > W::BITS {
+            return Err(ParseError::InvalidValue("width".to_string()));
+        }
+        Ok(CrcParams {
+            width: algorithm.width,
+            poly: W::from_u128(algorithm.poly),
+            init: W::from_u128(algorithm.init),
+            refin: algorithm.refin,
+            refout: algorithm.refout,
+            xorout: W::from_u128(algorithm.xorout),
+        })
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = self.width.div_ceil(4) as usize;
+        write!(
+            f,
+            "width={} poly={:#0w$x} init={:#0w$x} refin={} refout={} xorout={:#0w$x}",
+            self.width,
+            self.poly,
+            self.init,
+            self.refin,
+            self.refout,
+            self.xorout,
+            w = digits + 2
+        )?;
+        if let Some(check) = self.check {
+            write!(f, " check={:#0w$x}", check, w = digits + 2)?;
+        }
+        if let Some(residue) = self.residue {
+            write!(f, " residue={:#0w$x}", residue, w = digits + 2)?;
+        }
+        if let Some(name) = &self.name {
+            write!(f, " name=\"{}\"", name)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IBM_3740: &str = "width=16 poly=0x1021 init=0xffff refin=false refout=false \
+        xorout=0x0000 check=0x29b1 residue=0x0000 name=\"CRC-16/IBM-3740\"";
+
+    #[test]
+    fn parse_test() {
+        let algorithm: Algorithm = IBM_3740.parse().unwrap();
+        assert_eq!(
+            IBM_3740.parse::<CrcParams<u16>>(),
+            Ok(catalog::CRC_16_IBM_3740.params)
+        );
+        assert_eq!(
+            IBM_3740.parse::<CrcParams<u8>>(),
+            Err(ParseError::InvalidValue("width".to_string()))
+        );
+        let emitted = Algorithm {
+            name: Some("CRC-16/IBM-3740".to_string()),
+            ..Algorithm::from(catalog::CRC_16_IBM_3740.params)
+        };
+        assert_eq!(emitted, algorithm);
+        assert_eq!(algorithm.width, 16);
+        assert_eq!(algorithm.poly, 0x1021);
+        assert_eq!(algorithm.name.as_deref(), Some("CRC-16/IBM-3740"));
+        assert_eq!(algorithm.to_string(), IBM_3740);
+        assert_eq!(
+            algorithm.checksum(b"hello world") as u16,
+            crc::Crc::<u16>::new(&crc::CRC_16_IBM_3740).checksum(b"hello world")
+        );
+
+        // Odd widths, and reveng output without check, residue or name.
+        let cd = "width=82 poly=0x0308c0111011401440411 init=0x000000000000000000000 \
+            refin=true refout=true xorout=0x000000000000000000000 check=0x09ea83f625023801fd612";
+        let algorithm: Algorithm = cd.parse().unwrap();
+        assert_eq!(algorithm.check, Some(0x0_9ea8_3f62_5023_801f_d612));
+        let bare: Algorithm = "width=5 poly=0x05 init=0x1f refin=true refout=true xorout=0x1f"
+            .parse()
+            .unwrap();
+        assert_eq!(bare.checksum(b"123456789"), 0x19);
+    }
+
+    #[test]
+    fn error_test() {
+        let parse = |line: &str| line.parse::<Algorithm>().unwrap_err();
+        assert_eq!(
+            parse(&IBM_3740.replace("0x29b1", "0x29b2")),
+            ParseError::CheckMismatch {
+                stated: 0x29b2,
+                actual: 0x29b1
+            }
+        );
+        assert_eq!(
+            parse(&IBM_3740.replace("poly=0x1021", "poly=0x11021")),
+            ParseError::InvalidValue("poly".to_string())
+        );
+        assert_eq!(
+            parse(&IBM_3740.replace("init=0xffff ", "")),
+            ParseError::MissingKey("init")
+        );
+        assert_eq!(
+            parse(&IBM_3740.replace("refin", "reflect")),
+            ParseError::UnknownKey("reflect".to_string())
+        );
+        assert_eq!(
+            parse(&format!("{} width=16", IBM_3740)),
+            ParseError::DuplicateKey("width".to_string())
+        );
+        assert_eq!(parse("width=16 name=\"CRC-16"), ParseError::Syntax);
+    }
+
+    #[test]
+    fn search_test() {
+        let messages: [&[u8]; 6] = [
+            b"hello world",
+            b"HELLO WORLD",
+            b"Hello World",
+            b"123456789",
+            b"abcdefghi",
+            b"abc",
+        ];
+        for name in [
+            "CRC-16/MODBUS",
+            "CRC-32/ISO-HDLC",
+            "CRC-8/MAXIM-DOW",
+            "CRC-64/XZ",
+        ] {
+            let entry = catalog::by_name(name).unwrap();
+            let samples: Vec<(&[u8], u128)> = messages
+                .iter()
+                .map(|&data| (data, entry.checksum(data).into()))
+                .collect();
+            let found = search(entry.width(), &samples).unwrap();
+            assert!(found.contains(&Algorithm::from(entry)), "{}", name);
+            // Only polynomials divisible by x + 1 can leave other solutions.
+            if name == "CRC-32/ISO-HDLC" {
+                assert_eq!(found.len(), 1);
+            }
+        }
+        // Same-length samples cannot separate init from xorout.
+        let xmodem = crc::Crc::<u16>::new(&crc::CRC_16_XMODEM);
+        let samples: Vec<(&[u8], u128)> = [b"hello world", b"HELLO WORLD", b"Hello World"]
+            .iter()
+            .map(|&data| (&data[..], xmodem.checksum(data).into()))
+            .collect();
+        let found = search(16, &samples).unwrap();
+        assert!(found
+            .iter()
+            .any(|algorithm| algorithm.name.as_deref() == Some("CRC-16/XMODEM")));
+
+        assert_eq!(
+            search(16, &[(b"abc", 0x1234)]),
+            Err(SearchError::Underdetermined)
+        );
+        assert_eq!(
+            search(8, &[(b"abc", 0x1234)]),
+            Err(SearchError::ValueTooWide)
+        );
+        let entry = Algorithm::from(catalog::by_name("CRC-16/ARC").unwrap());
+        assert_eq!(entry.check, Some(0xBB3D));
+    }
+}
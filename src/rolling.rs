@@ -0,0 +1,126 @@
+//! CRC of a sliding window, for content-defined chunking.
+//!
+//! [`RollingCrc`] keeps the CRC of the last `WINDOW` bytes of a stream. Each
+//! [`roll`](RollingCrc::roll) adds the newest byte and cancels the oldest with one lookup in a
+//! second table: the oldest byte's contribution is the same for every window, namely that of
+//! the byte followed by `WINDOW` zero bytes, plus a constant for the shifted-out init.
+
+use crate::engine::Width;
+use crate::table;
+use crate::CrcParams;
+
+/// The CRC of a window of `WINDOW` bytes that slides one byte per [`RollingCrc::roll`].
+#[derive(Clone, Debug)]
+pub struct RollingCrc<W, const WINDOW: usize> {
+    params: CrcParams<W>,
+    table: [W; 256],
+    /// What to XOR in to drop each possible oldest byte.
+    out: [W; 256],
+    register: W,
+}
+
+impl<W: Width, const WINDOW: usize> RollingCrc<W, WINDOW> {
+    /// Starts with a window of zero bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `WINDOW` is zero, or if `params.width` is zero or wider than `W`.
+    pub fn new(params: impl Into<CrcParams<W>>) -> Self {
+        Self::with_window(params, &[0; WINDOW])
+    }
+
+    /// Starts with `window` as the first `WINDOW` bytes of the stream.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `WINDOW` is zero, or if `params.width` is zero or wider than `W`.
+    pub fn with_window(params: impl Into<CrcParams<W>>, window: &[u8; WINDOW]) -> Self {
+        assert!(WINDOW > 0, "the window must not be empty");
+        let params = table::checked(params.into());
+        let table = table::build(&params);
+        let zeros = |mut crc, len| {
+            for _ in 0..len {
+                crc = table::update(&table, params.refin, crc, &[0]);
+            }
+            crc
+        };
+        // Rolling turns init followed by `WINDOW + 1` bytes into init followed by `WINDOW`.
+        let init = zeros(table::start(&params), WINDOW);
+        let init = init ^ zeros(init, 1);
+        let mut bits = [W::ZERO; 8];
+        for (bit, entry) in bits.iter_mut().enumerate() {
+            *entry = zeros(
+                table::update(&table, params.refin, W::ZERO, &[1 << bit]),
+                WINDOW,
+            );
+        }
+        let mut out = [init; 256];
+        for (byte, entry) in out.iter_mut().enumerate() {
+            for (bit, value) in bits.iter().enumerate() {
+                if byte >> bit & 1 != 0 {
+                    *entry = *entry ^ *value;
+                }
+            }
+        }
+        let register = table::update(&table, params.refin, table::start(&params), window);
+        Self {
+            params,
+            table,
+            out,
+            register,
+        }
+    }
+
+    /// Slides the window by one byte: `out_byte`, the oldest byte, leaves it and `in_byte`
+    /// enters. The caller keeps the window contents, e.g. as the last `WINDOW` bytes of its
+    /// buffer.
+    pub fn roll(&mut self, out_byte: u8, in_byte: u8) {
+        let crc = table::update(&self.table, self.params.refin, self.register, &[in_byte]);
+        self.register = crc ^ self.out[out_byte as usize];
+    }
+
+    /// The checksum of the current window.
+    pub fn checksum(&self) -> W {
+        table::finalize(&self.params, self.register)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{catalog, Crc};
+
+    fn check<W: Width + core::fmt::Debug>(params: CrcParams<W>) {
+        const WINDOW: usize = 48;
+        let data: Vec<u8> = (0..500u32).map(|i| (i * 89 + i / 3) as u8).collect();
+        let crc = Crc::new(params);
+        let mut rolling = RollingCrc::<W, WINDOW>::with_window(params, &[0xA5; WINDOW]);
+        assert_eq!(rolling.checksum(), crc.checksum(&[0xA5; WINDOW]));
+        let mut rolling2 = RollingCrc::<W, WINDOW>::new(params);
+        for (i, &byte) in data.iter().enumerate() {
+            rolling.roll(if i < WINDOW { 0xA5 } else { data[i - WINDOW] }, byte);
+            rolling2.roll(if i < WINDOW { 0 } else { data[i - WINDOW] }, byte);
+            if i + 1 >= WINDOW {
+                let window = &data[i + 1 - WINDOW..=i];
+                assert_eq!(
+                    rolling.checksum(),
+                    crc.checksum(window),
+                    "{:?} {}",
+                    params,
+                    i
+                );
+                assert_eq!(rolling2.checksum(), crc.checksum(window));
+            }
+        }
+    }
+
+    #[test]
+    fn rolling_test() {
+        check(catalog::CRC_32_ISO_HDLC.params);
+        check(catalog::CRC_32_MPEG_2.params);
+        check(catalog::CRC_64_XZ.params);
+        check(catalog::CRC_16_MODBUS.params);
+        check(catalog::CRC_12_UMTS.params);
+        check(catalog::CRC_5_USB.params);
+    }
+}
@@ -0,0 +1,227 @@
+//! Integrity checks for retro console ROM dumps.
+//!
+//! Cartridge headers carry their own checksums: the SNES internal header stores a 16-bit
+//! sum of the whole ROM and its complement, and the Game Boy header stores an 8-bit header
+//! checksum and a 16-bit sum of the whole ROM. ROM managers and DAT files identify dumps by
+//! the CRC-32 of the file instead, without any copier header, which [`crc32`] computes.
+
+use crate::oneshot;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RomError {
+    /// The image is too small to contain a header.
+    Truncated,
+    /// No candidate location holds a checksum/complement pair.
+    NoHeader,
+}
+
+impl core::fmt::Display for RomError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Truncated => write!(f, "ROM image too small for a header"),
+            Self::NoHeader => write!(f, "no internal ROM header found"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RomError {}
+
+/// Removes the 512-byte header some copier devices prepend to SNES images.
+///
+/// SNES ROMs are a multiple of 1 KiB, so an image 512 bytes past that has one.
+pub fn strip_copier_header(image: &[u8]) -> &[u8] {
+    if image.len() % 1024 == 512 {
+        &image[512..]
+    } else {
+        image
+    }
+}
+
+/// The CRC-32 ROM managers and DAT files list for an image, computed without any copier
+/// header.
+pub fn crc32(image: &[u8]) -> u32 {
+    oneshot::crc32(strip_copier_header(image))
+}
+
+/// Where an SNES cartridge maps its internal header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SnesMapping {
+    LoRom,
+    HiRom,
+    ExHiRom,
+}
+
+impl SnesMapping {
+    fn header_offset(self) -> usize {
+        match self {
+            Self::LoRom => 0x7FC0,
+            Self::HiRom => 0xFFC0,
+            Self::ExHiRom => 0x40_FFC0,
+        }
+    }
+}
+
+/// The result of [`check_snes`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SnesCheck {
+    pub mapping: SnesMapping,
+    /// The checksum stored in the internal header.
+    pub stored: u16,
+    pub computed: u16,
+}
+
+impl SnesCheck {
+    pub fn is_valid(&self) -> bool {
+        self.stored == self.computed
+    }
+}
+
+/// Sums `data` as if mirrored up to `size` bytes, a power of two no smaller than it.
+fn mirrored_sum(data: &[u8], size: usize) -> u32 {
+    if data.len() == size {
+        data.iter().map(|&byte| byte as u32).sum()
+    } else if data.len() <= size / 2 {
+        2 * mirrored_sum(data, size / 2)
+    } else {
+        mirrored_sum(&data[..size / 2], size / 2) + mirrored_sum(&data[size / 2..], size / 2)
+    }
+}
+
+/// The SNES checksum of a ROM without copier header.
+///
+/// ROMs whose size is not a power of two are summed the way the console mirrors them:
+/// a 3 MiB ROM counts its last megabyte twice.
+pub fn snes_checksum(rom: &[u8]) -> u16 {
+    if rom.is_empty() {
+        return 0;
+    }
+    mirrored_sum(rom, rom.len().next_power_of_two()) as u16
+}
+
+/// Locates the internal header of an SNES image and checks its checksum.
+///
+/// Of the header locations holding a checksum and its complement, the one whose checksum
+/// matches is preferred, so a valid dump is always reported as such.
+pub fn check_snes(image: &[u8]) -> Result<SnesCheck, RomError> {
+    let rom = strip_copier_header(image);
+    if rom.len() < 0x8000 {
+        return Err(RomError::Truncated);
+    }
+    let computed = snes_checksum(rom);
+    let candidates = [SnesMapping::LoRom, SnesMapping::HiRom, SnesMapping::ExHiRom]
+        .into_iter()
+        .filter_map(|mapping| {
+            let field = rom.get(mapping.header_offset() + 0x1C..mapping.header_offset() + 0x20)?;
+            let complement = u16::from_le_bytes([field[0], field[1]]);
+            let stored = u16::from_le_bytes([field[2], field[3]]);
+            (complement ^ stored == 0xFFFF).then_some(SnesCheck {
+                mapping,
+                stored,
+                computed,
+            })
+        });
+    let mut first = None;
+    for check in candidates {
+        if check.is_valid() {
+            return Ok(check);
+        }
+        first.get_or_insert(check);
+    }
+    first.ok_or(RomError::NoHeader)
+}
+
+/// The result of [`check_gb`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GbCheck {
+    pub header_stored: u8,
+    pub header_computed: u8,
+    pub global_stored: u16,
+    pub global_computed: u16,
+}
+
+impl GbCheck {
+    /// Whether the header checksum matches. The boot ROM refuses to start a cartridge
+    /// whose header checksum is wrong, while the global checksum is never checked by the
+    /// hardware and is wrong in some licensed games.
+    pub fn header_valid(&self) -> bool {
+        self.header_stored == self.header_computed
+    }
+
+    pub fn global_valid(&self) -> bool {
+        self.global_stored == self.global_computed
+    }
+}
+
+/// Checks the header and global checksums of a Game Boy or Game Boy Color ROM.
+pub fn check_gb(rom: &[u8]) -> Result<GbCheck, RomError> {
+    if rom.len() < 0x150 {
+        return Err(RomError::Truncated);
+    }
+    let header_computed = rom[0x134..=0x14C]
+        .iter()
+        .fold(0u8, |x, &byte| x.wrapping_sub(byte).wrapping_sub(1));
+    let global_computed = rom
+        .iter()
+        .enumerate()
+        .filter(|&(i, _)| i != 0x14E && i != 0x14F)
+        .fold(0u16, |sum, (_, &byte)| sum.wrapping_add(byte as u16));
+    Ok(GbCheck {
+        header_stored: rom[0x14D],
+        header_computed,
+        global_stored: u16::from_be_bytes([rom[0x14E], rom[0x14F]]),
+        global_computed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snes_test() {
+        let mut rom: Vec<u8> = (0..0x30000u32).map(|i| (i * 7 + i / 251) as u8).collect();
+        // Any checksum/complement pair sums to the same value, so fill in a placeholder
+        // before summing.
+        rom[0xFFDC..0xFFE0].copy_from_slice(&[0xFF, 0xFF, 0x00, 0x00]);
+        let checksum = snes_checksum(&rom);
+        rom[0xFFDC..0xFFDE].copy_from_slice(&(!checksum).to_le_bytes());
+        rom[0xFFDE..0xFFE0].copy_from_slice(&checksum.to_le_bytes());
+
+        let check = check_snes(&rom).unwrap();
+        assert_eq!(check.mapping, SnesMapping::HiRom);
+        assert!(check.is_valid());
+
+        // 192 KiB mirrors its last 64 KiB once to fill 256 KiB.
+        let mirrored: u32 = rom.iter().chain(&rom[0x20000..]).map(|&b| b as u32).sum();
+        assert_eq!(checksum, mirrored as u16);
+
+        let mut copier = vec![0; 512];
+        copier.extend_from_slice(&rom);
+        assert_eq!(check_snes(&copier), Ok(check));
+        assert_eq!(crc32(&copier), crate::oneshot::crc32(&rom));
+
+        rom[0x100] ^= 1;
+        assert!(!check_snes(&rom).unwrap().is_valid());
+        assert_eq!(check_snes(&[0; 0x8000]), Err(RomError::NoHeader));
+    }
+
+    #[test]
+    fn gb_test() {
+        let mut rom = vec![0; 0x8000];
+        let check = check_gb(&rom).unwrap();
+        // 25 zero bytes: 0 - 25 * 1.
+        assert_eq!(check.header_computed, 0xE7);
+        rom[0x14D] = 0xE7;
+        rom[0x200] = 0x10;
+        let global = 0xE7 + 0x10u16;
+        rom[0x14E..0x150].copy_from_slice(&global.to_be_bytes());
+        let check = check_gb(&rom).unwrap();
+        assert!(check.header_valid() && check.global_valid());
+        assert_eq!(check_gb(&rom[..0x14F]), Err(RomError::Truncated));
+    }
+}
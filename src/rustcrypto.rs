@@ -0,0 +1,163 @@
+//! RustCrypto `digest` traits for the CRC states.
+//!
+//! The states, including the [`Digest`]s of a [`Crc`](crate::Crc), implement [`Update`],
+//! [`OutputSizeUser`], [`FixedOutput`], [`FixedOutputReset`] and [`Reset`], so code written
+//! against those traits, or against `DynDigest` which they add up to, takes a CRC next to
+//! SHA-256. The output is the checksum in big-endian byte order, as wide as the state's
+//! register type: one byte for CRC8 or a `Digest<u8>` up to sixteen for CRC128, with
+//! algorithms narrower than their type padded by leading zeros, e.g. a CRC-24 in a CRC32 gives
+//! four bytes. CRCs are not cryptographic hashes, so the states leave out `HashMarker` and
+//! with it the blanket `digest::Digest` impl.
+
+use crate::const_crc::{ConstCRC128, ConstCRC16, ConstCRC32, ConstCRC64, ConstCRC8};
+use crate::strategy::Strategy;
+use crate::{Digest, CRC, CRC128, CRC16, CRC32, CRC64, CRC8};
+use digest::typenum::{U1, U16, U2, U4, U8};
+use digest::{FixedOutput, FixedOutputReset, Output, OutputSizeUser, Reset, Update};
+
+macro_rules! impl_digest {
+    ($crc:ty, $size:ty $(, $($generics:tt)*)?) => {
+        impl<$($($generics)*)?> Update for $crc {
+            fn update(&mut self, data: &[u8]) {
+                CRC::update(self, data);
+            }
+        }
+
+        impl<$($($generics)*)?> OutputSizeUser for $crc {
+            type OutputSize = $size;
+        }
+
+        impl<$($($generics)*)?> FixedOutput for $crc {
+            fn finalize_into(self, out: &mut Output<Self>) {
+                out.copy_from_slice(&CRC::finalize(&self).to_be_bytes());
+            }
+        }
+
+        impl<$($($generics)*)?> FixedOutputReset for $crc {
+            fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+                out.copy_from_slice(&CRC::finalize_reset(self).to_be_bytes());
+            }
+        }
+
+        impl<$($($generics)*)?> Reset for $crc {
+            fn reset(&mut self) {
+                CRC::reset(self);
+            }
+        }
+    };
+}
+
+impl_digest!(CRC8, U1);
+impl_digest!(CRC16, U2);
+impl_digest!(CRC32, U4);
+impl_digest!(CRC64, U8);
+impl_digest!(CRC128, U16);
+
+macro_rules! impl_const_digest {
+    ($name:ident, $n:ty, $size:ty) => {
+        impl_digest!(
+            $name<POLY, INIT, REFIN, REFOUT, XOROUT>,
+            $size,
+            const POLY: $n,
+            const INIT: $n,
+            const REFIN: bool,
+            const REFOUT: bool,
+            const XOROUT: $n
+        );
+    };
+}
+
+impl_const_digest!(ConstCRC8, u8, U1);
+impl_const_digest!(ConstCRC16, u16, U2);
+impl_const_digest!(ConstCRC32, u32, U4);
+impl_const_digest!(ConstCRC64, u64, U8);
+impl_const_digest!(ConstCRC128, u128, U16);
+
+macro_rules! impl_running_digest {
+    ($($n:ty: $size:ty),*) => {
+        $(
+            impl<S: Strategy> Update for Digest<'_, $n, S> {
+                fn update(&mut self, data: &[u8]) {
+                    Digest::update(self, data);
+                }
+            }
+
+            impl<S: Strategy> OutputSizeUser for Digest<'_, $n, S> {
+                type OutputSize = $size;
+            }
+
+            impl<S: Strategy> FixedOutput for Digest<'_, $n, S> {
+                fn finalize_into(self, out: &mut Output<Self>) {
+                    out.copy_from_slice(&Digest::finalize(&self).to_be_bytes());
+                }
+            }
+
+            impl<S: Strategy> FixedOutputReset for Digest<'_, $n, S> {
+                fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+                    out.copy_from_slice(&Digest::finalize_reset(self).to_be_bytes());
+                }
+            }
+
+            impl<S: Strategy> Reset for Digest<'_, $n, S> {
+                fn reset(&mut self) {
+                    Digest::reset(self);
+                }
+            }
+        )*
+    };
+}
+
+impl_running_digest!(u8: U1, u16: U2, u32: U4, u64: U8, u128: U16);
+
+#[cfg(test)]
+mod tests {
+    use crate::const_crc::{Crc32Iscsi, Crc64Xz};
+    use crate::{catalog, Crc, CRC, CRC16, CRC32};
+    use digest::{DynDigest, FixedOutput, FixedOutputReset, Update};
+
+    #[test]
+    fn digest_test() {
+        let mut crc = CRC32::from_algorithm(&catalog::CRC_32_ISO_HDLC);
+        Update::update(&mut crc, b"12345");
+        Update::update(&mut crc, b"6789");
+        assert_eq!(crc.finalize_fixed()[..], 0xCBF4_3926u32.to_be_bytes());
+
+        let mut crc = Crc32Iscsi::new();
+        Update::update(&mut crc, b"123456789");
+        assert_eq!(crc.finalize_fixed_reset()[..], 0xE306_9283u32.to_be_bytes());
+        assert_eq!(crc, Crc32Iscsi::new());
+
+        // A CRC-24 fills the low three of its four bytes.
+        let mut crc = CRC32::from_algorithm(&catalog::CRC_24_OPENPGP);
+        Update::update(&mut crc, b"123456789");
+        assert_eq!(crc.finalize_fixed()[..], [0x00, 0x21, 0xCF, 0x02]);
+
+        let crc32c = Crc::<u32>::new(catalog::CRC_32_ISCSI.params);
+        let mut digest = crc32c.digest();
+        Update::update(&mut digest, b"123456789");
+        assert_eq!(
+            digest.finalize_fixed_reset()[..],
+            0xE306_9283u32.to_be_bytes()
+        );
+        assert_eq!(digest, crc32c.digest());
+
+        // Code generic over `DynDigest` takes them like any other hash.
+        let mut hashes: [Box<dyn DynDigest>; 3] = [
+            Box::new(CRC16::from_algorithm(&catalog::CRC_16_MODBUS)),
+            Box::new(Crc64Xz::new()),
+            Box::new(Crc::<u8>::cached(&catalog::CRC_8_SMBUS).digest()),
+        ];
+        for hash in &mut hashes {
+            hash.update(b"123456789");
+        }
+        let mut out = [0; 11];
+        let (modbus, rest) = out.split_at_mut(hashes[0].output_size());
+        let (xz, smbus) = rest.split_at_mut(hashes[1].output_size());
+        hashes[0].finalize_into_reset(modbus).unwrap();
+        hashes[1].finalize_into_reset(xz).unwrap();
+        hashes[2].finalize_into_reset(smbus).unwrap();
+        assert_eq!(out[..2], 0x4B37u16.to_be_bytes());
+        assert_eq!(out[2..10], 0x995D_C9BB_DF19_39FAu64.to_be_bytes());
+        assert_eq!(out[10], 0xF4);
+    }
+}
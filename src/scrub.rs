@@ -0,0 +1,300 @@
+//! Background re-verification of a tree against its manifest.
+//!
+//! A [`Scrub`] rereads every file listed in a [`Manifest`] and reports each outcome as an
+//! [`Event`], pass after pass, throttled by a [`RateLimit`] so that it does not starve the
+//! foreground workload of I/O. [`Scrub::run`] does this on the calling thread with a
+//! callback; [`Scrub::spawn`] moves it to a background thread and delivers the events over a
+//! channel.
+
+use crate::compare::read_full;
+use crate::manifest::{EntryKind, Manifest};
+use crate::trace;
+use std::fs::{self, File};
+use std::io;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const CHUNK_LEN: usize = 64 * 1024;
+
+/// How hard a scrub may use the disk.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum RateLimit {
+    #[default]
+    Unlimited,
+    /// Average read throughput in bytes per second.
+    BytesPerSecond(u64),
+    /// Fraction of wall-clock time spent reading, in `(0, 1]`; the rest is spent sleeping.
+    DutyCycle(f64),
+}
+
+#[derive(Debug)]
+pub enum Event {
+    /// A file still matches its manifest entry.
+    Verified { path: String, size: u64 },
+    /// A file or link no longer matches; `actual` is what was found on disk.
+    Mismatch {
+        path: String,
+        expected: EntryKind,
+        actual: EntryKind,
+    },
+    /// A file could not be read, e.g. because it was deleted.
+    Error { path: String, error: io::Error },
+    /// Every entry was visited once. `pass` counts from 1.
+    PassComplete { pass: u64, bytes: u64 },
+}
+
+/// A scrub job over the tree at `root`.
+#[derive(Clone, Debug)]
+pub struct Scrub {
+    pub root: PathBuf,
+    pub manifest: Manifest,
+    pub rate: RateLimit,
+    /// Number of passes to make; `None` scrubs until stopped.
+    pub passes: Option<u64>,
+}
+
+/// A scrub running on a background thread, see [`Scrub::spawn`].
+pub struct ScrubHandle {
+    events: mpsc::Receiver<Event>,
+    stop: Arc<AtomicBool>,
+    thread: thread::JoinHandle<()>,
+}
+
+impl ScrubHandle {
+    pub fn events(&self) -> &mpsc::Receiver<Event> {
+        &self.events
+    }
+
+    /// Asks the scrub to stop at the next chunk boundary and waits for it.
+    pub fn stop(self) {
+        self.stop.store(true, Ordering::Relaxed);
+        let _ = self.thread.join();
+    }
+}
+
+/// Sleeps as needed to keep reads within a [`RateLimit`].
+struct Throttle {
+    rate: RateLimit,
+    started: Instant,
+    bytes: u64,
+}
+
+impl Throttle {
+    fn new(rate: RateLimit) -> Self {
+        Self {
+            rate,
+            started: Instant::now(),
+            bytes: 0,
+        }
+    }
+
+    /// Accounts for `bytes` read in `busy` time.
+    fn pace(&mut self, bytes: usize, busy: Duration) {
+        self.bytes += bytes as u64;
+        match self.rate {
+            RateLimit::Unlimited => {}
+            RateLimit::BytesPerSecond(rate) => {
+                let due = Duration::from_secs_f64(self.bytes as f64 / rate.max(1) as f64);
+                if let Some(ahead) = due.checked_sub(self.started.elapsed()) {
+                    thread::sleep(ahead);
+                }
+            }
+            RateLimit::DutyCycle(duty) => {
+                let duty = duty.clamp(f64::MIN_POSITIVE, 1.0);
+                thread::sleep(busy.mul_f64((1.0 - duty) / duty));
+            }
+        }
+    }
+}
+
+impl Scrub {
+    pub fn new(root: impl Into<PathBuf>, manifest: Manifest) -> Self {
+        Self {
+            root: root.into(),
+            manifest,
+            rate: RateLimit::Unlimited,
+            passes: None,
+        }
+    }
+
+    /// Runs the scrub on the current thread until the configured number of passes is done or
+    /// `stop` is set. Returns immediately if the manifest is empty.
+    pub fn run(&self, stop: &AtomicBool, mut on_event: impl FnMut(Event)) {
+        if self.manifest.entries().is_empty() {
+            return;
+        }
+        let mut throttle = Throttle::new(self.rate);
+        let mut pass = 0;
+        while self.passes.is_none_or(|passes| pass < passes) {
+            let bytes_before = throttle.bytes;
+            for entry in self.manifest.entries() {
+                if stop.load(Ordering::Relaxed) {
+                    return;
+                }
+                let path = self.root.join(&entry.path);
+                let actual = match &entry.kind {
+                    EntryKind::File { .. } => self.hash(&path, stop, &mut throttle),
+                    EntryKind::Symlink { .. } => fs::read_link(&path).map(|target| {
+                        Some(EntryKind::Symlink {
+                            target: target
+                                .to_string_lossy()
+                                .replace(std::path::MAIN_SEPARATOR, "/"),
+                        })
+                    }),
+                };
+                if let (
+                    EntryKind::File { crc: expected, .. },
+                    Ok(Some(EntryKind::File { crc, .. })),
+                ) = (&entry.kind, &actual)
+                {
+                    trace::verified(
+                        self.manifest.algorithm().name(),
+                        &entry.path,
+                        *expected,
+                        *crc,
+                    );
+                }
+                let path = entry.path.clone();
+                on_event(match actual {
+                    Ok(None) => return,
+                    Ok(Some(actual)) if actual == entry.kind => Event::Verified {
+                        path,
+                        size: match actual {
+                            EntryKind::File { size, .. } => size,
+                            EntryKind::Symlink { .. } => 0,
+                        },
+                    },
+                    Ok(Some(actual)) => Event::Mismatch {
+                        path,
+                        expected: entry.kind.clone(),
+                        actual,
+                    },
+                    Err(error) => Event::Error { path, error },
+                });
+            }
+            pass += 1;
+            on_event(Event::PassComplete {
+                pass,
+                bytes: throttle.bytes - bytes_before,
+            });
+        }
+    }
+
+    /// Hashes one file; `Ok(None)` means the scrub was stopped part way.
+    fn hash(
+        &self,
+        path: &std::path::Path,
+        stop: &AtomicBool,
+        throttle: &mut Throttle,
+    ) -> io::Result<Option<EntryKind>> {
+        let mut file = File::open(path)?;
+        let mut buf = vec![0; CHUNK_LEN];
+        let (mut size, mut state) = (0, self.manifest.algorithm().state());
+        loop {
+            if stop.load(Ordering::Relaxed) {
+                return Ok(None);
+            }
+            let started = Instant::now();
+            let n = read_full(&mut file, &mut buf)?;
+            state.dyn_update(&buf[..n]);
+            size += n as u64;
+            throttle.pace(n, started.elapsed());
+            if n < CHUNK_LEN {
+                break;
+            }
+        }
+        Ok(Some(EntryKind::File {
+            size,
+            crc: state.dyn_finalize() as u64,
+        }))
+    }
+
+    /// Starts the scrub on a background thread.
+    ///
+    /// The thread ends when the configured passes are done, [`ScrubHandle::stop`] is called
+    /// or the handle is dropped and the event channel is closed.
+    pub fn spawn(self) -> ScrubHandle {
+        let (sender, events) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let thread = thread::spawn(move || {
+            self.run(&thread_stop, |event| {
+                if sender.send(event).is_err() {
+                    thread_stop.store(true, Ordering::Relaxed);
+                }
+            })
+        });
+        ScrubHandle {
+            events,
+            stop,
+            thread,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Event, RateLimit, Scrub};
+    use crate::manifest::{create, Options};
+    use std::fs;
+    use std::sync::atomic::AtomicBool;
+    use std::time::Instant;
+
+    #[test]
+    fn scrub_test() {
+        let root = std::env::temp_dir().join(format!("crclib-scrub-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("a"), vec![7u8; 100_000]).unwrap();
+        fs::write(root.join("b"), "hello world").unwrap();
+        fs::write(root.join("c"), "gone soon").unwrap();
+        let manifest = create(&root, &Options::default()).unwrap();
+
+        // Same length, different contents: bit rot as far as the scrub can tell.
+        fs::write(root.join("b"), "hello_world").unwrap();
+        fs::remove_file(root.join("c")).unwrap();
+
+        let mut scrub = Scrub::new(&root, manifest);
+        scrub.passes = Some(2);
+        scrub.rate = RateLimit::BytesPerSecond(1_000_000);
+        let mut events = Vec::new();
+        let started = Instant::now();
+        scrub.run(&AtomicBool::new(false), |event| events.push(event));
+        // Two passes over ~100 kB at 1 MB/s.
+        assert!(started.elapsed().as_secs_f64() >= 0.15);
+
+        assert_eq!(events.len(), 8);
+        assert!(matches!(&events[0], Event::Verified { path, size: 100_000 } if path == "a"));
+        assert!(matches!(&events[1], Event::Mismatch { path, .. } if path == "b"));
+        assert!(matches!(&events[2], Event::Error { path, .. } if path == "c"));
+        assert!(matches!(
+            events[3],
+            Event::PassComplete {
+                pass: 1,
+                bytes: 100_011
+            }
+        ));
+        assert!(matches!(events[7], Event::PassComplete { pass: 2, .. }));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn spawn_test() {
+        let root = std::env::temp_dir().join(format!("crclib-spawn-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("a"), "hello world").unwrap();
+        let scrub = Scrub::new(&root, create(&root, &Options::default()).unwrap());
+
+        // Runs forever until stopped.
+        let handle = scrub.spawn();
+        let first: Vec<Event> = handle.events().iter().take(4).collect();
+        assert!(matches!(first[1], Event::PassComplete { pass: 1, .. }));
+        assert!(matches!(first[3], Event::PassComplete { pass: 2, .. }));
+        handle.stop();
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
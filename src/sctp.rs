@@ -0,0 +1,91 @@
+//! SCTP packet checksums (RFC 9260, appendix A).
+//!
+//! The checksum is the CRC-32C of the whole packet with the checksum field in the common
+//! header set to zero, stored in the packet in little-endian byte order.
+
+use crate::const_crc::Crc32Iscsi;
+use crate::trace;
+use crate::CRC;
+
+/// Byte offset of the checksum field in the SCTP common header.
+pub const CHECKSUM_OFFSET: usize = 8;
+/// Length of the SCTP common header.
+pub const COMMON_HEADER_LEN: usize = 12;
+
+/// Computes the checksum of `packet`, ignoring whatever is stored in its checksum field.
+///
+/// Returns `None` if the packet is shorter than the common header.
+pub fn checksum(packet: &[u8]) -> Option<u32> {
+    if packet.len() < COMMON_HEADER_LEN {
+        return None;
+    }
+    let mut crc = Crc32Iscsi::new();
+    crc.update(&packet[..CHECKSUM_OFFSET]);
+    crc.update(&[0; 4]);
+    crc.update(&packet[CHECKSUM_OFFSET + 4..]);
+    Some(crc.finalize())
+}
+
+/// Computes the checksum of `packet` and stores it in the checksum field.
+///
+/// Returns the stored value, or `None` if the packet is shorter than the common header.
+pub fn write_checksum(packet: &mut [u8]) -> Option<u32> {
+    let crc = checksum(packet)?;
+    packet[CHECKSUM_OFFSET..CHECKSUM_OFFSET + 4].copy_from_slice(&crc.to_le_bytes());
+    Some(crc)
+}
+
+/// The checksum currently stored in the packet's checksum field.
+pub fn stored_checksum(packet: &[u8]) -> Option<u32> {
+    let field = packet.get(CHECKSUM_OFFSET..CHECKSUM_OFFSET + 4)?;
+    Some(u32::from_le_bytes(field.try_into().unwrap()))
+}
+
+/// Checks the stored checksum of `packet`; packets shorter than the common header fail.
+pub fn verify(packet: &[u8]) -> bool {
+    match (stored_checksum(packet), checksum(packet)) {
+        (Some(stored), Some(computed)) => {
+            trace::verified("CRC-32C", "SCTP packet", stored as u64, computed as u64);
+            stored == computed
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{checksum, stored_checksum, verify, write_checksum, CHECKSUM_OFFSET};
+    use crate::oneshot::crc32c;
+
+    /// An INIT chunk from port 5000 to port 80 with a zero verification tag.
+    const PACKET: [u8; 32] = [
+        0x13, 0x88, 0x00, 0x50, 0x00, 0x00, 0x00, 0x00, 0xDE, 0xAD, 0xBE, 0xEF, 0x01, 0x00, 0x00,
+        0x14, 0x12, 0x34, 0x56, 0x78, 0x00, 0x01, 0x00, 0x00, 0x00, 0x0A, 0xFF, 0xFF, 0x00, 0x00,
+        0x00, 0x01,
+    ];
+
+    #[test]
+    fn checksum_test() {
+        let mut zeroed = PACKET;
+        zeroed[CHECKSUM_OFFSET..CHECKSUM_OFFSET + 4].fill(0);
+        assert_eq!(checksum(&PACKET), Some(crc32c(&zeroed)));
+        assert_eq!(checksum(&PACKET[..11]), None);
+    }
+
+    #[test]
+    fn write_verify_test() {
+        let mut packet = PACKET;
+        assert!(!verify(&packet));
+        let crc = write_checksum(&mut packet).unwrap();
+        assert_eq!(stored_checksum(&packet), Some(crc));
+        assert_eq!(
+            &packet[CHECKSUM_OFFSET..CHECKSUM_OFFSET + 4],
+            &crc.to_le_bytes()
+        );
+        assert!(verify(&packet));
+
+        packet[20] ^= 0x40;
+        assert!(!verify(&packet));
+        assert!(!verify(&packet[..4]));
+    }
+}
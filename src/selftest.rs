@@ -0,0 +1,184 @@
+//! A start-up self test over the whole catalogue.
+//!
+//! Safety-critical firmware is expected to show that its checksum code works before relying
+//! on it. [`failures`] runs every [catalogue](crate::catalog) entry through every update
+//! path of the crate and yields each disagreement: the check value of `"123456789"` catches
+//! corrupted tables, and a 1 KiB buffer, long enough for the CRC and carry-less multiply
+//! instructions to kick in, catches a miscompiled accelerated path by comparing it with the
+//! bit-by-bit engine. Nothing is allocated; with `std`, [`self_test`] collects a [`Report`].
+
+use crate::catalog::{self, AnyAlgorithm};
+use crate::engine::{Params, Width};
+use crate::strategy::{NoTable, Sliced, Strategy, Table16, Table256};
+use crate::Crc;
+
+/// An update path of the crate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Backend {
+    /// The bit-by-bit reference engine, which never uses CPU instructions.
+    Bitwise,
+    /// A [`Crc`] of each strategy. All of them hand long buffers to the CPU's CRC or
+    /// carry-less multiply instructions when it has them.
+    NoTable,
+    Table16,
+    Table256,
+    Sliced8,
+    Sliced16,
+}
+
+impl Backend {
+    pub const ALL: [Backend; 6] = [
+        Self::Bitwise,
+        Self::NoTable,
+        Self::Table16,
+        Self::Table256,
+        Self::Sliced8,
+        Self::Sliced16,
+    ];
+}
+
+/// The data a [`Failure`] was found on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Input {
+    /// `"123456789"`, compared with the catalogued check value.
+    Check,
+    /// The 1 KiB buffer, compared with the [`Backend::Bitwise`] result.
+    Buffer,
+}
+
+/// A backend that computed the wrong checksum for an algorithm.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Failure {
+    pub algorithm: &'static str,
+    pub backend: Backend,
+    pub input: Input,
+    pub expected: u64,
+    pub actual: u64,
+}
+
+const BUFFER_LEN: usize = 1024;
+
+/// A fixed pseudo-random pattern, so that every byte value and table entry is used.
+const BUFFER: [u8; BUFFER_LEN] = {
+    let mut buffer = [0; BUFFER_LEN];
+    let mut state = 0x2545_F491u32;
+    let mut i = 0;
+    while i < BUFFER_LEN {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        buffer[i] = state as u8;
+        i += 1;
+    }
+    buffer
+};
+
+fn strategy<W: Width, S: Strategy>(algorithm: &catalog::Algorithm<W>, data: &[u8]) -> W {
+    Crc::<W, S>::with_strategy(algorithm.params).checksum(data)
+}
+
+fn checksum<W: Width>(algorithm: &catalog::Algorithm<W>, backend: Backend, data: &[u8]) -> W {
+    match backend {
+        Backend::Bitwise => Params::from(algorithm.params).checksum(data),
+        Backend::NoTable => strategy::<W, NoTable>(algorithm, data),
+        Backend::Table16 => strategy::<W, Table16>(algorithm, data),
+        Backend::Table256 => strategy::<W, Table256>(algorithm, data),
+        Backend::Sliced8 => strategy::<W, Sliced<8>>(algorithm, data),
+        Backend::Sliced16 => strategy::<W, Sliced<16>>(algorithm, data),
+    }
+}
+
+const INPUTS: [Input; 2] = [Input::Check, Input::Buffer];
+
+/// The failures of one algorithm, at most one per backend and input.
+fn check<W: Width>(algorithm: &catalog::Algorithm<W>) -> [Option<Failure>; 12] {
+    let reference = Params::from(algorithm.params).checksum(&BUFFER);
+    let mut failures = [None; 12];
+    let cases = Backend::ALL
+        .iter()
+        .flat_map(|&backend| INPUTS.map(|input| (backend, input)));
+    for (slot, (backend, input)) in failures.iter_mut().zip(cases) {
+        let (data, expected) = match input {
+            Input::Check => (&b"123456789"[..], algorithm.check),
+            Input::Buffer => (&BUFFER[..], reference),
+        };
+        let actual = checksum(algorithm, backend, data);
+        if actual != expected {
+            *slot = Some(Failure {
+                algorithm: algorithm.name,
+                backend,
+                input,
+                expected: Into::<u128>::into(expected) as u64,
+                actual: Into::<u128>::into(actual) as u64,
+            });
+        }
+    }
+    failures
+}
+
+fn check_any(algorithm: AnyAlgorithm) -> impl Iterator<Item = Failure> {
+    let failures = match algorithm {
+        AnyAlgorithm::Crc8(algorithm) => check(&algorithm),
+        AnyAlgorithm::Crc16(algorithm) => check(&algorithm),
+        AnyAlgorithm::Crc32(algorithm) => check(&algorithm),
+        AnyAlgorithm::Crc64(algorithm) => check(&algorithm),
+    };
+    failures.into_iter().flatten()
+}
+
+/// Runs every catalogue entry on every [`Backend`] and yields what went wrong; an empty
+/// iterator means the test passed. The work is done as the iterator is advanced.
+pub fn failures() -> impl Iterator<Item = Failure> {
+    catalog::algorithms().flat_map(check_any)
+}
+
+/// The outcome of [`self_test`].
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Report {
+    /// How many algorithm and backend pairs were run.
+    pub checked: usize,
+    pub failures: Vec<Failure>,
+}
+
+#[cfg(feature = "std")]
+impl Report {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Runs [`failures`] to the end.
+#[cfg(feature = "std")]
+pub fn self_test() -> Report {
+    Report {
+        checked: catalog::algorithms().count() * Backend::ALL.len(),
+        failures: failures().collect(),
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn self_test_test() {
+        let report = self_test();
+        assert!(report.passed(), "{:?}", report.failures);
+        assert_eq!(report.checked, catalog::algorithms().count() * 6);
+
+        // A corrupted check value is reported by every backend.
+        let broken = catalog::Algorithm {
+            check: 0x1234,
+            ..catalog::CRC_16_MODBUS
+        };
+        let failures: Vec<Failure> = check(&broken).into_iter().flatten().collect();
+        assert_eq!(failures.len(), Backend::ALL.len());
+        assert!(failures
+            .iter()
+            .all(|failure| failure.input == Input::Check && failure.actual == 0x4B37));
+    }
+}
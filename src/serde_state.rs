@@ -0,0 +1,83 @@
+//! `Serialize`/`Deserialize` for the CRC states.
+//!
+//! A state is stored as its parameters plus the register in the Rocksoft form, the
+//! unreflected value the register would hold in a plain MSB-first shift loop, so the
+//! encoding does not depend on how the state lays out its lookup table. The table is rebuilt
+//! on deserialization, and parameters that a state could not have been created with are
+//! rejected rather than panicking.
+
+use crate::{table, CrcBuilder, CrcParams, CRC, CRC128, CRC16, CRC32, CRC64, CRC8};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "CrcState")]
+struct State<N> {
+    params: CrcParams<N>,
+    register: N,
+}
+
+macro_rules! impl_serde {
+    ($crc:ident, $n:ty) => {
+        impl Serialize for $crc {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                State {
+                    params: self.params,
+                    register: table::to_engine(&self.params, self.crc),
+                }
+                .serialize(serializer)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $crc {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let State { params, register } = State::<$n>::deserialize(deserializer)?;
+                let params = CrcBuilder::from(params)
+                    .params()
+                    .map_err(D::Error::custom)?;
+                if register >> (params.width - 1) >> 1 != 0 {
+                    return Err(D::Error::custom("register does not fit the CRC width"));
+                }
+                Ok(Self {
+                    crc: table::from_engine(&params, register),
+                    ..Self::create(params)
+                })
+            }
+        }
+    };
+}
+
+impl_serde!(CRC8, u8);
+impl_serde!(CRC16, u16);
+impl_serde!(CRC32, u32);
+impl_serde!(CRC64, u64);
+impl_serde!(CRC128, u128);
+
+#[cfg(test)]
+mod tests {
+    use crate::{catalog, CrcParams, CRC, CRC16, CRC32};
+
+    #[test]
+    fn serde_test() {
+        let mut crc = CRC32::from_algorithm(&catalog::CRC_32_ISO_HDLC);
+        crc.update(b"12345");
+        let bytes = postcard::to_allocvec(&crc).unwrap();
+        let mut resumed: CRC32 = postcard::from_bytes(&bytes).unwrap();
+        resumed.update(b"6789");
+        assert_eq!(resumed.finalize(), catalog::CRC_32_ISO_HDLC.check);
+
+        let mut crc = CRC16::from_algorithm(&catalog::CRC_16_IBM_3740);
+        crc.update(b"1234");
+        let mut resumed: CRC16 =
+            postcard::from_bytes(&postcard::to_allocvec(&crc).unwrap()).unwrap();
+        resumed.update(b"56789");
+        assert_eq!(resumed.finalize(), catalog::CRC_16_IBM_3740.check);
+
+        let params = CrcParams {
+            width: 40u32,
+            ..catalog::CRC_32_ISO_HDLC.params
+        };
+        let bytes = postcard::to_allocvec(&(params, 0u32)).unwrap();
+        assert!(postcard::from_bytes::<CRC32>(&bytes).is_err());
+    }
+}
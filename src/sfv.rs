@@ -0,0 +1,178 @@
+//! Simple File Verification (`.sfv`) checksum files.
+//!
+//! An SFV file lists one file per line followed by its CRC-32/ISO-HDLC in hex, e.g.
+//! `disc1.iso 1A2B3C4D`. Lines starting with `;` are comments. File names may contain
+//! spaces, so the checksum is the last field of the line; names are relative to the
+//! directory holding the SFV file.
+
+use crate::catalog::CRC_32_ISO_HDLC;
+use crate::Crc;
+use std::fmt::Write as _;
+use std::io;
+use std::path::Path;
+
+/// A file listed in an SFV file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SfvEntry {
+    pub name: String,
+    pub crc: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// One-based line number.
+    pub line: usize,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "malformed SFV entry on line {}", self.line)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses the entries of an SFV file, skipping comments and blank lines.
+pub fn parse(text: &str) -> Result<Vec<SfvEntry>, ParseError> {
+    let mut entries = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(';') {
+            continue;
+        }
+        let error = ParseError { line: i + 1 };
+        let (name, crc) = line.rsplit_once([' ', '\t']).ok_or(error)?;
+        if crc.len() != 8 || !crc.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            return Err(error);
+        }
+        entries.push(SfvEntry {
+            name: name.trim_end().to_string(),
+            crc: u32::from_str_radix(crc, 16).map_err(|_| error)?,
+        });
+    }
+    Ok(entries)
+}
+
+/// Formats `entries` as an SFV file with upper-case checksums.
+pub fn format(entries: &[SfvEntry]) -> String {
+    let mut text = String::new();
+    for entry in entries {
+        let _ = writeln!(text, "{} {:08X}", entry.name, entry.crc);
+    }
+    text
+}
+
+/// Computes the CRC-32 of the file at `path`.
+pub fn crc32_file(path: &Path) -> io::Result<u32> {
+    Crc::<u32>::cached(&CRC_32_ISO_HDLC).checksum_path(path)
+}
+
+/// Creates an entry for each of `names`, relative to `dir`.
+pub fn create<'a>(
+    dir: &Path,
+    names: impl IntoIterator<Item = &'a str>,
+) -> io::Result<Vec<SfvEntry>> {
+    names
+        .into_iter()
+        .map(|name| {
+            Ok(SfvEntry {
+                name: name.to_string(),
+                crc: crc32_file(&dir.join(name))?,
+            })
+        })
+        .collect()
+}
+
+/// How a listed file compared.
+#[derive(Debug)]
+pub enum Status {
+    Ok,
+    Failed {
+        actual: u32,
+    },
+    Missing,
+    /// The file exists but could not be read.
+    Error(io::Error),
+}
+
+/// The result of [`verify`], one status per entry in the order of the SFV file.
+#[derive(Debug, Default)]
+pub struct Report {
+    pub results: Vec<(SfvEntry, Status)>,
+}
+
+impl Report {
+    fn count(&self, matches: fn(&Status) -> bool) -> usize {
+        self.results
+            .iter()
+            .filter(|(_, status)| matches(status))
+            .count()
+    }
+
+    pub fn ok(&self) -> usize {
+        self.count(|status| matches!(status, Status::Ok))
+    }
+
+    pub fn failed(&self) -> usize {
+        self.count(|status| matches!(status, Status::Failed { .. }))
+    }
+
+    /// Missing and unreadable files.
+    pub fn missing(&self) -> usize {
+        self.count(|status| matches!(status, Status::Missing | Status::Error(_)))
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.ok() == self.results.len()
+    }
+}
+
+/// Checks every entry against the file of its name in `dir`.
+pub fn verify(entries: &[SfvEntry], dir: &Path) -> Report {
+    let results = entries
+        .iter()
+        .map(|entry| {
+            let status = match crc32_file(&dir.join(&entry.name)) {
+                Ok(actual) if actual == entry.crc => Status::Ok,
+                Ok(actual) => Status::Failed { actual },
+                Err(err) if err.kind() == io::ErrorKind::NotFound => Status::Missing,
+                Err(err) => Status::Error(err),
+            };
+            (entry.clone(), status)
+        })
+        .collect();
+    Report { results }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn sfv_test() {
+        let dir = std::env::temp_dir().join(format!("crclib-sfv-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("check.txt"), b"123456789").unwrap();
+        fs::write(dir.join("with space.bin"), b"hello world").unwrap();
+
+        let entries = create(&dir, ["check.txt", "with space.bin"]).unwrap();
+        let text = format(&entries);
+        assert_eq!(text, "check.txt CBF43926\nwith space.bin 0D4A1185\n");
+        let parsed = parse(&format!("; generated\n\n{}gone.bin 00000000\n", text)).unwrap();
+        assert_eq!(parsed[..2], entries[..]);
+
+        fs::write(dir.join("with space.bin"), b"hello world!").unwrap();
+        let report = verify(&parsed, &dir);
+        assert!(matches!(report.results[0].1, Status::Ok));
+        assert!(matches!(report.results[1].1, Status::Failed { .. }));
+        assert!(matches!(report.results[2].1, Status::Missing));
+        assert_eq!((report.ok(), report.failed(), report.missing()), (1, 1, 1));
+        assert!(!report.is_ok());
+
+        assert_eq!(parse("a.bin\nb.bin 1234"), Err(ParseError { line: 1 }));
+        assert_eq!(parse("b.bin 1234"), Err(ParseError { line: 1 }));
+        assert_eq!(parse("b.bin +1234567"), Err(ParseError { line: 1 }));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
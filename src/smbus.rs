@@ -0,0 +1,75 @@
+//! SMBus Packet Error Codes.
+//!
+//! The PEC is the CRC-8/SMBUS (polynomial `0x07`, init `0x00`) of every byte of a
+//! transaction up to the PEC itself, address bytes included. The default
+//! [`CRC8`](crate::CRC8) starts from `0xFF` and inverts its result, so it does not compute
+//! the PEC.
+
+use crate::const_crc::Crc8Smbus;
+use crate::oneshot::crc8;
+use crate::CRC;
+
+/// The PEC of a transaction's bytes, address bytes included.
+pub fn pec(bytes: &[u8]) -> u8 {
+    crc8(bytes)
+}
+
+/// The byte addressing the 7-bit `address`, with the R/W bit set for reads.
+///
+/// # Panics
+///
+/// Panics if `address` does not fit in 7 bits.
+pub fn address_byte(address: u8, read: bool) -> u8 {
+    assert!(address < 0x80, "SMBus addresses have 7 bits");
+    address << 1 | read as u8
+}
+
+/// The PEC of a write to `address`: Send Byte, Write Byte/Word and Block Write, with
+/// `bytes` being the command code followed by the data.
+///
+/// # Panics
+///
+/// Panics if `address` does not fit in 7 bits.
+pub fn write_pec(address: u8, bytes: &[u8]) -> u8 {
+    let mut crc = Crc8Smbus::new();
+    crc.update(&[address_byte(address, false)]);
+    crc.update(bytes);
+    crc.finalize()
+}
+
+/// The PEC of a Read Byte/Word or Block Read of `command` from `address` that returned
+/// `data`. It covers the write of the command, the repeated start's address byte and the
+/// data.
+///
+/// # Panics
+///
+/// Panics if `address` does not fit in 7 bits.
+pub fn read_pec(address: u8, command: u8, data: &[u8]) -> u8 {
+    let mut crc = Crc8Smbus::new();
+    crc.update(&[
+        address_byte(address, false),
+        command,
+        address_byte(address, true),
+    ]);
+    crc.update(data);
+    crc.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pec_test() {
+        assert_eq!(pec(b"123456789"), 0xF4);
+        assert_eq!(address_byte(0x5A, false), 0xB4);
+        assert_eq!(address_byte(0x5A, true), 0xB5);
+        // Write Word 0x1234 to command 0x10, data low byte first.
+        assert_eq!(write_pec(0x5A, &[0x10, 0x34, 0x12]), 0xB1);
+        assert_eq!(
+            write_pec(0x5A, &[0x10, 0x34, 0x12]),
+            pec(&[0xB4, 0x10, 0x34, 0x12])
+        );
+        assert_eq!(read_pec(0x5A, 0x10, &[0x34, 0x12]), 0xD0);
+    }
+}
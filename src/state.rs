@@ -0,0 +1,194 @@
+//! A fixed binary layout for checkpointing CRC states.
+//!
+//! [`CRC32::export_state`](crate::CRC32::export_state) and the same method on the other state
+//! types return the state as `STATE_LEN` bytes, and `resume_from_state` turns them back into
+//! a state that continues where the exported one stopped. The layout is stable across crate
+//! versions and platforms. With `n` the size of the register type in bytes:
+//!
+//! | Offset       | Size | Field                                             |
+//! |--------------|------|---------------------------------------------------|
+//! | 0            | 1    | layout version, currently 1                       |
+//! | 1            | 1    | width in bits                                     |
+//! | 2            | 1    | flags: bit 0 `refin`, bit 1 `refout`              |
+//! | 3            | `n`  | `poly`, big-endian                                |
+//! | 3 + `n`      | `n`  | `init`, big-endian                                |
+//! | 3 + 2`n`     | `n`  | `xorout`, big-endian                              |
+//! | 3 + 3`n`     | `n`  | register, unreflected and right-aligned, big-endian |
+//!
+//! The register is the value a plain MSB-first shift loop would hold, independent of how
+//! a state stores it internally.
+
+use crate::algorithm::BuildError;
+use crate::{table, CrcBuilder, CrcParams, CRC, CRC128, CRC16, CRC32, CRC64, CRC8};
+use core::fmt;
+
+const VERSION: u8 = 1;
+
+/// Why bytes could not be resumed from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum StateError {
+    /// The layout version is not one this crate reads.
+    Version(u8),
+    /// Unknown bits are set in the flags byte.
+    Flags(u8),
+    /// The parameters are invalid.
+    Params(BuildError),
+    /// The register has bits set above the width.
+    Register,
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Version(version) => write!(f, "unsupported state layout version {}", version),
+            Self::Flags(flags) => write!(f, "unknown state flags {:#04x}", flags),
+            Self::Params(err) => write!(f, "invalid CRC parameters: {}", err),
+            Self::Register => write!(f, "register does not fit the CRC width"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for StateError {}
+
+macro_rules! impl_state {
+    ($crc:ident, $n:ty) => {
+        impl $crc {
+            /// Length of [`Self::export_state`]'s output.
+            pub const STATE_LEN: usize = 3 + 4 * core::mem::size_of::<$n>();
+
+            /// The raw CRC register, before the output reflection and the final XOR: the value
+            /// a plain MSB-first shift loop would hold after the data so far, right-aligned.
+            /// For reflected algorithms it is the bit reverse of the register LSB-first code
+            /// such as zlib keeps. [`Self::set_raw_state`] loads a register of this form, e.g.
+            /// one read from a hardware CRC unit.
+            pub fn raw_state(&self) -> $n {
+                table::to_engine(&self.params, self.crc)
+            }
+
+            /// Replaces the register by `register`, in the form [`Self::raw_state`] returns, so
+            /// that updating continues from it. Bits above the width are ignored.
+            pub fn set_raw_state(&mut self, register: $n) {
+                let mask = <$n>::MAX >> (<$n>::BITS - self.params.width);
+                self.crc = table::from_engine(&self.params, register & mask);
+            }
+
+            /// The state in the layout described in the [`state`](crate::state) module.
+            pub fn export_state(&self) -> [u8; Self::STATE_LEN] {
+                const N: usize = core::mem::size_of::<$n>();
+                let params = &self.params;
+                let mut bytes = [0; Self::STATE_LEN];
+                bytes[0] = VERSION;
+                bytes[1] = params.width as u8;
+                bytes[2] = params.refin as u8 | (params.refout as u8) << 1;
+                for (i, value) in [params.poly, params.init, params.xorout, self.raw_state()]
+                    .into_iter()
+                    .enumerate()
+                {
+                    bytes[3 + i * N..3 + (i + 1) * N].copy_from_slice(&value.to_be_bytes());
+                }
+                bytes
+            }
+
+            /// Recreates a state exported with [`Self::export_state`].
+            pub fn resume_from_state(bytes: &[u8; Self::STATE_LEN]) -> Result<Self, StateError> {
+                const N: usize = core::mem::size_of::<$n>();
+                if bytes[0] != VERSION {
+                    return Err(StateError::Version(bytes[0]));
+                }
+                if bytes[2] & !0b11 != 0 {
+                    return Err(StateError::Flags(bytes[2]));
+                }
+                let value = |i: usize| {
+                    <$n>::from_be_bytes(bytes[3 + i * N..3 + (i + 1) * N].try_into().unwrap())
+                };
+                let params = CrcParams {
+                    width: bytes[1].into(),
+                    poly: value(0),
+                    init: value(1),
+                    refin: bytes[2] & 1 != 0,
+                    refout: bytes[2] & 2 != 0,
+                    xorout: value(2),
+                };
+                let params = CrcBuilder::from(params)
+                    .params()
+                    .map_err(StateError::Params)?;
+                let register = value(3);
+                if register >> (params.width - 1) >> 1 != 0 {
+                    return Err(StateError::Register);
+                }
+                Ok(Self {
+                    crc: table::from_engine(&params, register),
+                    ..Self::create(params)
+                })
+            }
+        }
+    };
+}
+
+impl_state!(CRC8, u8);
+impl_state!(CRC16, u16);
+impl_state!(CRC32, u32);
+impl_state!(CRC64, u64);
+impl_state!(CRC128, u128);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog;
+
+    #[test]
+    fn state_test() {
+        let mut crc = CRC32::from_algorithm(&catalog::CRC_32_ISO_HDLC);
+        crc.update(b"12345");
+        let state = crc.export_state();
+        assert_eq!(state[..3], [1, 32, 0b11]);
+        assert_eq!(state[3..7], 0x04C1_1DB7u32.to_be_bytes());
+        let mut resumed = CRC32::resume_from_state(&state).unwrap();
+        resumed.update(b"6789");
+        assert_eq!(resumed.finalize(), catalog::CRC_32_ISO_HDLC.check);
+
+        let mut crc = CRC64::from_algorithm(&catalog::CRC_64_ECMA_182);
+        crc.update(b"1234");
+        let mut resumed = CRC64::resume_from_state(&crc.export_state()).unwrap();
+        resumed.update(b"56789");
+        assert_eq!(resumed.finalize(), catalog::CRC_64_ECMA_182.check);
+
+        let mut state = CRC8::default().export_state();
+        state[0] = 2;
+        assert_eq!(
+            CRC8::resume_from_state(&state).err(),
+            Some(StateError::Version(2))
+        );
+        state[0] = 1;
+        state[1] = 9;
+        assert_eq!(
+            CRC8::resume_from_state(&state).err(),
+            Some(StateError::Params(BuildError::InvalidWidth(9)))
+        );
+    }
+
+    #[test]
+    fn raw_state_test() {
+        // CRC-32/ISO-HDLC starts from an all-ones register, and reading "1" gives what the
+        // plain MSB-first loop holds, which is the reverse of zlib's register.
+        let mut crc = CRC32::from_algorithm(&catalog::CRC_32_ISO_HDLC);
+        assert_eq!(crc.raw_state(), u32::MAX);
+        crc.update(b"1");
+        let zlib = !crate::zlib::crc32(0, b"1");
+        assert_eq!(crc.raw_state(), zlib.reverse_bits());
+
+        let mut other = CRC32::from_algorithm(&catalog::CRC_32_ISO_HDLC);
+        other.set_raw_state(crc.raw_state());
+        other.update(b"23456789");
+        assert_eq!(other.finalize(), catalog::CRC_32_ISO_HDLC.check);
+
+        let mut crc = CRC16::from_algorithm(&catalog::CRC_16_XMODEM);
+        crc.update(b"123456789");
+        assert_eq!(crc.raw_state(), catalog::CRC_16_XMODEM.check);
+        let mut umts = CRC16::from_algorithm(&catalog::CRC_12_UMTS);
+        umts.set_raw_state(0xFABC);
+        assert_eq!(umts.raw_state(), 0xABC);
+    }
+}
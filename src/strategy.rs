@@ -0,0 +1,102 @@
+//! How a [`Crc`](crate::Crc) trades memory for speed.
+//!
+//! | Strategy          | Tables                 | Speed                          |
+//! |-------------------|------------------------|--------------------------------|
+//! | [`NoTable`]       | none                   | eight shifts per byte          |
+//! | [`Table16`]       | 16 entries             | two lookups per byte           |
+//! | [`Table256`]      | 256 entries            | one lookup per byte, default   |
+//! | [`Sliced<N>`]     | `N` times 256 entries  | `N` independent lookups per `N` bytes |
+//!
+//! An entry is one `W`, so a CRC-32 `Table256` takes 1 KiB and `Sliced<16>` 16 KiB. `NoTable`
+//! and `Table16` suit microcontrollers short on RAM; `Sliced<8>` and `Sliced<16>` pay off for
+//! buffers of a few hundred bytes and up. Where the CPU has CRC or carry-less multiply
+//! instructions they are used on top of any strategy.
+//!
+//! ```text
+//! let small = Crc::<u32, NoTable>::with_strategy(&catalog::CRC_32_ISO_HDLC);
+//! let fast = Crc::<u32, Sliced<16>>::with_strategy(&catalog::CRC_32_ISO_HDLC);
+//! ```
+
+use crate::engine::Width;
+use crate::table;
+use crate::CrcParams;
+
+/// A way of feeding bytes into a register in table layout.
+///
+/// The trait is implemented by the types of this module only.
+pub trait Strategy {
+    /// What is precomputed per algorithm.
+    type Tables<W: Width>: Clone;
+
+    fn build<W: Width>(params: &CrcParams<W>) -> Self::Tables<W>;
+
+    fn update<W: Width>(tables: &Self::Tables<W>, refin: bool, crc: W, data: &[u8]) -> W;
+}
+
+/// Bit by bit, keeping only the polynomial.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoTable;
+
+/// A nibble at a time with a 16-entry table.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Table16;
+
+/// A byte at a time with a 256-entry table.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Table256;
+
+/// Slice-by-`N`: `N` bytes at a time with `N` 256-entry tables, the tail with the first.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Sliced<const N: usize>;
+
+impl Strategy for NoTable {
+    type Tables<W: Width> = W;
+
+    fn build<W: Width>(params: &CrcParams<W>) -> W {
+        table::poly(params)
+    }
+
+    fn update<W: Width>(poly: &W, refin: bool, crc: W, data: &[u8]) -> W {
+        table::update_bitwise(*poly, refin, crc, data)
+    }
+}
+
+impl Strategy for Table16 {
+    type Tables<W: Width> = [W; 16];
+
+    fn build<W: Width>(params: &CrcParams<W>) -> [W; 16] {
+        table::build16(params)
+    }
+
+    fn update<W: Width>(table: &[W; 16], refin: bool, crc: W, data: &[u8]) -> W {
+        table::update16(table, refin, crc, data)
+    }
+}
+
+impl Strategy for Table256 {
+    type Tables<W: Width> = [W; 256];
+
+    fn build<W: Width>(params: &CrcParams<W>) -> [W; 256] {
+        table::build(params)
+    }
+
+    fn update<W: Width>(table: &[W; 256], refin: bool, crc: W, data: &[u8]) -> W {
+        table::update(table, refin, crc, data)
+    }
+}
+
+impl<const N: usize> Strategy for Sliced<N> {
+    type Tables<W: Width> = [[W; 256]; N];
+
+    /// # Panics
+    ///
+    /// Panics if `N` is zero.
+    fn build<W: Width>(params: &CrcParams<W>) -> [[W; 256]; N] {
+        assert!(N > 0, "slice-by-N needs at least one table");
+        table::build_sliced(params)
+    }
+
+    fn update<W: Width>(tables: &[[W; 256]; N], refin: bool, crc: W, data: &[u8]) -> W {
+        table::update_sliced(tables, refin, crc, data)
+    }
+}
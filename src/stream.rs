@@ -0,0 +1,134 @@
+use crate::CRC;
+use bytes::Bytes;
+use futures_core::Stream;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Drives `stream` to completion, feeding every chunk into `crc`.
+///
+/// Returns the total number of bytes seen together with the finalized CRC, or the first
+/// error produced by the stream. The chunks themselves are dropped as soon as they have
+/// been checksummed, so the body is never collected in memory.
+pub async fn checksum_stream<C, N, S, E>(mut crc: C, stream: S) -> Result<(u64, N), E>
+where
+    C: CRC<Width = N>,
+    S: Stream<Item = Result<Bytes, E>>,
+{
+    let mut stream = std::pin::pin!(stream);
+    let mut length = 0u64;
+    while let Some(chunk) = std::future::poll_fn(|cx| stream.as_mut().poll_next(cx)).await {
+        let chunk = chunk?;
+        crc.update(&chunk);
+        length += chunk.len() as u64;
+    }
+    Ok((length, crc.finalize()))
+}
+
+pin_project_lite::pin_project! {
+    /// Wraps a stream of byte chunks and computes their CRC as they are passed on.
+    ///
+    /// Items, errors included, are forwarded untouched, so the wrapper can sit in a proxy
+    /// between an upstream body and the client. Once the inner stream ends the finalized
+    /// CRC becomes available through [`CrcStream::checksum`].
+    pub struct CrcStream<S, C, N> {
+        #[pin]
+        inner: S,
+        crc: C,
+        checksum: Option<N>,
+    }
+}
+
+impl<S, C, N> CrcStream<S, C, N>
+where
+    C: CRC<Width = N>,
+    N: Copy,
+{
+    pub fn new(inner: S, crc: C) -> Self {
+        Self {
+            inner,
+            crc,
+            checksum: None,
+        }
+    }
+
+    /// The CRC of all chunks, or `None` while the stream has not ended.
+    pub fn checksum(&self) -> Option<N> {
+        self.checksum
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S, D, E, C, N> Stream for CrcStream<S, C, N>
+where
+    S: Stream<Item = Result<D, E>>,
+    D: AsRef<[u8]>,
+    C: CRC<Width = N>,
+    N: Copy,
+{
+    type Item = Result<D, E>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        let item = std::task::ready!(this.inner.poll_next(cx));
+        match &item {
+            Some(Ok(chunk)) => this.crc.update(chunk.as_ref()),
+            Some(Err(_)) => {}
+            None => *this.checksum = Some(this.crc.finalize()),
+        }
+        Poll::Ready(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{checksum_stream, CrcStream};
+    use crate::{CRC, CRC32};
+    use bytes::Bytes;
+    use futures::executor::block_on;
+    use futures::stream::{self, StreamExt};
+
+    const TEST_DATA: &[u8] = b"hello world";
+
+    #[test]
+    fn checksum_stream_test() {
+        let chunks = TEST_DATA
+            .chunks(3)
+            .map(|chunk| Ok::<_, ()>(Bytes::copy_from_slice(chunk)));
+        let (length, crc) = block_on(checksum_stream(CRC32::default(), stream::iter(chunks)))
+            .expect("stream should not fail");
+
+        let mut expected = CRC32::default();
+        expected.update(TEST_DATA);
+        assert_eq!(length, TEST_DATA.len() as u64);
+        assert_eq!(crc, expected.finalize());
+    }
+
+    #[test]
+    fn checksum_stream_error_test() {
+        let chunks = vec![Ok(Bytes::from_static(b"hello")), Err("connection reset")];
+        let result = block_on(checksum_stream(CRC32::default(), stream::iter(chunks)));
+        assert_eq!(result, Err("connection reset"));
+    }
+
+    #[test]
+    fn crc_stream_test() {
+        let chunks = TEST_DATA
+            .chunks(4)
+            .map(|chunk| Ok::<_, ()>(Bytes::copy_from_slice(chunk)));
+        let mut stream = CrcStream::new(stream::iter(chunks), CRC32::default());
+        let mut forwarded = Vec::new();
+        while let Some(chunk) = block_on(stream.next()) {
+            assert_eq!(stream.checksum(), None);
+            forwarded.extend_from_slice(&chunk.unwrap());
+        }
+        assert_eq!(forwarded, TEST_DATA);
+        assert_eq!(stream.checksum(), Some(0x44F71378));
+    }
+}
@@ -0,0 +1,254 @@
+//! Byte-at-a-time lookup tables shared by [`Crc`](crate::Crc) and the state types.
+//!
+//! The register layout is the same everywhere: MSB-first registers are kept in the top
+//! `width` bits of `W`, reflected ones in the low bits, so the tables and loops below only
+//! depend on the width through the shifted polynomial.
+
+use crate::engine::Width;
+use crate::CrcParams;
+
+/// Checks that `params` fit the register type.
+///
+/// # Panics
+///
+/// Panics if `params.width` is zero or wider than `W`.
+pub(crate) fn checked<W: Width>(params: CrcParams<W>) -> CrcParams<W> {
+    assert!(
+        (1..=W::BITS).contains(&params.width),
+        "CRC width must fit the register type"
+    );
+    params
+}
+
+/// The register before any data, in table layout.
+pub(crate) fn start<W: Width>(params: &CrcParams<W>) -> W {
+    let shift = W::BITS - params.width;
+    if params.refin {
+        params.init.reverse_bits() >> shift
+    } else {
+        params.init << shift
+    }
+}
+
+/// The polynomial in table layout: reflected, or shifted up to the top of `W`.
+pub(crate) fn poly<W: Width>(params: &CrcParams<W>) -> W {
+    let shift = W::BITS - params.width;
+    if params.refin {
+        params.poly.reverse_bits() >> shift
+    } else {
+        params.poly << shift
+    }
+}
+
+/// Shifts `bits` bits out of the register one at a time, `poly` being in table layout.
+fn shift<W: Width>(poly: W, refin: bool, mut crc: W, bits: u32) -> W {
+    for _bit in 0..bits {
+        if refin {
+            let lsb = crc & W::ONE != W::ZERO;
+            crc = crc >> 1;
+            if lsb {
+                crc = crc ^ poly;
+            }
+        } else {
+            let msb = crc >> (W::BITS - 1) != W::ZERO;
+            crc = crc << 1;
+            if msb {
+                crc = crc ^ poly;
+            }
+        }
+    }
+    crc
+}
+
+/// The effect of each byte value on an empty register, computed bit by bit.
+pub(crate) fn build<W: Width>(params: &CrcParams<W>) -> [W; 256] {
+    let poly = poly(params);
+    let mut table = [W::ZERO; 256];
+    for (i, entry) in table.iter_mut().enumerate() {
+        let crc = W::from_byte(i as u8);
+        let crc = if params.refin {
+            crc
+        } else {
+            crc << (W::BITS - 8)
+        };
+        *entry = shift(poly, params.refin, crc, 8);
+    }
+    table
+}
+
+/// Like [`build`], for the 16 nibble values.
+pub(crate) fn build16<W: Width>(params: &CrcParams<W>) -> [W; 16] {
+    let poly = poly(params);
+    let mut table = [W::ZERO; 16];
+    for (i, entry) in table.iter_mut().enumerate() {
+        let crc = W::from_byte(i as u8);
+        let crc = if params.refin {
+            crc
+        } else {
+            crc << (W::BITS - 4)
+        };
+        *entry = shift(poly, params.refin, crc, 4);
+    }
+    table
+}
+
+/// Feeds `data` into the register `crc` bit by bit, without any table.
+pub(crate) fn update_bitwise<W: Width>(poly: W, refin: bool, mut crc: W, data: &[u8]) -> W {
+    for &byte in data {
+        let byte = W::from_byte(byte);
+        crc = crc ^ if refin { byte } else { byte << (W::BITS - 8) };
+        crc = shift(poly, refin, crc, 8);
+    }
+    crc
+}
+
+/// Feeds `data` into the register `crc` with two lookups per byte in a [`build16`] table.
+pub(crate) fn update16<W: Width>(table: &[W; 16], refin: bool, mut crc: W, data: &[u8]) -> W {
+    for &byte in data {
+        let byte = W::from_byte(byte);
+        if refin {
+            crc = crc ^ byte;
+            for _nibble in 0..2 {
+                crc = table[(crc.low_byte() & 0xF) as usize] ^ (crc >> 4);
+            }
+        } else {
+            crc = crc ^ (byte << (W::BITS - 8));
+            for _nibble in 0..2 {
+                crc = table[(crc >> (W::BITS - 4)).low_byte() as usize] ^ (crc << 4);
+            }
+        }
+    }
+    crc
+}
+
+/// Feeds `data` into the register `crc` with one lookup per byte.
+pub(crate) fn update<W: Width>(table: &[W; 256], refin: bool, mut crc: W, data: &[u8]) -> W {
+    if refin {
+        for &byte in data {
+            let rest = if W::BITS > 8 { crc >> 8 } else { W::ZERO };
+            crc = table[(crc.low_byte() ^ byte) as usize] ^ rest;
+        }
+    } else {
+        for &byte in data {
+            let rest = if W::BITS > 8 { crc << 8 } else { W::ZERO };
+            crc = table[((crc >> (W::BITS - 8)).low_byte() ^ byte) as usize] ^ rest;
+        }
+    }
+    crc
+}
+
+/// Feeds the first `len` bytes of each of `data` into the matching register, one byte of every
+/// register per step. The registers do not depend on each other, so the CPU can overlap their
+/// lookups, where [`update`] has to wait for each lookup before starting the next.
+pub(crate) fn update_interleaved<W: Width, const N: usize>(
+    table: &[W; 256],
+    refin: bool,
+    crcs: &mut [W; N],
+    data: [&[u8]; N],
+    len: usize,
+) {
+    let data = data.map(|data| &data[..len]);
+    let mut regs = *crcs;
+    if refin {
+        for i in 0..len {
+            for k in 0..N {
+                let rest = if W::BITS > 8 { regs[k] >> 8 } else { W::ZERO };
+                regs[k] = table[(regs[k].low_byte() ^ data[k][i]) as usize] ^ rest;
+            }
+        }
+    } else {
+        for i in 0..len {
+            for k in 0..N {
+                let rest = if W::BITS > 8 { regs[k] << 8 } else { W::ZERO };
+                regs[k] =
+                    table[((regs[k] >> (W::BITS - 8)).low_byte() ^ data[k][i]) as usize] ^ rest;
+            }
+        }
+    }
+    *crcs = regs;
+}
+
+/// Converts a register in table layout to the engine's, which is MSB-first and right-aligned.
+pub(crate) fn to_engine<W: Width>(params: &CrcParams<W>, crc: W) -> W {
+    let shift = W::BITS - params.width;
+    if params.refin {
+        (crc << shift).reverse_bits()
+    } else {
+        crc >> shift
+    }
+}
+
+/// Inverse of [`to_engine`].
+pub(crate) fn from_engine<W: Width>(params: &CrcParams<W>, crc: W) -> W {
+    let shift = W::BITS - params.width;
+    if params.refin {
+        crc.reverse_bits() >> shift
+    } else {
+        crc << shift
+    }
+}
+
+/// Turns a register in table layout into the checksum.
+pub(crate) fn finalize<W: Width>(params: &CrcParams<W>, crc: W) -> W {
+    let shift = W::BITS - params.width;
+    let crc = if params.refin { crc } else { crc >> shift };
+    let crc = if params.refin != params.refout {
+        crc.reverse_bits() >> shift
+    } else {
+        crc
+    };
+    crc ^ params.xorout
+}
+
+/// Tables for slice-by-`N`: entry `k` holds the effect of a byte followed by `k` zero bytes,
+/// so `N` bytes can be folded into the register with `N` independent lookups.
+pub(crate) fn build_sliced<W: Width, const N: usize>(params: &CrcParams<W>) -> [[W; 256]; N] {
+    let mut tables = [[W::ZERO; 256]; N];
+    tables[0] = build(params);
+    for k in 1..N {
+        for i in 0..256 {
+            let prev = tables[k - 1][i];
+            tables[k][i] = if params.refin {
+                let rest = if W::BITS > 8 { prev >> 8 } else { W::ZERO };
+                rest ^ tables[0][prev.low_byte() as usize]
+            } else {
+                let rest = if W::BITS > 8 { prev << 8 } else { W::ZERO };
+                rest ^ tables[0][(prev >> (W::BITS - 8)).low_byte() as usize]
+            };
+        }
+    }
+    tables
+}
+
+/// Like [`update`], but takes `N` bytes per step using the tables of [`build_sliced`].
+pub(crate) fn update_sliced<W: Width, const N: usize>(
+    tables: &[[W; 256]; N],
+    refin: bool,
+    mut crc: W,
+    data: &[u8],
+) -> W {
+    let register_bytes = (W::BITS / 8) as usize;
+    let shift = 8 * N as u32;
+    let mut chunks = data.chunks_exact(N);
+    for chunk in &mut chunks {
+        let mut next = if shift >= W::BITS {
+            W::ZERO
+        } else if refin {
+            crc >> shift
+        } else {
+            crc << shift
+        };
+        for (i, &byte) in chunk.iter().enumerate() {
+            let overlap = if i >= register_bytes {
+                0
+            } else if refin {
+                (crc >> (8 * i as u32)).low_byte()
+            } else {
+                (crc >> (W::BITS - 8 - 8 * i as u32)).low_byte()
+            };
+            next = next ^ tables[N - 1 - i][(byte ^ overlap) as usize];
+        }
+        crc = next;
+    }
+    update(&tables[0], refin, crc, chunks.remainder())
+}
@@ -0,0 +1,197 @@
+//! Known-answer test vectors for the CRC state types.
+//!
+//! Every [`Vector`] holds an input and the checksum each of [`CRC8`](crate::CRC8) through
+//! [`CRC128`](crate::CRC128) produces for it with its default polynomial. Bindings and
+//! downstream crates can run these to check their integration, and should feed each input
+//! both whole and split by every entry of [`CHUNK_SIZES`] via [`chunks`]: a checksum that
+//! changes with the split points to state being lost between updates.
+
+/// The checksums of one input, one per state type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Expected {
+    pub crc8: u8,
+    pub crc16: u16,
+    pub crc32: u32,
+    pub crc64: u64,
+    pub crc128: u128,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Vector {
+    /// A short description of the input, for test output.
+    pub name: &'static str,
+    pub input: &'static [u8],
+    pub expected: Expected,
+}
+
+const fn all_bytes() -> [u8; 256] {
+    let mut bytes = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        bytes[i] = i as u8;
+        i += 1;
+    }
+    bytes
+}
+
+const ALL_BYTES: [u8; 256] = all_bytes();
+
+pub const VECTORS: &[Vector] = &[
+    Vector {
+        name: "empty",
+        input: b"",
+        expected: Expected {
+            crc8: 0x00,
+            crc16: 0x0000,
+            crc32: 0x0000_0000,
+            crc64: 0x0000_0000_0000_0000,
+            crc128: 0x0000_0000_0000_0000_0000_0000_0000_0000,
+        },
+    },
+    Vector {
+        name: "single byte",
+        input: b"a",
+        expected: Expected {
+            crc8: 0x2C,
+            crc16: 0x83B8,
+            crc32: 0x1993_9B6B,
+            crc64: 0xCE73_F427_ACC0_A99A,
+            crc128: 0x8200_AF11_2C2D_E4D0_8AB0_549F_4F44_7D76,
+        },
+    },
+    Vector {
+        name: "check string",
+        input: b"123456789",
+        expected: Expected {
+            crc8: 0x04,
+            crc16: 0x5118,
+            crc32: 0xFC89_1918,
+            crc64: 0x62EC_59E3_F1A4_F00A,
+            crc128: 0x1AA8_2DD5_8A18_1FEB_737E_A510_A7CA_5184,
+        },
+    },
+    Vector {
+        name: "text",
+        input: b"hello world",
+        expected: Expected {
+            crc8: 0x94,
+            crc16: 0xC814,
+            crc32: 0x44F7_1378,
+            crc64: 0xC287_0203_2194_3B9D,
+            crc128: 0x1B00_4A91_C7EF_1913_4E77_9C0A_C320_AD8C,
+        },
+    },
+    Vector {
+        name: "64 zero bytes",
+        input: &[0; 64],
+        expected: Expected {
+            crc8: 0x22,
+            crc16: 0xFD0B,
+            crc32: 0x6CC6_B1AE,
+            crc64: 0x4024_0C68_A55E_2A7B,
+            crc128: 0x2492_9FAE_9025_B208_8BB2_065F_3AB4_E3C6,
+        },
+    },
+    Vector {
+        name: "bytes 0 to 255",
+        input: &ALL_BYTES,
+        expected: Expected {
+            crc8: 0x3C,
+            crc16: 0x39A3,
+            crc32: 0xB6B5_EE95,
+            crc64: 0x59C3_325B_2927_A19A,
+            crc128: 0x0ECC_3573_6AFD_39B2_4DF2_3551_37A0_0CF7,
+        },
+    },
+];
+
+/// Chunk size patterns to split inputs by. Sizes repeat cyclically; the irregular pattern
+/// puts split points at every alignment.
+pub const CHUNK_SIZES: &[&[usize]] = &[&[1], &[3], &[8], &[5, 1, 7, 2, 16]];
+
+/// Splits `data` into consecutive chunks whose sizes cycle through `sizes`; the last chunk
+/// may be shorter.
+///
+/// # Panics
+///
+/// Panics if `sizes` is empty or contains a zero.
+pub fn chunks<'a>(data: &'a [u8], sizes: &'a [usize]) -> Chunks<'a> {
+    assert!(
+        !sizes.is_empty() && !sizes.contains(&0),
+        "chunk sizes must be non-zero"
+    );
+    Chunks {
+        data,
+        sizes,
+        next: 0,
+    }
+}
+
+/// Iterator returned by [`chunks`].
+#[derive(Clone, Debug)]
+pub struct Chunks<'a> {
+    data: &'a [u8],
+    sizes: &'a [usize],
+    next: usize,
+}
+
+impl<'a> Iterator for Chunks<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<&'a [u8]> {
+        if self.data.is_empty() {
+            return None;
+        }
+        let size = self.sizes[self.next].min(self.data.len());
+        self.next = (self.next + 1) % self.sizes.len();
+        let (chunk, rest) = self.data.split_at(size);
+        self.data = rest;
+        Some(chunk)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CRC, CRC128, CRC16, CRC32, CRC64, CRC8};
+
+    fn run<C, N>(input: &[u8], expected: N, name: &str)
+    where
+        C: CRC<Width = N>,
+        N: PartialEq + core::fmt::Debug,
+    {
+        let mut crc = C::default();
+        crc.update(input);
+        assert_eq!(crc.finalize(), expected, "{}", name);
+        for sizes in CHUNK_SIZES {
+            let mut crc = C::default();
+            chunks(input, sizes).for_each(|chunk| crc.update(chunk));
+            assert_eq!(
+                crc.finalize(),
+                expected,
+                "{} in chunks of {:?}",
+                name,
+                sizes
+            );
+        }
+    }
+
+    #[test]
+    fn vectors_test() {
+        for vector in VECTORS {
+            let (input, expected) = (vector.input, vector.expected);
+            run::<CRC8, _>(input, expected.crc8, vector.name);
+            run::<CRC16, _>(input, expected.crc16, vector.name);
+            run::<CRC32, _>(input, expected.crc32, vector.name);
+            run::<CRC64, _>(input, expected.crc64, vector.name);
+            run::<CRC128, _>(input, expected.crc128, vector.name);
+        }
+        // CRC32's default is CRC-32/BZIP2.
+        let bzip2 = crc::Crc::<u32>::new(&crc::CRC_32_BZIP2);
+        for vector in VECTORS {
+            assert_eq!(bzip2.checksum(vector.input), vector.expected.crc32);
+        }
+        let sizes: Vec<_> = chunks(b"abcdefg", &[2, 1]).map(<[u8]>::len).collect();
+        assert_eq!(sizes, [2, 1, 2, 1, 1]);
+    }
+}
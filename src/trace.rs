@@ -0,0 +1,114 @@
+//! Instrumentation hooks for the `tracing` feature.
+//!
+//! Call sites use these helpers unconditionally; without the feature they compile to
+//! nothing. File hashing is recorded as a `debug` span plus an event carrying the byte count
+//! and throughput, and every checksum comparison as a `debug` event when it matches or a
+//! `warn` event when it does not.
+
+#[cfg(feature = "tracing")]
+pub(crate) type Span = tracing::span::EnteredSpan;
+#[cfg(all(feature = "std", not(feature = "tracing")))]
+pub(crate) struct Span;
+
+/// Enters a span for an operation on the file at `path`.
+#[cfg(feature = "std")]
+pub(crate) fn file_span(operation: &'static str, path: &std::path::Path) -> Span {
+    #[cfg(feature = "tracing")]
+    return tracing::debug_span!("crclib", operation, path = %path.display()).entered();
+    #[cfg(not(feature = "tracing"))]
+    {
+        let _ = (operation, path);
+        Span
+    }
+}
+
+/// Records that `bytes` bytes were hashed since `started`.
+#[cfg(feature = "std")]
+pub(crate) fn hashed(algorithm: &'static str, bytes: u64, started: std::time::Instant) {
+    #[cfg(feature = "tracing")]
+    {
+        let elapsed = started.elapsed();
+        let mib_per_s = bytes as f64 / (1 << 20) as f64 / elapsed.as_secs_f64().max(1e-9);
+        tracing::debug!(
+            algorithm,
+            bytes,
+            elapsed_us = elapsed.as_micros() as u64,
+            mib_per_s,
+            "hashed"
+        );
+    }
+    #[cfg(not(feature = "tracing"))]
+    let _ = (algorithm, bytes, started);
+}
+
+/// Records the outcome of comparing a stored checksum of `subject` with a computed one.
+pub(crate) fn verified(algorithm: &'static str, subject: &str, expected: u64, actual: u64) {
+    #[cfg(feature = "tracing")]
+    if expected == actual {
+        tracing::debug!(algorithm, subject, checksum = actual, "checksum verified");
+    } else {
+        tracing::warn!(algorithm, subject, expected, actual, "checksum mismatch");
+    }
+    #[cfg(not(feature = "tracing"))]
+    let _ = (algorithm, subject, expected, actual);
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Level, Metadata, Subscriber};
+
+    #[derive(Default)]
+    struct Counts {
+        events: AtomicUsize,
+        warnings: AtomicUsize,
+        spans: AtomicUsize,
+    }
+
+    /// Counts spans, events and `warn` events.
+    struct Counter(Arc<Counts>);
+
+    impl Subscriber for Counter {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _: &Attributes<'_>) -> Id {
+            Id::from_u64(self.0.spans.fetch_add(1, Ordering::Relaxed) as u64 + 1)
+        }
+
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            self.0.events.fetch_add(1, Ordering::Relaxed);
+            if *event.metadata().level() == Level::WARN {
+                self.0.warnings.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[test]
+    fn events_test() {
+        let counter = Arc::new(Counts::default());
+        tracing::subscriber::with_default(Counter(counter.clone()), || {
+            let path = std::env::temp_dir().join(format!("crclib-trace-{}", std::process::id()));
+            std::fs::write(&path, "hello world").unwrap();
+            crate::manifest::hash_file(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+
+            assert!(crate::cloud::verify_crc32c("yZRlqg==", 0xC994_65AA).is_ok());
+            assert!(crate::cloud::verify_crc32c("yZRlqg==", 0).is_err());
+        });
+        assert_eq!(counter.spans.load(Ordering::Relaxed), 1);
+        assert_eq!(counter.events.load(Ordering::Relaxed), 3);
+        assert_eq!(counter.warnings.load(Ordering::Relaxed), 1);
+    }
+}
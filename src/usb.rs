@@ -0,0 +1,111 @@
+//! USB token and data packet CRCs (USB 2.0 specification, section 8.3.5).
+//!
+//! Token packets protect their 11-bit field (address and endpoint, or the SOF frame number)
+//! with CRC-5/USB; data packets protect their payload with CRC-16/USB. USB sends every
+//! field least significant bit first, and the reflected catalogue values already are in
+//! that order: the CRC-5 fills the top five bits of a token's last byte and the CRC-16
+//! follows the payload little-endian.
+//!
+//! CRC-5 is too narrow for a [`const_crc`](crate::const_crc) preset; the CRC-16 is
+//! [`Crc16Usb`].
+
+use crate::const_crc::Crc16Usb;
+use crate::engine::Params;
+
+/// Length of a token packet after the sync pattern: PID, 11-bit field and CRC-5.
+pub const TOKEN_LEN: usize = 3;
+
+const CRC_5_USB: Params<u8> = Params {
+    width: 5,
+    poly: 0x05,
+    init: 0x1F,
+    refin: true,
+    refout: true,
+    xorout: 0x1F,
+};
+
+/// The 11-bit token field addressing `endpoint` of the device at `address`.
+///
+/// Only the low 7 bits of `address` and 4 bits of `endpoint` are used.
+pub fn address_endpoint(address: u8, endpoint: u8) -> u16 {
+    (address as u16 & 0x7F) | (endpoint as u16 & 0xF) << 7
+}
+
+/// The CRC-5 of the low 11 bits of a token field.
+pub fn token_crc5(field: u16) -> u8 {
+    // The engine feeds bits most significant first; the field goes out LSB first.
+    let bits = (field & 0x7FF).reverse_bits() >> 5;
+    CRC_5_USB.finalize(CRC_5_USB.update_bits(CRC_5_USB.start(), bits as u64, 11))
+}
+
+/// Encodes a token packet: the 4-bit `pid` with its check nibble, then `field` and its
+/// CRC-5, in the order the bytes are sent.
+pub fn token(pid: u8, field: u16) -> [u8; TOKEN_LEN] {
+    let pid = pid & 0xF;
+    let rest = (field & 0x7FF) | (token_crc5(field) as u16) << 11;
+    let [low, high] = rest.to_le_bytes();
+    [pid | !pid << 4, low, high]
+}
+
+/// Checks the PID check nibble and the CRC-5 of a token packet.
+pub fn verify_token(packet: &[u8; TOKEN_LEN]) -> bool {
+    let rest = u16::from_le_bytes([packet[1], packet[2]]);
+    packet[0] >> 4 == !packet[0] & 0xF && token_crc5(rest) == (rest >> 11) as u8
+}
+
+/// The CRC-16 of a data packet's payload.
+pub fn data_crc16(payload: &[u8]) -> u16 {
+    Crc16Usb::checksum(payload)
+}
+
+/// Appends the CRC-16 to a data packet's payload.
+#[cfg(feature = "std")]
+pub fn append_data_crc16(payload: &mut Vec<u8>) {
+    let crc = data_crc16(payload);
+    payload.extend_from_slice(&crc.to_le_bytes());
+}
+
+/// Checks a data packet's payload followed by its CRC-16, without the PID.
+pub fn verify_data(data: &[u8]) -> bool {
+    match data.len().checked_sub(2) {
+        Some(len) => data_crc16(&data[..len]).to_le_bytes() == data[len..],
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SETUP: u8 = 0xD;
+    const IN: u8 = 0x9;
+
+    #[test]
+    fn token_test() {
+        assert_eq!(CRC_5_USB.checksum(b"123456789"), 0x19);
+        // SETUP and IN to the default address, as seen on the bus.
+        assert_eq!(token(SETUP, address_endpoint(0, 0)), [0x2D, 0x00, 0x10]);
+        assert_eq!(token(IN, address_endpoint(0, 0)), [0x69, 0x00, 0x10]);
+        let packet = token(IN, address_endpoint(0x15, 0xE));
+        assert!(verify_token(&packet));
+        for bit in 0..24 {
+            let mut corrupted = packet;
+            corrupted[bit / 8] ^= 1 << (bit % 8);
+            assert!(!verify_token(&corrupted), "bit {}", bit);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn data_test() {
+        // The DATA0 packet of a GET_DESCRIPTOR(DEVICE) setup transaction.
+        let mut data = vec![0x80, 0x06, 0x00, 0x01, 0x00, 0x00, 0x40, 0x00];
+        append_data_crc16(&mut data);
+        assert_eq!(data[8..], [0xDD, 0x94]);
+        assert!(verify_data(&data));
+        assert_eq!(data_crc16(&[]), 0x0000);
+        data[3] ^= 0x01;
+        assert!(!verify_data(&data));
+        assert!(!verify_data(&[0x00]));
+    }
+}
@@ -0,0 +1,80 @@
+//! JavaScript bindings through `wasm-bindgen`.
+//!
+//! ```text
+//! import { Crc, algorithms } from "crclib";
+//!
+//! const crc = new Crc("CRC-32C");
+//! for await (const chunk of file.stream()) crc.update(chunk);
+//! upload.headers["x-checksum"] = crc.hex();
+//! ```
+//!
+//! `update` takes `Uint8Array` chunks and runs the same code as [`CRC32`](crate::CRC32) and
+//! friends on the server.
+
+use crate::catalog;
+use crate::checksum::Checksum;
+use crate::DynCrc;
+use wasm_bindgen::prelude::*;
+
+/// The names of every catalogued algorithm, as accepted by `new Crc(name)`.
+#[wasm_bindgen]
+pub fn algorithms() -> Vec<String> {
+    catalog::algorithms()
+        .map(|algorithm| algorithm.name().into())
+        .collect()
+}
+
+/// A streaming checksum of a catalogued algorithm.
+#[wasm_bindgen(js_name = Crc)]
+pub struct WasmCrc {
+    state: Box<dyn DynCrc>,
+    width: u32,
+}
+
+#[wasm_bindgen(js_class = Crc)]
+impl WasmCrc {
+    /// Throws for a name that is not in the catalogue.
+    #[wasm_bindgen(constructor)]
+    pub fn new(name: &str) -> Result<WasmCrc, JsError> {
+        let algorithm = catalog::by_name(name).map_err(|err| JsError::new(&err.to_string()))?;
+        Ok(Self {
+            state: algorithm.state(),
+            width: algorithm.width(),
+        })
+    }
+
+    pub fn update(&mut self, chunk: &[u8]) {
+        self.state.dyn_update(chunk);
+    }
+
+    /// The checksum so far as a `BigInt`.
+    pub fn finalize(&self) -> u64 {
+        self.state.dyn_finalize() as u64
+    }
+
+    /// The checksum so far as upper-case hex, zero-padded to the width.
+    pub fn hex(&self) -> String {
+        Checksum::new(self.finalize(), self.width).to_string()
+    }
+
+    pub fn reset(&mut self) {
+        self.state.dyn_reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wasm_test() {
+        assert!(algorithms().iter().any(|name| name == "CRC-32/ISCSI"));
+        let mut crc = WasmCrc::new("crc-16/xmodem").unwrap();
+        crc.update(b"1234");
+        crc.update(b"56789");
+        assert_eq!(crc.finalize(), 0x31C3);
+        assert_eq!(crc.hex(), "31C3");
+        crc.reset();
+        assert_eq!(crc.hex(), "0000");
+    }
+}
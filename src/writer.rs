@@ -0,0 +1,98 @@
+//! Checksumming data on its way into a writer.
+//!
+//! A [`CrcWriter`] sits between a producer, such as a serializer, and the file or socket it
+//! writes to. The CRC is ready for a trailer as soon as the payload is written, without the
+//! payload ever being buffered.
+
+use crate::CRC;
+use std::io::{self, Write};
+use std::marker::PhantomData;
+
+/// Forwards writes to an inner writer and computes the CRC of the bytes it accepted.
+pub struct CrcWriter<W, C, N> {
+    inner: W,
+    crc: C,
+    _checksum: PhantomData<N>,
+}
+
+impl<W, C, N> CrcWriter<W, C, N>
+where
+    W: Write,
+    C: CRC<Width = N>,
+{
+    pub fn new(inner: W, crc: C) -> Self {
+        Self {
+            inner,
+            crc,
+            _checksum: PhantomData,
+        }
+    }
+
+    /// The CRC of the bytes written so far.
+    pub fn checksum(&self) -> N {
+        self.crc.finalize()
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// The inner writer. Writing through it directly bypasses the CRC.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W, C, N> Write for CrcWriter<W, C, N>
+where
+    W: Write,
+    C: CRC<Width = N>,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Short writes only count what the inner writer took; the caller retries the rest.
+        let n = self.inner.write(buf)?;
+        self.crc.update(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CrcWriter;
+    use crate::CRC32;
+    use std::io::{self, Write};
+
+    /// Accepts at most three bytes per call.
+    struct Short(Vec<u8>);
+
+    impl Write for Short {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let n = buf.len().min(3);
+            self.0.extend_from_slice(&buf[..n]);
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn writer_test() {
+        let mut writer = CrcWriter::new(Short(Vec::new()), CRC32::default());
+        let name = "world";
+        write!(writer, "hello {}", name).unwrap();
+        writer.flush().unwrap();
+        assert_eq!(writer.checksum(), 0x44F71378);
+        assert_eq!(writer.get_ref().0, b"hello world");
+        assert_eq!(writer.into_inner().0.len(), 11);
+    }
+}
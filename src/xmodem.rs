@@ -0,0 +1,180 @@
+//! XMODEM-CRC and YMODEM packets.
+//!
+//! A packet is `SOH` or `STX`, the block number, its complement, 128 or 1024 data bytes and
+//! the CRC-16/XMODEM of the data, big-endian. CRC-16/XMODEM starts from zero, unlike
+//! CRC-16/IBM-3740 ("CCITT-FALSE"), which shares its polynomial but starts from `0xFFFF`;
+//! mixing the two up is the classic reason a receiver NAKs every packet.
+//!
+//! Short final blocks are padded with `SUB` (`0x1A`), as senders conventionally do.
+
+use crate::oneshot::crc16_xmodem;
+use crate::trace;
+
+/// Starts a packet with 128 data bytes.
+pub const SOH: u8 = 0x01;
+/// Starts a packet with 1024 data bytes (XMODEM-1K and YMODEM).
+pub const STX: u8 = 0x02;
+/// Pads the data of the last packet.
+pub const SUB: u8 = 0x1A;
+
+/// Length of a packet around its data: start byte, block number, complement and CRC.
+pub const OVERHEAD: usize = 5;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum XmodemError {
+    /// The output buffer cannot hold a packet of `needed` bytes.
+    BufferTooSmall {
+        needed: usize,
+    },
+    /// More data than a 1024-byte packet holds.
+    TooLong,
+    /// The input is shorter than the packet its start byte announces.
+    Truncated,
+    /// The input starts with neither `SOH` nor `STX`.
+    Start,
+    /// The block number and its complement do not match.
+    BlockNumber,
+    Mismatch {
+        expected: u16,
+        actual: u16,
+    },
+}
+
+impl core::fmt::Display for XmodemError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::BufferTooSmall { needed } => {
+                write!(f, "buffer too small: packet needs {} bytes", needed)
+            }
+            Self::TooLong => write!(f, "data does not fit an XMODEM packet"),
+            Self::Truncated => write!(f, "XMODEM packet is truncated"),
+            Self::Start => write!(f, "XMODEM packet does not start with SOH or STX"),
+            Self::BlockNumber => write!(f, "XMODEM block number and complement differ"),
+            Self::Mismatch { expected, actual } => write!(
+                f,
+                "XMODEM CRC mismatch: packet has {:#06X}, computed {:#06X}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for XmodemError {}
+
+/// A received packet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Packet<'a> {
+    pub block: u8,
+    /// All 128 or 1024 data bytes, including any padding.
+    pub data: &'a [u8],
+}
+
+/// The CRC trailer of a packet's data, in wire order.
+pub fn trailer(data: &[u8]) -> [u8; 2] {
+    crc16_xmodem(data).to_be_bytes()
+}
+
+/// Writes the packet for `block` holding `data` to the start of `out` and returns its
+/// length.
+///
+/// Up to 128 bytes of data make a `SOH` packet and up to 1024 a `STX` one, padded with
+/// [`SUB`].
+pub fn encode_packet(block: u8, data: &[u8], out: &mut [u8]) -> Result<usize, XmodemError> {
+    let (start, size) = match data.len() {
+        0..=128 => (SOH, 128),
+        129..=1024 => (STX, 1024),
+        _ => return Err(XmodemError::TooLong),
+    };
+    let needed = size + OVERHEAD;
+    let packet = out
+        .get_mut(..needed)
+        .ok_or(XmodemError::BufferTooSmall { needed })?;
+    packet[..3].copy_from_slice(&[start, block, !block]);
+    let payload = &mut packet[3..3 + size];
+    payload[..data.len()].copy_from_slice(data);
+    payload[data.len()..].fill(SUB);
+    let crc = trailer(payload);
+    packet[3 + size..].copy_from_slice(&crc);
+    Ok(needed)
+}
+
+/// Checks the packet at the start of `input`, which may continue past it.
+pub fn decode_packet(input: &[u8]) -> Result<Packet<'_>, XmodemError> {
+    let size = match input.first() {
+        Some(&SOH) => 128,
+        Some(&STX) => 1024,
+        Some(_) => return Err(XmodemError::Start),
+        None => return Err(XmodemError::Truncated),
+    };
+    let packet = input.get(..size + OVERHEAD).ok_or(XmodemError::Truncated)?;
+    if packet[1] != !packet[2] {
+        return Err(XmodemError::BlockNumber);
+    }
+    let data = &packet[3..3 + size];
+    let expected = u16::from_be_bytes([packet[3 + size], packet[4 + size]]);
+    let actual = crc16_xmodem(data);
+    trace::verified(
+        "CRC-16/XMODEM",
+        "XMODEM packet",
+        expected as u64,
+        actual as u64,
+    );
+    if expected != actual {
+        return Err(XmodemError::Mismatch { expected, actual });
+    }
+    Ok(Packet {
+        block: packet[1],
+        data,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packet_test() {
+        let mut buf = [0u8; 1100];
+        let len = encode_packet(1, b"123456789", &mut buf).unwrap();
+        assert_eq!(len, 133);
+        assert_eq!(buf[..4], [SOH, 0x01, 0xFE, b'1']);
+        assert_eq!(buf[12], SUB);
+        let packet = decode_packet(&buf[..len]).unwrap();
+        assert_eq!(packet.block, 1);
+        assert_eq!(&packet.data[..9], b"123456789");
+        assert_eq!(trailer(b"123456789"), [0x31, 0xC3]);
+
+        let data = [0x55; 1000];
+        assert_eq!(encode_packet(0xFF, &data, &mut buf), Ok(1029));
+        assert_eq!(buf[..3], [STX, 0xFF, 0x00]);
+        assert_eq!(decode_packet(&buf).unwrap().data.len(), 1024);
+    }
+
+    #[test]
+    fn error_test() {
+        let mut buf = [0u8; 133];
+        assert_eq!(
+            encode_packet(1, &[0; 129], &mut buf),
+            Err(XmodemError::BufferTooSmall { needed: 1029 })
+        );
+        assert_eq!(
+            encode_packet(1, &[0; 1025], &mut buf),
+            Err(XmodemError::TooLong)
+        );
+        encode_packet(7, b"data", &mut buf).unwrap();
+        assert_eq!(decode_packet(&buf[..132]), Err(XmodemError::Truncated));
+        buf[2] ^= 1;
+        assert_eq!(decode_packet(&buf), Err(XmodemError::BlockNumber));
+        buf[2] ^= 1;
+        buf[10] ^= 1;
+        assert!(matches!(
+            decode_packet(&buf),
+            Err(XmodemError::Mismatch { .. })
+        ));
+        buf[0] = 0x04;
+        assert_eq!(decode_packet(&buf), Err(XmodemError::Start));
+    }
+}
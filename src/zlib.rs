@@ -0,0 +1,52 @@
+//! zlib's `crc32()`, for code ported from C.
+//!
+//! zlib threads the checksum itself through repeated calls: `crc32(0, NULL, 0)` is the
+//! starting value, and passing the returned checksum back in continues over more data. So
+//!
+//! ```text
+//! uLong crc = crc32(0L, Z_NULL, 0);
+//! while (read_buffer(buffer, length) != EOF) crc = crc32(crc, buffer, length);
+//! ```
+//!
+//! maps one-to-one to calls of [`crc32`], and every intermediate value is already the
+//! CRC-32/ISO-HDLC of the data so far. [`crc32_combine`] is zlib's function of that name,
+//! and [`adler32`] threads zlib's other checksum the same way, starting from `1`.
+
+use crate::const_crc::Crc32IsoHdlc;
+use crate::fletcher::Adler32;
+use crate::CRC;
+
+pub use crate::oneshot::crc32_combine;
+
+/// Continues the checksum `crc` over `buf`; `crc` is `0` for a fresh one.
+pub fn crc32(crc: u32, buf: &[u8]) -> u32 {
+    let mut state = Crc32IsoHdlc::with_register(!crc);
+    state.update(buf);
+    state.finalize()
+}
+
+/// Continues the Adler-32 `adler` over `buf`; `adler` is `1` for a fresh one.
+pub fn adler32(adler: u32, buf: &[u8]) -> u32 {
+    let mut state = Adler32::from_checksum(adler);
+    state.update(buf);
+    state.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zlib_test() {
+        assert_eq!(crc32(0, b""), 0);
+        assert_eq!(crc32(0, b"123456789"), 0xCBF4_3926);
+        let crc = crc32(0, b"1234");
+        assert_eq!(crc32(crc, b""), crc);
+        assert_eq!(crc32(crc, b"56789"), 0xCBF4_3926);
+        let large: Vec<u8> = (0..50_000u32).map(|i| ((i * 31) >> 3) as u8).collect();
+        let crc = large.chunks(777).fold(0, crc32);
+        assert_eq!(crc, crate::oneshot::crc32(&large));
+        assert_eq!(large.chunks(777).fold(1, adler32), adler32(1, &large));
+        assert_eq!(adler32(1, b"123456789"), 0x091E_01DE);
+    }
+}
@@ -0,0 +1,456 @@
+//! ZMODEM header and data subpacket CRCs.
+//!
+//! ZMODEM protects every header and data subpacket with either CRC-16/XMODEM or
+//! CRC-32/ISO-HDLC. Hex headers (`ZPAD ZPAD ZDLE 'B'`) always use CRC-16 and are sent as
+//! lowercase hex; binary headers use CRC-16 (`ZDLE 'A'`) or CRC-32 (`ZDLE 'C'`), and data
+//! subpackets use whichever CRC the preceding binary header did. Binary bytes are ZDLE
+//! escaped on the wire, and the CRC is computed over the unescaped bytes.
+//!
+//! The functions here encode into and decode from caller-provided slices, so they work on
+//! targets without an allocator.
+
+use crate::const_crc::{Crc16Xmodem, Crc32IsoHdlc};
+use crate::{trace, CRC};
+
+const ZPAD: u8 = b'*';
+const ZDLE: u8 = 0x18;
+const XON: u8 = 0x11;
+
+/// Length of an encoded hex header, including the trailing XON.
+pub const HEX_HEADER_LEN: usize = 21;
+
+/// Header types after which a hex header is not followed by XON.
+const ZACK: u8 = 3;
+const ZFIN: u8 = 8;
+
+fn checksum<C: CRC>(parts: &[&[u8]]) -> C::Width {
+    let mut crc = C::default();
+    for part in parts {
+        crc.update(part);
+    }
+    crc.finalize()
+}
+
+/// The CRC a binary header or data subpacket is protected with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CrcKind {
+    Crc16,
+    Crc32,
+}
+
+impl CrcKind {
+    fn checksum(self, parts: &[&[u8]]) -> u32 {
+        match self {
+            Self::Crc16 => checksum::<Crc16Xmodem>(parts) as u32,
+            Self::Crc32 => checksum::<Crc32IsoHdlc>(parts),
+        }
+    }
+
+    /// The CRC as sent: CRC-16 big-endian, CRC-32 little-endian.
+    fn to_bytes(self, crc: u32) -> ([u8; 4], usize) {
+        match self {
+            Self::Crc16 => {
+                let [hi, lo] = (crc as u16).to_be_bytes();
+                ([hi, lo, 0, 0], 2)
+            }
+            Self::Crc32 => (crc.to_le_bytes(), 4),
+        }
+    }
+
+    fn parse(self, bytes: &[u8]) -> u32 {
+        match self {
+            Self::Crc16 => u16::from_be_bytes([bytes[0], bytes[1]]) as u32,
+            Self::Crc32 => u32::from_le_bytes(bytes.try_into().unwrap()),
+        }
+    }
+}
+
+/// How a data subpacket ends, i.e. the byte after its closing ZDLE.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FrameEnd {
+    /// `ZCRCE`: end of frame, header follows.
+    End = b'h' as isize,
+    /// `ZCRCG`: frame continues nonstop.
+    Go = b'i' as isize,
+    /// `ZCRCQ`: frame continues, ZACK expected.
+    Quick = b'j' as isize,
+    /// `ZCRCW`: end of frame, ZACK expected.
+    Wait = b'k' as isize,
+}
+
+impl FrameEnd {
+    fn from_byte(byte: u8) -> Option<Self> {
+        Some(match byte {
+            b'h' => Self::End,
+            b'i' => Self::Go,
+            b'j' => Self::Quick,
+            b'k' => Self::Wait,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ZmodemError {
+    BufferTooSmall,
+    /// The input ends before the header or subpacket does.
+    Truncated,
+    /// The input is not a header or subpacket of the expected kind.
+    Malformed,
+    Mismatch {
+        expected: u32,
+        actual: u32,
+    },
+}
+
+impl core::fmt::Display for ZmodemError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::BufferTooSmall => write!(f, "output buffer too small"),
+            Self::Truncated => write!(f, "ZMODEM frame is truncated"),
+            Self::Malformed => write!(f, "malformed ZMODEM frame"),
+            Self::Mismatch { expected, actual } => write!(
+                f,
+                "ZMODEM CRC mismatch: frame has {:#X}, computed {:#X}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ZmodemError {}
+
+/// A ZMODEM header: the frame type and its four flag or position bytes, in wire order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Header {
+    pub kind: u8,
+    pub data: [u8; 4],
+}
+
+impl Header {
+    fn bytes(&self) -> [u8; 5] {
+        let [a, b, c, d] = self.data;
+        [self.kind, a, b, c, d]
+    }
+}
+
+fn needs_escape(byte: u8) -> bool {
+    matches!(byte & 0x7F, 0x10 | 0x11 | 0x13 | ZDLE)
+}
+
+/// Appends ZDLE-escaped bytes to a slice.
+struct Writer<'a> {
+    out: &'a mut [u8],
+    len: usize,
+}
+
+impl Writer<'_> {
+    fn raw(&mut self, bytes: &[u8]) -> Result<(), ZmodemError> {
+        let end = self.len + bytes.len();
+        self.out
+            .get_mut(self.len..end)
+            .ok_or(ZmodemError::BufferTooSmall)?
+            .copy_from_slice(bytes);
+        self.len = end;
+        Ok(())
+    }
+
+    fn escaped(&mut self, bytes: &[u8]) -> Result<(), ZmodemError> {
+        for &byte in bytes {
+            if needs_escape(byte) {
+                self.raw(&[ZDLE, byte ^ 0x40])?;
+            } else {
+                self.raw(&[byte])?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// What an escaped byte sequence decodes to next.
+enum Unescaped {
+    Byte(u8),
+    End(FrameEnd),
+}
+
+/// Reads ZDLE-escaped bytes.
+struct Reader<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn next(&mut self) -> Result<Unescaped, ZmodemError> {
+        let byte = *self.input.get(self.pos).ok_or(ZmodemError::Truncated)?;
+        self.pos += 1;
+        if byte != ZDLE {
+            return Ok(Unescaped::Byte(byte));
+        }
+        let byte = *self.input.get(self.pos).ok_or(ZmodemError::Truncated)?;
+        self.pos += 1;
+        Ok(match byte {
+            b'l' => Unescaped::Byte(0x7F),
+            b'm' => Unescaped::Byte(0xFF),
+            byte => match FrameEnd::from_byte(byte) {
+                Some(end) => Unescaped::End(end),
+                None => Unescaped::Byte(byte ^ 0x40),
+            },
+        })
+    }
+
+    fn byte(&mut self) -> Result<u8, ZmodemError> {
+        match self.next()? {
+            Unescaped::Byte(byte) => Ok(byte),
+            Unescaped::End(_) => Err(ZmodemError::Malformed),
+        }
+    }
+}
+
+fn check(crc: CrcKind, subject: &str, expected: u32, actual: u32) -> Result<(), ZmodemError> {
+    let algorithm = match crc {
+        CrcKind::Crc16 => "CRC-16/XMODEM",
+        CrcKind::Crc32 => "CRC-32/ISO-HDLC",
+    };
+    trace::verified(algorithm, subject, expected as u64, actual as u64);
+    if expected != actual {
+        return Err(ZmodemError::Mismatch { expected, actual });
+    }
+    Ok(())
+}
+
+/// Encodes `header` as a hex header and returns its length, 20 or [`HEX_HEADER_LEN`] bytes.
+pub fn encode_hex_header(header: &Header, out: &mut [u8]) -> Result<usize, ZmodemError> {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+    let bytes = header.bytes();
+    let crc = (CrcKind::Crc16.checksum(&[&bytes]) as u16).to_be_bytes();
+    let mut writer = Writer { out, len: 0 };
+    writer.raw(&[ZPAD, ZPAD, ZDLE, b'B'])?;
+    for byte in bytes.iter().chain(&crc) {
+        writer.raw(&[HEX[(byte >> 4) as usize], HEX[(byte & 0xF) as usize]])?;
+    }
+    writer.raw(b"\r\x8a")?;
+    if header.kind != ZACK && header.kind != ZFIN {
+        writer.raw(&[XON])?;
+    }
+    Ok(writer.len)
+}
+
+/// Decodes and checks a hex header at the start of `input`, returning it and the number of
+/// bytes up to and including the line feed.
+pub fn decode_hex_header(input: &[u8]) -> Result<(Header, usize), ZmodemError> {
+    let body = input.strip_prefix(&[ZPAD, ZPAD, ZDLE, b'B']);
+    let body = body.ok_or(ZmodemError::Malformed)?;
+    let digits = body.get(..14).ok_or(ZmodemError::Truncated)?;
+    let mut bytes = [0; 7];
+    for (byte, pair) in bytes.iter_mut().zip(digits.chunks(2)) {
+        let pair = core::str::from_utf8(pair).map_err(|_| ZmodemError::Malformed)?;
+        *byte = u8::from_str_radix(pair, 16).map_err(|_| ZmodemError::Malformed)?;
+    }
+    let header = Header {
+        kind: bytes[0],
+        data: [bytes[1], bytes[2], bytes[3], bytes[4]],
+    };
+    let expected = u16::from_be_bytes([bytes[5], bytes[6]]) as u32;
+    check(
+        CrcKind::Crc16,
+        "ZMODEM hex header",
+        expected,
+        CrcKind::Crc16.checksum(&[&bytes[..5]]),
+    )?;
+    // CR, then LF with or without its high bit set.
+    let end = body[14..]
+        .iter()
+        .position(|&byte| byte & 0x7F == b'\n')
+        .ok_or(ZmodemError::Truncated)?;
+    Ok((header, 4 + 14 + end + 1))
+}
+
+/// Encodes `header` as a binary header with the given CRC and returns its length.
+pub fn encode_binary_header(
+    header: &Header,
+    crc: CrcKind,
+    out: &mut [u8],
+) -> Result<usize, ZmodemError> {
+    let bytes = header.bytes();
+    let (crc_bytes, crc_len) = crc.to_bytes(crc.checksum(&[&bytes]));
+    let mut writer = Writer { out, len: 0 };
+    let frame = match crc {
+        CrcKind::Crc16 => b'A',
+        CrcKind::Crc32 => b'C',
+    };
+    writer.raw(&[ZPAD, ZDLE, frame])?;
+    writer.escaped(&bytes)?;
+    writer.escaped(&crc_bytes[..crc_len])?;
+    Ok(writer.len)
+}
+
+/// Decodes and checks a binary header at the start of `input`, returning it, the CRC it used
+/// and its encoded length.
+pub fn decode_binary_header(input: &[u8]) -> Result<(Header, CrcKind, usize), ZmodemError> {
+    let crc = match input {
+        [ZPAD, ZDLE, b'A', ..] => CrcKind::Crc16,
+        [ZPAD, ZDLE, b'C', ..] => CrcKind::Crc32,
+        [ZPAD] | [ZPAD, ZDLE] | [] => return Err(ZmodemError::Truncated),
+        _ => return Err(ZmodemError::Malformed),
+    };
+    let mut reader = Reader { input, pos: 3 };
+    let mut bytes = [0; 9];
+    let len = 5 + crc.to_bytes(0).1;
+    for byte in &mut bytes[..len] {
+        *byte = reader.byte()?;
+    }
+    let header = Header {
+        kind: bytes[0],
+        data: [bytes[1], bytes[2], bytes[3], bytes[4]],
+    };
+    check(
+        crc,
+        "ZMODEM binary header",
+        crc.parse(&bytes[5..len]),
+        crc.checksum(&[&bytes[..5]]),
+    )?;
+    Ok((header, crc, reader.pos))
+}
+
+/// Encodes a data subpacket carrying `data` and returns its length.
+pub fn encode_subpacket(
+    data: &[u8],
+    end: FrameEnd,
+    crc: CrcKind,
+    out: &mut [u8],
+) -> Result<usize, ZmodemError> {
+    let (crc_bytes, crc_len) = crc.to_bytes(crc.checksum(&[data, &[end as u8]]));
+    let mut writer = Writer { out, len: 0 };
+    writer.escaped(data)?;
+    writer.raw(&[ZDLE, end as u8])?;
+    writer.escaped(&crc_bytes[..crc_len])?;
+    Ok(writer.len)
+}
+
+/// A data subpacket decoded by [`decode_subpacket`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Subpacket {
+    /// Number of data bytes written to the output buffer.
+    pub len: usize,
+    pub end: FrameEnd,
+    /// Number of input bytes the subpacket took up.
+    pub consumed: usize,
+}
+
+/// Unescapes the data subpacket at the start of `input` into `out` and checks its CRC.
+pub fn decode_subpacket(
+    input: &[u8],
+    crc: CrcKind,
+    out: &mut [u8],
+) -> Result<Subpacket, ZmodemError> {
+    let mut reader = Reader { input, pos: 0 };
+    let mut len = 0;
+    let end = loop {
+        match reader.next()? {
+            Unescaped::Byte(byte) => {
+                *out.get_mut(len).ok_or(ZmodemError::BufferTooSmall)? = byte;
+                len += 1;
+            }
+            Unescaped::End(end) => break end,
+        }
+    };
+    let mut crc_bytes = [0; 4];
+    let crc_len = crc.to_bytes(0).1;
+    for byte in &mut crc_bytes[..crc_len] {
+        *byte = reader.byte()?;
+    }
+    check(
+        crc,
+        "ZMODEM subpacket",
+        crc.parse(&crc_bytes[..crc_len]),
+        crc.checksum(&[&out[..len], &[end as u8]]),
+    )?;
+    Ok(Subpacket {
+        len,
+        end,
+        consumed: reader.pos,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_header_test() {
+        let check = crc::Crc::<u16>::new(&crc::CRC_16_XMODEM).checksum(b"123456789");
+        assert_eq!(CrcKind::Crc16.checksum(&[b"12345", b"6789"]), check as u32);
+
+        // The ZRQINIT that `sz` sends to start a transfer.
+        let zrqinit = Header {
+            kind: 0,
+            data: [0; 4],
+        };
+        let mut out = [0; HEX_HEADER_LEN];
+        assert_eq!(encode_hex_header(&zrqinit, &mut out), Ok(21));
+        assert_eq!(&out, b"**\x18B00000000000000\r\x8a\x11");
+        assert_eq!(decode_hex_header(&out), Ok((zrqinit, 20)));
+
+        let zrpos = Header {
+            kind: 9,
+            data: [0x00, 0x10, 0x00, 0x00],
+        };
+        let len = encode_hex_header(&zrpos, &mut out).unwrap();
+        assert_eq!(decode_hex_header(&out[..len]).unwrap().0, zrpos);
+        out[5] = b'a';
+        assert!(matches!(
+            decode_hex_header(&out),
+            Err(ZmodemError::Mismatch { .. })
+        ));
+        assert_eq!(decode_hex_header(&out[..10]), Err(ZmodemError::Truncated));
+    }
+
+    #[test]
+    fn binary_test() {
+        // Flag bytes that need escaping.
+        let header = Header {
+            kind: 10,
+            data: [0x18, 0x11, 0x93, 0x00],
+        };
+        let mut out = [0; 32];
+        for crc in [CrcKind::Crc16, CrcKind::Crc32] {
+            let len = encode_binary_header(&header, crc, &mut out).unwrap();
+            assert_eq!(decode_binary_header(&out[..len]), Ok((header, crc, len)));
+        }
+
+        let data = b"hello \x18\x10\x11 world";
+        let mut packet = [0; 64];
+        let mut decoded = [0; 32];
+        for crc in [CrcKind::Crc16, CrcKind::Crc32] {
+            let len = encode_subpacket(data, FrameEnd::Wait, crc, &mut packet).unwrap();
+            let subpacket = decode_subpacket(&packet[..len], crc, &mut decoded).unwrap();
+            assert_eq!(subpacket.end, FrameEnd::Wait);
+            assert_eq!(subpacket.consumed, len);
+            assert_eq!(&decoded[..subpacket.len], data);
+        }
+        // The CRC-32 covers the data and the frame end byte.
+        let len = encode_subpacket(b"hi", FrameEnd::End, CrcKind::Crc32, &mut packet).unwrap();
+        let crc = crc::Crc::<u32>::new(&crc::CRC_32_ISO_HDLC).checksum(b"hih");
+        assert_eq!(packet[4..len], crc.to_le_bytes());
+
+        packet[0] = b'H';
+        assert!(matches!(
+            decode_subpacket(&packet[..len], CrcKind::Crc32, &mut decoded),
+            Err(ZmodemError::Mismatch { .. })
+        ));
+        assert_eq!(
+            decode_subpacket(&packet[..3], CrcKind::Crc32, &mut decoded),
+            Err(ZmodemError::Truncated)
+        );
+        assert_eq!(
+            encode_subpacket(data, FrameEnd::Go, CrcKind::Crc16, &mut packet[..8]),
+            Err(ZmodemError::BufferTooSmall)
+        );
+    }
+}